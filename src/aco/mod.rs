@@ -0,0 +1,397 @@
+//! Module for ant colony optimisation, which finds cheap routes through a
+//! graph by letting simulated ants wander it and lay pheromone along the
+//! routes they take.
+//! 
+//! Each ant walks the graph one edge at a time, never going back to a
+//! [`Node`] it has visited, and picks each edge with a chance weighted by
+//! `pheromone^alpha * (1 / (1 + cost))^beta`. Once every ant of an
+//! iteration has walked, some of the pheromone on every edge evaporates,
+//! and each ant which finished lays `1 / (1 + cost)` on every edge of its
+//! route, so cheap routes gather more pheromone and draw in more ants.
+//! 
+//! [`MtdAntColony`] looks for 2 kinds of routes:
+//! - A path from a source to a target, with [`MtdAntColony::find_path`].
+//! - A tour visiting every [`Node`] once and coming back to the start, with
+//!   [`MtdAntColony::find_tour`], which is the Travelling Salesman Problem
+//!   on the edges of the graph.
+//! 
+//! The ants of each iteration are split into batches, one job each on a
+//! [`ThreadPool`], and each batch draws from its own random stream. The
+//! ants read the pheromone left by earlier iterations, and each batch adds
+//! up the pheromone its ants lay on its own, then adds it to the shared
+//! total under a lock held only for that.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::{Arc, Mutex, RwLock},
+};
+
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, Cost, CsrGraph, Node, Path, check_node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+    unwrapmutex,
+};
+
+/// The number of ants of each iteration unless another is set with
+/// [`MtdAntColony::set_ants`].
+pub const DEFAULT_ANTS: usize = 64;
+/// The number of iterations unless another is set with
+/// [`MtdAntColony::set_iterations`].
+pub const DEFAULT_ITERATIONS: usize = 100;
+/// The weight of the pheromone unless another is set with
+/// [`MtdAntColony::set_weights`].
+pub const DEFAULT_ALPHA: f64 = 1.0;
+/// The weight of the [`Cost`] of an edge unless another is set with
+/// [`MtdAntColony::set_weights`].
+pub const DEFAULT_BETA: f64 = 2.0;
+/// The share of the pheromone evaporating after each iteration unless
+/// another is set with [`MtdAntColony::set_evaporation`].
+pub const DEFAULT_EVAPORATION: f64 = 0.1;
+/// The least pheromone left on an edge, so that no edge is ever ruled out.
+const MIN_PHEROMONE: f64 = 1e-6;
+
+/// What the ants are looking for.
+#[derive(Clone, Copy)]
+enum Goal {
+    Path {source: Node, target: Node},
+    Tour,
+}
+
+/// The pheromone on every edge, and the pheromone laid on every edge during
+/// the current iteration.
+struct Trail {
+    pheromones: RwLock<Vec<f64>>,
+    laid: Mutex<Vec<f64>>,
+}
+
+/// The graph and settings shared by every batch of ants.
+struct Colony {
+    graph: Arc<CsrGraph>,
+    /// Where the edges leaving each [`Node`] start in the pheromones.
+    offsets: Vec<usize>,
+    alpha: f64,
+    beta: f64,
+}
+
+impl Colony {
+    /// Walks one ant towards `goal`. Returns the [`Node`]s of its route, the
+    /// index of every edge it took and the [`Cost`] of the route, or
+    /// [`None`] if it got stuck.
+    fn walk(
+        &self,
+        pheromones: &[f64],
+        goal: Goal,
+        rng: &mut Rng,
+    ) -> Option<(Vec<Node>, Vec<usize>, Cost)> {
+        let total = self.graph.total();
+        let start = match goal {
+            Goal::Path {source, ..} => source,
+            Goal::Tour => rng.below(total),
+        };
+        let mut visited: Vec<bool> = vec![false; total];
+        visited[start] = true;
+        let (mut nodes, mut taken, mut cost) = (vec![start], Vec::new(), 0);
+        let mut weights: Vec<(usize, f64)> = Vec::new();
+        loop {
+            let node = nodes[nodes.len() - 1];
+            let finished = match goal {
+                Goal::Path {target, ..} => node == target,
+                Goal::Tour => nodes.len() == total,
+            };
+            let edges = self.graph.get_node(node).unwrap_or(&[]);
+            let first = self.offsets[node];
+            if finished {
+                if let Goal::Tour = goal {
+                    // The tour must close with an edge back to the start.
+                    let back = edges
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, adjacent)| adjacent.node == start)
+                        .min_by_key(|(_, adjacent)| adjacent.cost)?;
+                    nodes.push(start);
+                    taken.push(first + back.0);
+                    cost += back.1.cost;
+                }
+                return Some((nodes, taken, cost));
+            }
+
+            weights.clear();
+            for (index, adjacent) in edges.iter().enumerate() {
+                if !visited[adjacent.node] {
+                    let closeness = 1.0 / (1.0 + adjacent.cost as f64);
+                    let weight = pheromones[first + index].powf(self.alpha)
+                        * closeness.powf(self.beta);
+                    weights.push((index, weight));
+                }
+            }
+            if weights.is_empty() {
+                return None;
+            }
+            let sum: f64 = weights.iter().map(|(_, weight)| weight).sum();
+            let index = if sum > 0.0 && sum.is_finite() {
+                let mut left = rng.next_f64() * sum;
+                let mut picked = weights[weights.len() - 1].0;
+                for (index, weight) in &weights {
+                    if left < *weight {
+                        picked = *index;
+                        break;
+                    }
+                    left -= weight;
+                }
+                picked
+            } else {
+                weights[rng.below(weights.len())].0
+            };
+            let adjacent = edges[index];
+            visited[adjacent.node] = true;
+            nodes.push(adjacent.node);
+            taken.push(first + index);
+            cost += adjacent.cost;
+        }
+    }
+}
+
+/// This `struct` finds cheap routes through a graph with ant colony
+/// optimisation using multiple threads. See the module documentation.
+pub struct MtdAntColony {
+    pool: ThreadPool,
+    graph: Arc<CsrGraph>,
+    ants: usize,
+    iterations: usize,
+    alpha: f64,
+    beta: f64,
+    evaporation: f64,
+}
+
+impl MtdAntColony {
+    /// Creates a new [`MtdAntColony`] instance with [`DEFAULT_ANTS`],
+    /// [`DEFAULT_ITERATIONS`], [`DEFAULT_ALPHA`], [`DEFAULT_BETA`] and
+    /// [`DEFAULT_EVAPORATION`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix``` => The graph.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix) -> Result<Self, Error> {
+        return Self::from_csr(threads, CsrGraph::from(matrix));
+    }
+
+    /// Creates a new [`MtdAntColony`] instance from a graph which is already
+    /// a [`CsrGraph`]. See [`MtdAntColony::new`].
+    pub fn from_csr(threads: usize, graph: CsrGraph) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {
+            pool,
+            graph: Arc::new(graph),
+            ants: DEFAULT_ANTS,
+            iterations: DEFAULT_ITERATIONS,
+            alpha: DEFAULT_ALPHA,
+            beta: DEFAULT_BETA,
+            evaporation: DEFAULT_EVAPORATION,
+        });
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.graph.total();
+    }
+
+    /// Get the number of ants of each iteration.
+    pub fn ants(&self) -> usize {
+        return self.ants;
+    }
+
+    /// Sets the number of ants of each iteration.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `ants` is `0`, and the number of
+    /// ants is left as it was.
+    pub fn set_ants(&mut self, ants: usize) -> Result<(), Error> {
+        if ants == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "At least 1 ant is needed."
+            ));
+        }
+        self.ants = ants;
+        return Ok(());
+    }
+
+    /// Get the number of iterations.
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    /// Sets the number of iterations.
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+
+    /// Get the weights of the pheromone and of the [`Cost`] of an edge as
+    /// `(alpha, beta)`.
+    pub fn weights(&self) -> (f64, f64) {
+        return (self.alpha, self.beta);
+    }
+
+    /// Sets the weights of the pheromone and of the [`Cost`] of an edge.
+    /// With a higher `alpha`, the ants follow each other more closely, and
+    /// with a higher `beta`, they take cheap edges more greedily.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if either weight is negative or not
+    /// a number, and the weights are left as they were.
+    pub fn set_weights(&mut self, alpha: f64, beta: f64) -> Result<(), Error> {
+        if !(alpha >= 0.0 && beta >= 0.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The weights {} and {} are not both non-negative.", alpha, beta)
+            ));
+        }
+        self.alpha = alpha;
+        self.beta = beta;
+        return Ok(());
+    }
+
+    /// Get the share of the pheromone evaporating after each iteration.
+    pub fn evaporation(&self) -> f64 {
+        return self.evaporation;
+    }
+
+    /// Sets the share of the pheromone evaporating after each iteration.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `evaporation` is not above `0`
+    /// and at most `1`, and the share is left as it was.
+    pub fn set_evaporation(&mut self, evaporation: f64) -> Result<(), Error> {
+        if !(evaporation > 0.0 && evaporation <= 1.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The evaporation {} is not in (0, 1].", evaporation)
+            ));
+        }
+        self.evaporation = evaporation;
+        return Ok(());
+    }
+
+    /// Looks for a cheap path from `source` to `target`. See the module
+    /// documentation. Returns the cheapest path any ant found, or [`None`]
+    /// if none found one.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` or `target` is not in
+    /// the graph, or if a job on the [`ThreadPool`] fails.
+    pub fn find_path(
+        &mut self,
+        source: Node,
+        target: Node,
+        seed: u64,
+    ) -> Result<Option<Path>, Error> {
+        for node in [source, target] {
+            check_node(node, self.total())?;
+        }
+        return self.run(Goal::Path {source, target}, seed);
+    }
+
+    /// Looks for a cheap tour through every [`Node`] along the edges of the
+    /// graph. See the module documentation. Returns the cheapest tour any
+    /// ant found, starting and ending at the same [`Node`], or [`None`] if
+    /// none found one.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn find_tour(&mut self, seed: u64) -> Result<Option<Path>, Error> {
+        return match self.total() {
+            0 => Ok(None),
+            1 => Ok(Some(Path {nodes: vec![0], cost: 0})),
+            _ => self.run(Goal::Tour, seed),
+        };
+    }
+
+    /// Lets the ants look for `goal` over every iteration.
+    fn run(&mut self, goal: Goal, seed: u64) -> Result<Option<Path>, Error> {
+        let total = self.total();
+        let mut offsets: Vec<usize> = Vec::with_capacity(total);
+        let mut edges: usize = 0;
+        for node in 0..total {
+            offsets.push(edges);
+            edges += self.graph.get_node(node).map_or(0, <[_]>::len);
+        }
+        let colony = Arc::new(Colony {
+            graph: self.graph.clone(),
+            offsets,
+            alpha: self.alpha,
+            beta: self.beta,
+        });
+        let trail = Arc::new(Trail {
+            pheromones: RwLock::new(vec![1.0; edges]),
+            laid: Mutex::new(vec![0.0; edges]),
+        });
+        let (ants, batches) = (self.ants, self.pool.threads().min(self.ants));
+
+        let mut best: Option<Path> = None;
+        for iteration in 0..self.iterations {
+            let jobs: Vec<(u64, usize)> = (0..batches)
+                .map(|batch| {
+                    let stream = (iteration * batches + batch) as u64;
+                    let count = ants / batches + usize::from(batch < ants % batches);
+                    return (stream, count);
+                })
+                .collect();
+            let (shared, state) = (colony.clone(), trail.clone());
+            let policy = ChunkPolicy::Fixed(1);
+            let found = self.pool.map(Arc::new(jobs), policy, move |job| {
+                let mut rng = Rng::stream(seed, job.0);
+                let mut laid: Vec<(usize, f64)> = Vec::new();
+                let mut best: Option<Path> = None;
+                {
+                    let pheromones = unwrapmutex!(state.pheromones.read());
+                    for _ in 0..job.1 {
+                        let walked = shared.walk(&pheromones, goal, &mut rng);
+                        let Some((nodes, taken, cost)) = walked else {
+                            continue;
+                        };
+                        let amount = 1.0 / (1.0 + cost as f64);
+                        laid.extend(taken.into_iter().map(|edge| (edge, amount)));
+                        if best.as_ref().is_none_or(|best| cost < best.cost) {
+                            best = Some(Path {nodes, cost});
+                        }
+                    }
+                }
+                let mut total = unwrapmutex!(state.laid.lock());
+                for (edge, amount) in laid {
+                    total[edge] += amount;
+                }
+                return Ok(best);
+            })?;
+
+            for path in found.into_iter().collect::<Result<Vec<_>, Error>>()? {
+                let Some(path) = path else {
+                    continue;
+                };
+                if best.as_ref().is_none_or(|best| path.cost < best.cost) {
+                    best = Some(path);
+                }
+            }
+            let mut pheromones = unwrapmutex!(trail.pheromones.write());
+            let mut laid = unwrapmutex!(trail.laid.lock());
+            for (pheromone, amount) in pheromones.iter_mut().zip(laid.iter_mut()) {
+                *pheromone = (*pheromone * (1.0 - self.evaporation) + *amount)
+                    .max(MIN_PHEROMONE);
+                *amount = 0.0;
+            }
+        }
+        return Ok(best);
+    }
+}
@@ -0,0 +1,412 @@
+//! Module for simulated annealing, which looks for a state with the lowest
+//! energy by making random changes to a state, always keeping those which
+//! lower the energy and sometimes keeping those which raise it.
+//! 
+//! How likely a change raising the energy by `delta` is to be kept is
+//! `exp(-delta / temperature)`, and the temperature falls along a
+//! [`Cooling`] curve, so the search wanders widely at first and settles into
+//! a low valley by the end. [`MtdAnnealer`] is generic over the type of the
+//! states: the energy of a state and how to make a random change to it are
+//! given as closures.
+//! 
+//! Several chains are annealed at once on a [`ThreadPool`], each drawing
+//! from its own random stream. Every few steps, the chains stop and every
+//! chain carries on from the best state found by any of them so far, so
+//! that no chain wastes its steps in a poor valley. The [`Restart`]
+//! schedule decides when the temperature goes back to the start of its
+//! curve, letting the chains climb out of the valley they have settled in.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+};
+
+/// The number of steps of each chain unless another is set with
+/// [`MtdAnnealer::set_steps`].
+pub const DEFAULT_STEPS: usize = 10_000;
+/// The number of steps between exchanges unless another is set with
+/// [`MtdAnnealer::set_exchange_interval`].
+pub const DEFAULT_EXCHANGE_INTERVAL: usize = 1_000;
+
+/// Closure giving the energy of a state.
+type Energy<S> = Arc<dyn Fn(&S) -> f64 + Send + Sync + 'static>;
+/// Closure making a random change to a state.
+type Neighbour<S> = Arc<dyn Fn(&S, &mut Rng) -> S + Send + Sync + 'static>;
+/// Closure giving the temperature at some share of the way along a curve.
+type Curve = Arc<dyn Fn(f64) -> f64 + Send + Sync + 'static>;
+
+/// How the temperature falls from the start of its curve, `0`, to the end,
+/// `1`.
+#[derive(Clone)]
+pub enum Cooling {
+    /// Falls from `start` to `end` by the same factor at every step.
+    Geometric {start: f64, end: f64},
+    /// Falls from `start` to `end` by the same amount at every step.
+    Linear {start: f64, end: f64},
+    /// Given by a closure of how far along the curve it is, from `0` up to
+    /// `1`. The changes raising the energy are never kept at temperatures
+    /// which are not above `0`.
+    Custom(Curve),
+}
+
+impl Cooling {
+    /// The [`Cooling`] used unless another is set with
+    /// [`MtdAnnealer::set_cooling`], which falls from `1` to `0.001`
+    /// geometrically.
+    pub const DEFAULT: Self = Self::Geometric {start: 1.0, end: 1e-3};
+
+    /// Creates a [`Cooling::Custom`] curve from `curve`.
+    pub fn custom<F>(curve: F) -> Self
+    where
+        F: Fn(f64) -> f64 + Send + Sync + 'static,
+    {
+        return Self::Custom(Arc::new(curve));
+    }
+
+    /// Get the temperature `progress` of the way along the curve.
+    pub fn temperature(&self, progress: f64) -> f64 {
+        return match self {
+            Self::Geometric {start, end} => start * (end / start).powf(progress),
+            Self::Linear {start, end} => start + (end - start) * progress,
+            Self::Custom(curve) => curve(progress),
+        };
+    }
+}
+
+impl Default for Cooling {
+    fn default() -> Self {
+        return Self::DEFAULT;
+    }
+}
+
+/// When the temperature goes back to the start of its [`Cooling`] curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Restart {
+    /// The temperature follows the curve once over all the steps.
+    #[default]
+    Never,
+    /// The temperature follows the curve over this many steps, then starts
+    /// it again.
+    Every(usize),
+    /// The temperature follows the curve over all the steps, but starts it
+    /// again once this many exchanges in a row have not found a better
+    /// state.
+    Stalled(usize),
+}
+
+/// The best state found by [`MtdAnnealer::run`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Annealed<S> {
+    /// The state with the lowest energy found by any chain.
+    pub state: S,
+    /// The energy of [`Annealed::state`].
+    pub energy: f64,
+    /// The lowest energy found after each exchange.
+    pub history: Vec<f64>,
+    /// Number of times the temperature went back to the start of its
+    /// curve.
+    pub restarts: usize,
+}
+
+/// One chain of states being annealed.
+#[derive(Clone)]
+struct Chain<S> {
+    state: S,
+    energy: f64,
+    rng: Rng,
+}
+
+/// Where along the [`Cooling`] curve the steps of a segment are.
+#[derive(Clone, Copy)]
+struct Position {
+    /// The step the segment starts at, counted from the last restart.
+    step: usize,
+    /// Number of steps the curve is followed over.
+    period: usize,
+    /// Number of steps in the segment.
+    length: usize,
+}
+
+impl<S: Clone> Chain<S> {
+    /// Anneals a copy of the chain for the steps of `position`. Returns the
+    /// chain after them and the best state it passed through, with its
+    /// energy.
+    fn advance(
+        &self,
+        energy: &Energy<S>,
+        neighbour: &Neighbour<S>,
+        cooling: &Cooling,
+        position: Position,
+    ) -> (Self, (S, f64)) {
+        let mut chain = self.clone();
+        let mut best = (chain.state.clone(), chain.energy);
+        for step in position.step..position.step + position.length {
+            let progress = (step % position.period) as f64 / position.period as f64;
+            let temperature = cooling.temperature(progress);
+            let candidate = neighbour(&chain.state, &mut chain.rng);
+            let next = energy(&candidate);
+            let delta = next - chain.energy;
+            let chance = if temperature > 0.0 {
+                (-delta / temperature).exp()
+            } else {
+                0.0
+            };
+            let accepted = delta <= 0.0 || chain.rng.next_f64() < chance;
+            if accepted {
+                chain.state = candidate;
+                chain.energy = next;
+                if next < best.1 {
+                    best = (chain.state.clone(), next);
+                }
+            }
+        }
+        return (chain, best);
+    }
+}
+
+/// This `struct` anneals states of type `S` in several chains using
+/// multiple threads. See the module documentation.
+pub struct MtdAnnealer<S> {
+    pool: ThreadPool,
+    energy: Energy<S>,
+    neighbour: Neighbour<S>,
+    chains: usize,
+    steps: usize,
+    exchange_interval: usize,
+    cooling: Cooling,
+    restart: Restart,
+}
+
+impl<S> MtdAnnealer<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    /// Creates a new [`MtdAnnealer`] instance with one chain per thread,
+    /// [`DEFAULT_STEPS`], [`DEFAULT_EXCHANGE_INTERVAL`], [`Cooling::DEFAULT`]
+    /// and no restarts.
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```energy: E``` => Gives the energy of a state, which is to be as
+    ///    low as possible.
+    /// 3. ```neighbour: N``` => Makes a random change to a state, drawing
+    ///    from the given [`Rng`]. Small changes work best.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new<E, N>(threads: usize, energy: E, neighbour: N) -> Result<Self, Error>
+    where
+        E: Fn(&S) -> f64 + Send + Sync + 'static,
+        N: Fn(&S, &mut Rng) -> S + Send + Sync + 'static,
+    {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {
+            chains: pool.threads(),
+            pool,
+            energy: Arc::new(energy),
+            neighbour: Arc::new(neighbour),
+            steps: DEFAULT_STEPS,
+            exchange_interval: DEFAULT_EXCHANGE_INTERVAL,
+            cooling: Cooling::DEFAULT,
+            restart: Restart::Never,
+        });
+    }
+
+    /// Get the number of chains annealed at once.
+    pub fn chains(&self) -> usize {
+        return self.chains;
+    }
+
+    /// Sets the number of chains annealed at once.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `chains` is `0`, and the number
+    /// of chains is left as it was.
+    pub fn set_chains(&mut self, chains: usize) -> Result<(), Error> {
+        if chains == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "At least 1 chain is needed."
+            ));
+        }
+        self.chains = chains;
+        return Ok(());
+    }
+
+    /// Get the number of steps of each chain.
+    pub fn steps(&self) -> usize {
+        return self.steps;
+    }
+
+    /// Sets the number of steps of each chain.
+    pub fn set_steps(&mut self, steps: usize) {
+        self.steps = steps;
+    }
+
+    /// Get the number of steps between exchanges of the best state.
+    pub fn exchange_interval(&self) -> usize {
+        return self.exchange_interval;
+    }
+
+    /// Sets the number of steps between exchanges of the best state.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `exchange_interval` is `0`, and
+    /// the interval is left as it was.
+    pub fn set_exchange_interval(
+        &mut self,
+        exchange_interval: usize,
+    ) -> Result<(), Error> {
+        if exchange_interval == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The exchange interval must be at least 1 step."
+            ));
+        }
+        self.exchange_interval = exchange_interval;
+        return Ok(());
+    }
+
+    /// Get the curve the temperature falls along.
+    pub fn cooling(&self) -> &Cooling {
+        return &self.cooling;
+    }
+
+    /// Sets the curve the temperature falls along.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a [`Cooling::Geometric`] curve
+    /// does not start and end above `0`, or a [`Cooling::Linear`] curve does
+    /// not start above `0` and end at `0` or above, and the curve is left as
+    /// it was.
+    pub fn set_cooling(&mut self, cooling: Cooling) -> Result<(), Error> {
+        let valid = match cooling {
+            Cooling::Geometric {start, end} => start > 0.0 && end > 0.0,
+            Cooling::Linear {start, end} => start > 0.0 && end >= 0.0,
+            Cooling::Custom(_) => true,
+        };
+        if !valid {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The temperatures of the curve are out of range."
+            ));
+        }
+        self.cooling = cooling;
+        return Ok(());
+    }
+
+    /// Get when the temperature goes back to the start of its curve.
+    pub fn restart(&self) -> Restart {
+        return self.restart;
+    }
+
+    /// Sets when the temperature goes back to the start of its curve.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `restart` is
+    /// [`Restart::Every`] or [`Restart::Stalled`] with `0`, and the schedule
+    /// is left as it was.
+    pub fn set_restart(&mut self, restart: Restart) -> Result<(), Error> {
+        if matches!(restart, Restart::Every(0) | Restart::Stalled(0)) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "A restart schedule needs at least 1 step or exchange."
+            ));
+        }
+        self.restart = restart;
+        return Ok(());
+    }
+
+    /// Anneals every chain from `initial` and returns the best state found.
+    /// See the module documentation.
+    /// 
+    /// # Parameters
+    /// 1. ```initial: S``` => The state every chain starts from.
+    /// 2. ```seed: u64``` => Seed for the random streams of the chains. The
+    ///    same seed always gives the same result.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn run(&mut self, initial: S, seed: u64) -> Result<Annealed<S>, Error> {
+        let energy = (self.energy)(&initial);
+        let mut chains: Vec<Chain<S>> = (0..self.chains)
+            .map(|index| Chain {
+                state: initial.clone(),
+                energy,
+                rng: Rng::stream(seed, index as u64),
+            })
+            .collect();
+        let mut result = Annealed {
+            state: initial,
+            energy,
+            history: Vec::new(),
+            restarts: 0,
+        };
+        let period = match self.restart {
+            Restart::Every(steps) => steps,
+            Restart::Never | Restart::Stalled(_) => self.steps.max(1),
+        };
+
+        let (mut done, mut restarted, mut stalled) = (0, 0, 0);
+        while done < self.steps {
+            let position = Position {
+                step: done - restarted,
+                period,
+                length: self.exchange_interval.min(self.steps - done),
+            };
+            if let Restart::Every(steps) = self.restart {
+                result.restarts += (position.step..position.step + position.length)
+                    .filter(|step| *step > 0 && step % steps == 0)
+                    .count();
+            }
+            let (energy, neighbour) = (self.energy.clone(), self.neighbour.clone());
+            let cooling = self.cooling.clone();
+            let policy = ChunkPolicy::Fixed(1);
+            let advanced = self.pool.map(Arc::new(chains), policy, move |chain| {
+                return chain.advance(&energy, &neighbour, &cooling, position);
+            })?;
+            done += position.length;
+
+            let mut improved = false;
+            chains = Vec::with_capacity(advanced.len());
+            for (chain, (state, energy)) in advanced {
+                if energy < result.energy {
+                    result.state = state;
+                    result.energy = energy;
+                    improved = true;
+                }
+                chains.push(chain);
+            }
+            result.history.push(result.energy);
+            for chain in &mut chains {
+                chain.state.clone_from(&result.state);
+                chain.energy = result.energy;
+            }
+
+            stalled = if improved { 0 } else { stalled + 1 };
+            if let Restart::Stalled(exchanges) = self.restart {
+                if stalled >= exchanges {
+                    restarted = done;
+                    stalled = 0;
+                    result.restarts += 1;
+                }
+            }
+        }
+        return Ok(result);
+    }
+}
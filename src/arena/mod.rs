@@ -0,0 +1,264 @@
+//! This module contains the [`Arena`], a bump allocator for scratch data
+//! which only lives as long as a single job.
+//! 
+//! Each worker thread in a [`crate::pool::ThreadPool`] owns one [`Arena`],
+//! which can be borrowed from inside a job with [`with_arena`]. The worker
+//! resets its [`Arena`] after every job, so memory allocated from it is
+//! reused by the next job instead of going back to the global allocator.
+
+use std::{
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    mem::{align_of, size_of},
+    ptr::NonNull,
+    slice::from_raw_parts_mut,
+};
+
+/// Default size of each chunk of memory in bytes.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The unit chunks are made out of. Its alignment is the largest alignment
+/// the [`Arena`] can hand out.
+#[repr(align(16))]
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+struct Block([u8; 16]);
+
+/// A chunk of memory owned by the [`Arena`], stored as a raw pointer so that
+/// slices handed out from it are never invalidated by borrowing the chunk
+/// list again.
+struct Chunk {
+    start: NonNull<Block>,
+    blocks: usize,
+}
+
+impl Chunk {
+    /// Allocates a new zeroed [`Chunk`] which can hold at least `bytes`
+    /// bytes.
+    fn new(bytes: usize) -> Self {
+        let blocks = max_blocks(bytes);
+        let memory = vec![Block([0; 16]); blocks].into_boxed_slice();
+        let start = NonNull::new(Box::into_raw(memory) as *mut Block)
+            .expect("Box::into_raw never returns null.");
+        return Self {start, blocks};
+    }
+
+    /// Size of the [`Chunk`] in bytes.
+    fn len(&self) -> usize {
+        return self.blocks * size_of::<Block>();
+    }
+}
+
+impl Drop for Chunk {
+    fn drop(&mut self) {
+        // The pointer came from Box::into_raw in Chunk::new with the same
+        // length, and the Arena guarantees no slices outlive it.
+        unsafe {
+            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                self.start.as_ptr(),
+                self.blocks,
+            )));
+        }
+    }
+}
+
+/// Number of [`Block`]s needed to hold `bytes` bytes.
+fn max_blocks(bytes: usize) -> usize {
+    return bytes.div_ceil(size_of::<Block>()).max(1);
+}
+
+/// A bump allocator for [`Copy`] scratch data.
+/// 
+/// Allocating from an [`Arena`] only moves an offset forward, and
+/// [`Arena::reset`] makes all of its memory available again without
+/// returning it to the global allocator. Growable structures such as
+/// [`Vec`] and [`std::collections::BinaryHeap`] cannot live inside the
+/// [`Arena`] on stable Rust, so their buffers can be recycled through
+/// [`Arena::take_vec`] and [`Arena::give_vec`] instead.
+pub struct Arena {
+    chunk_size: usize,
+    chunks: RefCell<Vec<Chunk>>,
+    current: Cell<usize>,
+    offset: Cell<usize>,
+    recycled: RefCell<HashMap<TypeId, Box<dyn Any>>>,
+}
+
+impl Arena {
+    /// Creates a new [`Arena`] which allocates chunks of
+    /// [`DEFAULT_CHUNK_SIZE`] bytes.
+    pub fn new() -> Self {
+        return Self::with_chunk_size(DEFAULT_CHUNK_SIZE);
+    }
+
+    /// Creates a new [`Arena`] which allocates chunks of `chunk_size` bytes.
+    /// Allocations larger than `chunk_size` get a chunk of their own.
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        return Self {
+            chunk_size: chunk_size.max(1),
+            chunks: RefCell::new(Vec::new()),
+            current: Cell::new(0),
+            offset: Cell::new(0),
+            recycled: RefCell::new(HashMap::new()),
+        };
+    }
+
+    /// Allocates a slice of `len` items, each set to `value`, which lives
+    /// until the [`Arena`] is reset.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `T` needs an alignment greater than 16 bytes.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice<T: Copy>(&self, len: usize, value: T) -> &mut [T] {
+        assert!(
+            align_of::<T>() <= align_of::<Block>(),
+            "The Arena cannot align types to more than 16 bytes."
+        );
+        let bytes = size_of::<T>()
+            .checked_mul(len)
+            .expect("Arena allocation size overflowed.");
+        if bytes == 0 {
+            let start = NonNull::<T>::dangling().as_ptr();
+            // Zero sized allocations never touch memory.
+            return unsafe {from_raw_parts_mut(start, len)};
+        }
+
+        let start = self.bump(bytes, align_of::<T>()) as *mut T;
+        // `bump` returned `bytes` bytes which are suitably aligned for `T`
+        // and not handed out to anybody else until the Arena is reset.
+        unsafe {
+            for index in 0..len {
+                start.add(index).write(value);
+            }
+            return from_raw_parts_mut(start, len);
+        }
+    }
+
+    /// Allocates a single `value` which lives until the [`Arena`] is reset.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: Copy>(&self, value: T) -> &mut T {
+        return &mut self.alloc_slice(1, value)[0];
+    }
+
+    /// Reserves `bytes` bytes aligned to `align` and returns a pointer to
+    /// them, moving onto the next chunk (or allocating a new one) when the
+    /// current chunk is full.
+    fn bump(&self, bytes: usize, align: usize) -> *mut u8 {
+        let mut chunks = self.chunks.borrow_mut();
+        loop {
+            let current = self.current.get();
+            if current >= chunks.len() {
+                chunks.push(Chunk::new(self.chunk_size.max(bytes)));
+            }
+            let chunk = &chunks[current];
+            let start = self.offset.get().next_multiple_of(align);
+            if start + bytes <= chunk.len() {
+                self.offset.set(start + bytes);
+                // `start + bytes` is within the chunk.
+                return unsafe {(chunk.start.as_ptr() as *mut u8).add(start)};
+            }
+            self.current.set(current + 1);
+            self.offset.set(0);
+        }
+    }
+
+    /// Takes a cleared [`Vec`] out of the [`Arena`], reusing the buffer of a
+    /// [`Vec`] of the same type previously returned with
+    /// [`Arena::give_vec`] if there is one.
+    pub fn take_vec<T: 'static>(&self) -> Vec<T> {
+        let mut recycled = self.recycled.borrow_mut();
+        return match recycled.remove(&TypeId::of::<Vec<T>>()) {
+            Some(vec) => match vec.downcast::<Vec<T>>() {
+                Ok(vec) => *vec,
+                Err(_error) => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+    }
+
+    /// Gives a [`Vec`] back to the [`Arena`] so its buffer can be reused by
+    /// the next call to [`Arena::take_vec`]. The [`Vec`] is cleared first.
+    pub fn give_vec<T: 'static>(&self, mut vec: Vec<T>) {
+        vec.clear();
+        let mut recycled = self.recycled.borrow_mut();
+        let replace = match recycled.get(&TypeId::of::<Vec<T>>()) {
+            Some(existing) => match existing.downcast_ref::<Vec<T>>() {
+                Some(existing) => existing.capacity() < vec.capacity(),
+                None => true,
+            },
+            None => true,
+        };
+        if replace {
+            recycled.insert(TypeId::of::<Vec<T>>(), Box::new(vec));
+        }
+    }
+
+    /// Number of bytes the [`Arena`] holds in its chunks.
+    pub fn capacity(&self) -> usize {
+        return self.chunks.borrow().iter().map(Chunk::len).sum();
+    }
+
+    /// Makes all memory in the [`Arena`] available again. Recycled
+    /// [`Vec`]s are kept.
+    pub fn reset(&mut self) {
+        self.current.set(0);
+        self.offset.set(0);
+    }
+
+    /// Resets the [`Arena`] and gives all of its memory, including recycled
+    /// [`Vec`]s, back to the global allocator.
+    pub fn shrink(&mut self) {
+        self.reset();
+        self.chunks.get_mut().clear();
+        self.recycled.get_mut().clear();
+    }
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+thread_local! {
+    /// The [`Arena`] belonging to the current thread.
+    static LOCAL_ARENA: RefCell<Arena> = RefCell::new(Arena::new());
+}
+
+/// Runs `function` with the [`Arena`] belonging to the current thread.
+/// 
+/// Inside a job sent to a [`crate::pool::ThreadPool`], this is the [`Arena`]
+/// of the worker running the job, and everything allocated from it is
+/// released once the job returns.
+/// 
+/// # Panics
+/// 
+/// Panics if called from inside [`reset_local`], which cannot happen from
+/// within a job.
+pub fn with_arena<F, R>(function: F) -> R
+where
+    F: FnOnce(&Arena) -> R,
+{
+    return LOCAL_ARENA.with(|arena| function(&arena.borrow()));
+}
+
+/// Resets the [`Arena`] belonging to the current thread. Worker threads call
+/// this after every job.
+pub(crate) fn reset_local() {
+    LOCAL_ARENA.with(|arena| {
+        if let Ok(mut arena) = arena.try_borrow_mut() {
+            arena.reset();
+        }
+    });
+}
+
+/// Shrinks the [`Arena`] belonging to the current thread. Worker threads call
+/// this when memory pressure is signalled.
+pub(crate) fn shrink_local() {
+    LOCAL_ARENA.with(|arena| {
+        if let Ok(mut arena) = arena.try_borrow_mut() {
+            arena.shrink();
+        }
+    });
+}
@@ -0,0 +1,169 @@
+//! Module for backtracking, which finds every solution to a problem by
+//! extending a partial solution one choice at a time, and undoing the last
+//! choice whenever no valid choice is left.
+//! 
+//! A problem is described by implementing [`Backtrack`] on a partial
+//! solution, which gives the candidates for the next choice, whether a
+//! candidate is valid, how to make and undo a choice, and whether the
+//! partial solution is complete.
+//! 
+//! The top levels of the search tree are expanded on the calling thread
+//! until there are at least [`SUBTREES_PER_THREAD`] subtrees for every
+//! thread of the [`ThreadPool`]. Each subtree is then searched depth first
+//! by its own job, making and undoing choices in place. The subtrees are
+//! kept in the order a search on one thread would reach them, so
+//! [`solutions`] gives the same solutions in the same order whatever the
+//! number of threads.
+//! 
+//! [`count_queens`] counts the solutions to the N-queens problem this way,
+//! and [`Queens`] shows how to implement [`Backtrack`].
+
+mod queens;
+
+use std::{io::Error, sync::Arc};
+
+pub use queens::{MAX_QUEENS, Queens, count_queens};
+
+use crate::pool::{ThreadPool, chunk::ChunkPolicy};
+
+/// The number of subtrees the top levels of the search tree are split into
+/// for each thread, so that threads which finish early can take another.
+pub const SUBTREES_PER_THREAD: usize = 8;
+
+/// A partial solution which can be extended one choice at a time.
+pub trait Backtrack: Clone + Send + Sync + 'static {
+    /// A choice which can extend a partial solution.
+    type Candidate;
+
+    /// The candidates for the next choice, in the order they are tried.
+    /// Only those which are [`Backtrack::is_valid`] are made.
+    fn candidates(&self) -> Vec<Self::Candidate>;
+
+    /// Whether making `candidate` the next choice keeps the partial
+    /// solution valid.
+    fn is_valid(&self, candidate: &Self::Candidate) -> bool;
+
+    /// Makes `candidate` the next choice.
+    fn push(&mut self, candidate: Self::Candidate);
+
+    /// Undoes the last choice made with [`Backtrack::push`].
+    fn pop(&mut self);
+
+    /// Whether the partial solution is a complete solution, which is not
+    /// extended any further.
+    fn is_complete(&self) -> bool;
+}
+
+/// Searches every extension of `state` depth first, calling `visit` on
+/// each complete solution until it returns `false`. Returns whether the
+/// search should carry on.
+fn explore<P, F>(state: &mut P, visit: &mut F) -> bool
+where
+    P: Backtrack,
+    F: FnMut(&P) -> bool,
+{
+    if state.is_complete() {
+        return visit(state);
+    }
+    for candidate in state.candidates() {
+        if !state.is_valid(&candidate) {
+            continue;
+        }
+        state.push(candidate);
+        let carry_on = explore(state, visit);
+        state.pop();
+        if !carry_on {
+            return false;
+        }
+    }
+    return true;
+}
+
+/// Expands `root` level by level until there are at least `wanted`
+/// subtrees or every subtree is a complete solution, keeping the subtrees
+/// in the order a depth first search would reach them.
+fn split<P: Backtrack>(root: P, wanted: usize) -> Vec<P> {
+    let mut frontier: Vec<P> = vec![root];
+    while frontier.len() < wanted {
+        let mut next: Vec<P> = Vec::with_capacity(frontier.len());
+        let mut expanded = false;
+        for state in frontier {
+            if state.is_complete() {
+                next.push(state);
+                continue;
+            }
+            expanded = true;
+            for candidate in state.candidates() {
+                if state.is_valid(&candidate) {
+                    let mut child = state.clone();
+                    child.push(candidate);
+                    next.push(child);
+                }
+            }
+        }
+        frontier = next;
+        if !expanded {
+            break;
+        }
+    }
+    return frontier;
+}
+
+/// Counts the complete solutions extending `root`. See the module
+/// documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads searching the subtrees.
+/// 2. ```root: P``` => The partial solution to extend.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn count<P: Backtrack>(pool: &mut ThreadPool, root: P) -> Result<u64, Error> {
+    let subtrees = split(root, pool.threads() * SUBTREES_PER_THREAD);
+    let counts = pool.map(Arc::new(subtrees), ChunkPolicy::Fixed(1), |subtree| {
+        let mut state = subtree.clone();
+        let mut found: u64 = 0;
+        explore(&mut state, &mut |_: &P| {
+            found += 1;
+            return true;
+        });
+        return found;
+    })?;
+    return Ok(counts.into_iter().sum());
+}
+
+/// Finds the complete solutions extending `root`, in the order a search on
+/// one thread would find them. See the module documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads searching the subtrees.
+/// 2. ```root: P``` => The partial solution to extend.
+/// 3. ```limit: usize``` => The most solutions to return. Each subtree
+///    stops being searched once it has found this many.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn solutions<P: Backtrack>(
+    pool: &mut ThreadPool,
+    root: P,
+    limit: usize,
+) -> Result<Vec<P>, Error> {
+    if limit == 0 {
+        return Ok(Vec::new());
+    }
+    let subtrees = split(root, pool.threads() * SUBTREES_PER_THREAD);
+    let found = pool.map(Arc::new(subtrees), ChunkPolicy::Fixed(1), move |subtree| {
+        let mut state = subtree.clone();
+        let mut found: Vec<P> = Vec::new();
+        explore(&mut state, &mut |solution: &P| {
+            found.push(solution.clone());
+            return found.len() < limit;
+        });
+        return found;
+    })?;
+    let mut solutions: Vec<P> = found.into_iter().flatten().collect();
+    solutions.truncate(limit);
+    return Ok(solutions);
+}
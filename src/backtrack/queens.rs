@@ -0,0 +1,119 @@
+//! The N-queens problem, which asks for every way of placing `n` queens on
+//! an `n` by `n` board so that no 2 queens share a row, column or diagonal.
+//! 
+//! One queen is placed per row, top to bottom, and the columns and
+//! diagonals already taken are kept as bits, so checking a candidate and
+//! undoing a choice take constant time.
+
+use std::io::{Error, ErrorKind};
+
+use super::{Backtrack, count};
+use crate::pool::ThreadPool;
+
+/// The largest board [`Queens`] can hold.
+pub const MAX_QUEENS: usize = 64;
+
+/// Queens placed on the top rows of a board, one per row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Queens {
+    size: usize,
+    placed: Vec<usize>,
+    columns: u128,
+    rising: u128,
+    falling: u128,
+}
+
+impl Queens {
+    /// Creates an empty board.
+    /// 
+    /// # Parameters
+    /// 1. ```size: usize``` => The number of rows and columns of the board.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `size` is more than
+    /// [`MAX_QUEENS`].
+    pub fn new(size: usize) -> Result<Self, Error> {
+        if size > MAX_QUEENS {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} exceeds the largest board of {}.", size, MAX_QUEENS)
+            ));
+        }
+        return Ok(Self {size, placed: Vec::new(), columns: 0, rising: 0, falling: 0});
+    }
+
+    /// Get the number of rows and columns of the board.
+    pub fn size(&self) -> usize {
+        return self.size;
+    }
+
+    /// Get the column of the queen on each row placed so far.
+    pub fn columns(&self) -> &[usize] {
+        return &self.placed;
+    }
+
+    /// Get the bits of the column and diagonals `column` is on in the next
+    /// row.
+    fn bits(&self, column: usize) -> (u128, u128, u128) {
+        let row = self.placed.len();
+        return (
+            1 << column,
+            1 << (row + column),
+            1 << (row + self.size - 1 - column),
+        );
+    }
+}
+
+impl Backtrack for Queens {
+    type Candidate = usize;
+
+    fn candidates(&self) -> Vec<usize> {
+        return (0..self.size).collect();
+    }
+
+    fn is_valid(&self, column: &usize) -> bool {
+        let (column, rising, falling) = self.bits(*column);
+        return self.columns & column == 0
+            && self.rising & rising == 0
+            && self.falling & falling == 0;
+    }
+
+    fn push(&mut self, column: usize) {
+        let (columns, rising, falling) = self.bits(column);
+        self.columns |= columns;
+        self.rising |= rising;
+        self.falling |= falling;
+        self.placed.push(column);
+    }
+
+    fn pop(&mut self) {
+        let Some(column) = self.placed.pop() else {
+            return;
+        };
+        let (columns, rising, falling) = self.bits(column);
+        self.columns &= !columns;
+        self.rising &= !rising;
+        self.falling &= !falling;
+    }
+
+    fn is_complete(&self) -> bool {
+        return self.placed.len() == self.size;
+    }
+}
+
+/// Counts the ways of placing `size` queens on a `size` by `size` board
+/// with no 2 queens attacking each other. See the module documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads searching the placements.
+/// 2. ```size: usize``` => The number of queens and of rows and columns of
+///    the board.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `size` is more than [`MAX_QUEENS`],
+/// or if a job on the [`ThreadPool`] fails.
+pub fn count_queens(pool: &mut ThreadPool, size: usize) -> Result<u64, Error> {
+    return count(pool, Queens::new(size)?);
+}
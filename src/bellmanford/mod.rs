@@ -0,0 +1,243 @@
+//! Module for the Bellman-Ford Algorithm, which finds the cheapest routes
+//! from one source [`Node`] like [`crate::dijkstra`] but also accepts edges
+//! with negative costs.
+//! 
+//! The algorithm relaxes every edge of the graph in passes, and after `k`
+//! passes the costs of all routes with at most `k` edges are known. Each
+//! pass only reads the costs from the pass before it, so the [`Node`]s are
+//! split between the workers of a [`ThreadPool`], each working out the new
+//! costs of its own [`Node`]s from the edges going into them. The passes
+//! stop as soon as nothing changes. If the costs still change after as many
+//! passes as there are [`Node`]s, a route can keep getting cheaper by going
+//! around a negative cycle, and that cycle is returned instead.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    dijkstra::{
+        signed::{SignedCost, SignedMatrix},
+        simple::{JOBS_PER_THREAD, Node, check_node},
+    },
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+};
+
+/// The cheapest routes from one source [`Node`] found by
+/// [`MtdBellmanFord::shortest_from`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignedPaths {
+    /// The source [`Node`] of every route.
+    pub source: Node,
+    /// The [`SignedCost`] of reaching each [`Node`], with [`None`] marking
+    /// [`Node`]s which cannot be reached.
+    pub costs: Vec<Option<SignedCost>>,
+    /// The [`Node`] visited just before each [`Node`] on its cheapest route.
+    pub previous: Vec<Option<Node>>,
+}
+
+impl SignedPaths {
+    /// Get the [`Node`]s visited on the cheapest route from the source to
+    /// `target` in order, or [`None`] if `target` cannot be reached.
+    pub fn path_to(&self, target: Node) -> Option<Vec<Node>> {
+        self.costs.get(target).copied().flatten()?;
+        let mut nodes = vec![target];
+        let mut node = target;
+        while node != self.source {
+            node = self.previous[node]?;
+            nodes.push(node);
+        }
+        nodes.reverse();
+        return Some(nodes);
+    }
+}
+
+/// What [`MtdBellmanFord::shortest_from`] found.
+/// 
+/// # Variants
+/// 
+/// 1. Paths([`SignedPaths`]) => The cheapest routes to every [`Node`].
+/// 2. NegativeCycle([`Vec<Node>`]) => A cycle which can be reached from the
+///    source and whose edges cost less than nothing in total, in the order
+///    it is travelled, so no route through it has a cheapest cost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BellmanFordResult {
+    Paths(SignedPaths),
+    NegativeCycle(Vec<Node>),
+}
+
+/// The edges going into each [`Node`], as `(from, cost)`.
+type Incoming = Vec<Vec<(Node, SignedCost)>>;
+
+/// The [`Node`]s which got cheaper in a pass, as `(node, cost, from)`.
+type Updates = Vec<(Node, SignedCost, Node)>;
+
+/// This `struct` runs the Bellman-Ford Algorithm on a [`SignedMatrix`] using
+/// multiple threads. See the module documentation.
+pub struct MtdBellmanFord {
+    pool: ThreadPool,
+    incoming: Arc<Incoming>,
+}
+
+impl MtdBellmanFord {
+    /// Creates a new [`MtdBellmanFord`] instance.
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &SignedMatrix``` => The graph, which may have edges with
+    ///    negative costs.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize, matrix: &SignedMatrix) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        let mut incoming: Incoming = vec![Vec::new(); matrix.total()];
+        for (from, to, cost) in matrix.iter() {
+            incoming[to].push((from, cost));
+        }
+        return Ok(Self {pool, incoming: Arc::new(incoming)});
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.incoming.len();
+    }
+
+    /// Relaxes every edge once against `costs` on the [`ThreadPool`],
+    /// returning the [`Node`]s which got cheaper with their new
+    /// [`SignedCost`] and the [`Node`] the cheaper route comes from, or
+    /// [`None`] if the cost of a route does not fit in a [`SignedCost`].
+    fn pass(
+        &mut self,
+        ranges: &Arc<Vec<(Node, Node)>>,
+        costs: &Arc<Vec<Option<SignedCost>>>,
+    ) -> Result<Option<Updates>, Error> {
+        let incoming = self.incoming.clone();
+        let costs = costs.clone();
+        let policy = ChunkPolicy::Fixed(1);
+        let updates = self.pool.map(ranges.clone(), policy, move |range| {
+            let mut updates: Updates = Vec::new();
+            for node in range.0..range.1 {
+                let mut best = costs[node];
+                let mut from_node = None;
+                for (from, cost) in &incoming[node] {
+                    let through = match costs[*from] {
+                        Some(before) => before.checked_add(*cost)?,
+                        None => continue,
+                    };
+                    if best.is_none_or(|best| through < best) {
+                        best = Some(through);
+                        from_node = Some(*from);
+                    }
+                }
+                if let (Some(best), Some(from)) = (best, from_node) {
+                    updates.push((node, best, from));
+                }
+            }
+            return Some(updates);
+        })?;
+        let updates: Option<Vec<Updates>> = updates.into_iter().collect();
+        return Ok(updates.map(|updates| updates.into_iter().flatten().collect()));
+    }
+
+    /// Finds the cheapest route from `source` to every [`Node`], or a
+    /// negative cycle which can be reached from `source`.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` is not in the graph or a
+    /// job on the [`ThreadPool`] fails. If the cost of a route does not fit
+    /// in a [`SignedCost`], the negative cycle which drove it down is
+    /// returned if one can be traced, and otherwise an error of kind
+    /// [`ErrorKind::InvalidData`] is returned.
+    pub fn shortest_from(&mut self, source: Node) -> Result<BellmanFordResult, Error> {
+        let total = self.total();
+        check_node(source, total)?;
+        let pieces = self.pool.threads() * JOBS_PER_THREAD;
+        let ranges = Arc::new(ranges(self.total(), pieces));
+        let mut costs: Vec<Option<SignedCost>> = vec![None; total];
+        let mut previous: Vec<Option<Node>> = vec![None; total];
+        costs[source] = Some(0);
+
+        // Every cheapest route has fewer edges than there are Nodes, so
+        // one more pass only changes anything if there is a negative cycle.
+        // Routes around the cycle may need a few more passes to show up in
+        // `previous`, so the passes go on until they do.
+        let mut passes: usize = 0;
+        loop {
+            let shared = Arc::new(costs);
+            let updates = self.pass(&ranges, &shared)?;
+            costs = Arc::try_unwrap(shared).unwrap_or_else(|shared| (*shared).clone());
+            let Some(updates) = updates else {
+                if let Some(cycle) = find_cycle(&previous, 0..total) {
+                    return Ok(BellmanFordResult::NegativeCycle(cycle));
+                }
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "The cost of a route does not fit in a SignedCost."
+                ));
+            };
+            if updates.is_empty() {
+                let paths = SignedPaths {source, costs, previous};
+                return Ok(BellmanFordResult::Paths(paths));
+            }
+            for (node, cost, from) in &updates {
+                costs[*node] = Some(*cost);
+                previous[*node] = Some(*from);
+            }
+            passes += 1;
+            if passes >= total {
+                let starts = updates.iter().map(|(node, _, _)| *node);
+                if let Some(cycle) = find_cycle(&previous, starts) {
+                    return Ok(BellmanFordResult::NegativeCycle(cycle));
+                }
+            }
+            if passes > 2 * total {
+                return Err(Error::other("The negative cycle could not be traced."));
+            }
+        }
+    }
+}
+
+/// Follows the [`Node`]s before each of `starts` in `previous` and returns
+/// the first cycle found, in the order it is travelled.
+fn find_cycle<I>(previous: &[Option<Node>], starts: I) -> Option<Vec<Node>>
+where
+    I: IntoIterator<Item = Node>,
+{
+    // The walk from `start` which last visited each Node.
+    let mut visited: Vec<Option<Node>> = vec![None; previous.len()];
+    for start in starts {
+        let mut node = start;
+        loop {
+            match visited[node] {
+                Some(walk) if walk == start => {
+                    // `node` was seen earlier on this walk, so it is on a
+                    // cycle which is read off backwards from here.
+                    let mut cycle = vec![node];
+                    let mut before = previous[node]?;
+                    while before != node {
+                        cycle.push(before);
+                        before = previous[before]?;
+                    }
+                    cycle.reverse();
+                    return Some(cycle);
+                },
+                // An earlier walk already followed this route.
+                Some(_) => break,
+                None => visited[node] = Some(start),
+            }
+            node = match previous[node] {
+                Some(before) => before,
+                None => break,
+            };
+        }
+    }
+    return None;
+}
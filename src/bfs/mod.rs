@@ -0,0 +1,179 @@
+//! Module for Breadth-First Search, which finds the fewest number of edges
+//! needed to reach every [`Node`] of a graph from a source [`Node`].
+//! 
+//! The costs of the edges are ignored, so there is no need for the binary
+//! heap of the Dijkstra Algorithm. [`MtdBfs`] visits the graph one level at
+//! a time: every [`Node`] on the frontier is a number of hops away from the
+//! source, and the [`Node`]s it leads to which were not seen yet make up the
+//! next frontier. Large frontiers are split between the workers of a
+//! [`ThreadPool`], which claim the [`Node`]s they find without locking, so
+//! each [`Node`] joins the next frontier only once.
+
+use std::{
+    io::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, Node, check_node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// Frontiers with fewer [`Node`]s than this are expanded on the calling
+/// thread, since sending them to the [`ThreadPool`] takes longer.
+pub const SEQUENTIAL_FRONTIER: usize = 64;
+
+/// Marks a [`Node`] without a parent in the shared list of parents.
+const UNCLAIMED: usize = usize::MAX;
+
+/// The [`Node`]s reached by [`MtdBfs::search`] from one source [`Node`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BfsTree {
+    /// The [`Node`] the search started from.
+    pub source: Node,
+    /// Number of edges on the shortest route to each [`Node`], with
+    /// [`None`] marking [`Node`]s which cannot be reached.
+    pub hops: Vec<Option<usize>>,
+    /// The [`Node`] each [`Node`] was first reached from. The source and
+    /// the [`Node`]s which cannot be reached have none.
+    pub parents: Vec<Option<Node>>,
+}
+
+impl BfsTree {
+    /// Number of [`Node`]s reached, including the source.
+    pub fn reached(&self) -> usize {
+        return self.hops.iter().filter(|hops| hops.is_some()).count();
+    }
+
+    /// Get the [`Node`]s on a route from the source to `target` with the
+    /// fewest edges, in order, or [`None`] if `target` cannot be reached.
+    pub fn path_to(&self, target: Node) -> Option<Vec<Node>> {
+        self.hops.get(target).copied().flatten()?;
+        let mut nodes = vec![target];
+        let mut node = target;
+        while node != self.source {
+            node = self.parents[node]?;
+            nodes.push(node);
+        }
+        nodes.reverse();
+        return Some(nodes);
+    }
+}
+
+/// Claims every unclaimed [`Node`] `node` leads to in `parents`, and returns
+/// the [`Node`]s claimed.
+fn expand(graph: &CsrGraph, parents: &[AtomicUsize], node: Node) -> Vec<Node> {
+    let mut claimed: Vec<Node> = Vec::new();
+    for adjacent in graph.get_node(node).unwrap_or(&[]) {
+        let parent = match parents.get(adjacent.node) {
+            Some(parent) => parent,
+            None => continue,
+        };
+        if parent.load(SeqCst) == UNCLAIMED
+            && parent.compare_exchange(UNCLAIMED, node, SeqCst, SeqCst).is_ok()
+        {
+            claimed.push(adjacent.node);
+        }
+    }
+    return claimed;
+}
+
+/// This `struct` runs Breadth-First Searches over a graph using multiple
+/// threads. See the module documentation.
+pub struct MtdBfs {
+    pool: ThreadPool,
+    graph: Arc<CsrGraph>,
+}
+
+impl MtdBfs {
+    /// Creates a new [`MtdBfs`] instance.
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix``` => The graph. The [`Cost`]s of its
+    ///    edges are ignored.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    /// 
+    /// [`Cost`]: crate::dijkstra::simple::Cost
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix) -> Result<Self, Error> {
+        return Self::from_csr(threads, CsrGraph::from(matrix));
+    }
+
+    /// Creates a new [`MtdBfs`] instance from a graph which is already a
+    /// [`CsrGraph`]. See [`MtdBfs::new`].
+    pub fn from_csr(threads: usize, graph: CsrGraph) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {pool, graph: Arc::new(graph)});
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.graph.total();
+    }
+
+    /// Finds the fewest number of edges needed to reach every [`Node`] from
+    /// `source`, and the [`Node`] each one is reached from.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` is not in the graph or a
+    /// job on the [`ThreadPool`] fails.
+    pub fn search(&mut self, source: Node) -> Result<BfsTree, Error> {
+        let total = self.total();
+        check_node(source, total)?;
+        let parents: Arc<Vec<AtomicUsize>> = Arc::new(
+            (0..total).map(|_| AtomicUsize::new(UNCLAIMED)).collect()
+        );
+        parents[source].store(source, SeqCst);
+        let mut hops: Vec<Option<usize>> = vec![None; total];
+        hops[source] = Some(0);
+
+        let mut frontier: Vec<Node> = vec![source];
+        let mut level: usize = 0;
+        while !frontier.is_empty() {
+            level += 1;
+            let next: Vec<Node> = if frontier.len() < SEQUENTIAL_FRONTIER {
+                frontier
+                    .iter()
+                    .flat_map(|node| expand(&self.graph, &parents, *node))
+                    .collect()
+            } else {
+                let graph = self.graph.clone();
+                let shared = parents.clone();
+                self.pool
+                    .map(Arc::new(frontier), ChunkPolicy::Auto, move |node| {
+                        return expand(&graph, &shared, *node);
+                    })?
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            };
+            for node in &next {
+                hops[*node] = Some(level);
+            }
+            frontier = next;
+        }
+
+        let parents = parents
+            .iter()
+            .enumerate()
+            .map(|(node, parent)| {
+                return match parent.load(SeqCst) {
+                    UNCLAIMED => None,
+                    _ if node == source => None,
+                    parent => Some(parent),
+                };
+            })
+            .collect();
+        return Ok(BfsTree {source, hops, parents});
+    }
+}
@@ -0,0 +1,72 @@
+#![allow(clippy::needless_return)]
+
+use std::{
+    io::Error,
+    process::exit,
+    time::{Duration, Instant},
+};
+
+use mtdalgos::{
+    dijkstra::simple::{AdjacencyMatrix, MtdDijkstra, NodeWithCost},
+    rng::Rng,
+};
+
+/// Number of nodes in the benchmark graph.
+const NODES: usize = 3000;
+
+/// Number of edges leaving each node in the benchmark graph.
+const DEGREE: usize = 8;
+
+/// Builds the same random graph every time.
+fn graph() -> Result<AdjacencyMatrix, Error> {
+    let mut rng = Rng::new(7);
+    let mut matrix = AdjacencyMatrix::new(NODES);
+    for from in 0..NODES {
+        for _ in 0..DEGREE {
+            let to = rng.below(NODES);
+            let cost = rng.below(1000) as u128 + 1;
+            matrix.push(from, NodeWithCost::new(to, cost))?;
+        }
+    }
+    return Ok(matrix);
+}
+
+/// Calculates every row of the benchmark graph with `threads` threads and
+/// returns the time taken.
+fn time(threads: usize) -> Result<Duration, Error> {
+    let mut processor = MtdDijkstra::new(threads, NODES, graph()?)?;
+    let start = Instant::now();
+    processor.calculate()?;
+    if processor.get(0).is_none() {
+        return Err(Error::other("A job failed."));
+    }
+    return Ok(start.elapsed());
+}
+
+fn run() -> Result<(), Error> {
+    println!(
+        "All-pairs over {} nodes and {} edges.",
+        NODES,
+        NODES * DEGREE
+    );
+    for threads in [1, 2, 4, 8] {
+        let elapsed = time(threads)?;
+        println!(
+            "{:>2} threads: {:>10.3?}  {:>8.0} sources/s",
+            threads,
+            elapsed,
+            NODES as f64 / elapsed.as_secs_f64(),
+        );
+    }
+    return Ok(());
+}
+
+fn main() {
+    match run() {
+        Ok(_) => exit(0),
+        Err(error) => {
+            println!("{:?}", error);
+            exit(1)
+        },
+    }
+}
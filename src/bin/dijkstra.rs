@@ -1,3 +1,5 @@
+#![allow(clippy::needless_return)]
+
 use std::{
     io::Error,
     process::exit,
@@ -5,29 +7,22 @@ use std::{
     //time::Duration,
 };
 
-use mtdalgos::dijkstra::simple::{AdjacencyMatrix, NodeWithCost, MtdDijkstra};
-
-macro_rules! pushtomatrix {
-    ($matrix: expr, $from: expr, $to: expr, $cost: expr) => {
-        $matrix.push($from, NodeWithCost::new($to, $cost))?;
-    };
-}
+use mtdalgos::{dijkstra::simple::MtdDijkstra, graph};
 
 fn run() -> Result<(), Error> {
     //let delay = 500;
     //println!("Running dijkstra with delay of {}ms.", delay);
-    let mut matrix = AdjacencyMatrix::new(5);
-    pushtomatrix!(matrix, 0, 1, 1);
-    pushtomatrix!(matrix, 1, 2, 1);
-    pushtomatrix!(matrix, 2, 3, 2);
-    pushtomatrix!(matrix, 3, 4, 1);
-    pushtomatrix!(matrix, 2, 4, 6);
+    let matrix = graph!{
+        0 -> 1: 1,
+        1 -> 2: 1,
+        2 -> 3: 2,
+        3 -> 4: 1,
+        2 -> 4: 6,
+    }?;
     let mut processor = MtdDijkstra::new(3, 5, matrix)?;
     processor.calculate()?;
     //sleep(Duration::from_millis(delay));
-    for node in 0..5 {
-        println!("{}: {:?}", node, processor.get(node));
-    }
+    print!("{}", processor.render_table()?);
     return Ok(());
 }
 
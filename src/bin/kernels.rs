@@ -0,0 +1,66 @@
+#![allow(clippy::needless_return)]
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use mtdalgos::kernels::{self, scalar};
+
+/// Number of items in each input.
+const ITEMS: usize = 1 << 16;
+
+/// Number of times each kernel is run.
+const ROUNDS: usize = 2000;
+
+/// Runs `function` [`ROUNDS`] times and returns the total time taken.
+fn time<F: FnMut()>(mut function: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        function();
+    }
+    return start.elapsed();
+}
+
+/// Prints how long the scalar and dispatched versions of a kernel took.
+fn report(name: &str, scalar: Duration, dispatched: Duration) {
+    println!(
+        "{:<12} scalar: {:>10.3?}  dispatched: {:>10.3?}  speedup: {:.2}x",
+        name,
+        scalar,
+        dispatched,
+        scalar.as_secs_f64() / dispatched.as_secs_f64(),
+    );
+}
+
+fn main() {
+    println!("SIMD kernels in use: {}", kernels::simd_available());
+    let a: Vec<f64> = (0..ITEMS).map(|item| (item % 97) as f64 * 0.5).collect();
+    let b: Vec<f64> = (0..ITEMS).map(|item| (item % 89) as f64 * 0.25).collect();
+
+    report(
+        "dot",
+        time(|| {black_box(scalar::dot(black_box(&a), black_box(&b)));}),
+        time(|| {black_box(kernels::dot(black_box(&a), black_box(&b)));}),
+    );
+
+    let mut distances = vec![f64::INFINITY; ITEMS];
+    let scalar_relax = time(|| {
+        distances.iter_mut().for_each(|distance| *distance = 20.0);
+        black_box(scalar::relax(3.0, black_box(&a), &mut distances));
+    });
+    let dispatched_relax = time(|| {
+        distances.iter_mut().for_each(|distance| *distance = 20.0);
+        black_box(kernels::relax(3.0, black_box(&a), &mut distances));
+    });
+    report("relax", scalar_relax, dispatched_relax);
+
+    let mut counts = vec![0usize; 64];
+    let scalar_bins = time(|| {
+        black_box(scalar::count_bins(black_box(&a), 0.0, 0.75, &mut counts));
+    });
+    let dispatched_bins = time(|| {
+        black_box(kernels::count_bins(black_box(&a), 0.0, 0.75, &mut counts));
+    });
+    report("count_bins", scalar_bins, dispatched_bins);
+}
@@ -0,0 +1,150 @@
+//! The 0/1 knapsack problem, which asks for the most valuable set of items
+//! whose total weight fits within a capacity, each item being taken whole
+//! or not at all.
+//! 
+//! The items are sorted by value per unit of weight, and each
+//! [`Subproblem`] decides whether to take the next item, trying to take it
+//! first. Its bound is the value of the items taken plus the value of
+//! filling what is left of the capacity greedily, taking a share of the
+//! first item which does not fit.
+
+use std::{
+    cmp::Ordering,
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use super::{Subproblem, solve};
+use crate::pool::ThreadPool;
+
+/// An item which can be put in the knapsack.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Item {
+    /// How much of the capacity the item takes.
+    pub weight: u64,
+    /// How much the item is worth.
+    pub value: u64,
+}
+
+/// The most valuable set of items found by [`knapsack`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Packing {
+    /// The indices of the items taken, in ascending order.
+    pub items: Vec<usize>,
+    /// The total value of the items taken.
+    pub value: u64,
+    /// The total weight of the items taken.
+    pub weight: u64,
+}
+
+/// The choices made so far, with every item before `next` decided.
+#[derive(Clone)]
+struct Choices {
+    /// The items sorted by value per unit of weight, with their indices.
+    items: Arc<Vec<(usize, Item)>>,
+    capacity: u64,
+    next: usize,
+    value: u64,
+    weight: u64,
+    taken: Vec<usize>,
+}
+
+impl Subproblem for Choices {
+    fn bound(&self) -> u64 {
+        let mut bound = self.value;
+        let mut left = self.capacity - self.weight;
+        for (_, item) in &self.items[self.next..] {
+            if item.weight <= left {
+                bound += item.value;
+                left -= item.weight;
+            } else {
+                let share = left as u128 * item.value as u128 / item.weight as u128;
+                bound += share as u64;
+                break;
+            }
+        }
+        return bound;
+    }
+
+    fn value(&self) -> Option<u64> {
+        // Every set of items decided so far fits.
+        return Some(self.value);
+    }
+
+    fn branch(&self) -> Vec<Self> {
+        let Some((index, item)) = self.items.get(self.next) else {
+            return Vec::new();
+        };
+        let mut without = self.clone();
+        without.next += 1;
+        if item.weight > self.capacity - self.weight {
+            return vec![without];
+        }
+        let mut with = without.clone();
+        with.value += item.value;
+        with.weight += item.weight;
+        with.taken.push(*index);
+        return vec![with, without];
+    }
+}
+
+/// Orders `first` before `second` if it is worth more per unit of weight.
+fn density(first: &Item, second: &Item) -> Ordering {
+    let ahead = first.value as u128 * second.weight as u128;
+    let behind = second.value as u128 * first.weight as u128;
+    return behind.cmp(&ahead);
+}
+
+/// Finds the most valuable set of `items` whose total weight is at most
+/// `capacity` by branch and bound. See the module documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads exploring the choices.
+/// 2. ```items: &[Item]``` => The items which can be taken. Items worth
+///    nothing are never taken.
+/// 3. ```capacity: u64``` => The highest total weight of the items taken.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if the total value of the items which
+/// fit within `capacity` is too large for a [`u64`], or if a job on the
+/// [`ThreadPool`] fails.
+pub fn knapsack(
+    pool: &mut ThreadPool,
+    items: &[Item],
+    capacity: u64,
+) -> Result<Packing, Error> {
+    let mut fitting: Vec<(usize, Item)> = items
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, item)| item.weight <= capacity && item.value > 0)
+        .collect();
+    let total = fitting.iter().try_fold(0u64, |total, (_, item)| {
+        return total.checked_add(item.value);
+    });
+    if total.is_none() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The total value of the items is too large."
+        ));
+    }
+    fitting.sort_by(|(_, first), (_, second)| density(first, second));
+
+    let root = Choices {
+        items: Arc::new(fitting),
+        capacity,
+        next: 0,
+        value: 0,
+        weight: 0,
+        taken: Vec::new(),
+    };
+    let Some(best) = solve(pool, root)?.best else {
+        return Ok(Packing::default());
+    };
+    let mut taken = best.taken;
+    taken.sort_unstable();
+    return Ok(Packing {items: taken, value: best.value, weight: best.weight});
+}
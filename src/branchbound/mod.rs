@@ -0,0 +1,211 @@
+//! Module for branch and bound, which finds the best solution to a problem
+//! by splitting it into smaller subproblems, and skipping those which
+//! cannot hold a better solution than the best one found so far.
+//! 
+//! A problem is described by implementing [`Subproblem`], which gives the
+//! highest value a subproblem could hold, the value of the solution it
+//! stands for, if any, and how to split it. The value is maximised, so to
+//! minimise a cost, maximise how far it is below some limit instead.
+//! 
+//! [`solve`] explores the subproblems on every worker of a [`ThreadPool`].
+//! Each worker dives depth first through its own subproblems, and hands
+//! the shallowest half of them to a shared queue whenever another worker
+//! has run out. The best value found so far is kept in an atomic, so every
+//! worker prunes with the latest one without taking a lock. The search ends
+//! once every worker has run out and the queue is empty.
+//! 
+//! [`knapsack`] solves the 0/1 knapsack problem this way, and shows how to
+//! implement [`Subproblem`].
+
+mod knapsack;
+
+use std::{
+    io::Error,
+    sync::{
+        Arc, Condvar, Mutex,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+pub use knapsack::{Item, Packing, knapsack};
+
+use crate::{
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    unwrapmutex,
+};
+
+/// A part of a problem, covering some of its solutions.
+pub trait Subproblem: Clone + Send + Sync + 'static {
+    /// The highest value of any solution within this subproblem. It may be
+    /// higher than the true highest value, but never lower, or the best
+    /// solution may be pruned.
+    fn bound(&self) -> u64;
+
+    /// The value of the solution this subproblem stands for, or [`None`] if
+    /// it does not stand for one, such as when choices are left to make.
+    fn value(&self) -> Option<u64>;
+
+    /// Splits this subproblem into smaller ones which together cover every
+    /// solution within it, or returns an empty list if it cannot be split.
+    /// The worker splitting it explores the first one next.
+    fn branch(&self) -> Vec<Self>;
+}
+
+/// The best solution found by [`solve`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solved<S> {
+    /// The [`Subproblem`] standing for the solution with the highest value,
+    /// or [`None`] if no [`Subproblem`] stood for a solution.
+    pub best: Option<S>,
+    /// The value of [`Solved::best`], or `0` if there is none.
+    pub value: u64,
+    /// The number of [`Subproblem`]s which were split.
+    pub explored: usize,
+    /// The number of [`Subproblem`]s skipped because their bound was not
+    /// above the best value found so far.
+    pub pruned: usize,
+}
+
+/// The [`Subproblem`]s handed over between workers.
+struct Queue<S> {
+    waiting: Vec<S>,
+    idle: usize,
+}
+
+/// Everything the workers share during [`solve`].
+struct Shared<S> {
+    queue: Mutex<Queue<S>>,
+    ready: Condvar,
+    hungry: AtomicUsize,
+    workers: usize,
+    best: AtomicU64,
+    found: AtomicBool,
+    incumbent: Mutex<Option<S>>,
+    explored: AtomicUsize,
+    pruned: AtomicUsize,
+}
+
+impl<S: Subproblem> Shared<S> {
+    /// Whether a [`Subproblem`] with `bound` cannot beat the best solution
+    /// found so far.
+    fn prunable(&self, bound: u64) -> bool {
+        return self.found.load(SeqCst) && bound <= self.best.load(SeqCst);
+    }
+
+    /// Keeps `subproblem` as the best solution if its `value` is higher than
+    /// that of the best solution found so far.
+    fn offer(&self, value: u64, subproblem: &S) -> Result<(), Error> {
+        if self.prunable(value) {
+            return Ok(());
+        }
+        let mut incumbent = unwrapmutex!(self.incumbent.lock());
+        // Another worker may have found a better solution while waiting.
+        if !self.prunable(value) {
+            *incumbent = Some(subproblem.clone());
+            self.best.store(value, SeqCst);
+            self.found.store(true, SeqCst);
+        }
+        return Ok(());
+    }
+
+    /// Puts `subproblems` on the queue for idle workers.
+    fn give(&self, subproblems: Vec<S>) -> Result<(), Error> {
+        let mut queue = unwrapmutex!(self.queue.lock());
+        queue.waiting.extend(subproblems);
+        self.ready.notify_all();
+        return Ok(());
+    }
+
+    /// Waits for a [`Subproblem`] on the queue. Returns [`None`] once every
+    /// worker is waiting and the queue is empty, which ends the search.
+    fn take(&self) -> Result<Option<S>, Error> {
+        let mut queue = unwrapmutex!(self.queue.lock());
+        queue.idle += 1;
+        self.hungry.fetch_add(1, SeqCst);
+        loop {
+            if let Some(subproblem) = queue.waiting.pop() {
+                queue.idle -= 1;
+                self.hungry.fetch_sub(1, SeqCst);
+                return Ok(Some(subproblem));
+            }
+            if queue.idle == self.workers {
+                self.ready.notify_all();
+                return Ok(None);
+            }
+            queue = unwrapmutex!(self.ready.wait(queue));
+        }
+    }
+
+    /// Explores [`Subproblem`]s until the search ends.
+    fn work(&self) -> Result<(), Error> {
+        let mut local: Vec<S> = Vec::new();
+        loop {
+            let Some(subproblem) = local.pop() else {
+                match self.take()? {
+                    Some(subproblem) => local.push(subproblem),
+                    None => return Ok(()),
+                }
+                continue;
+            };
+            if self.prunable(subproblem.bound()) {
+                self.pruned.fetch_add(1, SeqCst);
+                continue;
+            }
+            self.explored.fetch_add(1, SeqCst);
+            if let Some(value) = subproblem.value() {
+                self.offer(value, &subproblem)?;
+            }
+            local.extend(subproblem.branch().into_iter().rev());
+            if local.len() > 1 && self.hungry.load(SeqCst) > 0 {
+                let shallowest: Vec<S> = local.drain(..local.len() / 2).collect();
+                self.give(shallowest)?;
+            }
+        }
+    }
+}
+
+/// Finds the solution with the highest value within `root` by branch and
+/// bound. See the module documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads exploring the
+///    [`Subproblem`]s. Every thread runs a worker until the search ends, so
+///    no other jobs should be waiting on `pool`.
+/// 2. ```root: S``` => The [`Subproblem`] covering every solution.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn solve<S: Subproblem>(
+    pool: &mut ThreadPool,
+    root: S,
+) -> Result<Solved<S>, Error> {
+    let workers = pool.threads();
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(Queue {waiting: vec![root], idle: 0}),
+        ready: Condvar::new(),
+        hungry: AtomicUsize::new(0),
+        workers,
+        best: AtomicU64::new(0),
+        found: AtomicBool::new(false),
+        incumbent: Mutex::new(None),
+        explored: AtomicUsize::new(0),
+        pruned: AtomicUsize::new(0),
+    });
+
+    let indices: Vec<usize> = (0..workers).collect();
+    let working = shared.clone();
+    pool.map(Arc::new(indices), ChunkPolicy::Fixed(1), move |_| working.work())?
+        .into_iter()
+        .collect::<Result<(), Error>>()?;
+
+    let best = unwrapmutex!(shared.incumbent.lock()).take();
+    let value = if best.is_some() { shared.best.load(SeqCst) } else { 0 };
+    return Ok(Solved {
+        best,
+        value,
+        explored: shared.explored.load(SeqCst),
+        pruned: shared.pruned.load(SeqCst),
+    });
+}
@@ -0,0 +1,166 @@
+//! Betweenness centrality with Brandes' Algorithm, which counts how many of
+//! the cheapest routes between other [`Node`]s pass through each [`Node`].
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use super::Sources;
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, Cost, CsrGraph, Node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// The working space for Brandes' Algorithm from one source after another,
+/// which is cleared after each source only where it was used.
+struct Brandes {
+    costs: Vec<Option<Cost>>,
+    /// Number of cheapest routes from the source to each [`Node`].
+    routes: Vec<f64>,
+    /// The [`Node`]s right before each [`Node`] on its cheapest routes.
+    previous: Vec<Vec<Node>>,
+    dependencies: Vec<f64>,
+    /// The [`Node`]s reached, in the order they were settled.
+    settled: Vec<Node>,
+}
+
+impl Brandes {
+    /// Creates the working space for a graph with `total` [`Node`]s.
+    fn new(total: Node) -> Self {
+        return Self {
+            costs: vec![None; total],
+            routes: vec![0.0; total],
+            previous: vec![Vec::new(); total],
+            dependencies: vec![0.0; total],
+            settled: Vec::new(),
+        };
+    }
+
+    /// Adds the share of the cheapest routes from `source` passing through
+    /// each [`Node`] of `graph` to `scores`.
+    fn accumulate(&mut self, graph: &CsrGraph, source: Node, scores: &mut [f64]) {
+        self.costs[source] = Some(0);
+        self.routes[source] = 1.0;
+        let mut heap: BinaryHeap<Reverse<(Cost, Node)>> = BinaryHeap::new();
+        heap.push(Reverse((0, source)));
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if self.costs[node] != Some(cost) {
+                continue;
+            }
+            self.settled.push(node);
+            for adjacent in graph.get_node(node).unwrap_or(&[]) {
+                let next = cost.saturating_add(adjacent.cost);
+                let to = adjacent.node;
+                match self.costs[to] {
+                    Some(known) if known < next => {},
+                    Some(known) if known == next => {
+                        self.routes[to] += self.routes[node];
+                        self.previous[to].push(node);
+                    },
+                    _ => {
+                        self.costs[to] = Some(next);
+                        self.routes[to] = self.routes[node];
+                        self.previous[to].clear();
+                        self.previous[to].push(node);
+                        heap.push(Reverse((next, to)));
+                    },
+                }
+            }
+        }
+
+        // Hand the dependencies back from the furthest Nodes first.
+        for node in self.settled.iter().rev() {
+            let share = (1.0 + self.dependencies[*node]) / self.routes[*node];
+            for before in &self.previous[*node] {
+                self.dependencies[*before] += self.routes[*before] * share;
+            }
+            if *node != source {
+                scores[*node] += self.dependencies[*node];
+            }
+        }
+
+        for node in self.settled.drain(..) {
+            self.costs[node] = None;
+            self.routes[node] = 0.0;
+            self.previous[node].clear();
+            self.dependencies[node] = 0.0;
+        }
+    }
+}
+
+/// Calculates the betweenness centrality of every [`Node`] of `matrix` with
+/// Brandes' Algorithm. The score of a [`Node`] is the sum, over every pair
+/// of other [`Node`]s, of the share of the cheapest routes from the first
+/// to the second which pass through it. Routes are followed in the direction
+/// of the edges, so every pair is counted both ways, and the scores of a
+/// graph whose edges were pushed in both directions are twice the usual
+/// undirected scores.
+/// 
+/// The search from each source is independent. The sources are split into
+/// one group per thread of `pool`, and each worker adds up the scores of
+/// its own group into its own list, so no locking is needed. The lists are
+/// added together at the end. With [`Sources::Sample`], the scores from the
+/// sampled sources are scaled up to estimate the scores from every source.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which run the searches.
+/// 2. ```matrix: &AdjacencyMatrix``` => The graph.
+/// 3. ```sources: Sources``` => Which [`Node`]s to search from.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a sample of `0` [`Node`]s is asked
+/// for, if an edge costs `0`, or if a job on the [`ThreadPool`] fails.
+/// Edges which cost nothing could let a [`Node`] gain cheapest routes after
+/// it was settled, or give it endlessly many of them over a loop of such
+/// edges, so the counts would be wrong.
+pub fn betweenness(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+    sources: Sources,
+) -> Result<Vec<f64>, Error> {
+    let graph = Arc::new(CsrGraph::from(matrix));
+    let total = graph.total();
+    for node in 0..total {
+        for adjacent in graph.get_node(node).unwrap_or(&[]) {
+            if adjacent.cost == 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "The edge from {} to {} costs nothing.",
+                        node, adjacent.node
+                    )
+                ));
+            }
+        }
+    }
+    let sources = sources.pick(total)?;
+    let picked = sources.len();
+    let size = picked.div_ceil(pool.threads()).max(1);
+    let groups: Vec<Vec<Node>> = sources.chunks(size).map(<[_]>::to_vec).collect();
+
+    let policy = ChunkPolicy::Fixed(1);
+    let partials = pool.map(Arc::new(groups), policy, move |group| {
+        let mut brandes = Brandes::new(total);
+        let mut scores: Vec<f64> = vec![0.0; total];
+        for source in group {
+            brandes.accumulate(&graph, *source, &mut scores);
+        }
+        return scores;
+    })?;
+
+    let mut scores: Vec<f64> = vec![0.0; total];
+    for partial in partials {
+        for (score, part) in scores.iter_mut().zip(partial) {
+            *score += part;
+        }
+    }
+    if picked < total {
+        let scale = total as f64 / picked as f64;
+        scores.iter_mut().for_each(|score| *score *= scale);
+    }
+    return Ok(scores);
+}
@@ -0,0 +1,137 @@
+//! Closeness and harmonic centrality, which score each [`Node`] by how
+//! cheaply it reaches every other [`Node`].
+
+use std::{collections::VecDeque, io::Error, sync::Arc};
+
+use crate::{
+    dijkstra::simple::{
+        AdjacencyMatrix, Cost, CsrGraph, DistanceTable, Node, shortest_from,
+    },
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// The closeness and harmonic centrality of every [`Node`] of a graph.
+/// 
+/// Both scores skip the [`Node`]s reached at a [`Cost`] of `0`, over edges
+/// which cost nothing, as if they were the [`Node`] itself, since `1 / 0`
+/// would make the scores infinite.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Closeness {
+    /// The closeness of each [`Node`]: the number of other [`Node`]s it
+    /// reaches divided by the total [`Cost`] of reaching them, scaled down
+    /// by the share of the other [`Node`]s it reaches, so that [`Node`]s
+    /// which only reach a few close neighbours do not score highest. A
+    /// [`Node`] which reaches nothing scores `0`.
+    pub closeness: Vec<f64>,
+    /// The harmonic centrality of each [`Node`]: the sum of `1 / cost` over
+    /// every other [`Node`] it reaches.
+    pub harmonic: Vec<f64>,
+}
+
+impl Closeness {
+    /// Calculates both scores of every [`Node`] from the [`Cost`]s found
+    /// by the Dijkstra Algorithm, such as those of
+    /// [`crate::dijkstra::simple::MtdDijkstra::into_result`]. [`Node`]s
+    /// without a row in `table` score `0`.
+    pub fn from_table(table: &DistanceTable) -> Self {
+        let total = table.values().next().map_or(table.len(), Vec::len);
+        let mut scores = Self {
+            closeness: vec![0.0; total],
+            harmonic: vec![0.0; total],
+        };
+        for (source, row) in table {
+            if *source < total {
+                let (closeness, harmonic) = score(*source, row);
+                scores.closeness[*source] = closeness;
+                scores.harmonic[*source] = harmonic;
+            }
+        }
+        return scores;
+    }
+}
+
+/// Works out the closeness and harmonic centrality of `source` from the
+/// [`Cost`] of reaching every [`Node`] from it, skipping the [`Node`]s
+/// reached at a [`Cost`] of `0`.
+fn score(source: Node, row: &[Option<Cost>]) -> (f64, f64) {
+    let (mut reached, mut sum, mut harmonic) = (0usize, 0.0, 0.0);
+    for (node, cost) in row.iter().enumerate() {
+        if let Some(cost) = cost {
+            if node == source || *cost == 0 {
+                continue;
+            }
+            reached += 1;
+            sum += *cost as f64;
+            harmonic += 1.0 / *cost as f64;
+        }
+    }
+    if reached == 0 {
+        return (0.0, harmonic);
+    }
+    let others = (row.len() - 1) as f64;
+    let closeness = (reached as f64 / sum) * (reached as f64 / others);
+    return (closeness, harmonic);
+}
+
+/// Counts the fewest edges needed to reach every [`Node`] of `graph` from
+/// `source` and multiplies them by `cost`, with [`None`] marking
+/// unreachable [`Node`]s. This gives the same [`Cost`]s as the Dijkstra
+/// Algorithm when every edge costs `cost`, without a binary heap.
+fn hops_from(graph: &CsrGraph, source: Node, cost: Cost) -> Vec<Option<Cost>> {
+    let mut costs: Vec<Option<Cost>> = vec![None; graph.total()];
+    costs[source] = Some(0);
+    let mut queue: VecDeque<Node> = VecDeque::from([source]);
+    while let Some(node) = queue.pop_front() {
+        let next = costs[node].map(|known| known.saturating_add(cost));
+        for adjacent in graph.get_node(node).unwrap_or(&[]) {
+            if costs[adjacent.node].is_none() {
+                costs[adjacent.node] = next;
+                queue.push_back(adjacent.node);
+            }
+        }
+    }
+    return costs;
+}
+
+/// Calculates the closeness and harmonic centrality of every [`Node`] of
+/// `matrix`. See [`Closeness`]. The [`Cost`]s are of routes leaving each
+/// [`Node`], following the direction of the edges.
+/// 
+/// Each [`Node`] is searched from as a job of its own on `pool`, and its
+/// [`Cost`]s are reduced to its scores right away, so the whole table of
+/// [`Cost`]s is never held at once. If every edge has the same [`Cost`],
+/// the searches are Breadth-First Searches instead of the Dijkstra
+/// Algorithm. If the [`Cost`]s were already found, use
+/// [`Closeness::from_table`] instead.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn closeness(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+) -> Result<Closeness, Error> {
+    let graph = Arc::new(CsrGraph::from(matrix));
+    let total = graph.total();
+    let mut costs = (0..total).flat_map(|node| graph.get_node(node).unwrap_or(&[]));
+    let first = costs.next().map(|adjacent| adjacent.cost);
+    let uniform = first.filter(|first| costs.all(|adjacent| adjacent.cost == *first));
+
+    let sources: Vec<Node> = (0..total).collect();
+    let scores = pool.map(Arc::new(sources), ChunkPolicy::Auto, move |source| {
+        let row = match uniform {
+            Some(cost) => hops_from(&graph, *source, cost),
+            None => shortest_from(*source, total, &graph, None)?,
+        };
+        return Ok(score(*source, &row));
+    })?;
+
+    let scores: Vec<(f64, f64)> = scores.into_iter().collect::<Result<_, Error>>()?;
+    let mut result = Closeness::default();
+    for (closeness, harmonic) in scores {
+        result.closeness.push(closeness);
+        result.harmonic.push(harmonic);
+    }
+    return Ok(result);
+}
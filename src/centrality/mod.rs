@@ -0,0 +1,60 @@
+//! Module for centrality measures, which score how central each [`Node`] of
+//! a graph is to the routes through it.
+//! 
+//! The routes are the cheapest routes of the Dijkstra Algorithm, so the
+//! [`Cost`]s of the edges should all be above `0`. To measure by the number
+//! of hops instead, give every edge a [`Cost`] of `1`.
+//! 
+//! These are the measures available:
+//! 1. [`betweenness`], how many of the cheapest routes pass through each
+//!    [`Node`].
+//! 2. [`closeness`], how cheaply each [`Node`] reaches the others, along
+//!    with its harmonic centrality.
+//! 
+//! [`Cost`]: crate::dijkstra::simple::Cost
+
+mod betweenness;
+mod closeness;
+
+pub use betweenness::betweenness;
+pub use closeness::{Closeness, closeness};
+
+use std::io::{Error, ErrorKind};
+
+use crate::{dijkstra::simple::Node, rng::Rng};
+
+/// Which [`Node`]s a centrality measure searches from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sources {
+    /// Every [`Node`], which gives the exact scores.
+    All,
+    /// `count` different [`Node`]s picked at random with `seed`, which
+    /// estimates the scores in a fraction of the time on huge graphs.
+    Sample {count: usize, seed: u64},
+}
+
+impl Sources {
+    /// Lists the sources for a graph with `total` [`Node`]s, in order. A
+    /// sample of at least `total` [`Node`]s takes every [`Node`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a sample of `0` [`Node`]s is
+    /// asked for.
+    fn pick(&self, total: Node) -> Result<Vec<Node>, Error> {
+        let mut sources: Vec<Node> = (0..total).collect();
+        if let Self::Sample {count, seed} = *self {
+            if count == 0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "At least 1 source must be sampled."
+                ));
+            }
+            Rng::new(seed).shuffle(&mut sources);
+            sources.truncate(count);
+            sources.sort_unstable();
+        }
+        return Ok(sources);
+    }
+}
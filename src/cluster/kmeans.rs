@@ -0,0 +1,374 @@
+//! Module for k-means clustering, which splits points into `k` clusters so
+//! that the squared distances from each point to the centroid, or mean, of
+//! its cluster add up to as little as possible.
+//! 
+//! [`MtdKMeans`] runs Lloyd's algorithm. Starting from `k` centroids picked
+//! by the [`Initialisation`], each iteration assigns every point to its
+//! nearest centroid and then moves every centroid to the mean of the points
+//! assigned to it. The points are split into ranges between the workers of
+//! a [`ThreadPool`], and each worker returns the sums and counts of the
+//! points it assigned to each cluster, which are merged into the new
+//! centroids once every worker is done. The iterations stop once no
+//! centroid moves further than the tolerance, or after the most iterations
+//! allowed.
+//! 
+//! A cluster which loses all its points keeps its centroid where it was.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    dijkstra::simple::JOBS_PER_THREAD,
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+    rng::Rng,
+};
+
+/// The tolerance used unless another is set with
+/// [`MtdKMeans::set_tolerance`].
+pub const DEFAULT_TOLERANCE: f64 = 1e-6;
+/// The most iterations run unless another limit is set with
+/// [`MtdKMeans::set_max_iterations`].
+pub const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+/// How the first centroids are picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Initialisation {
+    /// k-means++, which picks the first centroid at random, and each other
+    /// one at random with a chance in proportion to the squared distance
+    /// from the point to its nearest centroid so far. The centroids start
+    /// spread out, which usually gives better clusters in fewer iterations.
+    #[default]
+    PlusPlus,
+    /// `k` different points picked at random.
+    Random,
+}
+
+/// The clusters found by [`MtdKMeans::cluster`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clusters {
+    /// The centroid of each cluster, which is the mean of the points
+    /// assigned to it.
+    pub centroids: Vec<Vec<f64>>,
+    /// The cluster each point is assigned to.
+    pub assignments: Vec<usize>,
+    /// The sum of the squared distances from each point to the centroid it
+    /// was assigned to in the last iteration.
+    pub inertia: f64,
+    /// Number of iterations run.
+    pub iterations: usize,
+    /// Whether the centroids settled within the tolerance before the most
+    /// iterations allowed were run.
+    pub converged: bool,
+}
+
+impl Clusters {
+    /// Get the indices of the points assigned to `cluster`, in ascending
+    /// order.
+    pub fn members(&self, cluster: usize) -> Vec<usize> {
+        return self
+            .assignments
+            .iter()
+            .enumerate()
+            .filter(|(_, assigned)| **assigned == cluster)
+            .map(|(point, _)| point)
+            .collect();
+    }
+
+    /// Get the cluster whose centroid is nearest to `point`, or [`None`] if
+    /// there are no clusters or `point` has a different number of
+    /// dimensions.
+    pub fn nearest(&self, point: &[f64]) -> Option<usize> {
+        if self.centroids.first().is_none_or(|first| first.len() != point.len()) {
+            return None;
+        }
+        return Some(nearest(&self.centroids, point).0);
+    }
+}
+
+/// Get the squared distance between `first` and `second`.
+fn distance(first: &[f64], second: &[f64]) -> f64 {
+    return first
+        .iter()
+        .zip(second)
+        .map(|(first, second)| (first - second) * (first - second))
+        .sum();
+}
+
+/// Get the index of the centroid nearest to `point` and its squared
+/// distance, ties going to the lower index.
+fn nearest(centroids: &[Vec<f64>], point: &[f64]) -> (usize, f64) {
+    let mut best = (0, f64::INFINITY);
+    for (index, centroid) in centroids.iter().enumerate() {
+        let squared = distance(centroid, point);
+        if squared < best.1 {
+            best = (index, squared);
+        }
+    }
+    return best;
+}
+
+/// What a worker found about its range of points in one iteration.
+struct Partial {
+    assignments: Vec<usize>,
+    sums: Vec<Vec<f64>>,
+    counts: Vec<usize>,
+    inertia: f64,
+}
+
+/// This `struct` splits points into clusters with k-means using multiple
+/// threads. See the module documentation.
+pub struct MtdKMeans {
+    pool: ThreadPool,
+    initialisation: Initialisation,
+    tolerance: f64,
+    max_iterations: usize,
+}
+
+impl MtdKMeans {
+    /// Creates a new [`MtdKMeans`] instance with
+    /// [`Initialisation::PlusPlus`], [`DEFAULT_TOLERANCE`] and
+    /// [`DEFAULT_MAX_ITERATIONS`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {
+            pool,
+            initialisation: Initialisation::default(),
+            tolerance: DEFAULT_TOLERANCE,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        });
+    }
+
+    /// Get how the first centroids are picked.
+    pub fn initialisation(&self) -> Initialisation {
+        return self.initialisation;
+    }
+
+    /// Sets how the first centroids are picked.
+    pub fn set_initialisation(&mut self, initialisation: Initialisation) {
+        self.initialisation = initialisation;
+    }
+
+    /// Get how far a centroid may move in the last iteration.
+    pub fn tolerance(&self) -> f64 {
+        return self.tolerance;
+    }
+
+    /// Sets how far a centroid may move in the last iteration.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `tolerance` is negative or not a
+    /// number, and the tolerance is left as it was.
+    pub fn set_tolerance(&mut self, tolerance: f64) -> Result<(), Error> {
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The tolerance {} is not a non-negative number.", tolerance)
+            ));
+        }
+        self.tolerance = tolerance;
+        return Ok(());
+    }
+
+    /// Get the most iterations run.
+    pub fn max_iterations(&self) -> usize {
+        return self.max_iterations;
+    }
+
+    /// Sets the most iterations run. The clusters are returned as they are
+    /// after the last one even if they have not settled yet.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Splits `points` into `k` clusters. See the module documentation.
+    /// 
+    /// # Parameters
+    /// 1. ```points: &[Vec<f64>]``` => The points, which must all have the
+    ///    same number of dimensions.
+    /// 2. ```k: usize``` => The number of clusters.
+    /// 3. ```seed: u64``` => Seed for picking the first centroids. The same
+    ///    seed and number of threads always give the same clusters.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `k` is `0` or more than the
+    /// number of points, if the points have different numbers of dimensions
+    /// or a coordinate is not finite, or if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn cluster(
+        &mut self,
+        points: &[Vec<f64>],
+        k: usize,
+        seed: u64,
+    ) -> Result<Clusters, Error> {
+        if k == 0 || k > points.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} clusters cannot be made from {} points.",
+                    k, points.len()
+                )
+            ));
+        }
+        let dimensions = points[0].len();
+        for (index, point) in points.iter().enumerate() {
+            let finite = point.iter().all(|value| value.is_finite());
+            if point.len() != dimensions || !finite {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Point {} is not {} finite coordinates.", index, dimensions)
+                ));
+            }
+        }
+
+        let points = Arc::new(points.to_vec());
+        let pieces = self.pool.threads() * JOBS_PER_THREAD;
+        let ranges = Arc::new(ranges(points.len(), pieces));
+        let mut rng = Rng::new(seed);
+        let centroids = match self.initialisation {
+            Initialisation::PlusPlus => self.plus_plus(&points, &ranges, k, &mut rng)?,
+            Initialisation::Random => {
+                let mut indices: Vec<usize> = (0..points.len()).collect();
+                rng.shuffle(&mut indices);
+                indices[..k].iter().map(|index| points[*index].clone()).collect()
+            },
+        };
+
+        let mut result = Clusters {centroids, ..Clusters::default()};
+        while !result.converged && result.iterations < self.max_iterations {
+            let next = self.step(&points, &ranges, &result.centroids)?;
+            let moved = next
+                .centroids
+                .iter()
+                .zip(&result.centroids)
+                .map(|(next, previous)| distance(next, previous).sqrt())
+                .fold(0.0, f64::max);
+            result = Clusters {
+                iterations: result.iterations + 1,
+                converged: moved <= self.tolerance,
+                ..next
+            };
+        }
+        return Ok(result);
+    }
+
+    /// Picks `k` centroids from `points` with k-means++, updating the
+    /// squared distance from each point to its nearest centroid on the
+    /// [`ThreadPool`] after each pick.
+    fn plus_plus(
+        &mut self,
+        points: &Arc<Vec<Vec<f64>>>,
+        ranges: &Arc<Vec<(usize, usize)>>,
+        k: usize,
+        rng: &mut Rng,
+    ) -> Result<Vec<Vec<f64>>, Error> {
+        let first = points[rng.below(points.len())].clone();
+        let mut centroids: Vec<Vec<f64>> = vec![first];
+        let mut squared: Vec<f64> = vec![f64::INFINITY; points.len()];
+        while centroids.len() < k {
+            let latest = Arc::new(centroids[centroids.len() - 1].clone());
+            let (shared, nearest) = (points.clone(), Arc::new(squared));
+            let jobs = ranges.clone();
+            let chunks = self.pool.map(jobs, ChunkPolicy::Fixed(1), move |range| {
+                return (range.0..range.1)
+                    .map(|point| {
+                        return nearest[point].min(distance(&shared[point], &latest));
+                    })
+                    .collect::<Vec<f64>>();
+            })?;
+            squared = chunks.into_iter().flatten().collect();
+
+            let total: f64 = squared.iter().sum();
+            let picked = if total > 0.0 {
+                let mut left = rng.next_f64() * total;
+                let mut picked = squared.len() - 1;
+                for (point, weight) in squared.iter().enumerate() {
+                    if *weight > 0.0 && left < *weight {
+                        picked = point;
+                        break;
+                    }
+                    left -= weight;
+                }
+                picked
+            } else {
+                // Every point is on a centroid already, so any will do.
+                rng.below(points.len())
+            };
+            centroids.push(points[picked].clone());
+        }
+        return Ok(centroids);
+    }
+
+    /// Assigns every point to its nearest centroid of `centroids` on the
+    /// [`ThreadPool`]. Returns the new centroids, the assignments and the
+    /// inertia, without counting the iteration.
+    fn step(
+        &mut self,
+        points: &Arc<Vec<Vec<f64>>>,
+        ranges: &Arc<Vec<(usize, usize)>>,
+        centroids: &[Vec<f64>],
+    ) -> Result<Clusters, Error> {
+        let (k, dimensions) = (centroids.len(), points[0].len());
+        let shared = points.clone();
+        let used = Arc::new(centroids.to_vec());
+        let jobs = ranges.clone();
+        let partials = self.pool.map(jobs, ChunkPolicy::Fixed(1), move |range| {
+            let mut partial = Partial {
+                assignments: Vec::with_capacity(range.1 - range.0),
+                sums: vec![vec![0.0; dimensions]; k],
+                counts: vec![0; k],
+                inertia: 0.0,
+            };
+            for point in &shared[range.0..range.1] {
+                let (cluster, squared) = nearest(&used, point);
+                for (sum, value) in partial.sums[cluster].iter_mut().zip(point) {
+                    *sum += value;
+                }
+                partial.counts[cluster] += 1;
+                partial.inertia += squared;
+                partial.assignments.push(cluster);
+            }
+            return partial;
+        })?;
+
+        let mut sums: Vec<Vec<f64>> = vec![vec![0.0; dimensions]; k];
+        let mut counts: Vec<usize> = vec![0; k];
+        let mut assignments: Vec<usize> = Vec::with_capacity(points.len());
+        let mut inertia: f64 = 0.0;
+        for partial in partials {
+            for (cluster, sum) in partial.sums.into_iter().enumerate() {
+                for (total, value) in sums[cluster].iter_mut().zip(sum) {
+                    *total += value;
+                }
+                counts[cluster] += partial.counts[cluster];
+            }
+            assignments.extend(partial.assignments);
+            inertia += partial.inertia;
+        }
+        let centroids: Vec<Vec<f64>> = sums
+            .into_iter()
+            .zip(&counts)
+            .zip(centroids)
+            .map(|((sum, count), previous)| {
+                if *count == 0 {
+                    return previous.clone();
+                }
+                return sum.into_iter().map(|value| value / *count as f64).collect();
+            })
+            .collect();
+        return Ok(Clusters {centroids, assignments, inertia, ..Clusters::default()});
+    }
+}
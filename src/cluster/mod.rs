@@ -0,0 +1,8 @@
+//! Module for clustering points, which groups points so that the points in
+//! each group are close to one another.
+//! 
+//! [`self::kmeans`] splits the points into a chosen number of clusters
+//! around their means, assigning the points to clusters on several threads
+//! at once.
+
+pub mod kmeans;
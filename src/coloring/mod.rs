@@ -0,0 +1,171 @@
+//! Module for coloring graphs, which gives every [`Node`] a color so that
+//! no edge joins 2 [`Node`]s of the same color, using as few colors as it
+//! reasonably can. Colors are numbered from `0`.
+//! 
+//! This suits problems like register allocation, where [`Node`]s joined by
+//! an edge cannot share a register, or scheduling, where they cannot run at
+//! the same time. The edges of an [`AdjacencyMatrix`] are treated as
+//! undirected and their [`Cost`]s are ignored.
+//! 
+//! [`jones_plassmann`] colors the graph in rounds. Every [`Node`] is given
+//! a random priority, and each round, every uncolored [`Node`] whose
+//! priority beats those of all its uncolored neighbours takes the smallest
+//! color none of its neighbours has. No 2 of these [`Node`]s are
+//! neighbours, so they are all colored at the same time on a
+//! [`ThreadPool`]. This gives the same coloring as coloring the [`Node`]s
+//! one at a time in order of priority.
+//! 
+//! [`Cost`]: crate::dijkstra::simple::Cost
+
+use std::{
+    io::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+use crate::{
+    bfs::SEQUENTIAL_FRONTIER,
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, Node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+};
+
+/// Marks a [`Node`] which has not been colored yet.
+const UNCOLORED: usize = usize::MAX;
+
+/// A coloring of a graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coloring {
+    /// The color of each [`Node`].
+    pub colors: Vec<usize>,
+    /// Number of colors used.
+    pub count: usize,
+}
+
+impl Coloring {
+    /// Get the [`Node`]s of each color, in order.
+    pub fn classes(&self) -> Vec<Vec<Node>> {
+        let mut classes: Vec<Vec<Node>> = vec![Vec::new(); self.count];
+        for (node, color) in self.colors.iter().enumerate() {
+            classes[*color].push(node);
+        }
+        return classes;
+    }
+
+    /// Whether no edge of `matrix` joins 2 [`Node`]s of the same color.
+    pub fn is_proper(&self, matrix: &AdjacencyMatrix) -> bool {
+        return (0..matrix.total()).all(|from| {
+            let color = self.colors.get(from);
+            return matrix
+                .get_node(from)
+                .map_or(&[][..], |edges| edges)
+                .iter()
+                .all(|adjacent| color != self.colors.get(adjacent.node));
+        });
+    }
+}
+
+/// The shared state of a coloring in progress.
+struct Shared {
+    graph: CsrGraph,
+    reversed: CsrGraph,
+    priorities: Vec<(u64, Node)>,
+    colors: Vec<AtomicUsize>,
+}
+
+impl Shared {
+    /// Get the neighbours of `node`, following edges both ways.
+    fn neighbours(&self, node: Node) -> impl Iterator<Item = Node> + '_ {
+        return self
+            .graph
+            .get_node(node)
+            .unwrap_or(&[])
+            .iter()
+            .chain(self.reversed.get_node(node).unwrap_or(&[]))
+            .map(|adjacent| adjacent.node);
+    }
+
+    /// Get the color `node` takes this round, which is the smallest color
+    /// none of its neighbours has, or [`None`] if an uncolored neighbour
+    /// has a higher priority.
+    fn pick(&self, node: Node) -> Option<usize> {
+        let mut taken: Vec<bool> = Vec::new();
+        for neighbour in self.neighbours(node) {
+            let color = self.colors[neighbour].load(SeqCst);
+            if color == UNCOLORED {
+                if self.priorities[neighbour] > self.priorities[node] {
+                    return None;
+                }
+                continue;
+            }
+            if color >= taken.len() {
+                taken.resize(color + 1, false);
+            }
+            taken[color] = true;
+        }
+        return Some(taken.iter().position(|taken| !taken).unwrap_or(taken.len()));
+    }
+}
+
+/// Colors `matrix` with the Jones-Plassmann Algorithm. See the module
+/// documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which color the
+///    [`Node`]s.
+/// 2. ```matrix: &AdjacencyMatrix``` => The graph.
+/// 3. ```seed: u64``` => Seed for the random priorities. The same seed
+///    always gives the same coloring.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn jones_plassmann(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+    seed: u64,
+) -> Result<Coloring, Error> {
+    let graph = CsrGraph::from(matrix);
+    let total = graph.total();
+    let mut rng = Rng::new(seed);
+    let shared = Arc::new(Shared {
+        reversed: graph.reversed(),
+        graph,
+        priorities: (0..total).map(|node| (rng.next_u64(), node)).collect(),
+        colors: (0..total).map(|_| AtomicUsize::new(UNCOLORED)).collect(),
+    });
+
+    let mut remaining: Vec<Node> = (0..total).collect();
+    while !remaining.is_empty() {
+        let picked: Vec<Option<usize>> = if remaining.len() < SEQUENTIAL_FRONTIER {
+            remaining.iter().map(|node| shared.pick(*node)).collect()
+        } else {
+            let state = shared.clone();
+            let nodes = Arc::new(remaining.clone());
+            pool.map(nodes, ChunkPolicy::Auto, move |node| {
+                return state.pick(*node);
+            })?
+        };
+        // Colors are only stored once the round is over, so that every
+        // Node in the round sees the same uncolored neighbours.
+        let mut left: Vec<Node> = Vec::with_capacity(remaining.len());
+        for (node, color) in remaining.into_iter().zip(picked) {
+            match color {
+                Some(color) => shared.colors[node].store(color, SeqCst),
+                None => left.push(node),
+            }
+        }
+        remaining = left;
+    }
+
+    let colors: Vec<usize> = shared
+        .colors
+        .iter()
+        .map(|color| color.load(SeqCst))
+        .collect();
+    let count = colors.iter().max().map_or(0, |color| color + 1);
+    return Ok(Coloring {colors, count});
+}
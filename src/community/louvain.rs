@@ -0,0 +1,198 @@
+//! The Louvain Method, which moves [`Node`]s into the communities of their
+//! neighbours while the modularity rises, then merges each community into a
+//! single [`Node`] and starts again on the smaller graph.
+
+use std::{
+    collections::BTreeMap,
+    io::Error,
+    sync::{Arc, RwLock},
+};
+
+use super::{Hierarchy, Level, Weighted};
+use crate::{
+    bfs::SEQUENTIAL_FRONTIER,
+    coloring::jones_plassmann,
+    dijkstra::simple::{AdjacencyMatrix, Node, NodeWithCost},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    unwrapmutex,
+};
+
+/// The passes of moving [`Node`]s on a level stop once the modularity rises
+/// by less than this.
+const MIN_GAIN: f64 = 1e-7;
+/// The most passes of moving [`Node`]s on a level.
+const MAX_PASSES: usize = 100;
+
+/// The community of each [`Node`] and the sum of the degrees in each
+/// community.
+struct Assignment {
+    communities: Vec<usize>,
+    totals: Vec<f64>,
+}
+
+/// Get the community `node` would raise the modularity the most by moving
+/// to, or [`None`] if it is best where it is. Ties go to the lower
+/// community.
+fn best_move(graph: &Weighted, assignment: &Assignment, node: Node) -> Option<usize> {
+    let current = assignment.communities[node];
+    let degree = graph.degrees[node];
+    let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+    for (other, weight) in &graph.adjacency[node] {
+        *links.entry(assignment.communities[*other]).or_insert(0.0) += weight;
+    }
+    // How much the modularity rises by adding `node` to `community`, scaled
+    // up by the weight of the graph.
+    let gain = |community: usize, total: f64| {
+        let linked = links.get(&community).copied().unwrap_or(0.0);
+        return linked - total * degree / graph.weight;
+    };
+    let mut best = (gain(current, assignment.totals[current] - degree), current);
+    for community in links.keys() {
+        let candidate = gain(*community, assignment.totals[*community]);
+        if *community != current && candidate > best.0 {
+            best = (candidate, *community);
+        }
+    }
+    return if best.1 == current { None } else { Some(best.1) };
+}
+
+/// Moves the [`Node`]s of `graph` between communities, starting with every
+/// [`Node`] on its own, until the modularity stops rising. Returns the
+/// communities numbered from `0` in order of their smallest [`Node`], the
+/// number of communities and the modularity after each pass.
+/// 
+/// Each pass goes through the colors of a coloring of `graph`. No 2
+/// [`Node`]s of the same color are neighbours, so all the [`Node`]s of one
+/// color pick their moves at the same time on `pool` without the moves of
+/// one changing the gains seen by another through an edge between them.
+fn local_moves(
+    pool: &mut ThreadPool,
+    graph: &Arc<Weighted>,
+    seed: u64,
+) -> Result<(Vec<usize>, usize, Vec<f64>), Error> {
+    let total = graph.total();
+    let mut matrix = AdjacencyMatrix::new(total);
+    for node in 0..total {
+        for (other, _) in &graph.adjacency[node] {
+            if *other > node {
+                matrix.push(node, NodeWithCost::new(*other, 1))?;
+            }
+        }
+    }
+    let classes = jones_plassmann(pool, &matrix, seed)?.classes();
+    let assignment = Arc::new(RwLock::new(Assignment {
+        communities: (0..total).collect(),
+        totals: graph.degrees.clone(),
+    }));
+
+    let mut communities: Vec<usize> = (0..total).collect();
+    let mut modularity = graph.modularity(&communities);
+    let mut passes: Vec<f64> = Vec::new();
+    for _ in 0..MAX_PASSES {
+        let mut moved: usize = 0;
+        for class in &classes {
+            let moves: Vec<Option<usize>> = if class.len() < SEQUENTIAL_FRONTIER {
+                let assignment = unwrapmutex!(assignment.read());
+                class.iter().map(|node| best_move(graph, &assignment, *node)).collect()
+            } else {
+                let (graph, shared) = (graph.clone(), assignment.clone());
+                pool.map(Arc::new(class.clone()), ChunkPolicy::Auto, move |node| {
+                    let assignment = unwrapmutex!(shared.read());
+                    return Ok(best_move(&graph, &assignment, *node));
+                })?
+                .into_iter()
+                .collect::<Result<_, Error>>()?
+            };
+            let mut assignment = unwrapmutex!(assignment.write());
+            for (node, target) in class.iter().zip(moves) {
+                let Some(target) = target else {
+                    continue;
+                };
+                let source = assignment.communities[*node];
+                assignment.totals[source] -= graph.degrees[*node];
+                assignment.totals[target] += graph.degrees[*node];
+                assignment.communities[*node] = target;
+                moved += 1;
+            }
+        }
+
+        let assignment = unwrapmutex!(assignment.read());
+        let next = graph.modularity(&assignment.communities);
+        // Nodes of the same color moving into the same community at once
+        // can overshoot, so a pass which lowers the modularity is dropped
+        // and the communities before it are kept.
+        if next < modularity {
+            break;
+        }
+        communities.clone_from(&assignment.communities);
+        passes.push(next);
+        let gain = next - modularity;
+        modularity = next;
+        if moved == 0 || gain < MIN_GAIN {
+            break;
+        }
+    }
+    if passes.is_empty() {
+        passes.push(modularity);
+    }
+
+    let mut numbers: Vec<Option<usize>> = vec![None; total];
+    let mut count: usize = 0;
+    for community in &mut communities {
+        let number = *numbers[*community].get_or_insert_with(|| {
+            count += 1;
+            return count - 1;
+        });
+        *community = number;
+    }
+    return Ok((communities, count, passes));
+}
+
+/// Finds communities in `matrix` with the Louvain Method.
+/// 
+/// On each level, every [`Node`] starts in a community of its own, and the
+/// [`Node`]s are moved into the communities of their neighbours in passes
+/// while that raises the modularity. The moves of each pass are worked out
+/// in parallel on `pool`, a group of [`Node`]s which are not neighbours at a
+/// time. Each community is then merged into a single [`Node`] for the next
+/// level, until a level merges nothing.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which move the
+///    [`Node`]s.
+/// 2. ```matrix: &AdjacencyMatrix``` => The graph, with the [`Cost`] of
+///    each edge as its weight.
+/// 3. ```seed: u64``` => Seed for splitting the [`Node`]s into groups which
+///    are not neighbours. The same seed always gives the same communities.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+/// 
+/// [`Cost`]: crate::dijkstra::simple::Cost
+pub fn louvain(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+    seed: u64,
+) -> Result<Hierarchy, Error> {
+    let mut graph = Arc::new(Weighted::from(matrix));
+    let mut original: Vec<usize> = (0..matrix.total()).collect();
+    let mut hierarchy = Hierarchy::default();
+    loop {
+        let (communities, count, passes) = local_moves(pool, &graph, seed)?;
+        let merged = count < graph.total();
+        if merged || hierarchy.levels.is_empty() {
+            original = original.iter().map(|node| communities[*node]).collect();
+            hierarchy.levels.push(Level {
+                communities: original.clone(),
+                count,
+                modularity: passes.last().copied().unwrap_or_default(),
+                passes,
+            });
+        }
+        if !merged {
+            return Ok(hierarchy);
+        }
+        graph = Arc::new(graph.aggregate(&communities, count));
+    }
+}
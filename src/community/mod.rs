@@ -0,0 +1,187 @@
+//! Module for community detection, which groups the [`Node`]s of a graph
+//! into communities with many edges inside them and few between them.
+//! 
+//! How good a grouping is, is measured by its modularity: the share of the
+//! weight of the edges which falls inside communities, minus the share
+//! expected if the same [`Node`]s were joined at random. The edges of an
+//! [`AdjacencyMatrix`] are treated as undirected, with their [`Cost`]s as
+//! weights, so an edge pushed in both directions counts twice. Since
+//! modularity only depends on shares of the weight, this does not change
+//! anything for graphs whose edges were all pushed both ways.
+//! 
+//! These are the algorithms available:
+//! 1. [`louvain`], which merges communities level by level into a
+//!    hierarchy.
+//! 
+//! [`Cost`]: crate::dijkstra::simple::Cost
+
+mod louvain;
+
+pub use louvain::louvain;
+
+use crate::dijkstra::simple::{AdjacencyMatrix, Node};
+
+/// The communities found on one level of a [`Hierarchy`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Level {
+    /// The community of each [`Node`] of the graph, numbered from `0` in
+    /// order of the smallest [`Node`] in each.
+    pub communities: Vec<usize>,
+    /// Number of communities.
+    pub count: usize,
+    /// The modularity of the communities.
+    pub modularity: f64,
+    /// The modularity after each pass of moving [`Node`]s between
+    /// communities on this level.
+    pub passes: Vec<f64>,
+}
+
+impl Level {
+    /// Get the [`Node`]s of each community, in order.
+    pub fn members(&self) -> Vec<Vec<Node>> {
+        let mut members: Vec<Vec<Node>> = vec![Vec::new(); self.count];
+        for (node, community) in self.communities.iter().enumerate() {
+            members[*community].push(node);
+        }
+        return members;
+    }
+}
+
+/// Communities found level by level, each level merging the communities of
+/// the one before it.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hierarchy {
+    /// The levels, from the smallest communities to the largest.
+    pub levels: Vec<Level>,
+}
+
+impl Hierarchy {
+    /// Get the level with the highest modularity, which is usually the
+    /// last.
+    pub fn best(&self) -> Option<&Level> {
+        return self
+            .levels
+            .iter()
+            .rev()
+            .max_by(|a, b| a.modularity.total_cmp(&b.modularity));
+    }
+}
+
+/// Calculates the modularity of grouping the [`Node`]s of `matrix` into
+/// `communities`, where `communities[node]` is the community of `node`. See
+/// the module documentation. A graph without edges has a modularity of
+/// `0`.
+/// 
+/// # Panics
+/// 
+/// Panics if `communities` is shorter than the number of [`Node`]s.
+pub fn modularity(matrix: &AdjacencyMatrix, communities: &[usize]) -> f64 {
+    return Weighted::from(matrix).modularity(communities);
+}
+
+/// An undirected graph with weighted edges, kept as a symmetric matrix `A`
+/// where `A[i][j]` is the weight joining `i` and `j`.
+struct Weighted {
+    /// `A[i][j]` for every `j` other than `i` with some weight, without
+    /// repeats.
+    adjacency: Vec<Vec<(Node, f64)>>,
+    /// `A[i][i]`, which holds the weight inside a community once its
+    /// [`Node`]s were merged into `i`.
+    loops: Vec<f64>,
+    /// The sum of each row of `A`.
+    degrees: Vec<f64>,
+    /// The sum of all of `A`, which is twice the weight of the edges.
+    weight: f64,
+}
+
+impl Weighted {
+    /// Builds a [`Weighted`] graph from rows of `(node, weight)` which may
+    /// repeat [`Node`]s.
+    fn new(mut adjacency: Vec<Vec<(Node, f64)>>, loops: Vec<f64>) -> Self {
+        for row in &mut adjacency {
+            row.sort_unstable_by_key(|(node, _)| *node);
+            row.dedup_by(|later, kept| {
+                if later.0 == kept.0 {
+                    kept.1 += later.1;
+                    return true;
+                }
+                return false;
+            });
+        }
+        let degrees: Vec<f64> = adjacency
+            .iter()
+            .zip(&loops)
+            .map(|(row, own)| own + row.iter().map(|(_, weight)| weight).sum::<f64>())
+            .collect();
+        let weight = degrees.iter().sum();
+        return Self {adjacency, loops, degrees, weight};
+    }
+
+    /// Number of [`Node`]s in the graph.
+    fn total(&self) -> Node {
+        return self.adjacency.len();
+    }
+
+    /// Calculates the modularity of grouping the [`Node`]s into
+    /// `communities`.
+    fn modularity(&self, communities: &[usize]) -> f64 {
+        if self.weight <= 0.0 {
+            return 0.0;
+        }
+        let count = communities[..self.total()].iter().max().map_or(0, |last| last + 1);
+        let mut inside: Vec<f64> = vec![0.0; count];
+        let mut totals: Vec<f64> = vec![0.0; count];
+        for node in 0..self.total() {
+            let community = communities[node];
+            inside[community] += self.loops[node];
+            totals[community] += self.degrees[node];
+            for (other, weight) in &self.adjacency[node] {
+                if communities[*other] == community {
+                    inside[community] += weight;
+                }
+            }
+        }
+        return inside
+            .iter()
+            .zip(&totals)
+            .map(|(inside, total)| {
+                return inside / self.weight - (total / self.weight).powi(2);
+            })
+            .sum();
+    }
+
+    /// Merges every community of `communities`, numbered from `0` to
+    /// `count`, into a single [`Node`].
+    fn aggregate(&self, communities: &[usize], count: usize) -> Self {
+        let mut adjacency: Vec<Vec<(Node, f64)>> = vec![Vec::new(); count];
+        let mut loops: Vec<f64> = vec![0.0; count];
+        for node in 0..self.total() {
+            let community = communities[node];
+            loops[community] += self.loops[node];
+            for (other, weight) in &self.adjacency[node] {
+                match communities[*other] {
+                    other if other == community => loops[community] += weight,
+                    other => adjacency[community].push((other, *weight)),
+                }
+            }
+        }
+        return Self::new(adjacency, loops);
+    }
+}
+
+impl From<&AdjacencyMatrix> for Weighted {
+    fn from(matrix: &AdjacencyMatrix) -> Self {
+        let total = matrix.total();
+        let mut adjacency: Vec<Vec<(Node, f64)>> = vec![Vec::new(); total];
+        for from in 0..total {
+            for adjacent in matrix.get_node(from).map_or(&[][..], |edges| edges) {
+                let weight = adjacent.cost as f64;
+                adjacency[from].push((adjacent.node, weight));
+                adjacency[adjacent.node].push((from, weight));
+            }
+        }
+        return Self::new(adjacency, vec![0.0; total]);
+    }
+}
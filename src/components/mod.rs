@@ -0,0 +1,187 @@
+//! Module for finding the connected components of an undirected graph,
+//! which are the groups of [`Node`]s joined to each other by edges.
+//! 
+//! [`MtdComponents`] uses label propagation: every [`Node`] starts with its
+//! own number as its label and keeps taking the smallest label among itself
+//! and its neighbours until no label changes, at which point every [`Node`]
+//! carries the smallest [`Node`] of its component. The [`Node`]s are split
+//! between the workers of a [`ThreadPool`] in each pass and the labels are
+//! lowered in place without locking, so a pass can already see the labels
+//! lowered earlier in the same pass. Each [`Node`] also takes the label of
+//! the [`Node`] named by its label, which lets labels jump across long
+//! chains instead of moving one edge per pass.
+//! 
+//! Edges are followed both ways whichever direction they were pushed in,
+//! so on a directed graph this finds the weakly connected components.
+
+use std::{
+    io::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, JOBS_PER_THREAD, Node},
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+};
+
+/// The connected components found by [`MtdComponents::calculate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Components {
+    /// The component of each [`Node`]. Components are numbered from `0` in
+    /// order of the smallest [`Node`] in them.
+    pub labels: Vec<usize>,
+    /// Number of [`Node`]s in each component.
+    pub sizes: Vec<usize>,
+}
+
+impl Components {
+    /// Number of components.
+    pub fn count(&self) -> usize {
+        return self.sizes.len();
+    }
+
+    /// Get the component of `node`, or [`None`] if it is not in the graph.
+    pub fn component(&self, node: Node) -> Option<usize> {
+        return self.labels.get(node).copied();
+    }
+
+    /// Whether `a` and `b` are in the same component.
+    pub fn connected(&self, a: Node, b: Node) -> bool {
+        return match (self.component(a), self.component(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        };
+    }
+
+    /// Get the [`Node`]s in `component`, in order.
+    pub fn members(&self, component: usize) -> Vec<Node> {
+        return (0..self.labels.len())
+            .filter(|node| self.labels[*node] == component)
+            .collect();
+    }
+
+    /// Get the component with the most [`Node`]s and its size, or [`None`]
+    /// if the graph has no [`Node`]s. Ties go to the lower component.
+    pub fn largest(&self) -> Option<(usize, usize)> {
+        let mut largest: Option<(usize, usize)> = None;
+        for (component, size) in self.sizes.iter().enumerate() {
+            if largest.is_none_or(|(_, most)| *size > most) {
+                largest = Some((component, *size));
+            }
+        }
+        return largest;
+    }
+}
+
+/// This `struct` finds the connected components of a graph using multiple
+/// threads. See the module documentation.
+pub struct MtdComponents {
+    pool: ThreadPool,
+    graph: Arc<CsrGraph>,
+    reversed: Arc<CsrGraph>,
+}
+
+impl MtdComponents {
+    /// Creates a new [`MtdComponents`] instance.
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix``` => The graph. Its edges are
+    ///    followed both ways and their [`Cost`]s are ignored.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    /// 
+    /// [`Cost`]: crate::dijkstra::simple::Cost
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix) -> Result<Self, Error> {
+        return Self::from_csr(threads, CsrGraph::from(matrix));
+    }
+
+    /// Creates a new [`MtdComponents`] instance from a graph which is
+    /// already a [`CsrGraph`]. See [`MtdComponents::new`].
+    pub fn from_csr(threads: usize, graph: CsrGraph) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        let reversed = Arc::new(graph.reversed());
+        return Ok(Self {pool, graph: Arc::new(graph), reversed});
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.graph.total();
+    }
+
+    /// Finds the connected components of the graph.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn calculate(&mut self) -> Result<Components, Error> {
+        let total = self.total();
+        let labels: Arc<Vec<AtomicUsize>> = Arc::new(
+            (0..total).map(AtomicUsize::new).collect()
+        );
+        let ranges = Arc::new(ranges(total, self.pool.threads() * JOBS_PER_THREAD));
+        loop {
+            let changed = Arc::new(AtomicBool::new(false));
+            let graph = self.graph.clone();
+            let reversed = self.reversed.clone();
+            let shared = labels.clone();
+            let flag = changed.clone();
+            let policy = ChunkPolicy::Fixed(1);
+            self.pool.for_each_chunk(ranges.clone(), policy, move |chunk| {
+                for range in chunk {
+                    for node in range.0..range.1 {
+                        let current = shared[node].load(SeqCst);
+                        let mut lowest = shared[current].load(SeqCst);
+                        let adjacents = graph
+                            .get_node(node)
+                            .unwrap_or(&[])
+                            .iter()
+                            .chain(reversed.get_node(node).unwrap_or(&[]));
+                        for adjacent in adjacents {
+                            if let Some(label) = shared.get(adjacent.node) {
+                                lowest = lowest.min(label.load(SeqCst));
+                            }
+                        }
+                        if lowest < current {
+                            shared[node].fetch_min(lowest, SeqCst);
+                            flag.store(true, SeqCst);
+                        }
+                    }
+                }
+                return Ok(());
+            })?;
+            if !changed.load(SeqCst) {
+                break;
+            }
+        }
+
+        // Renumber the labels, which are the smallest Node of each
+        // component, in order.
+        let mut components = Components {labels: vec![0; total], sizes: Vec::new()};
+        let mut numbers: Vec<Option<usize>> = vec![None; total];
+        for node in 0..total {
+            let label = labels[node].load(SeqCst);
+            let number = match numbers[label] {
+                Some(number) => number,
+                None => {
+                    components.sizes.push(0);
+                    let number = components.sizes.len() - 1;
+                    numbers[label] = Some(number);
+                    number
+                },
+            };
+            components.labels[node] = number;
+            components.sizes[number] += 1;
+        }
+        return Ok(components);
+    }
+}
@@ -0,0 +1,94 @@
+//! Module for removing repeated items from large lists, such as repeated
+//! edges in a list of edges read in before a graph is built from it.
+//! 
+//! [`parallel_dedup`] hashes every item into one of a shard per thread of
+//! a [`ThreadPool`], so equal items always share a shard. The hashes are
+//! found in parallel, and once the items are moved into their shards each
+//! shard is deduplicated by its own job, without sharing a set of the items
+//! seen between the jobs.
+
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::Error,
+};
+
+use crate::{
+    pool::ThreadPool,
+    sort::{SEQUENTIAL_CUTOFF, quicksort_by, scatter},
+};
+
+/// The order the items kept by [`parallel_dedup`] are returned in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Order {
+    /// No particular order, which saves sorting the items kept.
+    #[default]
+    Any,
+    /// The order the items were first seen in.
+    FirstSeen,
+}
+
+/// Get the index of the shard `item` goes into.
+fn shard_of<T: Hash>(item: &T, shards: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+    return (hasher.finish() % shards as u64) as usize;
+}
+
+/// Keeps the first of every group of equal items of `shard`, which holds
+/// each item with its index in the list, in no particular order.
+fn dedup_shard<T: Eq + Hash>(shard: Vec<(usize, T)>) -> Vec<(usize, T)> {
+    let mut kept: HashMap<T, usize> = HashMap::with_capacity(shard.len());
+    for (index, item) in shard {
+        if let Entry::Vacant(entry) = kept.entry(item) {
+            entry.insert(index);
+        }
+    }
+    return kept.into_iter().map(|(item, index)| (index, item)).collect();
+}
+
+/// Removes repeated items from `items` in parallel on `pool`, keeping the
+/// first of every group of equal items. See the module documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which do the work.
+/// 2. ```items: Vec<T>``` => The list to remove repeated items from.
+/// 3. ```order: Order``` => The order to return the items kept in. Keeping
+///    the order the items were first seen in takes a parallel sort of the
+///    items kept by their index, with [`quicksort_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn parallel_dedup<T>(
+    pool: &mut ThreadPool,
+    items: Vec<T>,
+    order: Order,
+) -> Result<Vec<T>, Error>
+where
+    T: Eq + Hash + Send + Sync + 'static,
+{
+    let len = items.len();
+    let shards = pool.threads().min(len / SEQUENTIAL_CUTOFF);
+    let kept: Vec<(usize, T)> = if shards < 2 {
+        dedup_shard(items.into_iter().enumerate().collect())
+    } else {
+        let indexed: Vec<(usize, T)> = items.into_iter().enumerate().collect();
+        let pick = move |_list: &[(usize, T)], item: &(usize, T)| {
+            return shard_of(&item.1, shards);
+        };
+        let deduped = scatter(pool, indexed, shards, pick, dedup_shard)?;
+        let mut kept: Vec<(usize, T)> = Vec::new();
+        for mut shard in deduped {
+            kept.append(&mut shard);
+        }
+        kept
+    };
+
+    let kept = match order {
+        Order::Any => kept,
+        Order::FirstSeen => quicksort_by(pool, kept, |a, b| a.0.cmp(&b.0))?,
+    };
+    return Ok(kept.into_iter().map(|(_, item)| item).collect());
+}
@@ -0,0 +1,203 @@
+//! Module for Depth-First Search, which follows the edges of a graph as far
+//! as they go before turning back.
+//! 
+//! A single search cannot be split up well, since which [`Node`] comes next
+//! depends on everything visited before it. [`MtdDfs::reachable_from`]
+//! instead runs one search per root as a job on a [`ThreadPool`], which is
+//! what most uses of reachability need. [`depth_first`] numbers the
+//! [`Node`]s in the order a single search enters and leaves them, which is
+//! what algorithms like finding strongly connected components and
+//! topological sorting are built on.
+
+use std::{
+    io::Error,
+    sync::Arc,
+};
+
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, Node, check_node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// The order in which [`depth_first`] entered and left the [`Node`]s
+/// reached from its root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DfsOrder {
+    /// The [`Node`] the search started from.
+    pub root: Node,
+    /// The [`Node`]s in the order they were entered.
+    pub preorder: Vec<Node>,
+    /// The [`Node`]s in the order they were left, once every [`Node`]
+    /// after them was done.
+    pub postorder: Vec<Node>,
+    /// The position of each [`Node`] in [`DfsOrder::preorder`], with
+    /// [`None`] marking [`Node`]s which were not reached.
+    pub entered: Vec<Option<usize>>,
+    /// The position of each [`Node`] in [`DfsOrder::postorder`].
+    pub left: Vec<Option<usize>>,
+    /// The [`Node`] each [`Node`] was entered from. The root and the
+    /// [`Node`]s which were not reached have none.
+    pub parents: Vec<Option<Node>>,
+}
+
+impl DfsOrder {
+    /// Whether `node` was reached from the root.
+    pub fn reached(&self, node: Node) -> bool {
+        return self.entered.get(node).is_some_and(Option::is_some);
+    }
+
+    /// Whether `ancestor` was still being searched when `node` was entered,
+    /// so that `node` lies below it in the search tree. Every [`Node`] is
+    /// its own ancestor.
+    pub fn is_ancestor(&self, ancestor: Node, node: Node) -> bool {
+        let entered = |node: Node| self.entered.get(node).copied().flatten();
+        let left = |node: Node| self.left.get(node).copied().flatten();
+        return match (entered(ancestor), entered(node), left(ancestor), left(node)) {
+            (Some(first), Some(second), Some(first_left), Some(second_left)) => {
+                first <= second && second_left <= first_left
+            },
+            _ => false,
+        };
+    }
+}
+
+/// Marks every [`Node`] which can be reached from `root` in `graph`.
+fn reach(graph: &CsrGraph, root: Node) -> Vec<bool> {
+    let mut reached = vec![false; graph.total()];
+    reached[root] = true;
+    let mut stack: Vec<Node> = vec![root];
+    while let Some(node) = stack.pop() {
+        for adjacent in graph.get_node(node).unwrap_or(&[]) {
+            if let Some(seen) = reached.get_mut(adjacent.node) {
+                if !*seen {
+                    *seen = true;
+                    stack.push(adjacent.node);
+                }
+            }
+        }
+    }
+    return reached;
+}
+
+/// Runs a Depth-First Search over `graph` from `root` on the calling thread,
+/// taking the edges leaving each [`Node`] in the order they were pushed.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `root` is not in the graph.
+pub fn depth_first(graph: &CsrGraph, root: Node) -> Result<DfsOrder, Error> {
+    let total = graph.total();
+    check_node(root, total)?;
+    let mut order = DfsOrder {
+        root,
+        preorder: Vec::new(),
+        postorder: Vec::new(),
+        entered: vec![None; total],
+        left: vec![None; total],
+        parents: vec![None; total],
+    };
+    order.entered[root] = Some(0);
+    order.preorder.push(root);
+    // Each entry is a Node being searched and how many of its edges have
+    // been followed, so the search can go on where it left off.
+    let mut stack: Vec<(Node, usize)> = vec![(root, 0)];
+    while let Some((node, next)) = stack.last_mut() {
+        let adjacents = graph.get_node(*node).unwrap_or(&[]);
+        match adjacents.get(*next) {
+            Some(adjacent) => {
+                *next += 1;
+                let child = adjacent.node;
+                if child < total && order.entered[child].is_none() {
+                    order.entered[child] = Some(order.preorder.len());
+                    order.preorder.push(child);
+                    order.parents[child] = Some(*node);
+                    stack.push((child, 0));
+                }
+            },
+            None => {
+                order.left[*node] = Some(order.postorder.len());
+                order.postorder.push(*node);
+                stack.pop();
+            },
+        }
+    }
+    return Ok(order);
+}
+
+/// This `struct` runs Depth-First Searches over a graph using multiple
+/// threads. See the module documentation.
+pub struct MtdDfs {
+    pool: ThreadPool,
+    graph: Arc<CsrGraph>,
+}
+
+impl MtdDfs {
+    /// Creates a new [`MtdDfs`] instance.
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix``` => The graph. The [`Cost`]s of its
+    ///    edges are ignored.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    /// 
+    /// [`Cost`]: crate::dijkstra::simple::Cost
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix) -> Result<Self, Error> {
+        return Self::from_csr(threads, CsrGraph::from(matrix));
+    }
+
+    /// Creates a new [`MtdDfs`] instance from a graph which is already a
+    /// [`CsrGraph`]. See [`MtdDfs::new`].
+    pub fn from_csr(threads: usize, graph: CsrGraph) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {pool, graph: Arc::new(graph)});
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.graph.total();
+    }
+
+    /// Get the graph being searched.
+    pub fn graph(&self) -> &CsrGraph {
+        return &self.graph;
+    }
+
+    /// Finds which [`Node`]s can be reached from each of `roots`, with one
+    /// job per root on the [`ThreadPool`]. The `i`th list marks the
+    /// [`Node`]s reached from `roots[i]`, including the root itself.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if any of `roots` is not in the
+    /// graph or a job on the [`ThreadPool`] fails.
+    pub fn reachable_from(&mut self, roots: &[Node]) -> Result<Vec<Vec<bool>>, Error> {
+        let total = self.total();
+        for root in roots {
+            check_node(*root, total)?;
+        }
+        let graph = self.graph.clone();
+        return self.pool.map(
+            Arc::new(roots.to_vec()),
+            ChunkPolicy::Fixed(1),
+            move |root| {
+                return reach(&graph, *root);
+            }
+        );
+    }
+
+    /// Numbers the [`Node`]s reached from `root` in the order a single
+    /// search enters and leaves them. See [`depth_first`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `root` is not in the graph.
+    pub fn order(&self, root: Node) -> Result<DfsOrder, Error> {
+        return depth_first(&self.graph, root);
+    }
+}
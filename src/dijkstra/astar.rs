@@ -0,0 +1,108 @@
+//! Module for point-to-point queries with the A* search algorithm.
+//! 
+//! A* works like the Dijkstra Algorithm, but visits [`Node`]s in order of
+//! the [`Cost`] to reach them plus a guess of the [`Cost`] left to reach the
+//! target, given by a heuristic. The better the guess, the fewer [`Node`]s
+//! are visited. On grids and road networks, the straight line distance to
+//! the target is a good heuristic.
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    io::Error,
+};
+
+use super::simple::{AdjacencyMatrix, Cost, Node, NodeWithCost, Path, check_node};
+
+/// Finds the cheapest [`Path`] from `source` to `target` with the A* search
+/// algorithm, or [`None`] if `target` cannot be reached.
+/// 
+/// # Parameters
+/// 1. ```graph: &AdjacencyMatrix``` => The graph to search.
+/// 2. ```source: Node``` => Where the [`Path`] starts.
+/// 3. ```target: Node``` => Where the [`Path`] ends.
+/// 4. ```heuristic: H``` => A guess of the [`Cost`] to reach `target` from
+///    each [`Node`]. It must never guess more than the real [`Cost`] (it
+///    must be admissible), or the [`Path`] found may not be the cheapest.
+///    A heuristic which always returns `0` turns A* into the Dijkstra
+///    Algorithm, and [`Cost::MAX`] marks a [`Node`] which is known not to
+///    reach `target`, so it is never visited.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `source` or `target` is not in the
+/// graph.
+pub fn astar<H>(
+    graph: &AdjacencyMatrix,
+    source: Node,
+    target: Node,
+    heuristic: H,
+) -> Result<Option<Path>, Error>
+where
+    H: Fn(Node) -> Cost,
+{
+    return search(
+        graph.total(),
+        |node| graph.get_node(node).map_or(&[], Vec::as_slice),
+        source,
+        target,
+        heuristic,
+    );
+}
+
+/// The A* search loop behind [`astar`], over any graph with `total`
+/// [`Node`]s whose edges are given by `adjacents`. It is shared with
+/// [`super::landmarks`], which searches a [`super::simple::CsrGraph`].
+pub(crate) fn search<'a, A, H>(
+    total: Node,
+    adjacents: A,
+    source: Node,
+    target: Node,
+    heuristic: H,
+) -> Result<Option<Path>, Error>
+where
+    A: Fn(Node) -> &'a [NodeWithCost],
+    H: Fn(Node) -> Cost,
+{
+    for node in [source, target] {
+        check_node(node, total)?;
+    }
+
+    let mut distances: Vec<Option<Cost>> = vec![None; total];
+    let mut previous: Vec<Option<Node>> = vec![None; total];
+    // Ordered by the estimated total Cost, then by the Cost so far.
+    let mut unvisited: BinaryHeap<Reverse<(Cost, Cost, Node)>> = BinaryHeap::new();
+    distances[source] = Some(0);
+    unvisited.push(Reverse((heuristic(source), 0, source)));
+
+    while let Some(Reverse((_estimate, cost, node))) = unvisited.pop() {
+        if Some(cost) != distances[node] {
+            continue;
+        }
+        if node == target {
+            let mut nodes = vec![target];
+            let mut current = target;
+            while let Some(before) = previous[current] {
+                nodes.push(before);
+                current = before;
+            }
+            nodes.reverse();
+            return Ok(Some(Path {nodes, cost}));
+        }
+        for adjacent in adjacents(node) {
+            let new_distance = cost.saturating_add(adjacent.cost);
+            match distances[adjacent.node] {
+                Some(distance) if distance <= new_distance => continue,
+                _ => distances[adjacent.node] = Some(new_distance),
+            }
+            previous[adjacent.node] = Some(node);
+            let guess = heuristic(adjacent.node);
+            if guess == Cost::MAX {
+                continue;
+            }
+            let estimate = new_distance.saturating_add(guess);
+            unvisited.push(Reverse((estimate, new_distance, adjacent.node)));
+        }
+    }
+    return Ok(None);
+}
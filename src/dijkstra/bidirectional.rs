@@ -0,0 +1,155 @@
+//! Module for point-to-point queries with the bidirectional Dijkstra
+//! Algorithm.
+//! 
+//! One worker searches forwards from the source while another searches
+//! backwards from the target over the reversed graph. Every time either
+//! search reaches a [`Node`] the other one has already reached, the route
+//! through that [`Node`] becomes a candidate, and both searches stop once
+//! the cheapest [`Node`]s left on both sides together cost at least as much
+//! as the best candidate. Both searches only cover about half the distance
+//! between the 2 [`Node`]s, so far fewer [`Node`]s are visited than by a
+//! full single-source run.
+
+use std::{
+    collections::BinaryHeap,
+    io::Error,
+    sync::{Arc, Mutex},
+};
+
+use super::simple::{
+    AdjacencyMatrix, Cost, CsrGraph, Node, NodeWithCost, Path, check_node,
+};
+use crate::{pool::ThreadPool, unwrapmutex};
+
+/// Index of the forward search in [`Meeting`].
+const FORWARD: usize = 0;
+
+/// Index of the backward search in [`Meeting`].
+const BACKWARD: usize = 1;
+
+/// The state shared by the forward and backward searches.
+struct Meeting {
+    /// Tentative [`Cost`]s from the source and to the target.
+    distances: [Vec<Option<Cost>>; 2],
+    /// The [`Node`] each [`Node`] was reached from by each search.
+    previous: [Vec<Option<Node>>; 2],
+    /// The [`Cost`] of the last [`Node`] each search took off its heap,
+    /// which no [`Node`] it takes later can be cheaper than.
+    top: [Cost; 2],
+    /// The cheapest route found so far and the [`Node`] where the 2
+    /// searches met on it.
+    best: Option<(Cost, Node)>,
+    /// Set once either search has decided the best route cannot improve.
+    done: bool,
+}
+
+/// Runs one side of the search over `graph`, which is the reversed graph
+/// for the backward search.
+fn search(
+    side: usize,
+    start: Node,
+    graph: &CsrGraph,
+    meeting: &Mutex<Meeting>,
+) -> Result<(), Error> {
+    let other = 1 - side;
+    let mut unvisited: BinaryHeap<NodeWithCost> = BinaryHeap::new();
+    unvisited.push(NodeWithCost::new(start, 0));
+    while let Some(current) = unvisited.pop() {
+        let mut state = unwrapmutex!(meeting.lock());
+        if state.done {
+            return Ok(());
+        }
+        if Some(current.cost) != state.distances[side][current.node] {
+            continue;
+        }
+        if let Some((best, _node)) = state.best {
+            if current.cost.saturating_add(state.top[other]) >= best {
+                state.done = true;
+                return Ok(());
+            }
+        }
+        state.top[side] = current.cost;
+        for adjacent in graph.get_node(current.node).into_iter().flatten() {
+            let new_distance = current.cost.saturating_add(adjacent.cost);
+            match state.distances[side][adjacent.node] {
+                Some(distance) if distance <= new_distance => continue,
+                _ => state.distances[side][adjacent.node] = Some(new_distance),
+            }
+            state.previous[side][adjacent.node] = Some(current.node);
+            if let Some(remaining) = state.distances[other][adjacent.node] {
+                let total = new_distance.saturating_add(remaining);
+                if state.best.is_none_or(|(best, _node)| total < best) {
+                    state.best = Some((total, adjacent.node));
+                }
+            }
+            unvisited.push(NodeWithCost::new(adjacent.node, new_distance));
+        }
+    }
+    // Every Node this side can reach has been visited, so every route
+    // between the 2 Nodes has already been seen.
+    unwrapmutex!(meeting.lock()).done = true;
+    return Ok(());
+}
+
+/// Finds the cheapest [`Path`] from `source` to `target` with one forward
+/// and one backward search running on 2 workers of `pool`. [`None`] is
+/// returned if `target` cannot be reached.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `source` or `target` is not in the
+/// graph, or a job on the [`ThreadPool`] fails.
+pub fn shortest_path(
+    pool: &mut ThreadPool,
+    graph: &AdjacencyMatrix,
+    source: Node,
+    target: Node,
+) -> Result<Option<Path>, Error> {
+    let total = graph.total();
+    for node in [source, target] {
+        check_node(node, total)?;
+    }
+    if source == target {
+        return Ok(Some(Path {nodes: vec![source], cost: 0}));
+    }
+
+    let forward = Arc::new(CsrGraph::from(graph));
+    let backward = Arc::new(forward.reversed());
+    let mut meeting = Meeting {
+        distances: [vec![None; total], vec![None; total]],
+        previous: [vec![None; total], vec![None; total]],
+        top: [0, 0],
+        best: None,
+        done: false,
+    };
+    meeting.distances[FORWARD][source] = Some(0);
+    meeting.distances[BACKWARD][target] = Some(0);
+    let meeting = Arc::new(Mutex::new(meeting));
+    for (side, start, graph) in [
+        (FORWARD, source, forward),
+        (BACKWARD, target, backward),
+    ] {
+        let meeting = meeting.clone();
+        pool.execute(move || search(side, start, &graph, &meeting))?;
+    }
+    pool.wait()?;
+
+    let state = unwrapmutex!(meeting.lock());
+    let (cost, middle) = match state.best {
+        Some(best) => best,
+        None => return Ok(None),
+    };
+    let mut nodes = vec![middle];
+    let mut node = middle;
+    while let Some(before) = state.previous[FORWARD][node] {
+        nodes.push(before);
+        node = before;
+    }
+    nodes.reverse();
+    node = middle;
+    while let Some(after) = state.previous[BACKWARD][node] {
+        nodes.push(after);
+        node = after;
+    }
+    return Ok(Some(Path {nodes, cost}));
+}
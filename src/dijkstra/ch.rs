@@ -0,0 +1,482 @@
+//! Module for answering point-to-point queries on large graphs which do not
+//! change, such as road networks, with contraction hierarchies.
+//! 
+//! Every [`Node`] is given a rank, and the [`Node`]s are contracted (taken
+//! out of the graph) from the lowest rank to the highest. When a [`Node`] is
+//! contracted, a shortcut edge is added between each pair of its remaining
+//! neighbours whose cheapest route went through it, unless a short witness
+//! search finds another route which is just as cheap. Afterwards, the
+//! cheapest route between any 2 [`Node`]s only ever climbs up the ranks and
+//! then comes back down, so a query only searches upwards from both ends and
+//! visits a tiny part of the graph.
+//! 
+//! The rank of a [`Node`] is decided by how many shortcuts contracting it
+//! would add compared to the edges it removes. [`Node`]s which rank lower
+//! than all of their neighbours do not affect each other, so each round
+//! contracts all of them at once, with their witness searches spread over a
+//! [`ThreadPool`].
+
+use std::{
+    collections::{BinaryHeap, HashMap},
+    io::Error,
+    sync::Arc,
+};
+
+use super::simple::{
+    AdjacencyMatrix, Cost, CsrGraph, Node, NodeWithCost, Path, check_node,
+};
+use crate::pool::{ThreadPool, chunk::ChunkPolicy};
+
+/// Most [`Node`]s a witness search settles before giving up, in which case
+/// the shortcut is added to be safe.
+const WITNESS_LIMIT: usize = 500;
+
+/// Most [`Node`]s a witness search settles while only estimating how many
+/// shortcuts a [`Node`] needs for [`Overlay::priority`].
+const ESTIMATE_LIMIT: usize = 20;
+
+/// An edge of the graph while it is being contracted. The [`Node`] it leads
+/// to or comes from depends on the list it is in.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    node: Node,
+    cost: Cost,
+    /// The contracted [`Node`] a shortcut skips over.
+    middle: Option<Node>,
+}
+
+/// The [`Node`]s which have not been contracted yet and the edges between
+/// them, including shortcuts.
+#[derive(Debug, Clone)]
+struct Overlay {
+    /// Edges leaving each [`Node`].
+    out: Vec<Vec<Edge>>,
+    /// Edges entering each [`Node`], pointing at where they come from.
+    into: Vec<Vec<Edge>>,
+    /// How deep each [`Node`] sits in the hierarchy: one more than the
+    /// deepest neighbour contracted before it.
+    depth: Vec<i64>,
+}
+
+impl Overlay {
+    /// Builds an [`Overlay`] with the same [`Node`]s and edges as `graph`.
+    fn new(graph: &AdjacencyMatrix) -> Self {
+        let total = graph.total();
+        let mut out: Vec<Vec<Edge>> = vec![Vec::new(); total];
+        let mut into: Vec<Vec<Edge>> = vec![Vec::new(); total];
+        for (from, adjacents) in out.iter_mut().enumerate() {
+            for adjacent in graph.get_node(from).into_iter().flatten() {
+                let cost = adjacent.cost;
+                adjacents.push(Edge {node: adjacent.node, cost, middle: None});
+                into[adjacent.node].push(Edge {node: from, cost, middle: None});
+            }
+        }
+        return Self {out, into, depth: vec![0; total]};
+    }
+
+    /// Finds the [`Cost`]s from `source` to the [`Node`]s around it which
+    /// cost at most `limit`, without passing through a `banned` [`Node`].
+    /// The search gives up after settling `settle` [`Node`]s, so a missing
+    /// [`Node`] may still be reachable.
+    fn witness<B>(
+        &self,
+        source: Node,
+        limit: Cost,
+        settle: usize,
+        banned: B,
+    ) -> HashMap<Node, Cost>
+    where
+        B: Fn(Node) -> bool,
+    {
+        let mut distances: HashMap<Node, Cost> = HashMap::new();
+        let mut unvisited = BinaryHeap::new();
+        let mut settled = 0;
+        distances.insert(source, 0);
+        unvisited.push(NodeWithCost::new(source, 0));
+        while let Some(current) = unvisited.pop() {
+            if distances.get(&current.node) != Some(&current.cost) {
+                continue;
+            }
+            settled += 1;
+            if settled > settle {
+                break;
+            }
+            for edge in &self.out[current.node] {
+                let new_distance = current.cost.saturating_add(edge.cost);
+                if new_distance > limit || banned(edge.node) {
+                    continue;
+                }
+                match distances.get(&edge.node) {
+                    Some(distance) if *distance <= new_distance => continue,
+                    _ => distances.insert(edge.node, new_distance),
+                };
+                unvisited.push(NodeWithCost::new(edge.node, new_distance));
+            }
+        }
+        return distances;
+    }
+
+    /// Finds the shortcuts needed to contract `node`, as
+    /// `(from, to, cost)`, when the witness searches settle at most `settle`
+    /// [`Node`]s and may not pass through `node` or any `banned` [`Node`].
+    fn shortcuts<B>(
+        &self,
+        node: Node,
+        settle: usize,
+        banned: B,
+    ) -> Vec<(Node, Node, Cost)>
+    where
+        B: Fn(Node) -> bool,
+    {
+        let mut shortcuts: Vec<(Node, Node, Cost)> = Vec::new();
+        for into in &self.into[node] {
+            let targets: Vec<(Node, Cost)> = self.out[node]
+                .iter()
+                .filter(|out| out.node != into.node)
+                .map(|out| (out.node, into.cost.saturating_add(out.cost)))
+                .collect();
+            let limit = match targets.iter().map(|(_to, cost)| *cost).max() {
+                Some(limit) => limit,
+                None => continue,
+            };
+            let witnesses = self.witness(into.node, limit, settle, |other| {
+                return other == node || banned(other);
+            });
+            for (to, cost) in targets {
+                if witnesses.get(&to).is_none_or(|witness| *witness > cost) {
+                    shortcuts.push((into.node, to, cost));
+                }
+            }
+        }
+        return shortcuts;
+    }
+
+    /// How early `node` should be contracted: the number of shortcuts it
+    /// needs minus the edges it removes, plus its depth so the contraction
+    /// spreads evenly over the graph. Lower is earlier.
+    fn priority(&self, node: Node) -> i64 {
+        let shortcuts = self.shortcuts(node, ESTIMATE_LIMIT, |_other| false);
+        let shortcuts = shortcuts.len() as i64;
+        let edges = (self.out[node].len() + self.into[node].len()) as i64;
+        return shortcuts - edges + self.depth[node];
+    }
+
+    /// Get the [`Node`]s connected to `node` in either direction.
+    fn neighbours(&self, node: Node) -> impl Iterator<Item = Node> + '_ {
+        return self.out[node]
+            .iter()
+            .chain(&self.into[node])
+            .map(|edge| edge.node);
+    }
+
+    /// Adds or cheapens the edge from `from` to `to`, which skips over
+    /// `middle`.
+    fn add_shortcut(&mut self, from: Node, to: Node, cost: Cost, middle: Node) {
+        let shortcut = Edge {node: to, cost, middle: Some(middle)};
+        match self.out[from].iter_mut().find(|edge| edge.node == to) {
+            Some(edge) if edge.cost <= cost => return,
+            Some(edge) => *edge = shortcut,
+            None => self.out[from].push(shortcut),
+        }
+        let reverse = Edge {node: from, cost, middle: Some(middle)};
+        match self.into[to].iter_mut().find(|edge| edge.node == from) {
+            Some(edge) => *edge = reverse,
+            None => self.into[to].push(reverse),
+        }
+    }
+}
+
+/// A graph which has been contracted for fast point-to-point queries.
+#[derive(Debug, Clone)]
+pub struct ContractionHierarchy {
+    /// The rank of each [`Node`], from `0` for the first one contracted.
+    rank: Vec<usize>,
+    /// Edges from each [`Node`] to higher ranked [`Node`]s.
+    up: CsrGraph,
+    /// Edges into each [`Node`] from higher ranked [`Node`]s, turned
+    /// around.
+    down: CsrGraph,
+    /// The [`Node`] each shortcut `(from, to)` skips over.
+    middles: HashMap<(Node, Node), Node>,
+}
+
+impl ContractionHierarchy {
+    /// Contracts `graph`, running the witness searches of each round on
+    /// `pool`.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job in `pool` fails.
+    pub fn new(
+        pool: &mut ThreadPool,
+        graph: &AdjacencyMatrix,
+    ) -> Result<Self, Error> {
+        let total = graph.total();
+        let mut overlay = Arc::new(Overlay::new(graph));
+        let mut rank: Vec<Option<usize>> = vec![None; total];
+        let mut up: Vec<(Node, Node, Cost)> = Vec::new();
+        let mut down: Vec<(Node, Node, Cost)> = Vec::new();
+        let mut middles: HashMap<(Node, Node), Node> = HashMap::new();
+
+        let mut priorities: Vec<i64> = Self::priorities(
+            pool,
+            &overlay,
+            (0..total).collect(),
+        )?;
+        let mut remaining: Vec<Node> = (0..total).collect();
+        let mut next_rank = 0;
+        while !remaining.is_empty() {
+            // Nodes ranked lower than every neighbour never share an edge,
+            // so they can be contracted together.
+            let chosen: Vec<Node> = remaining
+                .iter()
+                .copied()
+                .filter(|node| {
+                    let key = (priorities[*node], *node);
+                    return overlay.neighbours(*node).all(|other| {
+                        return key < (priorities[other], other);
+                    });
+                })
+                .collect();
+            let mut in_round = vec![false; total];
+            for node in &chosen {
+                in_round[*node] = true;
+            }
+            let in_round = Arc::new(in_round);
+
+            // Witnesses may not pass through another Node contracted in the
+            // same round, since its edges are about to disappear.
+            let searched = overlay.clone();
+            let banned = in_round.clone();
+            let shortcuts = pool.map(
+                Arc::new(chosen.clone()),
+                ChunkPolicy::Auto,
+                move |node| {
+                    let banned = |other: Node| banned[other];
+                    return searched.shortcuts(*node, WITNESS_LIMIT, banned);
+                },
+            )?;
+
+            let current = Arc::make_mut(&mut overlay);
+            let mut touched: Vec<Node> = Vec::new();
+            for node in &chosen {
+                rank[*node] = Some(next_rank);
+                next_rank += 1;
+                let depth = current.depth[*node] + 1;
+                for edge in std::mem::take(&mut current.out[*node]) {
+                    up.push((*node, edge.node, edge.cost));
+                    if let Some(middle) = edge.middle {
+                        middles.insert((*node, edge.node), middle);
+                    }
+                    current.into[edge.node].retain(|other| other.node != *node);
+                    current.depth[edge.node] = depth.max(current.depth[edge.node]);
+                    touched.push(edge.node);
+                }
+                for edge in std::mem::take(&mut current.into[*node]) {
+                    down.push((*node, edge.node, edge.cost));
+                    if let Some(middle) = edge.middle {
+                        middles.insert((edge.node, *node), middle);
+                    }
+                    current.out[edge.node].retain(|other| other.node != *node);
+                    current.depth[edge.node] = depth.max(current.depth[edge.node]);
+                    touched.push(edge.node);
+                }
+            }
+            for (node, found) in chosen.iter().zip(shortcuts) {
+                for (from, to, cost) in found {
+                    current.add_shortcut(from, to, cost, *node);
+                }
+            }
+            remaining.retain(|node| !in_round[*node]);
+
+            touched.sort_unstable();
+            touched.dedup();
+            let updated = Self::priorities(pool, &overlay, touched.clone())?;
+            for (node, priority) in touched.into_iter().zip(updated) {
+                priorities[node] = priority;
+            }
+        }
+
+        let rank = rank.into_iter().map(Option::unwrap_or_default).collect();
+        return Ok(Self {
+            rank,
+            up: CsrGraph::from(AdjacencyMatrix::from_edges(total, up)?),
+            down: CsrGraph::from(AdjacencyMatrix::from_edges(total, down)?),
+            middles,
+        });
+    }
+
+    /// Calculates [`Overlay::priority`] for each of `nodes` on `pool`.
+    fn priorities(
+        pool: &mut ThreadPool,
+        overlay: &Arc<Overlay>,
+        nodes: Vec<Node>,
+    ) -> Result<Vec<i64>, Error> {
+        let overlay = overlay.clone();
+        return pool.map(Arc::new(nodes), ChunkPolicy::Auto, move |node| {
+            return overlay.priority(*node);
+        });
+    }
+
+    /// Get the number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.rank.len();
+    }
+
+    /// Get the number of shortcut edges added by the contraction.
+    pub fn shortcuts(&self) -> usize {
+        return self.middles.len();
+    }
+
+    /// Get the rank of `node`, from `0` for the first [`Node`] contracted,
+    /// or [`None`] if it is not in the graph.
+    pub fn rank(&self, node: Node) -> Option<usize> {
+        return self.rank.get(node).copied();
+    }
+
+    /// Finds the [`Cost`] of the cheapest route from `source` to `target`,
+    /// or [`None`] if `target` cannot be reached.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` or `target` is not in
+    /// the graph.
+    pub fn distance(
+        &self,
+        source: Node,
+        target: Node,
+    ) -> Result<Option<Cost>, Error> {
+        return Ok(self.search(source, target)?.map(|meeting| meeting.cost));
+    }
+
+    /// Finds the cheapest [`Path`] from `source` to `target`, with every
+    /// shortcut on it expanded back into the original edges, or [`None`] if
+    /// `target` cannot be reached.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` or `target` is not in
+    /// the graph.
+    pub fn shortest_path(
+        &self,
+        source: Node,
+        target: Node,
+    ) -> Result<Option<Path>, Error> {
+        let meeting = match self.search(source, target)? {
+            Some(meeting) => meeting,
+            None => return Ok(None),
+        };
+        // The route up from the source, then down to the target.
+        let mut hops = vec![meeting.node];
+        while let Some(before) = meeting.previous[0].get(&hops[hops.len() - 1]) {
+            hops.push(*before);
+        }
+        hops.reverse();
+        while let Some(after) = meeting.previous[1].get(&hops[hops.len() - 1]) {
+            hops.push(*after);
+        }
+
+        let mut nodes = vec![source];
+        for hop in hops.windows(2) {
+            let mut pending = vec![(hop[0], hop[1])];
+            while let Some((from, to)) = pending.pop() {
+                match self.middles.get(&(from, to)) {
+                    Some(middle) => {
+                        pending.push((*middle, to));
+                        pending.push((from, *middle));
+                    },
+                    None => nodes.push(to),
+                }
+            }
+        }
+        return Ok(Some(Path {nodes, cost: meeting.cost}));
+    }
+
+    /// Searches upwards from both `source` and `target` until neither
+    /// search can improve on the cheapest route where they met.
+    fn search(
+        &self,
+        source: Node,
+        target: Node,
+    ) -> Result<Option<Meeting>, Error> {
+        for node in [source, target] {
+            check_node(node, self.total())?;
+        }
+        let graphs = [&self.up, &self.down];
+        let mut distances: [HashMap<Node, Cost>; 2] = Default::default();
+        let mut previous: [HashMap<Node, Node>; 2] = Default::default();
+        let mut unvisited: [BinaryHeap<NodeWithCost>; 2] = Default::default();
+        let mut best: Option<(Cost, Node)> = None;
+        for (side, start) in [source, target].iter().copied().enumerate() {
+            distances[side].insert(start, 0);
+            unvisited[side].push(NodeWithCost::new(start, 0));
+        }
+
+        loop {
+            // Take from whichever search has the cheaper Node left, as long
+            // as it can still lead to a cheaper route.
+            let side = match (unvisited[0].peek(), unvisited[1].peek()) {
+                (Some(forward), Some(backward)) if forward.cost <= backward.cost => {
+                    0
+                },
+                (Some(_forward), Some(_backward)) => 1,
+                (Some(_forward), None) => 0,
+                (None, Some(_backward)) => 1,
+                (None, None) => break,
+            };
+            let current = match unvisited[side].pop() {
+                Some(current) => current,
+                None => break,
+            };
+            if best.is_some_and(|(cost, _node)| current.cost >= cost) {
+                unvisited[side].clear();
+                continue;
+            }
+            if distances[side].get(&current.node) != Some(&current.cost) {
+                continue;
+            }
+            if let Some(other) = distances[1 - side].get(&current.node) {
+                let total = current.cost.saturating_add(*other);
+                if best.is_none_or(|(cost, _node)| total < cost) {
+                    best = Some((total, current.node));
+                }
+            }
+            // A Node which a higher ranked Node reaches more cheaply is not
+            // on the cheapest route, so its edges are not searched (stall on
+            // demand). The edges coming down into it are the ones the other
+            // search climbs.
+            let stalled = graphs[1 - side]
+                .get_node(current.node)
+                .unwrap_or(&[])
+                .iter()
+                .any(|higher| {
+                    return distances[side]
+                        .get(&higher.node)
+                        .is_some_and(|cost| {
+                            return cost.saturating_add(higher.cost) < current.cost;
+                        });
+                });
+            if stalled {
+                continue;
+            }
+            for adjacent in graphs[side].get_node(current.node).unwrap_or(&[]) {
+                let new_distance = current.cost.saturating_add(adjacent.cost);
+                match distances[side].get(&adjacent.node) {
+                    Some(distance) if *distance <= new_distance => continue,
+                    _ => distances[side].insert(adjacent.node, new_distance),
+                };
+                previous[side].insert(adjacent.node, current.node);
+                unvisited[side].push(NodeWithCost::new(adjacent.node, new_distance));
+            }
+        }
+        return Ok(best.map(|(cost, node)| Meeting {cost, node, previous}));
+    }
+}
+
+/// Where the 2 searches of a query met on the cheapest route.
+struct Meeting {
+    cost: Cost,
+    node: Node,
+    /// The [`Node`] each [`Node`] was reached from by each search.
+    previous: [HashMap<Node, Node>; 2],
+}
@@ -1,11 +1,167 @@
+//! Complex version of the Dijkstra Algorithm, which lets you identify nodes
+//! with any [`Hash`]able type, such as [`String`]s or UUIDs, and use any
+//! type implementing [`Cost`] as the cost, such as the unsigned integers,
+//! floats, or a `(time, toll)` pair compared one after the other.
+//! 
+//! [`MtdDijkstra`] gives each identifier an index when it is created and
+//! runs the same search as the [`super::simple`] edition over those
+//! indices, translating the results back to identifiers. Use the
+//! [`super::simple`] edition when the [`Node`]s are already numbered, as it
+//! avoids the translation and keeps less in memory.
+
 use std::{
-    cmp::Ordering,
-    collections::HashMap,
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
     hash::Hash,
+    io::{Error, ErrorKind},
     sync::{Arc, Mutex},
 };
 
-#[derive(Debug, Hash, PartialEq, Clone)]
+use super::simple::{DuplicateEdgePolicy, JOBS_PER_THREAD};
+use crate::{pool::ThreadPool, unwrapmutex};
+
+/// A cost of going along an edge, which can be added up along a route and
+/// compared with other costs. Costs must never be negative, as the Dijkstra
+/// Algorithm assumes a route never gets cheaper by going further.
+pub trait Cost: Clone + Send + Sync + 'static {
+    /// The cost of not moving at all.
+    fn zero() -> Self;
+
+    /// Adds `other` to this cost, or returns [`None`] if the sum is too large
+    /// to be held.
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    /// Adds `other` to this cost, stopping at the largest cost if the sum is
+    /// too large to be held.
+    fn saturating_add(&self, other: &Self) -> Self;
+
+    /// Compares this cost with `other`. Unlike [`PartialOrd`], every 2 costs
+    /// must be comparable, and the order must be total. It is only called
+    /// on costs which are [`Cost::is_valid`].
+    fn compare(&self, other: &Self) -> Ordering;
+
+    /// Whether this cost can be put on an edge. Costs which are not, such as
+    /// a `NaN` or negative float, are rejected by
+    /// [`AdjacencyMatrix::push`]. Every cost is valid unless this is
+    /// overridden.
+    fn is_valid(&self) -> bool {
+        return true;
+    }
+}
+
+/// Implements [`Cost`] for unsigned integer types.
+macro_rules! unsignedcost {
+    ($($type: ty),*) => {
+        $(
+            impl Cost for $type {
+                fn zero() -> Self {
+                    return 0;
+                }
+
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    return <$type>::checked_add(*self, *other);
+                }
+
+                fn saturating_add(&self, other: &Self) -> Self {
+                    return <$type>::saturating_add(*self, *other);
+                }
+
+                fn compare(&self, other: &Self) -> Ordering {
+                    return self.cmp(other);
+                }
+            }
+        )*
+    };
+}
+
+unsignedcost!(u8, u16, u32, u64, u128, usize);
+
+/// Implements [`Cost`] for float types. `NaN` and negative floats are not
+/// valid, so every valid float can be compared with every other one.
+macro_rules! floatcost {
+    ($($type: ty),*) => {
+        $(
+            impl Cost for $type {
+                fn zero() -> Self {
+                    return 0.0;
+                }
+
+                fn checked_add(&self, other: &Self) -> Option<Self> {
+                    return Some(self + other);
+                }
+
+                fn saturating_add(&self, other: &Self) -> Self {
+                    return self + other;
+                }
+
+                fn compare(&self, other: &Self) -> Ordering {
+                    return self.partial_cmp(other).unwrap_or(Ordering::Equal);
+                }
+
+                fn is_valid(&self) -> bool {
+                    return *self >= 0.0;
+                }
+            }
+        )*
+    };
+}
+
+floatcost!(f32, f64);
+
+/// Pairs of costs are added up separately and compared lexicographically,
+/// so a `(time, toll)` pair finds the fastest route and only looks at the
+/// tolls to choose between equally fast ones.
+impl<A: Cost, B: Cost> Cost for (A, B) {
+    fn zero() -> Self {
+        return (A::zero(), B::zero());
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        return Some((self.0.checked_add(&other.0)?, self.1.checked_add(&other.1)?));
+    }
+
+    fn saturating_add(&self, other: &Self) -> Self {
+        return (self.0.saturating_add(&other.0), self.1.saturating_add(&other.1));
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        return self.0.compare(&other.0).then_with(|| self.1.compare(&other.1));
+    }
+
+    fn is_valid(&self) -> bool {
+        return self.0.is_valid() && self.1.is_valid();
+    }
+}
+
+/// An entry of the heap in [`shortest_from`], ordered by [`Cost::compare`]
+/// with the cheapest first, so [`Cost`]s do not have to implement [`Ord`].
+/// This lets floats, which are only [`PartialOrd`], be used as costs.
+struct Cheapest<C: Cost> {
+    cost: C,
+    node: usize,
+}
+
+impl<C: Cost> PartialEq for Cheapest<C> {
+    fn eq(&self, other: &Self) -> bool {
+        return self.cmp(other) == Ordering::Equal;
+    }
+}
+
+impl<C: Cost> Eq for Cheapest<C> {}
+
+impl<C: Cost> PartialOrd for Cheapest<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<C: Cost> Ord for Cheapest<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return other.cost.compare(&self.cost).then_with(|| other.node.cmp(&self.node));
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub struct Node<T>
 where
     T: Hash + PartialEq + Clone,
@@ -13,6 +169,16 @@ where
     pub identifier: T,
 }
 
+impl<T> Node<T>
+where
+    T: Hash + PartialEq + Clone,
+{
+    /// Creates a new [`Node`].
+    pub fn new(identifier: T) -> Self {
+        return Self {identifier};
+    }
+}
+
 #[derive(Debug, Hash, PartialEq, Clone)]
 pub struct NodeWithCost<T, C>
 where
@@ -23,6 +189,17 @@ where
     pub cost: C,
 }
 
+impl<T, C> NodeWithCost<T, C>
+where
+    T: Hash + PartialEq + Clone,
+    C: PartialOrd + Clone,
+{
+    /// Creates a new [`NodeWithCost`] going to `identifier`.
+    pub fn new(identifier: T, cost: C) -> Self {
+        return Self {node: Node::new(identifier), cost};
+    }
+}
+
 impl<T, C> PartialOrd for NodeWithCost<T, C>
 where
     T: Hash + PartialEq + Clone,
@@ -39,13 +216,36 @@ where
     }
 }
 
-#[derive(Debug)]
+/// Combines the [`Cost`] of an `existing` edge with the [`Cost`] of a `new`
+/// edge between the same 2 [`Node`]s according to `policy`, like
+/// [`DuplicateEdgePolicy::combine`].
+fn combine<C: Cost>(policy: DuplicateEdgePolicy, existing: &C, new: C) -> C {
+    let keep_existing = match policy {
+        DuplicateEdgePolicy::KeepMin => existing.compare(&new).is_le(),
+        DuplicateEdgePolicy::KeepMax => existing.compare(&new).is_ge(),
+        DuplicateEdgePolicy::Replace => false,
+        DuplicateEdgePolicy::Sum => return existing.saturating_add(&new),
+    };
+    if keep_existing {
+        return existing.clone();
+    }
+    return new;
+}
+
+/// An adjacency matrix which represents the graph, with the neighbouring
+/// [`NodeWithCost`]s of each [`Node`]. [`Node`]s are added the first time
+/// an edge to or from them is pushed, or with
+/// [`AdjacencyMatrix::add_node`], and are kept in that order.
+#[derive(Debug, Clone)]
 pub struct AdjacencyMatrix<T, C>
 where
     T: Hash + PartialEq + Clone,
     C: PartialOrd + Clone,
 {
-    pub matrix: Arc<Mutex<HashMap<Node<T>, Vec<NodeWithCost<T, C>>>>>,
+    identifiers: Vec<T>,
+    indices: HashMap<T, usize>,
+    matrix: Vec<Vec<NodeWithCost<T, C>>>,
+    policy: DuplicateEdgePolicy,
 }
 
 impl<T, C> AdjacencyMatrix<T, C>
@@ -53,8 +253,525 @@ where
     T: Hash + PartialEq + Clone,
     C: PartialOrd + Clone,
 {
+    /// Creates a new [`AdjacencyMatrix`] without any [`Node`]s, which keeps
+    /// the cheaper route when a duplicate edge is pushed.
     pub fn new() -> Self {
-        let matrix = Arc::new(Mutex::new(HashMap::new()));
-        return Self { matrix };
+        return Self::with_policy(DuplicateEdgePolicy::default());
+    }
+
+    /// Creates a new [`AdjacencyMatrix`] without any [`Node`]s which
+    /// handles duplicate edges according to `policy`.
+    pub fn with_policy(policy: DuplicateEdgePolicy) -> Self {
+        return Self {
+            identifiers: Vec::new(),
+            indices: HashMap::new(),
+            matrix: Vec::new(),
+            policy,
+        };
+    }
+
+    /// Get the [`DuplicateEdgePolicy`] used by [`AdjacencyMatrix::push`].
+    pub fn policy(&self) -> DuplicateEdgePolicy {
+        return self.policy;
+    }
+
+    /// Sets the [`DuplicateEdgePolicy`] used by [`AdjacencyMatrix::push`].
+    /// Edges which are already in the matrix are not changed.
+    pub fn set_policy(&mut self, policy: DuplicateEdgePolicy) {
+        self.policy = policy;
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> usize {
+        return self.identifiers.len();
+    }
+
+    /// Number of edges in the graph.
+    pub fn edges(&self) -> usize {
+        return self.matrix.iter().map(Vec::len).sum();
+    }
+
+    /// Iterates over the identifiers of every [`Node`] in the order they
+    /// were added.
+    pub fn nodes(&self) -> impl Iterator<Item = &T> {
+        return self.identifiers.iter();
+    }
+}
+
+impl<T, C> AdjacencyMatrix<T, C>
+where
+    T: Hash + Eq + Clone,
+    C: Cost + PartialOrd,
+{
+    /// Builds an [`AdjacencyMatrix`] from a list of `(from, to, cost)`
+    /// edges, keeping the cheaper route between duplicate edges.
+    /// 
+    /// # Error
+    /// 
+    /// If any edge cannot be pushed, an error is returned. See
+    /// [`AdjacencyMatrix::push`].
+    pub fn from_edges<I>(edges: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (T, T, C)>,
+    {
+        return Self::from_edges_with_policy(DuplicateEdgePolicy::default(), edges);
+    }
+
+    /// Builds an [`AdjacencyMatrix`] from a list of `(from, to, cost)`
+    /// edges, handling duplicate edges according to `policy`.
+    /// 
+    /// # Error
+    /// 
+    /// If any edge cannot be pushed, an error is returned. See
+    /// [`AdjacencyMatrix::push`].
+    pub fn from_edges_with_policy<I>(
+        policy: DuplicateEdgePolicy,
+        edges: I,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (T, T, C)>,
+    {
+        let mut graph = Self::with_policy(policy);
+        for (from, to, cost) in edges {
+            graph.push(from, NodeWithCost::new(to, cost))?;
+        }
+        return Ok(graph);
+    }
+
+    /// Adds a [`Node`] without any edges, returning whether it was new.
+    pub fn add_node(&mut self, identifier: T) -> bool {
+        if self.indices.contains_key(&identifier) {
+            return false;
+        }
+        self.index_of(identifier);
+        return true;
+    }
+
+    /// Get the index of `identifier`, adding it as a [`Node`] if it is new.
+    fn index_of(&mut self, identifier: T) -> usize {
+        if let Some(index) = self.indices.get(&identifier) {
+            return *index;
+        }
+        self.identifiers.push(identifier.clone());
+        self.indices.insert(identifier, self.matrix.len());
+        self.matrix.push(Vec::new());
+        return self.matrix.len() - 1;
+    }
+
+    /// Whether `identifier` is a [`Node`] in the graph.
+    pub fn contains(&self, identifier: &T) -> bool {
+        return self.indices.contains_key(identifier);
+    }
+
+    /// Pushes an edge going from `from` to `to`, adding either [`Node`] if
+    /// it is new. An edge from a [`Node`] to itself is never useful for
+    /// finding the cheapest route, so it is ignored. If `from` already has
+    /// an edge to the same [`Node`], the 2 [`Cost`]s are combined according
+    /// to [`AdjacencyMatrix::policy`].
+    /// 
+    /// # Error
+    /// 
+    /// If the [`Cost`] of the edge is not [`Cost::is_valid`], such as a `NaN`
+    /// or negative float, an error is returned and nothing is added.
+    pub fn push(&mut self, from: T, to: NodeWithCost<T, C>) -> Result<(), Error> {
+        if !to.cost.is_valid() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The cost of the edge is not valid."
+            ));
+        }
+        let from = self.index_of(from);
+        let target = self.index_of(to.node.identifier.clone());
+        if from == target {
+            return Ok(());
+        }
+        let policy = self.policy;
+        let adjacents = &mut self.matrix[from];
+        match adjacents.iter_mut().find(|existing| existing.node == to.node) {
+            Some(existing) => existing.cost = combine(policy, &existing.cost, to.cost),
+            None => adjacents.push(to),
+        }
+        return Ok(());
+    }
+
+    /// Pushes an edge going both ways between `a` and `b` with the same
+    /// `cost`. See [`AdjacencyMatrix::push`].
+    /// 
+    /// # Error
+    /// 
+    /// If either edge cannot be pushed, an error is returned.
+    pub fn push_undirected(&mut self, a: T, b: T, cost: C) -> Result<(), Error> {
+        self.push(a.clone(), NodeWithCost::new(b.clone(), cost.clone()))?;
+        self.push(b, NodeWithCost::new(a, cost))?;
+        return Ok(());
+    }
+
+    /// Get the edges leaving `identifier`, or [`None`] if it is not a
+    /// [`Node`] in the graph.
+    pub fn neighbors(&self, identifier: &T) -> Option<&[NodeWithCost<T, C>]> {
+        let index = self.indices.get(identifier)?;
+        return Some(&self.matrix[*index]);
+    }
+
+    /// Checks the graph for [`Node`]s without any edges and parts which are
+    /// not connected to each other. Self-loops and duplicate edges cannot
+    /// be pushed, so unlike [`super::validate::GraphReport`] they are not
+    /// looked for. See [`GraphReport`].
+    pub fn validate(&self) -> GraphReport<T> {
+        let total = self.total();
+        // Edges in both directions, so the components ignore direction.
+        let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); total];
+        for (from, adjacents) in self.matrix.iter().enumerate() {
+            for adjacent in adjacents {
+                let to = self.indices[&adjacent.node.identifier];
+                neighbours[from].push(to);
+                neighbours[to].push(from);
+            }
+        }
+        let isolated = (0..total)
+            .filter(|node| neighbours[*node].is_empty())
+            .map(|node| self.identifiers[node].clone())
+            .collect();
+
+        let mut components: Vec<Vec<T>> = Vec::new();
+        let mut visited = vec![false; total];
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        for start in 0..total {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            queue.push_back(start);
+            let mut component: Vec<T> = Vec::new();
+            while let Some(node) = queue.pop_front() {
+                component.push(self.identifiers[node].clone());
+                for other in &neighbours[node] {
+                    if !visited[*other] {
+                        visited[*other] = true;
+                        queue.push_back(*other);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        // Stable, so components of the same size stay in order.
+        components.sort_by_key(|component| Reverse(component.len()));
+        return GraphReport {nodes: total, edges: self.edges(), isolated, components};
+    }
+}
+
+impl<T, C> Default for AdjacencyMatrix<T, C>
+where
+    T: Hash + PartialEq + Clone,
+    C: PartialOrd + Clone,
+{
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+/// What [`AdjacencyMatrix::validate`] found in a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphReport<T> {
+    /// Number of [`Node`]s in the graph.
+    pub nodes: usize,
+    /// Number of edges in the graph.
+    pub edges: usize,
+    /// [`Node`]s which no edge leaves or enters.
+    pub isolated: Vec<T>,
+    /// The groups of [`Node`]s which are connected when the direction of
+    /// the edges is ignored, from largest to smallest. No [`Node`] in one
+    /// group can reach a [`Node`] in another.
+    pub components: Vec<Vec<T>>,
+}
+
+impl<T> GraphReport<T> {
+    /// Whether nothing suspicious was found: there are no isolated
+    /// [`Node`]s and the graph is in one piece.
+    pub fn is_clean(&self) -> bool {
+        return self.isolated.is_empty() && self.components.len() <= 1;
+    }
+
+    /// Number of [`Node`]s outside the largest component, which cannot be
+    /// reached from most of the graph.
+    pub fn unreachable(&self) -> usize {
+        return self.components.iter().skip(1).map(Vec::len).sum();
+    }
+}
+
+/// A route through the graph, made of the identifiers of the [`Node`]s
+/// visited in order and the total cost of the edges between them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Path<T, C> {
+    pub nodes: Vec<T>,
+    pub cost: C,
+}
+
+/// The edges leaving each indexed [`Node`], as `(index, cost)`.
+type IndexedGraph<C> = Vec<Vec<(usize, C)>>;
+
+/// The cost of reaching each indexed [`Node`] from one source and the index
+/// of the [`Node`] visited just before it, with [`None`] marking unreachable
+/// [`Node`]s.
+type Row<C> = Vec<Option<(C, Option<usize>)>>;
+
+/// Runs the Dijkstra Algorithm from the [`Node`] at index `source`. Routes
+/// whose [`Cost`] is too large to be held are left out, as if the last edge
+/// was not there.
+fn shortest_from<C: Cost>(graph: &IndexedGraph<C>, source: usize) -> Row<C> {
+    let mut row: Row<C> = vec![None; graph.len()];
+    row[source] = Some((C::zero(), None));
+    let mut unvisited: BinaryHeap<Cheapest<C>> = BinaryHeap::new();
+    unvisited.push(Cheapest {cost: C::zero(), node: source});
+    while let Some(Cheapest {cost, node}) = unvisited.pop() {
+        let stale = row[node]
+            .as_ref()
+            .is_some_and(|(best, _)| best.compare(&cost) == Ordering::Less);
+        if stale {
+            continue;
+        }
+        for (adjacent, edge) in &graph[node] {
+            let new_cost = match cost.checked_add(edge) {
+                Some(new_cost) => new_cost,
+                None => continue,
+            };
+            match &row[*adjacent] {
+                Some((existing, _)) if existing.compare(&new_cost).is_le() => continue,
+                _ => row[*adjacent] = Some((new_cost.clone(), Some(node))),
+            }
+            unvisited.push(Cheapest {cost: new_cost, node: *adjacent});
+        }
+    }
+    return row;
+}
+
+/// This `struct` calculates the shortest route from each [`Node`] of an
+/// [`AdjacencyMatrix`] using multiple threads, like
+/// [`super::simple::MtdDijkstra`] but with generic identifiers and costs.
+/// 
+/// The identifiers `T` must be [`Hash`]able and comparable, and the costs
+/// `C` must implement [`Cost`].
+pub struct MtdDijkstra<T, C> {
+    pool: ThreadPool,
+    identifiers: Vec<T>,
+    indices: HashMap<T, usize>,
+    graph: Arc<IndexedGraph<C>>,
+    rows: Arc<Mutex<HashMap<usize, Row<C>>>>,
+    scheduled: HashSet<usize>,
+}
+
+impl<T, C> MtdDijkstra<T, C>
+where
+    T: Hash + Eq + Clone + Send + Sync + 'static,
+    C: Cost + PartialOrd,
+{
+    /// Creates a new [`MtdDijkstra`] instance, copying the graph out of
+    /// `matrix`.
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix<T, C>``` => The adjacency matrix which
+    ///    describes the graph.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix<T, C>) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        let graph: IndexedGraph<C> = matrix
+            .matrix
+            .iter()
+            .map(|adjacents| {
+                return adjacents
+                    .iter()
+                    .map(|adjacent| {
+                        let to = matrix.indices[&adjacent.node.identifier];
+                        return (to, adjacent.cost.clone());
+                    })
+                    .collect();
+            })
+            .collect();
+        return Ok(Self {
+            pool,
+            identifiers: matrix.identifiers.clone(),
+            indices: matrix.indices.clone(),
+            graph: Arc::new(graph),
+            rows: Arc::new(Mutex::new(HashMap::new())),
+            scheduled: HashSet::new(),
+        });
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> usize {
+        return self.identifiers.len();
+    }
+
+    /// Iterates over the identifiers of every [`Node`] in the graph.
+    pub fn nodes(&self) -> impl Iterator<Item = &T> {
+        return self.identifiers.iter();
+    }
+
+    /// Whether `identifier` is a [`Node`] in the graph.
+    pub fn contains(&self, identifier: &T) -> bool {
+        return self.indices.contains_key(identifier);
+    }
+
+    /// Get the index of `identifier`.
+    fn index(&self, identifier: &T) -> Result<usize, Error> {
+        return match self.indices.get(identifier) {
+            Some(index) => Ok(*index),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The node is not in the graph."
+            )),
+        };
+    }
+
+    /// Sends a job calculating the rows of each of `sources` to the
+    /// [`ThreadPool`]. Sources which have already been scheduled are
+    /// skipped.
+    fn schedule(&mut self, sources: &[usize]) -> Result<(), Error> {
+        let sources: Vec<usize> = sources
+            .iter()
+            .copied()
+            .filter(|source| self.scheduled.insert(*source))
+            .collect();
+        if sources.is_empty() {
+            return Ok(());
+        }
+        let graph = self.graph.clone();
+        let rows = self.rows.clone();
+        return self.pool.execute(move || {
+            for source in sources {
+                let row = shortest_from(&graph, source);
+                unwrapmutex!(rows.lock()).insert(source, row);
+            }
+            return Ok(());
+        });
+    }
+
+    /// Calculates the shortest distance to all (if possible) nodes in the
+    /// graph from each node, on the [`ThreadPool`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job could not be sent.
+    pub fn calculate(&mut self) -> Result<(), Error> {
+        let sources: Vec<usize> = (0..self.total()).collect();
+        let jobs = self.pool.threads() * JOBS_PER_THREAD;
+        let chunk_size = self.total().div_ceil(jobs).max(1);
+        for chunk in sources.chunks(chunk_size) {
+            self.schedule(chunk)?;
+        }
+        return Ok(());
+    }
+
+    /// Calculates the shortest distance to all (if possible) nodes in the
+    /// graph from `source` only.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` is not in the graph.
+    pub fn calculate_from(&mut self, source: &T) -> Result<(), Error> {
+        let source = self.index(source)?;
+        return self.schedule(&[source]);
+    }
+
+    /// Waits for every scheduled job and gets a copy of the row of
+    /// `source`, or [`None`] if a job failed or the row was never
+    /// scheduled.
+    fn row(&mut self, source: &T) -> Option<Row<C>> {
+        let source = self.index(source).ok()?;
+        self.pool.wait().ok()?;
+        return self.rows.lock().ok()?.get(&source).cloned();
+    }
+
+    /// Translates a calculated row to a map from the identifier of each
+    /// [`Node`] which can be reached to the cost of reaching it.
+    fn costs(&self, row: Row<C>) -> HashMap<T, C> {
+        return row
+            .into_iter()
+            .zip(&self.identifiers)
+            .filter_map(|(cost, node)| cost.map(|(cost, _)| (node.clone(), cost)))
+            .collect();
+    }
+
+    /// Get the cost to get to every [`Node`] which can be reached from
+    /// `source`, keyed by identifier. [`Node`]s which cannot be reached are
+    /// left out.
+    /// 
+    /// Blocks until all scheduled [`Node`]s have been calculated. [`None`]
+    /// is returned if `source` was never calculated or a job failed.
+    pub fn get(&mut self, source: &T) -> Option<HashMap<T, C>> {
+        let row = self.row(source)?;
+        return Some(self.costs(row));
+    }
+
+    /// Waits for every scheduled job to finish and gets the costs from every
+    /// calculated source, as source to destination to cost. Like
+    /// [`MtdDijkstra::get`], [`Node`]s which cannot be reached are left out.
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned.
+    pub fn get_all(&mut self) -> Result<HashMap<T, HashMap<T, C>>, Error> {
+        self.pool.wait()?;
+        let rows = unwrapmutex!(self.rows.lock()).clone();
+        return Ok(
+            rows.into_iter()
+                .map(|(source, row)| {
+                    return (self.identifiers[source].clone(), self.costs(row));
+                })
+                .collect()
+        );
+    }
+
+    /// Waits for every scheduled job to finish and iterates over the cost of
+    /// every route found, as `(source, destination, cost)`, without building
+    /// a map for each source or copying the calculated rows. The sources are
+    /// in no particular order.
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned.
+    pub fn iter(&mut self) -> Result<impl Iterator<Item = (&T, &T, &C)> + '_, Error> {
+        self.pool.wait()?;
+        // Every job has finished and dropped its handle to the rows, so they
+        // can be borrowed without holding the lock.
+        let rows = match Arc::get_mut(&mut self.rows) {
+            Some(rows) => unwrapmutex!(rows.get_mut()),
+            None => return Err(Error::other("A job still holds the rows.")),
+        };
+        let identifiers = &self.identifiers;
+        return Ok(rows.iter().flat_map(move |(source, row)| {
+            return row
+                .iter()
+                .enumerate()
+                .filter_map(move |(node, cost)| {
+                    let (cost, _) = cost.as_ref()?;
+                    return Some((&identifiers[*source], &identifiers[node], cost));
+                });
+        }));
+    }
+
+    /// Get the cheapest [`Path`] from `source` to `target`, or [`None`] if
+    /// `target` cannot be reached, `source` was never calculated or a job
+    /// failed.
+    /// 
+    /// Blocks until all scheduled [`Node`]s have been calculated.
+    pub fn path(&mut self, source: &T, target: &T) -> Option<Path<T, C>> {
+        let row = self.row(source)?;
+        let target = self.index(target).ok()?;
+        let (cost, _) = row[target].clone()?;
+        let mut nodes = vec![self.identifiers[target].clone()];
+        let mut node = target;
+        while let Some((_, Some(before))) = &row[node] {
+            nodes.push(self.identifiers[*before].clone());
+            node = *before;
+        }
+        nodes.reverse();
+        return Some(Path {nodes, cost});
     }
 }
@@ -0,0 +1,201 @@
+//! Module for calculating the shortest routes from a single source [`Node`]
+//! with the delta-stepping algorithm, which spreads the work of one search
+//! over a [`ThreadPool`].
+//! 
+//! [`super::simple::MtdDijkstra`] runs one source per job, so a single
+//! source on a huge graph only ever uses one thread. Delta-stepping groups
+//! the [`Node`]s into buckets of width `delta` by their tentative [`Cost`]
+//! and settles a whole bucket at a time. The edges leaving every [`Node`] in
+//! the current bucket are scanned in parallel, and the cheaper routes they
+//! offer are then applied in one pass. Light edges (costing at most `delta`)
+//! can lead back into the current bucket, so they are scanned until the
+//! bucket stops changing, while heavy edges are scanned once when the
+//! bucket is done.
+//! 
+//! A small `delta` does less wasted work but settles fewer [`Node`]s at a
+//! time, and a large `delta` does the opposite. [`DeltaStepping::new`]
+//! picks the average edge [`Cost`], which is a reasonable start.
+
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use super::simple::{Cost, CsrGraph, Node, check_node};
+use crate::pool::{ThreadPool, chunk::ChunkPolicy};
+
+/// Single-source shortest routes over a [`CsrGraph`] with the
+/// delta-stepping algorithm.
+#[derive(Debug, Clone)]
+pub struct DeltaStepping {
+    graph: Arc<CsrGraph>,
+    delta: Cost,
+}
+
+impl DeltaStepping {
+    /// Creates a new [`DeltaStepping`] over `graph`, with `delta` set to the
+    /// average [`Cost`] of its edges (at least `1`).
+    pub fn new<G: Into<CsrGraph>>(graph: G) -> Self {
+        let graph: CsrGraph = graph.into();
+        let total_cost: Cost = (0..graph.total())
+            .filter_map(|node| graph.get_node(node))
+            .flatten()
+            .map(|adjacent| adjacent.cost)
+            .fold(0, Cost::saturating_add);
+        let delta = (total_cost / graph.edges().max(1) as Cost).max(1);
+        return Self {graph: Arc::new(graph), delta};
+    }
+
+    /// Creates a new [`DeltaStepping`] over `graph` with buckets `delta`
+    /// wide.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `delta` is `0`.
+    pub fn with_delta<G: Into<CsrGraph>>(graph: G, delta: Cost) -> Result<Self, Error> {
+        let mut stepping = Self::new(graph);
+        stepping.set_delta(delta)?;
+        return Ok(stepping);
+    }
+
+    /// Get the width of each bucket.
+    pub fn delta(&self) -> Cost {
+        return self.delta;
+    }
+
+    /// Sets the width of each bucket.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `delta` is `0`, and the width is
+    /// left as it was.
+    pub fn set_delta(&mut self, delta: Cost) -> Result<(), Error> {
+        if delta == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The bucket width must be at least 1."
+            ));
+        }
+        self.delta = delta;
+        return Ok(());
+    }
+
+    /// Get the graph being searched.
+    pub fn graph(&self) -> &CsrGraph {
+        return &self.graph;
+    }
+
+    /// Calculates the [`Cost`] to reach every [`Node`] in the graph from
+    /// `source`, with [`None`] marking unreachable [`Node`]s.
+    /// 
+    /// # Parameters
+    /// 1. ```pool: &mut ThreadPool``` => The threads which scan the edges.
+    /// 2. ```source: Node``` => Where every route starts.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` is not in the graph or
+    /// a job in `pool` fails.
+    pub fn shortest_from(
+        &self,
+        pool: &mut ThreadPool,
+        source: Node,
+    ) -> Result<Vec<Option<Cost>>, Error> {
+        let total = self.graph.total();
+        check_node(source, total)?;
+
+        let delta = self.delta;
+        let mut distances: Vec<Option<Cost>> = vec![None; total];
+        let mut buckets: BTreeMap<Cost, Vec<Node>> = BTreeMap::new();
+        let mut queued: Vec<bool> = vec![false; total];
+        distances[source] = Some(0);
+        buckets.insert(0, vec![source]);
+
+        while let Some((index, mut nodes)) = buckets.pop_first() {
+            let mut settled: Vec<Node> = Vec::new();
+            loop {
+                // Nodes which have since moved to a cheaper route in a later
+                // bucket, or which appear twice, are dropped.
+                let mut frontier: Vec<(Node, Cost)> = Vec::with_capacity(nodes.len());
+                for node in nodes.drain(..) {
+                    if let Some(cost) = distances[node] {
+                        if cost / delta == index && !queued[node] {
+                            queued[node] = true;
+                            frontier.push((node, cost));
+                        }
+                    }
+                }
+                if frontier.is_empty() {
+                    break;
+                }
+                for (node, _cost) in &frontier {
+                    queued[*node] = false;
+                }
+                settled.extend(frontier.iter().map(|(node, _cost)| *node));
+                let light = move |cost| cost <= delta;
+                let requests = self.requests(pool, frontier, light)?;
+                relax(&mut distances, &mut buckets, &mut nodes, index, delta, requests);
+            }
+
+            settled.sort_unstable();
+            settled.dedup();
+            let frontier: Vec<(Node, Cost)> = settled
+                .into_iter()
+                .filter_map(|node| Some((node, distances[node]?)))
+                .collect();
+            let requests = self.requests(pool, frontier, move |cost| cost > delta)?;
+            relax(&mut distances, &mut buckets, &mut nodes, index, delta, requests);
+        }
+        return Ok(distances);
+    }
+
+    /// Scans the edges for which `keep` returns `true` leaving every
+    /// [`Node`] in `frontier` in parallel, and returns the routes they
+    /// offer as `(node, cost)` pairs.
+    fn requests<F>(
+        &self,
+        pool: &mut ThreadPool,
+        frontier: Vec<(Node, Cost)>,
+        keep: F,
+    ) -> Result<Vec<Vec<(Node, Cost)>>, Error>
+    where
+        F: Fn(Cost) -> bool + Send + Sync + 'static,
+    {
+        let graph = self.graph.clone();
+        return pool.map(Arc::new(frontier), ChunkPolicy::Auto, move |(node, cost)| {
+            return graph
+                .get_node(*node)
+                .into_iter()
+                .flatten()
+                .filter(|adjacent| keep(adjacent.cost))
+                .map(|adjacent| (adjacent.node, cost.saturating_add(adjacent.cost)))
+                .collect::<Vec<(Node, Cost)>>();
+        });
+    }
+}
+
+/// Applies every route in `requests` which is cheaper than the one known so
+/// far, moving the [`Node`] into its new bucket. [`Node`]s which land in the
+/// bucket numbered `current` go into `nodes` to be scanned again instead.
+fn relax(
+    distances: &mut [Option<Cost>],
+    buckets: &mut BTreeMap<Cost, Vec<Node>>,
+    nodes: &mut Vec<Node>,
+    current: Cost,
+    delta: Cost,
+    requests: Vec<Vec<(Node, Cost)>>,
+) {
+    for (node, cost) in requests.into_iter().flatten() {
+        match distances[node] {
+            Some(distance) if distance <= cost => continue,
+            _ => distances[node] = Some(cost),
+        }
+        let index = cost / delta;
+        if index == current {
+            nodes.push(node);
+        } else {
+            buckets.entry(index).or_default().push(node);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Module for the errors returned when building graphs for and reading
+//! results from the [`super::simple`] edition.
+
+use std::{
+    error::Error as StdError,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{Error, ErrorKind},
+};
+
+use super::{signed::SignedCost, simple::Node};
+
+/// The reason the costs from a source [`Node`] could not be returned.
+/// 
+/// # Variants
+/// 
+/// 1. UnknownNode([`Node`]) => The [`Node`] is not in the graph.
+/// 2. NotScheduled([`Node`]) => The [`Node`] is in the graph, but the costs
+///    from it were never asked for with
+///    [`super::simple::MtdDijkstra::calculate`] or
+///    [`super::simple::MtdDijkstra::calculate_from`].
+/// 3. JobFailed(usize) => This many jobs calculating costs returned an
+///    error, so the costs may never be ready.
+/// 4. Poisoned => A thread panicked while holding the lock on the results.
+/// 5. Channel(String) => The [`crate::pool::ThreadPool`] could not hear back
+///    from its worker threads, with the reason why.
+/// 6. NegativeEdge([`Node`], [`Node`], [`SignedCost`]) => The edge from the
+///    first [`Node`] to the second has a negative cost, which the Dijkstra
+///    Algorithm cannot handle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DijkstraError {
+    UnknownNode(Node),
+    NotScheduled(Node),
+    JobFailed(usize),
+    Poisoned,
+    Channel(String),
+    NegativeEdge(Node, Node, SignedCost),
+}
+
+impl Display for DijkstraError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        return match self {
+            Self::UnknownNode(node) => {
+                write!(formatter, "{} exceeds matrix size.", node)
+            },
+            Self::NotScheduled(node) => {
+                write!(formatter, "{} has not been scheduled.", node)
+            },
+            Self::JobFailed(failed) => {
+                write!(formatter, "{} job(s) calculating the costs failed.", failed)
+            },
+            Self::Poisoned => {
+                write!(formatter, "The lock on the results was poisoned.")
+            },
+            Self::Channel(reason) => {
+                write!(formatter, "Could not hear back from the workers: {}", reason)
+            },
+            Self::NegativeEdge(from, to, cost) => {
+                write!(
+                    formatter,
+                    "The edge {} -> {} has a negative cost of {}, but the \
+                    Dijkstra Algorithm needs every cost to be at least 0.",
+                    from, to, cost
+                )
+            },
+        };
+    }
+}
+
+impl StdError for DijkstraError {}
+
+impl From<DijkstraError> for Error {
+    /// Converts a [`DijkstraError`] into a [`std::io::Error`] of a matching
+    /// kind, so it can be passed on with `?` where the rest of the crate is
+    /// used.
+    fn from(error: DijkstraError) -> Self {
+        let kind = match error {
+            DijkstraError::UnknownNode(_)
+            | DijkstraError::NegativeEdge(..) => ErrorKind::InvalidInput,
+            DijkstraError::NotScheduled(_) => ErrorKind::NotFound,
+            DijkstraError::JobFailed(_) | DijkstraError::Poisoned => ErrorKind::Other,
+            DijkstraError::Channel(_) => ErrorKind::BrokenPipe,
+        };
+        return Error::new(kind, error);
+    }
+}
@@ -0,0 +1,349 @@
+//! Floating point version of the Dijkstra Algorithm, which uses [`usize`] to
+//! identify nodes like [`super::simple`] but [`f64`] to represent the costs
+//! to go between 2 nodes.
+//! 
+//! Edge weights are checked when they are added, since a single `NaN` or
+//! negative weight would quietly break the ordering the algorithm relies on.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+    io::{Error, ErrorKind},
+    sync::{Arc, Mutex},
+};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use super::simple::{Node, check_node};
+use crate::{arena::with_arena, pool::ThreadPool, unwrapmutex, unwrapoption};
+
+/// The cost to get from one node to another node. It can be any finite
+/// [`f64`] which is not negative.
+pub type Cost = f64;
+
+/// The calculated [`Cost`]s, mapping each source [`Node`] to the [`Cost`] of
+/// reaching every [`Node`] from it, with [`None`] marking unreachable
+/// [`Node`]s.
+pub type DistanceTable = HashMap<Node, Vec<Option<Cost>>>;
+
+/// A destination [`Node`] and the [`Cost`] to reach it from an arbitrary
+/// starting point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeWithCost {
+    pub node: Node,
+    pub cost: Cost,
+}
+
+impl NodeWithCost {
+    /// Creates a new [`NodeWithCost`].
+    pub fn new(node: Node, cost: Cost) -> Self {
+        return Self {node, cost};
+    }
+}
+
+impl Eq for NodeWithCost {}
+
+impl PartialOrd for NodeWithCost {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for NodeWithCost {
+    /// A greater cost is marked as [`Ordering::Less`] so the BinaryHeap
+    /// floats the cheaper nodes to the top. [`f64::total_cmp`] is used, which
+    /// is safe because [`AdjacencyMatrix`] never holds a `NaN`.
+    fn cmp(&self, other: &Self) -> Ordering {
+        return other.cost.total_cmp(&self.cost);
+    }
+}
+
+/// Checks that `cost` can be used as the [`Cost`] of the edge from `from`
+/// to `to`.
+fn check_cost(from: Node, to: Node, cost: Cost) -> Result<(), Error> {
+    let problem = if cost.is_nan() {
+        "is NaN"
+    } else if cost.is_infinite() {
+        "is infinite"
+    } else if cost < 0.0 {
+        "is negative"
+    } else {
+        return Ok(());
+    };
+    return Err(Error::new(
+        ErrorKind::InvalidInput,
+        format!("The cost {} of the edge {} -> {} {}.", cost, from, to, problem)
+    ));
+}
+
+/// An adjacency matrix which represents a graph with [`f64`] costs. Every
+/// [`Cost`] is checked when it is pushed, so the matrix never holds a
+/// `NaN`, infinite or negative [`Cost`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "AdjacencyMatrixData"))]
+pub struct AdjacencyMatrix {
+    matrix: Vec<Vec<NodeWithCost>>,
+}
+
+/// The fields of an [`AdjacencyMatrix`] as they are deserialized, before
+/// they are checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct AdjacencyMatrixData {
+    matrix: Vec<Vec<NodeWithCost>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AdjacencyMatrixData> for AdjacencyMatrix {
+    type Error = Error;
+
+    /// Checks the edges like [`AdjacencyMatrix::push`] does, so that every
+    /// edge leads to a [`Node`] in the matrix with a valid [`Cost`], no edge
+    /// leads back to where it starts and no 2 edges leave a [`Node`] for the
+    /// same [`Node`].
+    fn try_from(data: AdjacencyMatrixData) -> Result<Self, Error> {
+        let graph = Self {matrix: data.matrix};
+        let mut seen: HashSet<Node> = HashSet::new();
+        for (from, adjacents) in graph.matrix.iter().enumerate() {
+            seen.clear();
+            for adjacent in adjacents {
+                graph.check_node(adjacent.node)?;
+                check_cost(from, adjacent.node, adjacent.cost)?;
+                if adjacent.node == from {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("{} has an edge to itself.", from)
+                    ));
+                } else if !seen.insert(adjacent.node) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "The edge from {} to {} is repeated.",
+                            from, adjacent.node
+                        )
+                    ));
+                }
+            }
+        }
+        return Ok(graph);
+    }
+}
+
+impl AdjacencyMatrix {
+    /// Creates a new [`AdjacencyMatrix`] with a fixed amount of [`Node`]s.
+    pub fn new(total: Node) -> Self {
+        return Self {matrix: vec![Vec::new(); total]};
+    }
+
+    /// Builds an [`AdjacencyMatrix`] with `total` [`Node`]s from a list of
+    /// `(from, to, cost)` edges. See [`AdjacencyMatrix::push`].
+    /// 
+    /// # Error
+    /// 
+    /// An error is returned for the first edge which cannot be pushed.
+    pub fn from_edges<I>(total: Node, edges: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (Node, Node, Cost)>,
+    {
+        let mut graph = Self::new(total);
+        for (from, to, cost) in edges {
+            graph.push(from, NodeWithCost::new(to, cost))?;
+        }
+        return Ok(graph);
+    }
+
+    /// Pushes an adjacent [`Node`] and the [`Cost`] to reach it to an origin
+    /// [`Node`]. If the 2 [`Node`]s are already connected in the same
+    /// direction, the cheaper route is kept.
+    /// 
+    /// # Error
+    /// 
+    /// If `from` or `to.node` exceeds the length of the matrix, or `to.cost`
+    /// is `NaN`, infinite or negative, an error naming the edge is returned
+    /// and the matrix is left as it was.
+    pub fn push(&mut self, from: Node, to: NodeWithCost) -> Result<(), Error> {
+        self.check_node(from)?;
+        self.check_node(to.node)?;
+        check_cost(from, to.node, to.cost)?;
+        if from == to.node {
+            return Ok(());
+        }
+        let adjacents = &mut self.matrix[from];
+        match adjacents.iter_mut().find(|existing| existing.node == to.node) {
+            Some(existing) => existing.cost = existing.cost.min(to.cost),
+            None => adjacents.push(to),
+        }
+        return Ok(());
+    }
+
+    /// Pushes an edge which can be travelled both ways between `a` and `b`
+    /// for the same [`Cost`].
+    /// 
+    /// # Error
+    /// 
+    /// See [`AdjacencyMatrix::push`]. Neither direction is added if there is
+    /// an error.
+    pub fn push_undirected(
+        &mut self,
+        a: Node,
+        b: Node,
+        cost: Cost,
+    ) -> Result<(), Error> {
+        self.check_node(a)?;
+        self.check_node(b)?;
+        check_cost(a, b, cost)?;
+        self.push(a, NodeWithCost::new(b, cost))?;
+        self.push(b, NodeWithCost::new(a, cost))?;
+        return Ok(());
+    }
+
+    /// Checks that `node` is in the matrix.
+    fn check_node(&self, node: Node) -> Result<(), Error> {
+        return check_node(node, self.matrix.len());
+    }
+
+    /// Get the number of edges in the graph.
+    pub fn edges(&self) -> usize {
+        return self.matrix.iter().map(Vec::len).sum();
+    }
+
+    /// Get the number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.matrix.len();
+    }
+
+    /// Get the adjacent [`Node`]s from a starting node.
+    pub fn get_node(&self, node: Node) -> Option<&Vec<NodeWithCost>> {
+        return self.matrix.get(node);
+    }
+}
+
+/// Runs the Dijkstra Algorithm from a single `source` [`Node`] over `graph`
+/// and returns the [`Cost`] to reach every [`Node`] in the graph, with
+/// [`None`] marking unreachable [`Node`]s.
+fn shortest_from(
+    source: Node,
+    graph: &AdjacencyMatrix,
+) -> Result<Vec<Option<Cost>>, Error> {
+    return with_arena(|arena| {
+        let distances: &mut [Option<Cost>] = arena.alloc_slice(graph.total(), None);
+        *unwrapoption!(distances.get_mut(source)) = Some(0.0);
+
+        let mut unvisited = BinaryHeap::from(arena.take_vec::<NodeWithCost>());
+        unvisited.push(NodeWithCost::new(source, 0.0));
+
+        while let Some(current) = unvisited.pop() {
+            if Some(current.cost) != *unwrapoption!(distances.get(current.node)) {
+                continue;
+            }
+            for adjacent in unwrapoption!(graph.get_node(current.node)) {
+                let new_distance = current.cost + adjacent.cost;
+                let adjacent_distance = unwrapoption!(
+                    distances.get_mut(adjacent.node)
+                );
+                match adjacent_distance {
+                    Some(distance) if *distance <= new_distance => continue,
+                    _ => *adjacent_distance = Some(new_distance),
+                }
+                unvisited.push(NodeWithCost::new(adjacent.node, new_distance));
+            }
+        }
+        arena.give_vec(unvisited.into_vec());
+        return Ok(distances.to_vec());
+    });
+}
+
+/// This `struct` calculates the shortest routes over an
+/// [`AdjacencyMatrix`] with [`f64`] costs using multiple threads, with one
+/// job per source [`Node`].
+pub struct MtdDijkstra {
+    pool: ThreadPool,
+    costs: Arc<Mutex<DistanceTable>>,
+    graph: Arc<AdjacencyMatrix>,
+    scheduled: HashSet<Node>,
+}
+
+impl MtdDijkstra {
+    /// Creates a new [`MtdDijkstra`] instance.
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: AdjacencyMatrix``` => The adjacency matrix which
+    ///    describes the graph.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize, matrix: AdjacencyMatrix) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        let costs = Arc::new(Mutex::new(HashMap::new()));
+        let graph = Arc::new(matrix);
+        let scheduled = HashSet::new();
+        return Ok(Self {pool, costs, graph, scheduled});
+    }
+
+    /// Sends a job calculating the costs from `source` to the
+    /// [`ThreadPool`], unless it has already been scheduled.
+    fn schedule(&mut self, source: Node) -> Result<(), Error> {
+        if !self.scheduled.insert(source) {
+            return Ok(());
+        }
+        let graph = self.graph.clone();
+        let costs = self.costs.clone();
+        return self.pool.execute(move || {
+            let distances = shortest_from(source, &graph)?;
+            unwrapmutex!(costs.lock()).insert(source, distances);
+            return Ok(());
+        });
+    }
+
+    /// Calculates the shortest distance to all (if possible) nodes in the
+    /// graph from each node.
+    pub fn calculate(&mut self) -> Result<(), Error> {
+        for source in 0..self.graph.total() {
+            self.schedule(source)?;
+        }
+        return Ok(());
+    }
+
+    /// Calculates the shortest distance to all (if possible) nodes in the
+    /// graph from `source` only.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` is not in the graph.
+    pub fn calculate_from(&mut self, source: Node) -> Result<(), Error> {
+        self.graph.check_node(source)?;
+        return self.schedule(source);
+    }
+
+    /// Waits for every scheduled job to finish and returns a copy of the
+    /// cost to get to all destination [`Node`]s from `source`, or [`None`]
+    /// if it was never scheduled.
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned.
+    pub fn get(&mut self, source: Node) -> Result<Option<Vec<Option<Cost>>>, Error> {
+        self.pool.wait()?;
+        return Ok(unwrapmutex!(self.costs.lock()).get(&source).cloned());
+    }
+
+    /// Waits for every scheduled job to finish and returns the
+    /// [`DistanceTable`], shutting down the [`ThreadPool`].
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned.
+    pub fn into_result(mut self) -> Result<DistanceTable, Error> {
+        self.pool.wait()?;
+        return match Arc::try_unwrap(self.costs) {
+            Ok(costs) => Ok(unwrapmutex!(costs.into_inner())),
+            Err(costs) => Ok(unwrapmutex!(costs.lock()).clone()),
+        };
+    }
+}
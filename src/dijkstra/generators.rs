@@ -0,0 +1,342 @@
+//! Module for generating graphs for the [`super::simple`] edition, so common
+//! shapes do not have to be built edge by edge.
+//! 
+//! Grids number their cells row by row from the top left corner, so the
+//! cell in column `x` of row `y` is [`Node`] `y * width + x`. Use
+//! [`grid_node`] and [`grid_position`] to convert between the two.
+//! 
+//! The random generators are seeded, so the same seed always gives the same
+//! graph no matter how many threads generate it. Each [`Node`] draws its
+//! edges from its own stream of the seed (see [`Rng::stream`]), which lets
+//! [`erdos_renyi`] and [`random_geometric`] generate them in parallel on a
+//! [`ThreadPool`]. [`barabasi_albert`] attaches each [`Node`] to the ones
+//! before it, so it is generated on the calling thread.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use super::simple::{AdjacencyMatrix, Cost, Node};
+use crate::{
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+};
+
+/// Get the [`Node`] of the cell at column `x` and row `y` of a grid which is
+/// `width` cells wide.
+pub fn grid_node(width: usize, x: usize, y: usize) -> Node {
+    return y * width + x;
+}
+
+/// Get the column and row of `node` in a grid which is `width` cells wide,
+/// as `(x, y)`.
+pub fn grid_position(width: usize, node: Node) -> (usize, usize) {
+    return (node % width, node / width);
+}
+
+/// Builds a grid of `width` by `height` cells where every move between
+/// neighbouring cells costs `1`, and can be travelled both ways.
+/// 
+/// # Parameters
+/// 1. ```width: usize``` => Number of columns.
+/// 2. ```height: usize``` => Number of rows.
+/// 3. ```diagonal: bool``` => Whether cells which touch at a corner are
+///    neighbours as well.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if the grid has more cells than a
+/// [`Node`] can number.
+pub fn grid(
+    width: usize,
+    height: usize,
+    diagonal: bool,
+) -> Result<AdjacencyMatrix, Error> {
+    let diagonal = if diagonal { Some(1) } else { None };
+    return grid_with_costs(width, height, 1, diagonal);
+}
+
+/// Builds a grid of `width` by `height` cells like [`grid`], with the given
+/// [`Cost`]s for moving between neighbouring cells.
+/// 
+/// # Parameters
+/// 1. ```width: usize``` => Number of columns.
+/// 2. ```height: usize``` => Number of rows.
+/// 3. ```straight: Cost``` => The [`Cost`] of moving to a cell which shares
+///    a side.
+/// 4. ```diagonal: Option<Cost>``` => The [`Cost`] of moving to a cell which
+///    only touches at a corner, or [`None`] to forbid diagonal moves.
+///    `straight` and `diagonal` of `10` and `14` approximate straight line
+///    distances well.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if the grid has more cells than a
+/// [`Node`] can number.
+pub fn grid_with_costs(
+    width: usize,
+    height: usize,
+    straight: Cost,
+    diagonal: Option<Cost>,
+) -> Result<AdjacencyMatrix, Error> {
+    let total = match width.checked_mul(height) {
+        Some(total) => total,
+        None => return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("A {} by {} grid has too many cells.", width, height)
+        )),
+    };
+    let mut graph = AdjacencyMatrix::new(total);
+    for y in 0..height {
+        for x in 0..width {
+            let node = grid_node(width, x, y);
+            if x + 1 < width {
+                graph.push_undirected(node, grid_node(width, x + 1, y), straight)?;
+            }
+            if y + 1 < height {
+                graph.push_undirected(node, grid_node(width, x, y + 1), straight)?;
+            }
+            let cost = match diagonal {
+                Some(cost) if y + 1 < height => cost,
+                _ => continue,
+            };
+            if x + 1 < width {
+                graph.push_undirected(node, grid_node(width, x + 1, y + 1), cost)?;
+            }
+            if x > 0 {
+                graph.push_undirected(node, grid_node(width, x - 1, y + 1), cost)?;
+            }
+        }
+    }
+    return Ok(graph);
+}
+
+/// Checks that `costs` is a valid `(lowest, highest)` range of [`Cost`]s.
+fn check_costs(costs: (Cost, Cost)) -> Result<(), Error> {
+    if costs.0 > costs.1 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "The lowest cost {} is above the highest cost {}.",
+                costs.0,
+                costs.1
+            )
+        ));
+    }
+    return Ok(());
+}
+
+/// Picks a random [`Cost`] between `costs.0` and `costs.1` inclusive.
+fn random_cost(rng: &mut Rng, costs: (Cost, Cost)) -> Cost {
+    let span = costs.1 - costs.0;
+    if span > u64::MAX as Cost {
+        let value = ((rng.next_u64() as Cost) << 64) | rng.next_u64() as Cost;
+        return match span.checked_add(1) {
+            Some(width) => costs.0 + value % width,
+            None => costs.0 + value,
+        };
+    }
+    return costs.0 + ((rng.next_u64() as Cost * (span + 1)) >> 64);
+}
+
+/// Builds a directed Erd\u{151}s-R\u{e9}nyi graph, where every possible edge
+/// between 2 different [`Node`]s is added with the same probability.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which generate the edges.
+/// 2. ```nodes: usize``` => Number of [`Node`]s.
+/// 3. ```probability: f64``` => The chance of each edge being added. Each
+///    [`Node`] gets about `probability * (nodes - 1)` edges.
+/// 4. ```costs: (Cost, Cost)``` => The lowest and highest [`Cost`] of an
+///    edge. Each edge gets a random [`Cost`] between them.
+/// 5. ```seed: u64``` => The seed of the graph.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `probability` is not between `0`
+/// and `1`, `costs` is not a valid range, or a job in `pool` fails.
+pub fn erdos_renyi(
+    pool: &mut ThreadPool,
+    nodes: usize,
+    probability: f64,
+    costs: (Cost, Cost),
+    seed: u64,
+) -> Result<AdjacencyMatrix, Error> {
+    if !(0.0..=1.0).contains(&probability) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("The probability {} is not between 0 and 1.", probability)
+        ));
+    }
+    check_costs(costs)?;
+    let sources: Vec<Node> = (0..nodes).collect();
+    let edges = pool.map(Arc::new(sources), ChunkPolicy::Auto, move |from| {
+        let mut rng = Rng::stream(seed, *from as u64);
+        let mut adjacents: Vec<(Node, Node, Cost)> = Vec::new();
+        let others = nodes.saturating_sub(1);
+        if probability <= 0.0 {
+            return adjacents;
+        }
+        // Jump straight to the next edge instead of rolling for every
+        // other Node, which matters for sparse graphs.
+        let log_miss = (1.0 - probability).ln();
+        let mut index: usize = 0;
+        loop {
+            if probability < 1.0 {
+                let skip = ((1.0 - rng.next_f64()).ln() / log_miss).floor();
+                if skip >= (others - index) as f64 {
+                    break;
+                }
+                index += skip as usize;
+            }
+            if index >= others {
+                break;
+            }
+            // Leave out the edge from the Node to itself.
+            let to = if index >= *from { index + 1 } else { index };
+            adjacents.push((*from, to, random_cost(&mut rng, costs)));
+            index += 1;
+        }
+        return adjacents;
+    })?;
+    return AdjacencyMatrix::from_edges(nodes, edges.into_iter().flatten());
+}
+
+/// Builds an undirected Barab\u{e1}si-Albert graph, where each new [`Node`]
+/// connects to `attach` earlier [`Node`]s, preferring the ones which are
+/// already well connected. The result has a few hubs with many edges and
+/// many [`Node`]s with few, like many real networks.
+/// 
+/// # Parameters
+/// 1. ```nodes: usize``` => Number of [`Node`]s.
+/// 2. ```attach: usize``` => Number of edges each new [`Node`] brings. The
+///    first `attach + 1` [`Node`]s are all connected to each other.
+/// 3. ```costs: (Cost, Cost)``` => The lowest and highest [`Cost`] of an
+///    edge. Each edge gets a random [`Cost`] between them, the same both
+///    ways.
+/// 4. ```seed: u64``` => The seed of the graph.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `attach` is `0` or `costs` is not a
+/// valid range.
+pub fn barabasi_albert(
+    nodes: usize,
+    attach: usize,
+    costs: (Cost, Cost),
+    seed: u64,
+) -> Result<AdjacencyMatrix, Error> {
+    if attach == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Each new node must attach to at least 1 node."
+        ));
+    }
+    check_costs(costs)?;
+    let mut rng = Rng::new(seed);
+    let mut graph = AdjacencyMatrix::new(nodes);
+    // Every end of every edge, so picking from it uniformly picks a Node in
+    // proportion to how many edges it has.
+    let mut ends: Vec<Node> = Vec::new();
+    let core = (attach + 1).min(nodes);
+    for a in 0..core {
+        for b in a + 1..core {
+            graph.push_undirected(a, b, random_cost(&mut rng, costs))?;
+            ends.push(a);
+            ends.push(b);
+        }
+    }
+    for node in core..nodes {
+        let mut chosen: Vec<Node> = Vec::with_capacity(attach);
+        while chosen.len() < attach {
+            let other = ends[rng.below(ends.len())];
+            if !chosen.contains(&other) {
+                chosen.push(other);
+            }
+        }
+        for other in chosen {
+            graph.push_undirected(node, other, random_cost(&mut rng, costs))?;
+            ends.push(node);
+            ends.push(other);
+        }
+    }
+    return Ok(graph);
+}
+
+/// Builds an undirected random geometric graph: the [`Node`]s are scattered
+/// over a unit square and every pair closer than `radius` is connected,
+/// with a [`Cost`] of their distance times `scale`, rounded up. The
+/// positions are returned with the graph, as `(x, y)` for each [`Node`], so
+/// they can be used for A* heuristics or drawing.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which find the edges.
+/// 2. ```nodes: usize``` => Number of [`Node`]s.
+/// 3. ```radius: f64``` => How close 2 [`Node`]s must be to be connected.
+/// 4. ```scale: f64``` => What the distance is multiplied by to get the
+///    [`Cost`] of an edge, such as `1000.0`.
+/// 5. ```seed: u64``` => The seed of the graph.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `radius` or `scale` is not a
+/// positive number, or a job in `pool` fails.
+pub fn random_geometric(
+    pool: &mut ThreadPool,
+    nodes: usize,
+    radius: f64,
+    scale: f64,
+    seed: u64,
+) -> Result<(AdjacencyMatrix, Vec<(f64, f64)>), Error> {
+    if !(radius > 0.0 && radius.is_finite() && scale > 0.0 && scale.is_finite()) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The radius and scale must be positive numbers."
+        ));
+    }
+    let mut rng = Rng::new(seed);
+    let points: Vec<(f64, f64)> = (0..nodes)
+        .map(|_node| (rng.next_f64(), rng.next_f64()))
+        .collect();
+
+    // Bucket the points into square cells at least as wide as the radius,
+    // so each point only has to be compared with the points in the 9 cells
+    // around it. A tiny radius would need far more cells than points, so
+    // there are at most about as many cells as points.
+    let most = (nodes as f64).sqrt().ceil() as usize;
+    let cells = ((1.0 / radius).floor() as usize).clamp(1, most.max(1));
+    let cell_of = move |point: (f64, f64)| -> (usize, usize) {
+        let x = ((point.0 * cells as f64) as usize).min(cells - 1);
+        let y = ((point.1 * cells as f64) as usize).min(cells - 1);
+        return (x, y);
+    };
+    let mut buckets: Vec<Vec<Node>> = vec![Vec::new(); cells * cells];
+    for (node, point) in points.iter().enumerate() {
+        let (x, y) = cell_of(*point);
+        buckets[y * cells + x].push(node);
+    }
+
+    let shared = Arc::new((points.clone(), buckets));
+    let sources: Vec<Node> = (0..nodes).collect();
+    let edges = pool.map(Arc::new(sources), ChunkPolicy::Auto, move |from| {
+        let (points, buckets) = &*shared;
+        let point = points[*from];
+        let (x, y) = cell_of(point);
+        let mut adjacents: Vec<(Node, Node, Cost)> = Vec::new();
+        for cell_y in y.saturating_sub(1)..=(y + 1).min(cells - 1) {
+            for cell_x in x.saturating_sub(1)..=(x + 1).min(cells - 1) {
+                for to in &buckets[cell_y * cells + cell_x] {
+                    let other = points[*to];
+                    let distance = (point.0 - other.0).hypot(point.1 - other.1);
+                    if *to != *from && distance < radius {
+                        adjacents.push((*from, *to, (distance * scale).ceil() as Cost));
+                    }
+                }
+            }
+        }
+        return adjacents;
+    })?;
+    let graph = AdjacencyMatrix::from_edges(nodes, edges.into_iter().flatten())?;
+    return Ok((graph, points));
+}
@@ -0,0 +1,560 @@
+//! Module for reading and writing graphs for the [`super::simple`] edition.
+//! 
+//! 2 formats are supported: DIMACS `.gr` files with [`parse_dimacs`] and
+//! [`write_dimacs`], and Graphviz DOT files with [`parse_dot`] and
+//! [`write_dot`]. DOT files are handy for drawing graphs and for writing
+//! small test graphs by hand.
+//! 
+//! The [9th DIMACS Implementation Challenge](http://www.diag.uniroma1.it/challenge9/format.shtml)
+//! shortest path format (`.gr`) is used by large benchmark graphs. Each
+//! line of a `.gr` file is one of:
+//! 
+//! 1. `c <text>` => A comment, which is ignored.
+//! 2. `p sp <nodes> <arcs>` => The problem line, which must come before any
+//!    arcs.
+//! 3. `a <from> <to> <cost>` => An arc from `from` to `to`. Nodes are
+//!    numbered from 1 in the file and from 0 in the [`AdjacencyMatrix`].
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use super::simple::{AdjacencyMatrix, Cost, Node};
+
+/// The most arcs [`parse_dimacs`] makes room for before reading them, so a
+/// problem line claiming a huge number of arcs cannot exhaust the memory on
+/// its own.
+const RESERVED_ARCS: usize = 1 << 16;
+
+/// The most nodes a `.gr` file may have beyond the 2 ends of each of its
+/// arcs. Every node takes memory without a line of the file backing it, so
+/// larger node counts are rejected.
+const ISOLATED_NODES: usize = 1 << 16;
+
+/// Creates an error pointing at line `line` of a file.
+fn invalid(line: usize, message: &str) -> Error {
+    return Error::new(
+        ErrorKind::InvalidData,
+        format!("Line {}: {}", line, message)
+    );
+}
+
+/// Parses the next field on line `line` as a `T`.
+fn field<'a, T, I>(fields: &mut I, line: usize, name: &str) -> Result<T, Error>
+where
+    T: FromStr,
+    I: Iterator<Item = &'a str>,
+{
+    return match fields.next() {
+        Some(value) => value.parse::<T>().map_err(|_error| {
+            invalid(line, &format!("{} `{}` is not a valid number.", name, value))
+        }),
+        None => Err(invalid(line, &format!("Missing {}.", name))),
+    };
+}
+
+/// Reads a graph in the DIMACS shortest path format from `reader`.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] of kind [`ErrorKind::InvalidData`] naming the line
+/// at fault is returned if the problem line is missing or repeated, an arc
+/// refers to a node outside of the graph, or the number of arcs does not
+/// match the problem line. It is also returned if the problem line claims
+/// more nodes than twice the arcs plus 65536, which the file
+/// cannot back. Errors from `reader` are passed on as they are.
+pub fn parse_dimacs<R: BufRead>(reader: R) -> Result<AdjacencyMatrix, Error> {
+    let mut problem: Option<(Node, usize)> = None;
+    let mut edges: Vec<(Node, Node, Cost)> = Vec::new();
+    for (index, text) in reader.lines().enumerate() {
+        let text = text?;
+        let line = index + 1;
+        let mut fields = text.split_whitespace();
+        match fields.next() {
+            None | Some("c") => continue,
+            Some("p") => {
+                if problem.is_some() {
+                    return Err(invalid(line, "Repeated problem line."));
+                }
+                if fields.next() != Some("sp") {
+                    return Err(invalid(line, "Expected `p sp <nodes> <arcs>`."));
+                }
+                let nodes: Node = field(&mut fields, line, "node count")?;
+                let arcs: usize = field(&mut fields, line, "arc count")?;
+                edges.reserve(arcs.min(RESERVED_ARCS));
+                problem = Some((nodes, arcs));
+            },
+            Some("a") => {
+                let nodes = match problem {
+                    Some((nodes, _arcs)) => nodes,
+                    None => return Err(invalid(
+                        line,
+                        "Arc found before the problem line."
+                    )),
+                };
+                let mut endpoint = |name: &str| -> Result<Node, Error> {
+                    let node: Node = field(&mut fields, line, name)?;
+                    if node < 1 || node > nodes {
+                        return Err(invalid(line, &format!(
+                            "{} {} is not between 1 and {}.", name, node, nodes
+                        )));
+                    }
+                    return Ok(node - 1);
+                };
+                let from = endpoint("tail node")?;
+                let to = endpoint("head node")?;
+                let cost: Cost = field(&mut fields, line, "arc cost")?;
+                edges.push((from, to, cost));
+            },
+            Some(other) => {
+                return Err(invalid(
+                    line,
+                    &format!("Unknown line type `{}`.", other)
+                ));
+            },
+        }
+    }
+
+    let (nodes, arcs) = match problem {
+        Some(problem) => problem,
+        None => return Err(Error::new(
+            ErrorKind::InvalidData,
+            "Missing problem line."
+        )),
+    };
+    if edges.len() != arcs {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Expected {} arcs but found {}.", arcs, edges.len())
+        ));
+    }
+    if nodes > arcs.saturating_mul(2).saturating_add(ISOLATED_NODES) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "{} nodes cannot be backed by {} arcs, which leaves more than \
+                {} nodes without an arc.",
+                nodes, arcs, ISOLATED_NODES
+            )
+        ));
+    }
+    return AdjacencyMatrix::from_edges(nodes, edges);
+}
+
+/// Reads a graph from the DIMACS shortest path (`.gr`) file at `path`.
+/// 
+/// # Error
+/// 
+/// See [`parse_dimacs`]. Errors from opening or reading the file are passed
+/// on as they are.
+pub fn load_dimacs<P: AsRef<Path>>(path: P) -> Result<AdjacencyMatrix, Error> {
+    return parse_dimacs(BufReader::new(File::open(path)?));
+}
+
+/// Writes `graph` to `writer` in the DIMACS shortest path format, which can
+/// be read back with [`parse_dimacs`].
+pub fn write_dimacs<W: Write>(
+    graph: &AdjacencyMatrix,
+    mut writer: W,
+) -> Result<(), Error> {
+    writeln!(writer, "p sp {} {}", graph.total(), graph.edges())?;
+    for from in 0..graph.total() {
+        for adjacent in graph.get_node(from).into_iter().flatten() {
+            writeln!(writer, "a {} {} {}", from + 1, adjacent.node + 1, adjacent.cost)?;
+        }
+    }
+    return writer.flush();
+}
+
+/// Writes `graph` to a DIMACS shortest path (`.gr`) file at `path`,
+/// replacing the file if it exists.
+pub fn save_dimacs<P: AsRef<Path>>(
+    graph: &AdjacencyMatrix,
+    path: P,
+) -> Result<(), Error> {
+    return write_dimacs(graph, BufWriter::new(File::create(path)?));
+}
+
+/// A token in a DOT file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DotToken {
+    Id(String),
+    Edge,
+    Open(char),
+    Close(char),
+    Equals,
+    Separator,
+}
+
+/// Splits the DOT file `text` into [`DotToken`]s, dropping comments.
+fn tokenize_dot(text: &str) -> Result<Vec<(DotToken, usize)>, Error> {
+    let mut tokens: Vec<(DotToken, usize)> = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line: usize = 1;
+    while let Some(current) = chars.next() {
+        match current {
+            '\n' => line += 1,
+            _ if current.is_whitespace() => {},
+            '#' => {
+                while chars.next_if(|next| *next != '\n').is_some() {}
+            },
+            '/' if chars.next_if_eq(&'/').is_some() => {
+                while chars.next_if(|next| *next != '\n').is_some() {}
+            },
+            '/' if chars.next_if_eq(&'*').is_some() => {
+                let start = line;
+                let mut previous = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if previous == '*' => break,
+                        Some(next) => {
+                            if next == '\n' {
+                                line += 1;
+                            }
+                            previous = next;
+                        },
+                        None => return Err(invalid(start, "Unclosed comment.")),
+                    }
+                }
+            },
+            '-' if chars.next_if(|next| *next == '>' || *next == '-').is_some() => {
+                tokens.push((DotToken::Edge, line));
+            },
+            '{' | '[' => tokens.push((DotToken::Open(current), line)),
+            '}' | ']' => tokens.push((DotToken::Close(current), line)),
+            '=' => tokens.push((DotToken::Equals, line)),
+            ';' | ',' => tokens.push((DotToken::Separator, line)),
+            '"' => {
+                let start = line;
+                let mut id = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next_if(|next| {
+                            return *next == '"' || *next == '\\';
+                        }) {
+                            Some(escaped) => id.push(escaped),
+                            None => id.push('\\'),
+                        },
+                        Some(next) => {
+                            if next == '\n' {
+                                line += 1;
+                            }
+                            id.push(next);
+                        },
+                        None => return Err(invalid(start, "Unclosed string.")),
+                    }
+                }
+                tokens.push((DotToken::Id(id), start));
+            },
+            _ if current.is_alphanumeric() || current == '_' || current == '.'
+                || current == '-' => {
+                let mut id = String::from(current);
+                while let Some(next) = chars.next_if(|next| {
+                    next.is_alphanumeric() || *next == '_' || *next == '.'
+                }) {
+                    id.push(next);
+                }
+                tokens.push((DotToken::Id(id), line));
+            },
+            _ => {
+                return Err(invalid(
+                    line,
+                    &format!("Unexpected character `{}`.", current)
+                ));
+            },
+        }
+    }
+    return Ok(tokens);
+}
+
+/// Reads the attribute lists following a statement in a DOT file and
+/// returns each attribute as `(name, value, line)`.
+fn dot_attributes<I>(
+    tokens: &mut std::iter::Peekable<I>,
+) -> Result<Vec<(String, String, usize)>, Error>
+where
+    I: Iterator<Item = (DotToken, usize)>,
+{
+    let mut attributes: Vec<(String, String, usize)> = Vec::new();
+    while tokens.next_if(|(token, _line)| *token == DotToken::Open('[')).is_some() {
+        loop {
+            let (key, line) = match tokens.next() {
+                Some((DotToken::Close(']'), _line)) => break,
+                Some((DotToken::Separator, _line)) => continue,
+                Some((DotToken::Id(key), line)) => (key, line),
+                Some((_token, line)) => {
+                    return Err(invalid(line, "Expected an attribute name."));
+                },
+                None => return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "Unclosed attribute list."
+                )),
+            };
+            let value = match (tokens.next(), tokens.next()) {
+                (Some((DotToken::Equals, _)), Some((DotToken::Id(value), _))) => value,
+                _ => return Err(invalid(
+                    line,
+                    &format!("Expected a value for attribute `{}`.", key)
+                )),
+            };
+            attributes.push((key, value, line));
+        }
+    }
+    return Ok(attributes);
+}
+
+/// Get the [`Cost`] the `attributes` of an edge give it, if any. `weight`
+/// is used if it is present, otherwise `label` is used if it is a number,
+/// since labels are often text describing the edge.
+fn dot_cost(attributes: &[(String, String, usize)]) -> Result<Option<Cost>, Error> {
+    let find = |wanted: &str| {
+        return attributes.iter().find(|(key, _value, _line)| key == wanted);
+    };
+    if let Some((_key, value, line)) = find("weight") {
+        return value.parse::<Cost>().map(Some).map_err(|_error| {
+            invalid(*line, &format!("weight `{}` is not a valid cost.", value))
+        });
+    }
+    return Ok(find("label").and_then(|(_key, value, _line)| value.parse().ok()));
+}
+
+/// Reads a graph in the Graphviz DOT format from `reader`, returning the
+/// graph and the name of each [`Node`] in it.
+/// 
+/// Only the parts of the format which describe a graph are understood:
+/// `graph` and `digraph` blocks, node statements, and edge statements such
+/// as `a -> b -> c`. Nodes are numbered in the order they first appear, so
+/// declaring them up front fixes their numbering. The cost of an edge is
+/// taken from its `weight` attribute, then its `label` attribute if that is
+/// a number, and is `1` otherwise. Edges in a `graph` go both ways. Other
+/// attributes and statements are ignored. Each [`Node`] is labelled (see
+/// [`AdjacencyMatrix::set_label`]) with the `label` attribute of its node
+/// statement, or else its name. The `label` attributes are given out
+/// first, then the names, in the order the nodes first appear, and a label
+/// which already belongs to another [`Node`] is skipped, so a [`Node`] may
+/// fall back to its name or be left without a label. The names returned
+/// always tell the nodes apart.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] of kind [`ErrorKind::InvalidData`] naming the line
+/// at fault is returned if the file is not a single graph block, uses
+/// subgraphs, or gives an edge a `weight` which is not a [`Cost`]. Errors
+/// from `reader` are passed on as they are.
+pub fn parse_dot<R: BufRead>(
+    mut reader: R,
+) -> Result<(AdjacencyMatrix, Vec<String>), Error> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut tokens = tokenize_dot(&text)?.into_iter().peekable();
+
+    tokens.next_if(|(token, _line)| *token == DotToken::Id("strict".into()));
+    let directed = match tokens.next() {
+        Some((DotToken::Id(kind), _line)) if kind == "digraph" => true,
+        Some((DotToken::Id(kind), _line)) if kind == "graph" => false,
+        Some((_token, line)) => {
+            return Err(invalid(line, "Expected `graph` or `digraph`."));
+        },
+        None => return Err(Error::new(ErrorKind::InvalidData, "Empty file.")),
+    };
+    tokens.next_if(|(token, _line)| matches!(token, DotToken::Id(_)));
+    match tokens.next() {
+        Some((DotToken::Open('{'), _line)) => {},
+        Some((_token, line)) => return Err(invalid(line, "Expected `{`.")),
+        None => return Err(Error::new(ErrorKind::InvalidData, "Missing `{`.")),
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    let mut numbers: HashMap<String, Node> = HashMap::new();
+    let mut node = |name: String| -> Node {
+        return *numbers.entry(name.clone()).or_insert_with(|| {
+            names.push(name);
+            return names.len() - 1;
+        });
+    };
+    let mut edges: Vec<(Node, Node, Cost)> = Vec::new();
+    let mut labels: HashMap<Node, String> = HashMap::new();
+    loop {
+        let (token, line) = match tokens.next() {
+            Some(next) => next,
+            None => return Err(Error::new(ErrorKind::InvalidData, "Missing `}`.")),
+        };
+        let id = match token {
+            DotToken::Close('}') => break,
+            DotToken::Separator => continue,
+            DotToken::Id(id) => id,
+            DotToken::Open('{') => {
+                return Err(invalid(line, "Subgraphs are not supported."));
+            },
+            _ => return Err(invalid(line, "Expected a statement.")),
+        };
+        if matches!(id.as_str(), "graph" | "node" | "edge") {
+            dot_attributes(&mut tokens)?;
+            continue;
+        }
+        if id == "subgraph" {
+            return Err(invalid(line, "Subgraphs are not supported."));
+        }
+        if tokens.next_if(|(token, _line)| *token == DotToken::Equals).is_some() {
+            tokens.next();
+            continue;
+        }
+        let mut chain = vec![node(id)];
+        while tokens.next_if(|(token, _line)| *token == DotToken::Edge).is_some() {
+            match tokens.next() {
+                Some((DotToken::Id(id), _line)) => chain.push(node(id)),
+                Some((DotToken::Open('{'), line)) => {
+                    return Err(invalid(line, "Subgraphs are not supported."));
+                },
+                _ => return Err(invalid(line, "Expected a node after an edge.")),
+            }
+        }
+        let attributes = dot_attributes(&mut tokens)?;
+        if chain.len() == 1 {
+            let label = attributes
+                .into_iter()
+                .find(|(key, _value, _line)| key == "label");
+            if let Some((_key, label, _line)) = label {
+                labels.insert(chain[0], label);
+            }
+            continue;
+        }
+        let cost = dot_cost(&attributes)?.unwrap_or(1);
+        for pair in chain.windows(2) {
+            edges.push((pair[0], pair[1], cost));
+            if !directed {
+                edges.push((pair[1], pair[0], cost));
+            }
+        }
+    }
+    if let Some((_token, line)) = tokens.next() {
+        return Err(invalid(line, "Unexpected text after the graph."));
+    }
+    let mut graph = AdjacencyMatrix::from_edges(names.len(), edges)?;
+    // Labels only name Nodes for display, so a label which is already taken
+    // is skipped rather than failing the whole file.
+    for node in 0..names.len() {
+        if let Some(label) = labels.remove(&node) {
+            graph.set_label(node, label).ok();
+        }
+    }
+    for (node, name) in names.iter().enumerate() {
+        if graph.label(node).is_none() {
+            graph.set_label(node, name.as_str()).ok();
+        }
+    }
+    return Ok((graph, names));
+}
+
+/// Reads a graph from the Graphviz DOT (`.dot` or `.gv`) file at `path`.
+/// 
+/// # Error
+/// 
+/// See [`parse_dot`]. Errors from opening or reading the file are passed on
+/// as they are.
+pub fn load_dot<P: AsRef<Path>>(
+    path: P,
+) -> Result<(AdjacencyMatrix, Vec<String>), Error> {
+    return parse_dot(BufReader::new(File::open(path)?));
+}
+
+/// Wraps `text` in double quotes for the DOT format, escaping any quotes
+/// and backslashes in it.
+fn quote_dot(text: &str) -> String {
+    return format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+/// Writes `graph` to `writer` as a Graphviz `digraph`, which can be read
+/// back with [`parse_dot`]. Every edge is labelled with its [`Cost`], and
+/// every labelled [`Node`] is drawn with its label.
+/// 
+/// If `costs` is given, it should be a row of shortest path [`Cost`]s from
+/// one source, such as the one returned by
+/// [`super::simple::MtdDijkstra::get_from`]. Each [`Node`] is then labelled
+/// with its [`Cost`] from the source and the edges which lie on a shortest
+/// path are drawn in bold.
+pub fn write_dot<W: Write>(
+    graph: &AdjacencyMatrix,
+    costs: Option<&[Option<Cost>]>,
+    mut writer: W,
+) -> Result<(), Error> {
+    let cost_of = |node: Node| -> Option<Cost> {
+        return costs.and_then(|costs| costs.get(node).copied().flatten());
+    };
+    writeln!(writer, "digraph {{")?;
+    for node in 0..graph.total() {
+        let mut attributes: Vec<String> = Vec::new();
+        if let Some(label) = graph.label(node) {
+            attributes.push(format!("label={}", quote_dot(label)));
+        }
+        match (costs, cost_of(node)) {
+            (Some(_costs), Some(cost)) => {
+                attributes.push(format!("xlabel=\"{}\"", cost));
+            },
+            (Some(_costs), None) => {
+                attributes.push("xlabel=\"unreachable\"".to_string());
+            },
+            (None, _cost) => {},
+        }
+        if attributes.is_empty() {
+            writeln!(writer, "    {};", node)?;
+        } else {
+            writeln!(writer, "    {} [{}];", node, attributes.join(", "))?;
+        }
+    }
+    for from in 0..graph.total() {
+        for adjacent in graph.get_node(from).into_iter().flatten() {
+            write!(
+                writer,
+                "    {} -> {} [label=\"{}\"",
+                from, adjacent.node, adjacent.cost
+            )?;
+            if let (Some(from_cost), Some(to_cost)) =
+                (cost_of(from), cost_of(adjacent.node))
+            {
+                if from_cost.checked_add(adjacent.cost) == Some(to_cost) {
+                    write!(writer, ", style=bold")?;
+                }
+            }
+            writeln!(writer, "];")?;
+        }
+    }
+    writeln!(writer, "}}")?;
+    return writer.flush();
+}
+
+/// Writes `graph` to a Graphviz DOT file at `path`, replacing the file if
+/// it exists. See [`write_dot`].
+pub fn save_dot<P: AsRef<Path>>(
+    graph: &AdjacencyMatrix,
+    costs: Option<&[Option<Cost>]>,
+    path: P,
+) -> Result<(), Error> {
+    return write_dot(graph, costs, BufWriter::new(File::create(path)?));
+}
+
+impl AdjacencyMatrix {
+    /// Get the graph in the Graphviz DOT format. See [`write_dot`].
+    pub fn to_dot(&self) -> String {
+        return self.dot(None);
+    }
+
+    /// Get the graph in the Graphviz DOT format, labelled with the shortest
+    /// path `costs` from one source. See [`write_dot`].
+    pub fn to_dot_with_costs(&self, costs: &[Option<Cost>]) -> String {
+        return self.dot(Some(costs));
+    }
+
+    /// Writes the graph to a [`String`] with [`write_dot`].
+    fn dot(&self, costs: Option<&[Option<Cost>]>) -> String {
+        let mut dot: Vec<u8> = Vec::new();
+        // Writing to a Vec cannot fail and the output is always UTF-8.
+        let _ = write_dot(self, costs, &mut dot);
+        return String::from_utf8_lossy(&dot).into_owned();
+    }
+}
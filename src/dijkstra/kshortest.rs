@@ -0,0 +1,176 @@
+//! Module for finding the `k` shortest loopless paths between 2 [`Node`]s
+//! with Yen's algorithm, on top of the [`super::simple`] edition.
+//! 
+//! Each path after the first is found by branching off the previous path at
+//! every one of its [`Node`]s in turn (the spur [`Node`]), while blocking the
+//! edges already used by earlier paths sharing the same start. The searches
+//! from each spur [`Node`] do not depend on each other, so they are run in
+//! parallel on a [`ThreadPool`].
+
+use std::{
+    collections::{BTreeSet, BinaryHeap, HashSet},
+    io::Error,
+    sync::Arc,
+};
+
+use super::simple::{
+    AdjacencyMatrix, Cost, CsrGraph, Node, NodeWithCost, Path, check_node,
+};
+use crate::pool::{ThreadPool, chunk::ChunkPolicy};
+
+/// Get the [`Cost`] of the edge from `from` to `to`, if there is one.
+fn edge_cost(graph: &CsrGraph, from: Node, to: Node) -> Option<Cost> {
+    return graph
+        .get_node(from)?
+        .iter()
+        .find(|adjacent| adjacent.node == to)
+        .map(|adjacent| adjacent.cost);
+}
+
+/// Finds the cheapest [`Path`] from `source` to `target` which avoids the
+/// [`Node`]s marked in `banned_nodes` and the edges in `banned_edges`.
+fn shortest_path(
+    graph: &CsrGraph,
+    source: Node,
+    target: Node,
+    banned_nodes: &[bool],
+    banned_edges: &HashSet<(Node, Node)>,
+) -> Option<Path> {
+    let mut distances: Vec<Option<Cost>> = vec![None; graph.total()];
+    let mut previous: Vec<Option<Node>> = vec![None; graph.total()];
+    let mut unvisited = BinaryHeap::new();
+    distances[source] = Some(0);
+    unvisited.push(NodeWithCost::new(source, 0));
+    while let Some(current) = unvisited.pop() {
+        if Some(current.cost) != distances[current.node] {
+            continue;
+        }
+        if current.node == target {
+            break;
+        }
+        for adjacent in graph.get_node(current.node)? {
+            if banned_nodes[adjacent.node]
+                || banned_edges.contains(&(current.node, adjacent.node))
+            {
+                continue;
+            }
+            let new_distance = current.cost.saturating_add(adjacent.cost);
+            match distances[adjacent.node] {
+                Some(distance) if distance <= new_distance => continue,
+                _ => distances[adjacent.node] = Some(new_distance),
+            }
+            previous[adjacent.node] = Some(current.node);
+            unvisited.push(NodeWithCost::new(adjacent.node, new_distance));
+        }
+    }
+
+    let cost = distances[target]?;
+    let mut nodes = vec![target];
+    let mut node = target;
+    while let Some(before) = previous[node] {
+        nodes.push(before);
+        node = before;
+    }
+    nodes.reverse();
+    return Some(Path {nodes, cost});
+}
+
+/// Finds the path which branches off `previous` at its `spur`th [`Node`],
+/// avoiding every edge the paths in `found` take from the same start.
+fn spur_path(
+    graph: &CsrGraph,
+    found: &[Path],
+    previous: &Path,
+    spur: usize,
+    target: Node,
+) -> Option<Path> {
+    let root = &previous.nodes[..=spur];
+    let mut banned_edges: HashSet<(Node, Node)> = HashSet::new();
+    for path in found {
+        if path.nodes.len() > spur + 1 && path.nodes[..=spur] == *root {
+            banned_edges.insert((path.nodes[spur], path.nodes[spur + 1]));
+        }
+    }
+    // The root may not be visited again, or the path would have a loop.
+    let mut banned_nodes = vec![false; graph.total()];
+    for node in &root[..spur] {
+        banned_nodes[*node] = true;
+    }
+
+    let spur_path = shortest_path(
+        graph,
+        root[spur],
+        target,
+        &banned_nodes,
+        &banned_edges,
+    )?;
+    let mut cost = spur_path.cost;
+    for pair in root.windows(2) {
+        cost = cost.saturating_add(edge_cost(graph, pair[0], pair[1])?);
+    }
+    let mut nodes = root[..spur].to_vec();
+    nodes.extend(spur_path.nodes);
+    return Some(Path {nodes, cost});
+}
+
+/// Finds up to `k` loopless [`Path`]s from `source` to `target`, cheapest
+/// first, with Yen's algorithm. Fewer than `k` [`Path`]s are returned if
+/// there are not that many, and none if `target` cannot be reached.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The [`ThreadPool`] the searches from
+///    each spur [`Node`] are run on.
+/// 2. ```graph: &AdjacencyMatrix``` => The graph to search.
+/// 3. ```source: Node``` => Where the [`Path`]s start.
+/// 4. ```target: Node``` => Where the [`Path`]s end.
+/// 5. ```k: usize``` => The largest number of [`Path`]s to find.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `source` or `target` is not in the
+/// graph, or a job on the [`ThreadPool`] fails.
+pub fn k_shortest(
+    pool: &mut ThreadPool,
+    graph: &AdjacencyMatrix,
+    source: Node,
+    target: Node,
+    k: usize,
+) -> Result<Vec<Path>, Error> {
+    for node in [source, target] {
+        check_node(node, graph.total())?;
+    }
+    let graph = Arc::new(CsrGraph::from(graph));
+    let mut found: Vec<Path> = Vec::new();
+    if k == 0 {
+        return Ok(found);
+    }
+    let banned_nodes = vec![false; graph.total()];
+    match shortest_path(&graph, source, target, &banned_nodes, &HashSet::new()) {
+        Some(path) => found.push(path),
+        None => return Ok(found),
+    }
+
+    // Ordered by cost, then by the nodes visited, so ties are broken the
+    // same way every time.
+    let mut candidates: BTreeSet<(Cost, Vec<Node>)> = BTreeSet::new();
+    while found.len() < k {
+        let shared = Arc::new(found.clone());
+        // Every node of the last path except the target is a spur node.
+        let spurs: Vec<usize> = (0..found[found.len() - 1].nodes.len() - 1).collect();
+        let graph = graph.clone();
+        let paths = pool.map(Arc::new(spurs), ChunkPolicy::Auto, move |spur| {
+            let previous = &shared[shared.len() - 1];
+            return spur_path(&graph, &shared, previous, *spur, target);
+        })?;
+        for path in paths.into_iter().flatten() {
+            if !found.iter().any(|existing| existing.nodes == path.nodes) {
+                candidates.insert((path.cost, path.nodes));
+            }
+        }
+        match candidates.pop_first() {
+            Some((cost, nodes)) => found.push(Path {nodes, cost}),
+            None => break,
+        }
+    }
+    return Ok(found);
+}
@@ -0,0 +1,227 @@
+//! Module for speeding up repeated point-to-point queries on a graph which
+//! does not change with ALT (A*, landmarks and the triangle inequality).
+//! 
+//! A few landmark [`Node`]s are picked ahead of time, and the [`Cost`]s from
+//! and to each of them are calculated for every [`Node`] in parallel on a
+//! [`ThreadPool`]. For a landmark `l`, the triangle inequality means the
+//! [`Cost`] from `v` to `t` is at least `d(l, t) - d(l, v)` and at least
+//! `d(v, l) - d(t, l)`. The best of these bounds over every landmark is used
+//! as the heuristic of an A* search (see [`super::astar`]), which steers the
+//! search towards the target and visits far fewer [`Node`]s than the
+//! Dijkstra Algorithm.
+//! 
+//! Landmarks are picked one at a time, each being the [`Node`] furthest from
+//! the ones picked before it, which tends to place them around the edges of
+//! the graph where their bounds are tightest.
+
+use std::{
+    io::Error,
+    sync::Arc,
+};
+
+use super::{
+    astar::search,
+    simple::{AdjacencyMatrix, Cost, CsrGraph, Node, Path, check_node, shortest_from},
+};
+use crate::{
+    arena::reset_local,
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// The [`Cost`]s between every [`Node`] and a set of landmark [`Node`]s,
+/// used to answer point-to-point queries with ALT.
+#[derive(Debug, Clone)]
+pub struct Landmarks {
+    graph: Arc<CsrGraph>,
+    reversed: Arc<CsrGraph>,
+    landmarks: Vec<Node>,
+    /// The [`Cost`] from each landmark to every [`Node`].
+    from: Vec<Vec<Option<Cost>>>,
+    /// The [`Cost`] from every [`Node`] to each landmark.
+    to: Vec<Vec<Option<Cost>>>,
+}
+
+impl Landmarks {
+    /// Picks `count` landmarks in `graph` and calculates the [`Cost`]s from
+    /// and to each of them.
+    /// 
+    /// # Parameters
+    /// 1. ```pool: &mut ThreadPool``` => The threads which calculate the
+    ///    [`Cost`]s.
+    /// 2. ```graph: &AdjacencyMatrix``` => The graph to answer queries on.
+    /// 3. ```count: usize``` => Number of landmarks. More landmarks give
+    ///    tighter bounds but take more memory and more time per [`Node`]
+    ///    visited. It is capped at the number of [`Node`]s.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job in `pool` fails.
+    pub fn new(
+        pool: &mut ThreadPool,
+        graph: &AdjacencyMatrix,
+        count: usize,
+    ) -> Result<Self, Error> {
+        let mut landmarks = Self::empty(graph);
+        let total = landmarks.graph.total();
+        // The smallest Cost from any landmark so far to every Node.
+        let mut nearest: Vec<Option<Cost>> = vec![None; total];
+        for _ in 0..count.min(total) {
+            // A Node no landmark reaches yet is always the best pick, and
+            // the first landmark is Node 0.
+            let next = match nearest.iter().position(Option::is_none) {
+                Some(node) => node,
+                None => (0..total)
+                    .filter(|node| !landmarks.landmarks.contains(node))
+                    .max_by_key(|node| nearest[*node])
+                    .unwrap_or(0),
+            };
+            if landmarks.landmarks.contains(&next) {
+                break;
+            }
+            landmarks.add(pool, &[next])?;
+            let from = &landmarks.from[landmarks.from.len() - 1];
+            for (cost, new) in nearest.iter_mut().zip(from) {
+                *cost = match (*cost, *new) {
+                    (Some(cost), Some(new)) => Some(cost.min(new)),
+                    (cost, new) => cost.or(new),
+                };
+            }
+        }
+        return Ok(landmarks);
+    }
+
+    /// Calculates the [`Cost`]s from and to each of the given `landmarks`
+    /// in `graph`, instead of picking them.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a landmark is not in the graph or
+    /// a job in `pool` fails.
+    pub fn with_landmarks(
+        pool: &mut ThreadPool,
+        graph: &AdjacencyMatrix,
+        landmarks: &[Node],
+    ) -> Result<Self, Error> {
+        let mut built = Self::empty(graph);
+        for node in landmarks {
+            check_node(*node, graph.total())?;
+        }
+        built.add(pool, landmarks)?;
+        return Ok(built);
+    }
+
+    /// Creates a [`Landmarks`] over `graph` without any landmarks.
+    fn empty(graph: &AdjacencyMatrix) -> Self {
+        let graph = CsrGraph::from(graph);
+        let reversed = graph.reversed();
+        return Self {
+            graph: Arc::new(graph),
+            reversed: Arc::new(reversed),
+            landmarks: Vec::new(),
+            from: Vec::new(),
+            to: Vec::new(),
+        };
+    }
+
+    /// Calculates the [`Cost`]s from and to each of `landmarks` in
+    /// parallel, with one job per direction of each landmark, and adds
+    /// them.
+    fn add(
+        &mut self,
+        pool: &mut ThreadPool,
+        landmarks: &[Node],
+    ) -> Result<(), Error> {
+        let searches: Vec<(Node, bool)> = landmarks
+            .iter()
+            .flat_map(|landmark| [(*landmark, false), (*landmark, true)])
+            .collect();
+        let graph = self.graph.clone();
+        let reversed = self.reversed.clone();
+        let rows = pool.map(
+            Arc::new(searches),
+            ChunkPolicy::Fixed(1),
+            move |(landmark, backwards)| {
+                let searched = if *backwards { &reversed } else { &graph };
+                let row = shortest_from(*landmark, searched.total(), searched, None);
+                reset_local();
+                return row;
+            },
+        )?;
+        let mut rows = rows.into_iter();
+        for landmark in landmarks {
+            let (from, to) = match (rows.next(), rows.next()) {
+                (Some(from), Some(to)) => (from?, to?),
+                _ => return Err(Error::other("A landmark search went missing.")),
+            };
+            self.landmarks.push(*landmark);
+            self.from.push(from);
+            self.to.push(to);
+        }
+        return Ok(());
+    }
+
+    /// Get the landmark [`Node`]s.
+    pub fn landmarks(&self) -> &[Node] {
+        return &self.landmarks;
+    }
+
+    /// Get the graph queries are answered on.
+    pub fn graph(&self) -> &CsrGraph {
+        return &self.graph;
+    }
+
+    /// Get a lower bound on the [`Cost`] from `from` to `to` from the
+    /// triangle inequality, or [`Cost::MAX`] if some landmark proves `to`
+    /// cannot be reached from `from`.
+    pub fn lower_bound(&self, from: Node, to: Node) -> Cost {
+        let mut bound: Cost = 0;
+        for (forward, backward) in self.from.iter().zip(&self.to) {
+            // d(l, to) <= d(l, from) + d(from, to)
+            match (forward[from], forward[to]) {
+                (Some(l_from), Some(l_to)) => {
+                    bound = bound.max(l_to.saturating_sub(l_from));
+                },
+                (Some(_), None) => return Cost::MAX,
+                _ => {},
+            }
+            // d(from, l) <= d(from, to) + d(to, l)
+            match (backward[from], backward[to]) {
+                (Some(from_l), Some(to_l)) => {
+                    bound = bound.max(from_l.saturating_sub(to_l));
+                },
+                (None, Some(_)) => return Cost::MAX,
+                _ => {},
+            }
+        }
+        return bound;
+    }
+
+    /// Finds the cheapest [`Path`] from `source` to `target` with an A*
+    /// search guided by the landmarks, or [`None`] if `target` cannot be
+    /// reached.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` or `target` is not in
+    /// the graph.
+    pub fn shortest_path(
+        &self,
+        source: Node,
+        target: Node,
+    ) -> Result<Option<Path>, Error> {
+        let graph = &self.graph;
+        for node in [source, target] {
+            check_node(node, graph.total())?;
+        }
+        if self.lower_bound(source, target) == Cost::MAX {
+            return Ok(None);
+        }
+        return search(
+            graph.total(),
+            |node| graph.get_node(node).unwrap_or(&[]),
+            source,
+            target,
+            |node| self.lower_bound(node, target),
+        );
+    }
+}
@@ -2,12 +2,47 @@
 //! graph from one starting node if possible.
 //! 
 //! This module has 2 editions: [`self::complex`] and [`self::simple`].
-//! As of writing, [`self::simple`] is somewhat complete while
-//! [`self::complex`] is not ready. The main difference between the 2
-//! editions is that you can use generic identifiers and numbers to calculate
-//! costs in the [`self::complex`] edition but you can only use [`usize`] and
-//! [`u128`] for identifiers and costs respectively in the [`self::simple`]
-//! edition.
+//! [`self::simple`] has the most features, while [`self::complex`] can
+//! calculate costs and paths but little else yet. The main difference
+//! between the 2 editions is that you can use generic identifiers and
+//! numbers to calculate costs in the [`self::complex`] edition but you can
+//! only use [`usize`] and [`u128`] for identifiers and costs respectively in
+//! the [`self::simple`] edition. The [`self::float`] edition is like
+//! [`self::simple`] but uses [`f64`] costs, which are checked for `NaN` and
+//! negative values when they are added to the graph. Graphs with negative
+//! costs can be loaded into a [`self::signed::SignedMatrix`], which explains
+//! which edge is at fault when it is handed to
+//! [`self::simple::MtdDijkstra`]. Graphs whose nodes are known by name can
+//! be built as a [`self::named::NamedGraph`], which runs on the
+//! [`self::simple`] edition underneath.
+//! 
+//! Single routes between 2 nodes can be found quickly with
+//! [`self::bidirectional`], or with [`self::astar`] when a good guess of
+//! the remaining cost is known. Repeated queries on a graph which does not
+//! change are much faster with [`self::landmarks`], and faster still with
+//! the contraction hierarchies in [`self::ch`]. Alternative routes can be
+//! found with [`self::kshortest`]. A single source on a huge graph can be spread over
+//! several threads with [`self::deltastep`].
+//! 
+//! Graphs for the [`self::simple`] edition can be loaded from files with
+//! [`self::io`] or built from common shapes with [`self::generators`], and
+//! [`self::error`] describes what can go wrong when reading its results.
+//! Graphs can be checked for likely mistakes with [`self::validate`] and
+//! summarised with [`self::stats`] before they are solved.
 
+pub mod astar;
+pub mod bidirectional;
+pub mod ch;
 pub mod complex;
+pub mod deltastep;
+pub mod error;
+pub mod float;
+pub mod generators;
+pub mod io;
+pub mod kshortest;
+pub mod landmarks;
+pub mod named;
+pub mod signed;
 pub mod simple;
+pub mod stats;
+pub mod validate;
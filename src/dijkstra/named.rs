@@ -0,0 +1,220 @@
+//! Module for graphs whose [`Node`]s are known by name rather than by
+//! number, on top of the [`super::simple`] edition.
+//! 
+//! A [`NamedGraph`] numbers each name the first time it is seen and keeps
+//! the names as the labels of an [`AdjacencyMatrix`], so every search runs
+//! as fast as it does on numbered [`Node`]s and only the results are
+//! translated back to names. This covers most graphs people would use the
+//! [`super::complex`] edition for, when the costs fit in a [`Cost`].
+
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+};
+
+use super::{
+    bidirectional,
+    simple::{
+        AdjacencyMatrix,
+        Cost,
+        CsrGraph,
+        DuplicateEdgePolicy,
+        MtdDijkstra,
+        Node,
+        NodeWithCost,
+        shortest_from,
+    },
+};
+use crate::{arena::reset_local, pool::ThreadPool};
+
+/// A route through a [`NamedGraph`], made of the names of the [`Node`]s
+/// visited in order and the total [`Cost`] of the edges between them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamedPath {
+    pub nodes: Vec<String>,
+    pub cost: Cost,
+}
+
+/// A graph whose [`Node`]s are given by name. See the module documentation.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamedGraph {
+    matrix: AdjacencyMatrix,
+}
+
+impl NamedGraph {
+    /// Creates a new [`NamedGraph`] without any [`Node`]s, which keeps the
+    /// cheaper route when a duplicate edge is pushed.
+    pub fn new() -> Self {
+        return Self::with_policy(DuplicateEdgePolicy::default());
+    }
+
+    /// Creates a new [`NamedGraph`] without any [`Node`]s which handles
+    /// duplicate edges according to `policy`.
+    pub fn with_policy(policy: DuplicateEdgePolicy) -> Self {
+        return Self {matrix: AdjacencyMatrix::with_policy(0, policy)};
+    }
+
+    /// Get the [`Node`] named `name`, adding it if there is none.
+    pub fn add_node<S: Into<String>>(&mut self, name: S) -> Result<Node, Error> {
+        let name = name.into();
+        if let Some(node) = self.matrix.node_by_label(&name) {
+            return Ok(node);
+        }
+        let node = self.matrix.add_node();
+        self.matrix.set_label(node, name)?;
+        return Ok(node);
+    }
+
+    /// Pushes an edge going from the [`Node`] named `from` to the one named
+    /// `to`, adding either of them if it is new. See
+    /// [`AdjacencyMatrix::push`].
+    /// 
+    /// # Error
+    /// 
+    /// If the edge cannot be pushed, an error is returned.
+    pub fn push<A, B>(&mut self, from: A, to: B, cost: Cost) -> Result<(), Error>
+    where
+        A: Into<String>,
+        B: Into<String>,
+    {
+        let from = self.add_node(from)?;
+        let to = self.add_node(to)?;
+        return self.matrix.push(from, NodeWithCost::new(to, cost));
+    }
+
+    /// Pushes an edge going both ways between the [`Node`]s named `a` and
+    /// `b` with the same `cost`. See [`NamedGraph::push`].
+    /// 
+    /// # Error
+    /// 
+    /// If either edge cannot be pushed, an error is returned.
+    pub fn push_undirected<A, B>(&mut self, a: A, b: B, cost: Cost) -> Result<(), Error>
+    where
+        A: Into<String>,
+        B: Into<String>,
+    {
+        let a = self.add_node(a)?;
+        let b = self.add_node(b)?;
+        return self.matrix.push_undirected(a, b, cost);
+    }
+
+    /// Get the [`Node`] named `name`, if there is one.
+    pub fn node(&self, name: &str) -> Option<Node> {
+        return self.matrix.node_by_label(name);
+    }
+
+    /// Get the name of `node`, if it is in the graph.
+    pub fn name(&self, node: Node) -> Option<&str> {
+        return self.matrix.label(node);
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> usize {
+        return self.matrix.total();
+    }
+
+    /// Number of edges in the graph.
+    pub fn edges(&self) -> usize {
+        return self.matrix.edges();
+    }
+
+    /// Get the numbered [`AdjacencyMatrix`] underneath, whose labels are the
+    /// names of its [`Node`]s, to run other algorithms of the
+    /// [`super::simple`] edition on.
+    pub fn matrix(&self) -> &AdjacencyMatrix {
+        return &self.matrix;
+    }
+
+    /// Get the [`Node`] named `name`, or an error if there is none.
+    fn find(&self, name: &str) -> Result<Node, Error> {
+        return match self.node(name) {
+            Some(node) => Ok(node),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("No node is named {:?}.", name)
+            )),
+        };
+    }
+
+    /// Translates a list of [`Node`]s to their names.
+    fn names(&self, nodes: &[Node]) -> Vec<String> {
+        return nodes
+            .iter()
+            .map(|node| self.matrix.labels().name(*node))
+            .collect();
+    }
+
+    /// Calculates the [`Cost`] of reaching every [`Node`] from the one named
+    /// `source` on the calling thread, keyed by name. [`Node`]s which
+    /// cannot be reached are left out.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if no [`Node`] is named `source`.
+    pub fn distances_from(&self, source: &str) -> Result<HashMap<String, Cost>, Error> {
+        let source = self.find(source)?;
+        let graph = CsrGraph::from(&self.matrix);
+        let costs = shortest_from(source, self.total(), &graph, None)?;
+        reset_local();
+        return Ok(
+            costs
+                .into_iter()
+                .enumerate()
+                .filter_map(|(node, cost)| {
+                    return cost.map(|cost| (self.matrix.labels().name(node), cost));
+                })
+                .collect()
+        );
+    }
+
+    /// Finds the cheapest route from the [`Node`] named `source` to the one
+    /// named `target` with [`bidirectional::shortest_path`] on 2 workers of
+    /// `pool`. [`None`] is returned if `target` cannot be reached.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if either name is not in the graph
+    /// or a job on the [`ThreadPool`] fails.
+    pub fn shortest_path(
+        &self,
+        pool: &mut ThreadPool,
+        source: &str,
+        target: &str,
+    ) -> Result<Option<NamedPath>, Error> {
+        let source = self.find(source)?;
+        let target = self.find(target)?;
+        let path = bidirectional::shortest_path(pool, &self.matrix, source, target)?;
+        return Ok(path.map(|path| NamedPath {
+            nodes: self.names(&path.nodes),
+            cost: path.cost,
+        }));
+    }
+
+    /// Creates an [`MtdDijkstra`] over a copy of the graph with `threads`
+    /// threads, for calculating many rows of the distance table. Its
+    /// results can be looked up by name with [`MtdDijkstra::get_by_label`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `threads` is less than `1`.
+    pub fn dijkstra(&self, threads: usize) -> Result<MtdDijkstra, Error> {
+        let graph = CsrGraph::from(&self.matrix);
+        let mut processor = MtdDijkstra::from_csr(threads, self.total(), graph)?;
+        processor.set_labels(self.matrix.labels().clone());
+        return Ok(processor);
+    }
+}
+
+impl Default for NamedGraph {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl From<NamedGraph> for AdjacencyMatrix {
+    fn from(graph: NamedGraph) -> Self {
+        return graph.matrix;
+    }
+}
@@ -0,0 +1,165 @@
+//! Module for graphs with signed [`i64`] costs.
+//! 
+//! The Dijkstra Algorithm cannot handle negative costs, which is why
+//! [`super::simple`] only has unsigned costs. A [`SignedMatrix`] accepts
+//! negative costs anyway so graphs can be loaded as they are, and explains
+//! which edge is at fault when it is turned into a graph for
+//! [`MtdDijkstra`].
+
+use std::io::Error;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use super::{
+    error::DijkstraError,
+    simple::{AdjacencyMatrix, Cost, MtdDijkstra, Node, check_node},
+};
+
+/// A signed cost to get from one node to another node.
+pub type SignedCost = i64;
+
+/// An adjacency matrix whose edges can have negative [`SignedCost`]s.
+/// Duplicate edges are kept as they are, and self-loops are kept too since
+/// a negative one matters to algorithms which accept negative costs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SignedMatrixData"))]
+pub struct SignedMatrix {
+    matrix: Vec<Vec<(Node, SignedCost)>>,
+}
+
+/// The fields of a [`SignedMatrix`] as they are deserialized, before they
+/// are checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SignedMatrixData {
+    matrix: Vec<Vec<(Node, SignedCost)>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SignedMatrixData> for SignedMatrix {
+    type Error = Error;
+
+    /// Checks that every edge leads to a [`Node`] in the matrix, like
+    /// [`SignedMatrix::push`] does.
+    fn try_from(data: SignedMatrixData) -> Result<Self, Error> {
+        let total = data.matrix.len();
+        for adjacents in &data.matrix {
+            for (to, _cost) in adjacents {
+                check_node(*to, total)?;
+            }
+        }
+        return Ok(Self {matrix: data.matrix});
+    }
+}
+
+impl SignedMatrix {
+    /// Creates a new [`SignedMatrix`] with a fixed amount of [`Node`]s.
+    pub fn new(total: Node) -> Self {
+        return Self {matrix: vec![Vec::new(); total]};
+    }
+
+    /// Builds a [`SignedMatrix`] with `total` [`Node`]s from a list of
+    /// `(from, to, cost)` edges.
+    /// 
+    /// # Error
+    /// 
+    /// If any edge refers to a [`Node`] which exceeds `total`, an error is
+    /// returned.
+    pub fn from_edges<I>(total: Node, edges: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (Node, Node, SignedCost)>,
+    {
+        let mut graph = Self::new(total);
+        for (from, to, cost) in edges {
+            graph.push(from, to, cost)?;
+        }
+        return Ok(graph);
+    }
+
+    /// Adds an edge from `from` to `to` with a [`SignedCost`] of `cost`.
+    /// 
+    /// # Error
+    /// 
+    /// If `from` or `to` exceeds the length of the matrix, an error is
+    /// returned.
+    pub fn push(
+        &mut self,
+        from: Node,
+        to: Node,
+        cost: SignedCost,
+    ) -> Result<(), Error> {
+        for node in [from, to] {
+            check_node(node, self.matrix.len())?;
+        }
+        self.matrix[from].push((to, cost));
+        return Ok(());
+    }
+
+    /// Get the number of edges in the graph.
+    pub fn edges(&self) -> usize {
+        return self.matrix.iter().map(Vec::len).sum();
+    }
+
+    /// Get the number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.matrix.len();
+    }
+
+    /// Get the edges leaving `node` as `(to, cost)` pairs.
+    pub fn get_node(&self, node: Node) -> Option<&Vec<(Node, SignedCost)>> {
+        return self.matrix.get(node);
+    }
+
+    /// Iterates over every edge as `(from, to, cost)`, in order of `from`.
+    pub fn iter(&self) -> impl Iterator<Item = (Node, Node, SignedCost)> + '_ {
+        return self.matrix.iter().enumerate().flat_map(|(from, adjacents)| {
+            return adjacents.iter().map(move |(to, cost)| (from, *to, *cost));
+        });
+    }
+
+    /// Lists every edge with a negative [`SignedCost`] as
+    /// `(from, to, cost)`.
+    pub fn negative_edges(&self) -> Vec<(Node, Node, SignedCost)> {
+        return self.iter().filter(|(_from, _to, cost)| *cost < 0).collect();
+    }
+
+    /// Converts the graph into an [`AdjacencyMatrix`] for the
+    /// [`super::simple`] edition, keeping the cheaper route between
+    /// duplicate edges.
+    /// 
+    /// # Error
+    /// 
+    /// If any edge has a negative [`SignedCost`], a [`std::io::Error`] of
+    /// kind [`std::io::ErrorKind::InvalidInput`] wrapping a
+    /// [`DijkstraError::NegativeEdge`] naming the first one is returned.
+    pub fn to_unsigned(&self) -> Result<AdjacencyMatrix, Error> {
+        let mut edges: Vec<(Node, Node, Cost)> = Vec::with_capacity(self.edges());
+        for (from, to, cost) in self.iter() {
+            if cost < 0 {
+                return Err(DijkstraError::NegativeEdge(from, to, cost).into());
+            }
+            edges.push((from, to, cost as Cost));
+        }
+        return AdjacencyMatrix::from_edges(self.total(), edges);
+    }
+}
+
+impl MtdDijkstra {
+    /// Creates a new [`MtdDijkstra`] instance for a [`SignedMatrix`]. See
+    /// [`MtdDijkstra::new`].
+    /// 
+    /// # Error
+    /// 
+    /// If any edge has a negative [`SignedCost`], the error from
+    /// [`SignedMatrix::to_unsigned`] is returned, since the Dijkstra
+    /// Algorithm gives wrong answers for such graphs. An error is also
+    /// returned if `threads` is less than `1`.
+    pub fn from_signed(
+        threads: usize,
+        matrix: &SignedMatrix,
+    ) -> Result<Self, Error> {
+        let unsigned = matrix.to_unsigned()?;
+        return Self::new(threads, unsigned.total(), unsigned);
+    }
+}
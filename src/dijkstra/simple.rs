@@ -4,16 +4,27 @@
 
 //#![allow(unused_imports)]
 use std::{
-    collections::{BinaryHeap, HashMap},
-    cmp::{Ordering, max},
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet},
+    cmp::{Ordering, max, min},
+    fmt::{Display, Formatter, Result as FmtResult, Write as FmtWrite},
     io::{Error, ErrorKind},
-    sync::{Arc, Mutex},
+    mem::size_of,
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering::SeqCst},
+    },
     thread::sleep,
-    time::Duration,
+    time::{Duration, Instant},
 };
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
 
+use super::error::DijkstraError;
 use crate::{
-    pool::ThreadPool,
+    arena::{reset_local, with_arena},
+    estimate::{Estimate, log2_ceil},
+    pool::{ThreadPool, chunk::ChunkPolicy},
     unwrapoption,
     unwrapmutex,
 };
@@ -27,9 +38,26 @@ pub type Cost = u128;
 /// Identifier for a node in the graph. [`usize`] is used to identify it.
 pub type Node = usize;
 
+/// The calculated [`Cost`]s, mapping each source [`Node`] to the [`Cost`] of
+/// reaching every [`Node`] from it, with [`None`] marking unreachable
+/// [`Node`]s.
+pub type DistanceTable = HashMap<Node, Vec<Option<Cost>>>;
+
+/// Checks that `node` is one of the `total` [`Node`]s of a graph.
+pub(crate) fn check_node(node: Node, total: Node) -> Result<(), Error> {
+    if node >= total {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} exceeds matrix size.", node)
+        ));
+    }
+    return Ok(());
+}
+
 /// A custom struct to represent a destination [`Node`] and the [`Cost`] to
 /// reach it from an arbitrary starting point.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeWithCost {
     pub node: Node,
     /// u128 since dijkstra doesn't handle negatives too well
@@ -44,24 +72,180 @@ impl NodeWithCost {
 }
 
 impl PartialOrd for NodeWithCost {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for NodeWithCost {
     /// This function marks a greater cost as [`Ordering::Less`] and vice versa
     /// for [`Ordering::Greater`] to trick the BinaryHeap into floating the
     /// cheaper nodes to the top.
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        use Ordering::*;
-        return if self.cost > other.cost {
-            Some(Less)
-        } else if self.cost < other.cost {
-            Some(Greater)
-        } else {
-            Some(Equal)
+    fn cmp(&self, other: &Self) -> Ordering {
+        return other.cost.cmp(&self.cost);
+    }
+}
+
+/// A route through the graph, made of the [`Node`]s visited in order and
+/// the total [`Cost`] of the edges between them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Path {
+    pub nodes: Vec<Node>,
+    pub cost: Cost,
+}
+
+/// The source [`Node`] nearest to some [`Node`] and the [`Cost`] of
+/// reaching that [`Node`] from it, as found by
+/// [`MtdDijkstra::calculate_multi_source`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NearestSource {
+    pub source: Node,
+    pub cost: Cost,
+}
+
+/// Decides what happens when an edge is pushed to an [`AdjacencyMatrix`]
+/// between 2 [`Node`]s which are already connected in the same direction.
+/// 
+/// # Variants
+/// 
+/// 1. KeepMin => Keep the cheaper of the 2 [`Cost`]s. This is the default.
+/// 2. KeepMax => Keep the more expensive of the 2 [`Cost`]s.
+/// 3. Replace => Overwrite the existing [`Cost`] with the new one.
+/// 4. Sum => Add the new [`Cost`] to the existing one, saturating at
+///    [`u128::MAX`]. Routes through such an edge saturate at
+///    [`u128::MAX`] too rather than overflowing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DuplicateEdgePolicy {
+    #[default]
+    KeepMin,
+    KeepMax,
+    Replace,
+    Sum,
+}
+
+impl DuplicateEdgePolicy {
+    /// Combines the [`Cost`] of an `existing` edge with the [`Cost`] of a
+    /// `new` edge between the same 2 [`Node`]s.
+    pub fn combine(&self, existing: Cost, new: Cost) -> Cost {
+        return match self {
+            Self::KeepMin => min(existing, new),
+            Self::KeepMax => max(existing, new),
+            Self::Replace => new,
+            Self::Sum => existing.saturating_add(new),
         };
     }
 }
 
-impl Ord for NodeWithCost where Self: PartialOrd {
-    fn cmp(&self, other: &Self) -> Ordering {
-        return self.partial_cmp(other).unwrap();
+/// Names given to [`Node`]s, so callers with their own identifiers do not
+/// have to map them to and from [`Node`]s by hand. Every label belongs to
+/// at most one [`Node`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "NodeLabelsData"))]
+pub struct NodeLabels {
+    labels: HashMap<Node, String>,
+    nodes: HashMap<String, Node>,
+}
+
+/// The fields of [`NodeLabels`] as they are deserialized, before they are
+/// checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct NodeLabelsData {
+    labels: HashMap<Node, String>,
+    nodes: HashMap<String, Node>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<NodeLabelsData> for NodeLabels {
+    type Error = Error;
+
+    /// Checks that every label belongs to one [`Node`] and that the labels
+    /// can be looked up both ways.
+    fn try_from(data: NodeLabelsData) -> Result<Self, Error> {
+        let mut labels = Self::new();
+        for (node, label) in data.labels {
+            labels.set(node, label)?;
+        }
+        if labels.nodes != data.nodes {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The labels of the nodes and the nodes of the labels differ."
+            ));
+        }
+        return Ok(labels);
+    }
+}
+
+impl NodeLabels {
+    /// Creates a new [`NodeLabels`] without any labels.
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Labels `node` with `label`, replacing its old label.
+    /// 
+    /// # Error
+    /// 
+    /// If `label` already belongs to another [`Node`], an error is returned
+    /// and the labels are left as they were.
+    pub fn set<S: Into<String>>(
+        &mut self,
+        node: Node,
+        label: S,
+    ) -> Result<(), Error> {
+        let label = label.into();
+        if let Some(other) = self.nodes.get(&label) {
+            if *other != node {
+                return Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("The label {:?} already belongs to {}.", label, other)
+                ));
+            }
+        }
+        self.remove(node);
+        self.nodes.insert(label.clone(), node);
+        self.labels.insert(node, label);
+        return Ok(());
+    }
+
+    /// Removes the label of `node`, returning it if there was one.
+    pub fn remove(&mut self, node: Node) -> Option<String> {
+        let label = self.labels.remove(&node)?;
+        self.nodes.remove(&label);
+        return Some(label);
+    }
+
+    /// Get the label of `node`, if it has one.
+    pub fn get(&self, node: Node) -> Option<&str> {
+        return self.labels.get(&node).map(String::as_str);
+    }
+
+    /// Get the [`Node`] labelled `label`, if there is one.
+    pub fn node(&self, label: &str) -> Option<Node> {
+        return self.nodes.get(label).copied();
+    }
+
+    /// Get the label of `node`, or its number if it has no label, for
+    /// printing.
+    pub fn name(&self, node: Node) -> String {
+        return match self.get(node) {
+            Some(label) => label.to_string(),
+            None => node.to_string(),
+        };
+    }
+
+    /// Number of labelled [`Node`]s.
+    pub fn len(&self) -> usize {
+        return self.labels.len();
+    }
+
+    /// Whether no [`Node`] is labelled.
+    pub fn is_empty(&self) -> bool {
+        return self.labels.is_empty();
     }
 }
 
@@ -72,42 +256,170 @@ impl Ord for NodeWithCost where Self: PartialOrd {
 /// An array is not used because Rust requires the size of the array to be
 /// known at compile time. This is not necessary with [`Vec`]tors.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "AdjacencyMatrixData"))]
 pub struct AdjacencyMatrix {
     matrix: Vec<Vec<NodeWithCost>>,
+    policy: DuplicateEdgePolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    labels: NodeLabels,
+}
+
+/// The fields of an [`AdjacencyMatrix`] as they are deserialized, before
+/// they are checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct AdjacencyMatrixData {
+    matrix: Vec<Vec<NodeWithCost>>,
+    policy: DuplicateEdgePolicy,
+    #[serde(default)]
+    labels: NodeLabels,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AdjacencyMatrixData> for AdjacencyMatrix {
+    type Error = Error;
+
+    /// Checks the edges like [`AdjacencyMatrix::push`] does, so that every
+    /// edge leads to a [`Node`] in the matrix, no edge leads back to where
+    /// it starts and no 2 edges leave a [`Node`] for the same [`Node`], and
+    /// that every labelled [`Node`] is in the matrix.
+    fn try_from(data: AdjacencyMatrixData) -> Result<Self, Error> {
+        let AdjacencyMatrixData {matrix, policy, labels} = data;
+        let graph = Self {matrix, policy, labels};
+        let mut seen: HashSet<Node> = HashSet::new();
+        for (from, adjacents) in graph.matrix.iter().enumerate() {
+            seen.clear();
+            for adjacent in adjacents {
+                graph.check_node(adjacent.node)?;
+                if adjacent.node == from {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("{} has an edge to itself.", from)
+                    ));
+                } else if !seen.insert(adjacent.node) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "The edge from {} to {} is repeated.",
+                            from, adjacent.node
+                        )
+                    ));
+                }
+            }
+        }
+        for node in graph.labels.labels.keys() {
+            graph.check_node(*node)?;
+        }
+        return Ok(graph);
+    }
 }
 
 impl AdjacencyMatrix {
-    /// Creates a new [`AdjacencyMatrix`] with a fixed amount of [`Node`]s.
+    /// Creates a new [`AdjacencyMatrix`] with a fixed amount of [`Node`]s,
+    /// which keeps the cheaper route when a duplicate edge is pushed.
     pub fn new(total: Node) -> Self {
+        return Self::with_policy(total, DuplicateEdgePolicy::default());
+    }
+
+    /// Creates a new [`AdjacencyMatrix`] with a fixed amount of [`Node`]s
+    /// which handles duplicate edges according to `policy`.
+    pub fn with_policy(total: Node, policy: DuplicateEdgePolicy) -> Self {
         let mut matrix: Vec<Vec<NodeWithCost>> = Vec::with_capacity(total);
         matrix.resize(total, Vec::new());
-        return Self {matrix};
+        return Self {matrix, policy, labels: NodeLabels::default()};
+    }
+
+    /// Builds an [`AdjacencyMatrix`] with `total` [`Node`]s from a list of
+    /// `(from, to, cost)` edges, keeping the cheaper route between duplicate
+    /// edges.
+    /// 
+    /// # Error
+    /// 
+    /// If any edge refers to a [`Node`] which exceeds `total`, an error is
+    /// returned.
+    pub fn from_edges<I>(total: Node, edges: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (Node, Node, Cost)>,
+    {
+        return Self::from_edges_with_policy(
+            total,
+            DuplicateEdgePolicy::default(),
+            edges,
+        );
+    }
+
+    /// Builds an [`AdjacencyMatrix`] with `total` [`Node`]s from a list of
+    /// `(from, to, cost)` edges, handling duplicate edges according to
+    /// `policy`.
+    /// 
+    /// Rather than searching for an existing edge every time like
+    /// [`AdjacencyMatrix::push`], the edges leaving each [`Node`] are sorted
+    /// once and duplicates are merged in the order they were given, so the
+    /// result is the same as pushing every edge one by one.
+    /// 
+    /// # Error
+    /// 
+    /// If any edge refers to a [`Node`] which exceeds `total`, an error is
+    /// returned.
+    pub fn from_edges_with_policy<I>(
+        total: Node,
+        policy: DuplicateEdgePolicy,
+        edges: I,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (Node, Node, Cost)>,
+    {
+        let mut graph = Self::with_policy(total, policy);
+        for (from, to, cost) in edges {
+            graph.check_node(from)?;
+            graph.check_node(to)?;
+            if from != to {
+                graph.matrix[from].push(NodeWithCost::new(to, cost));
+            }
+        }
+        for adjacents in graph.matrix.iter_mut() {
+            // Stable, so duplicates stay in the order they were given.
+            adjacents.sort_by_key(|adjacent| adjacent.node);
+            adjacents.dedup_by(|duplicate, kept| {
+                if duplicate.node != kept.node {
+                    return false;
+                }
+                kept.cost = policy.combine(kept.cost, duplicate.cost);
+                return true;
+            });
+        }
+        return Ok(graph);
+    }
+
+    /// Get the [`DuplicateEdgePolicy`] used by [`AdjacencyMatrix::push`].
+    pub fn policy(&self) -> DuplicateEdgePolicy {
+        return self.policy;
+    }
+
+    /// Change the [`DuplicateEdgePolicy`] used by [`AdjacencyMatrix::push`].
+    /// Edges which have already been pushed are left as they are.
+    pub fn set_policy(&mut self, policy: DuplicateEdgePolicy) {
+        self.policy = policy;
     }
 
     /// Pushes an adjacent [`Node`] and the [`Cost`] to reach it (as a
     /// [`NodeWithCost`]) to an origin [`Node`].
     /// 
     /// If the destination [`Node`] is already added to the origin [`Node`],
-    /// the cheaper route (i.e. the `to` with the lower [`Cost`]) is used as
-    /// the route used for calculations.
+    /// the [`DuplicateEdgePolicy`] of the matrix decides the [`Cost`] of the
+    /// route used for calculations. By default, the cheaper route (i.e. the
+    /// one with the lower [`Cost`]) is kept.
     /// 
     /// If `from` or `to.node` exceeds the length of the matrix, an error is
     /// returned.
     pub fn push(&mut self, from: Node, to: NodeWithCost) -> Result<(), Error> {
-        if from >= self.matrix.len() {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                format!("{} exceeds matrix size.", from)
-            ));
-        } else if to.node >= self.matrix.len() {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                format!("{} exceeds matrix size.", to.node)
-            ));
-        }
+        self.check_node(from)?;
+        self.check_node(to.node)?;
         if from == to.node {
             return Ok(());
         }
+        let policy = self.policy;
         let target = match self.matrix.get_mut(from) {
             Some(adjacents) => adjacents,
             None => return Err(Error::new(
@@ -118,7 +430,7 @@ impl AdjacencyMatrix {
         let mut found = false;
         for existing in target.iter_mut() {
             if existing.node == to.node {
-                existing.cost = max(existing.cost, to.cost);
+                existing.cost = policy.combine(existing.cost, to.cost);
                 found = true;
                 break;
             }
@@ -129,6 +441,125 @@ impl AdjacencyMatrix {
         return Ok(());
     }
 
+    /// Pushes an edge which can be travelled both ways between `a` and `b`
+    /// for the same [`Cost`]. Both directions go through
+    /// [`AdjacencyMatrix::push`], so the [`DuplicateEdgePolicy`] is applied
+    /// to each of them.
+    /// 
+    /// If `a` or `b` exceeds the length of the matrix, an error is returned
+    /// and neither direction is added.
+    pub fn push_undirected(
+        &mut self,
+        a: Node,
+        b: Node,
+        cost: Cost,
+    ) -> Result<(), Error> {
+        self.check_node(a)?;
+        self.check_node(b)?;
+        self.push(a, NodeWithCost::new(b, cost))?;
+        self.push(b, NodeWithCost::new(a, cost))?;
+        return Ok(());
+    }
+
+    /// Checks that `node` is in the matrix.
+    fn check_node(&self, node: Node) -> Result<(), Error> {
+        return check_node(node, self.matrix.len());
+    }
+
+    /// Removes the edge going from `from` to `to`, returning its [`Cost`] if
+    /// there was one.
+    /// 
+    /// If `from` or `to` exceeds the length of the matrix, an error is
+    /// returned.
+    pub fn remove_edge(
+        &mut self,
+        from: Node,
+        to: Node,
+    ) -> Result<Option<Cost>, Error> {
+        self.check_node(from)?;
+        self.check_node(to)?;
+        let adjacents = &mut self.matrix[from];
+        return Ok(adjacents
+            .iter()
+            .position(|adjacent| adjacent.node == to)
+            .map(|index| adjacents.remove(index).cost));
+    }
+
+    /// Adds a [`Node`] without any edges at the end of the matrix and
+    /// returns it.
+    pub fn add_node(&mut self) -> Node {
+        self.matrix.push(Vec::new());
+        return self.matrix.len() - 1;
+    }
+
+    /// Removes every edge going into or out of `node`.
+    /// 
+    /// The [`Node`] is tombstoned rather than taken out of the matrix, so the
+    /// indices of the other [`Node`]s and [`AdjacencyMatrix::total`] stay the
+    /// same. It will show up as unreachable from every other [`Node`] until
+    /// new edges are pushed to it.
+    /// 
+    /// If `node` exceeds the length of the matrix, an error is returned.
+    pub fn remove_node(&mut self, node: Node) -> Result<(), Error> {
+        self.check_node(node)?;
+        self.matrix[node].clear();
+        for adjacents in self.matrix.iter_mut() {
+            adjacents.retain(|adjacent| adjacent.node != node);
+        }
+        return Ok(());
+    }
+
+    /// Builds the transposed graph, with every edge turned around but
+    /// keeping its [`Cost`]. The costs from every [`Node`] of the reversed
+    /// graph are the costs of reaching that [`Node`] in this graph, so one
+    /// row of it answers "how far is everywhere from this target". The
+    /// labels and [`DuplicateEdgePolicy`] are kept.
+    pub fn reverse(&self) -> Self {
+        let mut matrix: Vec<Vec<NodeWithCost>> = vec![Vec::new(); self.matrix.len()];
+        for (from, adjacents) in self.matrix.iter().enumerate() {
+            for adjacent in adjacents {
+                matrix[adjacent.node].push(NodeWithCost::new(from, adjacent.cost));
+            }
+        }
+        return Self {matrix, policy: self.policy, labels: self.labels.clone()};
+    }
+
+    /// Labels `node` with `label`, replacing its old label. See
+    /// [`NodeLabels`].
+    /// 
+    /// # Error
+    /// 
+    /// If `node` exceeds the length of the matrix or `label` already belongs
+    /// to another [`Node`], an error is returned.
+    pub fn set_label<S: Into<String>>(
+        &mut self,
+        node: Node,
+        label: S,
+    ) -> Result<(), Error> {
+        self.check_node(node)?;
+        return self.labels.set(node, label);
+    }
+
+    /// Get the label of `node`, if it has one.
+    pub fn label(&self, node: Node) -> Option<&str> {
+        return self.labels.get(node);
+    }
+
+    /// Get the [`Node`] labelled `label`, if there is one.
+    pub fn node_by_label(&self, label: &str) -> Option<Node> {
+        return self.labels.node(label);
+    }
+
+    /// Get the labels of the [`Node`]s.
+    pub fn labels(&self) -> &NodeLabels {
+        return &self.labels;
+    }
+
+    /// Get the number of edges in the graph.
+    pub fn edges(&self) -> usize {
+        return self.matrix.iter().map(Vec::len).sum();
+    }
+
     /// Get the number of [`Node`]s in the graph.
     pub fn total(&self) -> Node {
         return self.matrix.len();
@@ -140,13 +571,312 @@ impl AdjacencyMatrix {
     }
 }
 
+/// A graph in the compressed sparse row layout, built from an
+/// [`AdjacencyMatrix`].
+/// 
+/// Instead of one [`Vec`] per [`Node`], the edges leaving every [`Node`] are
+/// stored back to back in a single [`Vec`], and `offsets[node]` to
+/// `offsets[node + 1]` marks the edges leaving `node`. Walking the edges of
+/// neighbouring [`Node`]s therefore stays in the same block of memory, and
+/// there is no per-[`Node`] allocation or spare capacity. The graph cannot
+/// be edited once built, which makes it the layout used by [`MtdDijkstra`]
+/// while calculating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "CsrGraphData"))]
+pub struct CsrGraph {
+    offsets: Vec<usize>,
+    edges: Vec<NodeWithCost>,
+}
+
+/// The fields of a [`CsrGraph`] as they are deserialized, before they are
+/// checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CsrGraphData {
+    offsets: Vec<usize>,
+    edges: Vec<NodeWithCost>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CsrGraphData> for CsrGraph {
+    type Error = Error;
+
+    /// Checks that `offsets` starts at `0`, never goes down and ends at the
+    /// number of edges, and that every edge leads to a [`Node`] in the
+    /// graph.
+    fn try_from(data: CsrGraphData) -> Result<Self, Error> {
+        let CsrGraphData {offsets, edges} = data;
+        if offsets.first() != Some(&0) || offsets.last() != Some(&edges.len()) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "The offsets must start at 0 and end at the {} edges.",
+                    edges.len()
+                )
+            ));
+        }
+        if let Some(node) = offsets.windows(2).position(|pair| pair[0] > pair[1]) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The offsets go down after node {}.", node)
+            ));
+        }
+        let total = offsets.len() - 1;
+        if let Some(edge) = edges.iter().find(|edge| edge.node >= total) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{} exceeds the {} nodes of the graph.", edge.node, total)
+            ));
+        }
+        return Ok(Self {offsets, edges});
+    }
+}
+
+impl CsrGraph {
+    /// Builds a [`CsrGraph`] with the same [`Node`]s and edges as `matrix`.
+    pub fn from_matrix(matrix: &AdjacencyMatrix) -> Self {
+        let mut offsets: Vec<usize> = Vec::with_capacity(matrix.total() + 1);
+        let mut edges: Vec<NodeWithCost> = Vec::with_capacity(matrix.edges());
+        offsets.push(0);
+        for adjacents in &matrix.matrix {
+            edges.extend_from_slice(adjacents);
+            offsets.push(edges.len());
+        }
+        return Self {offsets, edges};
+    }
+
+    /// Builds an [`AdjacencyMatrix`] with the same [`Node`]s and edges,
+    /// which keeps the cheaper route when a duplicate edge is pushed.
+    pub fn to_matrix(&self) -> AdjacencyMatrix {
+        let mut matrix = AdjacencyMatrix::new(self.total());
+        for (node, adjacents) in matrix.matrix.iter_mut().enumerate() {
+            let (start, end) = (self.offsets[node], self.offsets[node + 1]);
+            adjacents.extend_from_slice(&self.edges[start..end]);
+        }
+        return matrix;
+    }
+
+    /// Builds the [`CsrGraph`] with every edge turned around, so the edges
+    /// leaving a [`Node`] in it are the edges going into that [`Node`] in
+    /// this graph.
+    pub fn reversed(&self) -> Self {
+        let mut offsets: Vec<usize> = vec![0; self.offsets.len()];
+        for edge in &self.edges {
+            offsets[edge.node + 1] += 1;
+        }
+        for node in 1..offsets.len() {
+            offsets[node] += offsets[node - 1];
+        }
+        let mut next = offsets.clone();
+        let mut edges = vec![NodeWithCost::new(0, 0); self.edges.len()];
+        for from in 0..self.total() {
+            for edge in &self.edges[self.offsets[from]..self.offsets[from + 1]] {
+                edges[next[edge.node]] = NodeWithCost::new(from, edge.cost);
+                next[edge.node] += 1;
+            }
+        }
+        return Self {offsets, edges};
+    }
+
+    /// Get the number of edges in the graph.
+    pub fn edges(&self) -> usize {
+        return self.edges.len();
+    }
+
+    /// Get the number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.offsets.len() - 1;
+    }
+
+    /// Get the adjacent [`Node`]s from a starting node.
+    pub fn get_node(&self, node: Node) -> Option<&[NodeWithCost]> {
+        let start = *self.offsets.get(node)?;
+        let end = *self.offsets.get(node + 1)?;
+        return Some(&self.edges[start..end]);
+    }
+}
+
+impl Default for CsrGraph {
+    /// An empty [`CsrGraph`] without any [`Node`]s.
+    fn default() -> Self {
+        return Self {offsets: vec![0], edges: Vec::new()};
+    }
+}
+
+impl From<&AdjacencyMatrix> for CsrGraph {
+    fn from(matrix: &AdjacencyMatrix) -> Self {
+        return Self::from_matrix(matrix);
+    }
+}
+
+impl From<AdjacencyMatrix> for CsrGraph {
+    fn from(matrix: AdjacencyMatrix) -> Self {
+        return Self::from_matrix(&matrix);
+    }
+}
+
+/// Runs the Dijkstra Algorithm from a single `source` [`Node`] over `graph`
+/// and returns the [`Cost`] to reach every [`Node`] in the graph, with
+/// [`None`] marking unreachable [`Node`]s. If `max_cost` is given, the
+/// search stops once every [`Node`] within it has been found and the
+/// [`Node`]s further away are marked unreachable.
+/// 
+/// The temporary distances and heap are taken from the worker's
+/// [`crate::arena::Arena`].
+pub(crate) fn shortest_from(
+    source: Node,
+    nodes: Node,
+    graph: &CsrGraph,
+    max_cost: Option<Cost>,
+) -> Result<Vec<Option<Cost>>, Error> {
+    return with_arena(|arena| {
+        // Set everything to unvisited
+        let distances: &mut [Option<Cost>] = arena.alloc_slice(nodes, None);
+        // Set starting node to 0
+        *unwrapoption!(distances.get_mut(source)) = Some(0);
+
+        let mut unvisited = BinaryHeap::from(arena.take_vec::<NodeWithCost>());
+        unvisited.push(NodeWithCost::new(source, 0));
+
+        while let Some(current) = unvisited.pop() {
+            if Some(current.cost) != *unwrapoption!(distances.get(current.node)) {
+                continue;
+            }
+            for adjacent in unwrapoption!(graph.get_node(current.node)) {
+                let new_distance = current.cost.saturating_add(adjacent.cost);
+                if max_cost.is_some_and(|max_cost| new_distance > max_cost) {
+                    continue;
+                }
+                let adjacent_distance = unwrapoption!(
+                    distances.get_mut(adjacent.node)
+                );
+                match adjacent_distance {
+                    Some(distance) if *distance <= new_distance => continue,
+                    _ => *adjacent_distance = Some(new_distance),
+                }
+                unvisited.push(NodeWithCost::new(adjacent.node, new_distance));
+            }
+        }
+        arena.give_vec(unvisited.into_vec());
+        return Ok(distances.to_vec());
+    });
+}
+
+/// Runs the Dijkstra Algorithm from all of `sources` at once, as if a
+/// virtual [`Node`] had an edge costing `0` to each of them, and returns the
+/// nearest source of every [`Node`] in the graph, with [`None`] marking
+/// [`Node`]s no source can reach.
+fn nearest_from(
+    sources: &[Node],
+    nodes: Node,
+    graph: &CsrGraph,
+    max_cost: Option<Cost>,
+) -> Result<Vec<Option<NearestSource>>, Error> {
+    let mut nearest: Vec<Option<NearestSource>> = vec![None; nodes];
+    let mut unvisited = BinaryHeap::new();
+    for source in sources {
+        *unwrapoption!(nearest.get_mut(*source)) = Some(NearestSource {
+            source: *source,
+            cost: 0,
+        });
+        unvisited.push(NodeWithCost::new(*source, 0));
+    }
+
+    while let Some(current) = unvisited.pop() {
+        let origin = match unwrapoption!(nearest.get(current.node)) {
+            Some(origin) if origin.cost == current.cost => origin.source,
+            _ => continue,
+        };
+        for adjacent in unwrapoption!(graph.get_node(current.node)) {
+            let new_distance = current.cost.saturating_add(adjacent.cost);
+            if max_cost.is_some_and(|max_cost| new_distance > max_cost) {
+                continue;
+            }
+            let adjacent_nearest = unwrapoption!(nearest.get_mut(adjacent.node));
+            match adjacent_nearest {
+                Some(nearest) if nearest.cost <= new_distance => continue,
+                _ => {
+                    *adjacent_nearest = Some(NearestSource {
+                        source: origin,
+                        cost: new_distance,
+                    })
+                },
+            }
+            unvisited.push(NodeWithCost::new(adjacent.node, new_distance));
+        }
+    }
+    return Ok(nearest);
+}
+
+/// A set of [`Node`]s which can be marked from any thread without a lock,
+/// used to track which rows of the [`DistanceTable`] are finished.
+#[derive(Debug)]
+struct ReadyBitmap {
+    words: Vec<AtomicU64>,
+    count: AtomicUsize,
+}
+
+impl ReadyBitmap {
+    /// Creates a new [`ReadyBitmap`] for `nodes` [`Node`]s, none of which
+    /// are marked.
+    fn new(nodes: Node) -> Self {
+        let words = (0..nodes.div_ceil(64)).map(|_| AtomicU64::new(0)).collect();
+        return Self {words, count: AtomicUsize::new(0)};
+    }
+
+    /// Marks `node`. Marking a [`Node`] twice only counts it once.
+    fn mark(&self, node: Node) {
+        if let Some(word) = self.words.get(node / 64) {
+            let bit = 1 << (node % 64);
+            if word.fetch_or(bit, SeqCst) & bit == 0 {
+                self.count.fetch_add(1, SeqCst);
+            }
+        }
+    }
+
+    /// Whether `node` has been marked.
+    fn contains(&self, node: Node) -> bool {
+        return match self.words.get(node / 64) {
+            Some(word) => word.load(SeqCst) & (1 << (node % 64)) != 0,
+            None => false,
+        };
+    }
+
+    /// Number of marked [`Node`]s.
+    fn count(&self) -> usize {
+        return self.count.load(SeqCst);
+    }
+}
+
+/// Callback given the number of sources done and scheduled by
+/// [`MtdDijkstra::on_progress`].
+type ProgressCallback = Arc<dyn Fn(usize, usize) + Send + Sync + 'static>;
+
+/// How long the blocking getters of [`MtdDijkstra`] sleep between checks
+/// on the rows they are waiting for.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Number of jobs [`MtdDijkstra::calculate`] aims to send to each thread
+/// when the chunk size is picked automatically.
+pub const JOBS_PER_THREAD: usize = 8;
+
 /// This `struct` contains the implementations to calculate the shortest route
 /// from [`Node`] in the graph using multiple threads.
 pub struct MtdDijkstra {
     pool: ThreadPool,
-    costs: Arc<Mutex<HashMap<Node, Vec<Option<Cost>>>>>,
+    costs: Arc<Mutex<DistanceTable>>,
     nodes: Node,
-    matrix: Arc<Mutex<AdjacencyMatrix>>,
+    graph: Arc<CsrGraph>,
+    scheduled: HashSet<Node>,
+    ready: Arc<ReadyBitmap>,
+    chunk_size: Option<usize>,
+    max_cost: Option<Cost>,
+    /// Number of sources scheduled, shared with the jobs for reporting
+    /// progress.
+    total: Arc<AtomicUsize>,
+    progress: Option<ProgressCallback>,
+    labels: NodeLabels,
 }
 
 impl MtdDijkstra {
@@ -154,10 +884,11 @@ impl MtdDijkstra {
     /// 
     /// # Parameters
     /// 1. ```threads: usize``` => Number of threads to use. At least one
-    /// thread is needed to run the algorithm.
+    ///    thread is needed to run the algorithm.
     /// 2. ```nodes: Node``` => Number of nodes in the graph.
     /// 3. ```matrix: AdjacencyMatrix``` => The adjacency matrix which
-    /// describes the graph.
+    ///    describes the graph. It is converted to a [`CsrGraph`] for the
+    ///    calculations, and its labels are kept for looking up results.
     /// 
     /// # Error
     /// 
@@ -167,105 +898,415 @@ impl MtdDijkstra {
         threads: usize,
         nodes: Node,
         matrix: AdjacencyMatrix
+    ) -> Result<Self, Error> {
+        let labels = matrix.labels().clone();
+        let mut processor = Self::from_csr(threads, nodes, CsrGraph::from(matrix))?;
+        processor.set_labels(labels);
+        return Ok(processor);
+    }
+
+    /// Creates a new [`MtdDijkstra`] instance from a graph which is already
+    /// a [`CsrGraph`]. See [`MtdDijkstra::new`].
+    pub fn from_csr(
+        threads: usize,
+        nodes: Node,
+        graph: CsrGraph,
     ) -> Result<Self, Error> {
         let pool = ThreadPool::new(threads)?;
-        let costs: Arc<Mutex<HashMap<Node, Vec<Option<Cost>>>>> = Arc::new(
+        let costs: Arc<Mutex<DistanceTable>> = Arc::new(
             Mutex::new(HashMap::new())
         );
-        let matrix = Arc::new(Mutex::new(matrix));
-        return Ok(Self {pool, costs, nodes, matrix});
+        // The graph never changes while calculating, so the workers can
+        // share it without a lock.
+        let graph = Arc::new(graph);
+        let scheduled = HashSet::new();
+        let ready = Arc::new(ReadyBitmap::new(nodes));
+        return Ok(Self {
+            pool,
+            costs,
+            nodes,
+            graph,
+            scheduled,
+            ready,
+            chunk_size: None,
+            max_cost: None,
+            total: Arc::new(AtomicUsize::new(0)),
+            progress: None,
+            labels: NodeLabels::default(),
+        });
+    }
+
+    /// Estimates the cost of calculating the rows of `sources` source
+    /// [`Node`]s over `matrix` with `threads` threads, without starting any
+    /// threads. Pass [`AdjacencyMatrix::total`] as `sources` to estimate
+    /// [`MtdDijkstra::calculate`].
+    /// 
+    /// Each source takes about `(n + m) log n` steps for `n` [`Node`]s and
+    /// `m` edges. Memory is counted for the result table, a scratch distance
+    /// table and heap per thread, and the [`CsrGraph`] built from the matrix.
+    pub fn estimate(
+        threads: usize,
+        matrix: &AdjacencyMatrix,
+        sources: usize,
+    ) -> Estimate {
+        let nodes = matrix.total() as u128;
+        let edges = matrix.edges() as u128;
+        let sources = sources.min(matrix.total()) as u128;
+        let threads = threads.max(1) as u128;
+        let row = nodes * size_of::<Option<Cost>>() as u128;
+        let heap = (edges + 1) * size_of::<NodeWithCost>() as u128;
+        let graph = (nodes + 1) * size_of::<usize>() as u128
+            + edges * size_of::<NodeWithCost>() as u128;
+        let memory = sources * (row + size_of::<(Node, Vec<Option<Cost>>)>() as u128)
+            + threads.min(sources.max(1)) * (row + heap)
+            + graph;
+        let work = sources * (nodes + edges) * log2_ceil(nodes);
+        return Estimate::new(memory, work);
+    }
+
+    /// Set how many sources [`MtdDijkstra::calculate`] puts in each job.
+    /// With [`None`], which is the default, the size is picked by
+    /// [`MtdDijkstra::chunk_size`].
+    pub fn set_chunk_size(&mut self, chunk_size: Option<usize>) {
+        self.chunk_size = chunk_size.map(|size| size.max(1));
+    }
+
+    /// Get how many sources [`MtdDijkstra::calculate`] puts in each job.
+    /// 
+    /// Unless set with [`MtdDijkstra::set_chunk_size`], the sources are
+    /// split into about [`JOBS_PER_THREAD`] jobs for each thread, so small
+    /// graphs are not split into jobs too small to be worth sending and
+    /// large graphs do not flood the [`ThreadPool`] with millions of jobs.
+    pub fn chunk_size(&self) -> usize {
+        if let Some(size) = self.chunk_size {
+            return size;
+        }
+        let jobs = self.pool.threads() * JOBS_PER_THREAD;
+        return self.nodes.div_ceil(jobs).max(1);
+    }
+
+    /// Set the largest [`Cost`] searched for from each source. [`Node`]s
+    /// which cost more than `max_cost` to reach are marked unreachable, and
+    /// the search from each source stops as soon as the cheapest [`Node`]
+    /// left to visit costs more, instead of exploring the whole graph. This
+    /// answers queries like "everything within 30 minutes". With [`None`],
+    /// which is the default, there is no limit.
+    /// 
+    /// Only sources scheduled after this is called are affected.
+    pub fn set_max_cost(&mut self, max_cost: Option<Cost>) {
+        self.max_cost = max_cost;
+    }
+
+    /// Get the largest [`Cost`] searched for from each source, set with
+    /// [`MtdDijkstra::set_max_cost`].
+    pub fn max_cost(&self) -> Option<Cost> {
+        return self.max_cost;
+    }
+
+    /// Sends a job calculating the costs from each of `sources` to the
+    /// [`ThreadPool`]. Sources which have already been scheduled are
+    /// skipped.
+    fn schedule(&mut self, sources: &[Node]) -> Result<(), Error> {
+        let sources: Vec<Node> = sources
+            .iter()
+            .copied()
+            .filter(|source| self.scheduled.insert(*source))
+            .collect();
+        if sources.is_empty() {
+            return Ok(());
+        }
+        self.total.fetch_add(sources.len(), SeqCst);
+        let total = self.total.clone();
+        let progress = self.progress.clone();
+        let max_cost = self.max_cost;
+        let nodes = self.nodes;
+        let graph = self.graph.clone();
+        let costs = self.costs.clone();
+        let ready = self.ready.clone();
+        let queued = sources.clone();
+        let sent = self.pool.execute(move || {
+            for source in queued {
+                let distances = shortest_from(source, nodes, &graph, max_cost)?;
+                // The worker only resets its arena after the whole job.
+                reset_local();
+                unwrapmutex!(costs.lock()).insert(source, distances);
+                ready.mark(source);
+                if let Some(progress) = &progress {
+                    progress(ready.count(), total.load(SeqCst));
+                }
+            }
+            return Ok(());
+        });
+        if sent.is_err() {
+            // No job will calculate these sources, so nothing may wait on them.
+            for source in &sources {
+                self.scheduled.remove(source);
+            }
+            self.total.fetch_sub(sources.len(), SeqCst);
+        }
+        return sent;
+    }
+
+    /// Waits for every scheduled job to finish, returning an error if any
+    /// job failed, including a failure already counted while polling, whose
+    /// error the [`ThreadPool`] no longer holds.
+    fn finish(&mut self) -> Result<(), Error> {
+        self.pool.wait()?;
+        if self.pool.jobs_err()? > 0 {
+            return Err(Error::other("A job calculating the costs failed."));
+        }
+        return Ok(());
     }
 
     /// Calculates the shortest distance to all (if possible) nodes in the
     /// graph from each node. This method uses a [`ThreadPool`] to run the
-    /// algorithm. If something wrong happens, a [`std::io::Error`] is
-    /// returned.
+    /// algorithm, with [`MtdDijkstra::chunk_size`] sources in each job. If
+    /// something wrong happens, a [`std::io::Error`] is returned.
     pub fn calculate(&mut self) -> Result<(), Error> {
-        for node in 0..self.nodes {
-            let nodes = self.nodes;
-            let node = node;
-            let matrix = self.matrix.clone();
-            let costs = self.costs.clone();
-            self.pool.execute(move || {
-
-                let mut distances: Vec<Option<Cost>> = Vec::with_capacity(
-                    nodes
-                );
-                // Set everything to unvisited
-                distances.resize(nodes, None);
-                // Set starting node to 0
-                *unwrapoption!(distances.get_mut(node)) = Some(0);
-                
-                let mut unvisited: BinaryHeap<NodeWithCost> = BinaryHeap::new();
-                unvisited.push(NodeWithCost::new(node, 0));
-
-                while !unvisited.is_empty() {
-                    if let Some(current) = unvisited.pop() {
-                        if Some(current.cost) != *unwrapoption!(
-                            distances.get(current.node)
-                        ) {
-                            continue;
-                        }
-                        for adjacent in unwrapoption!(
-                            unwrapmutex!(matrix.lock())
-                                .matrix
-                                .get(current.node)
-                        ) {
-                            let adjacent_distance = unwrapoption!(
-                                distances.get_mut(adjacent.node)
-                            );
-                            if let Some(distance) = adjacent_distance {
-                                let mut new_distance = *distance;
-                                if *distance > current.cost + adjacent.cost {
-                                    *distance = current.cost + adjacent.cost;
-                                    new_distance = *distance;
-                                }
-                                unvisited.push(NodeWithCost::new(
-                                    adjacent.node,
-                                    new_distance,
-                                ));
-                            } else if let None = adjacent_distance {
-                                let new_distance = current.cost+adjacent.cost;
-                                *adjacent_distance = Some(new_distance);
-                                unvisited.push(NodeWithCost::new(
-                                    adjacent.node,
-                                    new_distance,
-                                ));
-                            }
-                        }
-                    } else {
-                        break;
-                    }
-                }
+        let sources: Vec<Node> = (0..self.nodes).collect();
+        for chunk in sources.chunks(self.chunk_size()) {
+            self.schedule(chunk)?;
+        }
+        return Ok(());
+    }
 
-                let mut inner_cost = unwrapmutex!(costs.lock());
-                inner_cost.insert(node, distances);
-                
-                return Ok(());
-            })?;
+    /// Calculates the shortest distance to all (if possible) nodes in the
+    /// graph from `source` only, which is much cheaper than
+    /// [`MtdDijkstra::calculate`] when only one row of the distance table is
+    /// needed.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` is not in the graph.
+    pub fn calculate_from(&mut self, source: Node) -> Result<(), Error> {
+        check_node(source, self.nodes)?;
+        return self.schedule(&[source]);
+    }
+
+    /// Calculates the shortest distance to all (if possible) nodes in the
+    /// graph from each of `sources` only, with [`MtdDijkstra::chunk_size`]
+    /// sources in each job. Only these rows of the distance table are
+    /// filled, which saves most of the work of [`MtdDijkstra::calculate`]
+    /// when a few rows of a large graph are needed.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if any of `sources` is not in the
+    /// graph, in which case nothing is scheduled.
+    pub fn calculate_sources(&mut self, sources: &[Node]) -> Result<(), Error> {
+        for source in sources {
+            check_node(*source, self.nodes)?;
+        }
+        for chunk in sources.chunks(self.chunk_size()) {
+            self.schedule(chunk)?;
         }
         return Ok(());
     }
 
-    /// Get the inner cost [`std::collections::HashMap`].
-    pub fn get_result(self) -> Arc<Mutex<HashMap<Node, Vec<Option<Cost>>>>> {
+    /// Calculates, for every [`Node`] in the graph, which of `sources` is
+    /// the nearest to it and the [`Cost`] of reaching it from there, with
+    /// [`None`] marking [`Node`]s no source can reach. This answers
+    /// "nearest depot" questions with a single search instead of one row of
+    /// the distance table per source.
+    /// 
+    /// The search runs on the calling thread, so it does not wait behind
+    /// jobs already sent to the [`ThreadPool`], and its result is not
+    /// stored in the distance table. [`MtdDijkstra::max_cost`] is honoured.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if any of `sources` is not in the
+    /// graph.
+    pub fn calculate_multi_source(
+        &self,
+        sources: &[Node],
+    ) -> Result<Vec<Option<NearestSource>>, Error> {
+        for source in sources {
+            check_node(*source, self.nodes)?;
+        }
+        return nearest_from(sources, self.nodes, &self.graph, self.max_cost);
+    }
+
+    /// Get a [`DijkstraResults`] handle which can take snapshots of the
+    /// rows calculated so far while jobs are still running, for example
+    /// from another thread drawing the progress.
+    pub fn results(&self) -> DijkstraResults {
+        return DijkstraResults::new(self.costs.clone());
+    }
+
+    /// Waits for every scheduled job to finish and returns a copy of the
+    /// [`DistanceTable`].
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned, or a general one if
+    /// the failure was already seen while polling, such as by
+    /// [`MtdDijkstra::get`].
+    pub fn get_all(&mut self) -> Result<DistanceTable, Error> {
+        self.finish()?;
+        return Ok(unwrapmutex!(self.costs.lock()).clone());
+    }
+
+    /// Waits for every scheduled job to finish and renders the calculated
+    /// rows as an aligned table, with `∞` for unreachable [`Node`]s and
+    /// labels in place of numbers. See [`render_table`].
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned.
+    pub fn render_table(&mut self) -> Result<String, Error> {
+        self.finish()?;
+        let costs = unwrapmutex!(self.costs.lock());
+        return Ok(render_table(&costs, &self.labels));
+    }
+
+    /// Waits for every scheduled job to finish and returns which [`Node`]s
+    /// can be reached from each calculated source, for callers which only
+    /// care about connectivity.
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned.
+    pub fn reachability(&mut self) -> Result<Reachability, Error> {
+        self.finish()?;
+        let costs = unwrapmutex!(self.costs.lock());
+        return Ok(Reachability::from_costs(self.nodes, &costs));
+    }
+
+    /// Calculates every row of the distance table which is still missing,
+    /// then returns the eccentricity of each [`Node`]: the [`Cost`] of
+    /// reaching the [`Node`] furthest from it. A [`Node`] which cannot reach
+    /// every other [`Node`] has an infinite eccentricity, marked by [`None`],
+    /// as do rows cut short by [`MtdDijkstra::max_cost`].
+    /// 
+    /// The rows are reduced in parallel on the [`ThreadPool`].
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned.
+    pub fn eccentricities(&mut self) -> Result<Vec<Option<Cost>>, Error> {
+        self.calculate()?;
+        self.finish()?;
+        let mut rows: Vec<Vec<Option<Cost>>> = Vec::with_capacity(self.nodes);
+        {
+            let costs = unwrapmutex!(self.costs.lock());
+            for source in 0..self.nodes {
+                rows.push(unwrapoption!(costs.get(&source)).clone());
+            }
+        }
+        let rows = Arc::new(rows);
+        let sources: Vec<Node> = (0..self.nodes).collect();
+        return self.pool.map(Arc::new(sources), ChunkPolicy::Auto, move |source| {
+            let mut furthest: Cost = 0;
+            for cost in &rows[*source] {
+                furthest = furthest.max((*cost)?);
+            }
+            return Some(furthest);
+        });
+    }
+
+    /// The radius of the graph: the smallest eccentricity of any [`Node`],
+    /// or [`None`] if no [`Node`] can reach every other one. See
+    /// [`MtdDijkstra::eccentricities`].
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned.
+    pub fn radius(&mut self) -> Result<Option<Cost>, Error> {
+        return Ok(self.eccentricities()?.into_iter().flatten().min());
+    }
+
+    /// The diameter of the graph: the largest eccentricity of any [`Node`],
+    /// or [`None`] if some [`Node`] cannot reach every other one (or the
+    /// graph is empty). See [`MtdDijkstra::eccentricities`].
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned.
+    pub fn diameter(&mut self) -> Result<Option<Cost>, Error> {
+        let eccentricities: Option<Vec<Cost>> = self
+            .eccentricities()?
+            .into_iter()
+            .collect();
+        return Ok(eccentricities.and_then(|all| all.into_iter().max()));
+    }
+
+    /// Waits for every scheduled job to finish and returns the
+    /// [`DistanceTable`], shutting down the [`ThreadPool`]. The table is
+    /// only copied if a [`DijkstraResults`] handle to it is still alive.
+    /// 
+    /// # Error
+    /// 
+    /// If a job failed, the first error is returned, or a general one if
+    /// the failure was already seen while polling, such as by
+    /// [`MtdDijkstra::get`].
+    pub fn into_result(mut self) -> Result<DistanceTable, Error> {
+        self.finish()?;
+        let costs = self.costs;
+        return match Arc::try_unwrap(costs) {
+            Ok(costs) => Ok(unwrapmutex!(costs.into_inner())),
+            Err(costs) => Ok(unwrapmutex!(costs.lock()).clone()),
+        };
+    }
+
+    /// Get the inner cost [`std::collections::HashMap`], which jobs may
+    /// still be writing to. [`MtdDijkstra::into_result`] waits for the jobs
+    /// and gives back the table itself instead.
+    pub fn get_result(self) -> Arc<Mutex<DistanceTable>> {
         return self.costs;
     }
 
+    /// Get how many of the sources scheduled so far have been calculated,
+    /// and how many have been scheduled, as `(done, total)`.
+    pub fn progress(&self) -> (usize, usize) {
+        return (self.ready.count(), self.total.load(SeqCst));
+    }
+
+    /// Registers `callback` to be called with `(done, total)` like
+    /// [`MtdDijkstra::progress`] every time the costs from a source have
+    /// been calculated. It is called on the worker threads, so it should
+    /// return quickly, and only applies to sources scheduled after it is
+    /// set.
+    pub fn on_progress<F>(&mut self, callback: F)
+    where
+        F: Fn(usize, usize) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+    }
+
+    /// Whether the row of costs from `source` has been calculated.
+    pub fn is_ready(&self, source: Node) -> bool {
+        return self.ready.contains(source);
+    }
+
+    /// Number of rows of costs which have been calculated.
+    pub fn ready_count(&self) -> usize {
+        return self.ready.count();
+    }
+
+    /// Iterates over the rows of costs which have been calculated so far,
+    /// in order of their source [`Node`]. Each row is copied out of the
+    /// [`DistanceTable`] as the iterator reaches it, so rows finished in
+    /// the meantime show up as well.
+    pub fn ready_rows(&self) -> impl Iterator<Item = (Node, Vec<Option<Cost>>)> + '_ {
+        return (0..self.nodes)
+            .filter(move |source| self.ready.contains(*source))
+            .filter_map(move |source| {
+                let row = self.costs.lock().ok()?.get(&source)?.clone();
+                return Some((source, row));
+            });
+    }
+
     /// Get a copy of the cost to get to all destination [`Node`]s from one
     /// starting [`Node`].
     /// 
     /// Since 0.2: Blocks until all [`Node`]s have been calculated.
     pub fn get(&mut self, node: Node) -> Option<Vec<Option<Cost>>> {
-        let mut jobs_ok: usize = 0;
-        let mut jobs_err: usize = 0;
-        while jobs_ok < self.nodes && jobs_err == 0 {
-            sleep(Duration::from_millis(50));
-            jobs_ok = self.pool.jobs_ok().ok()?;
-            jobs_err = self.pool.jobs_err().ok()?;
-        }
-        if jobs_err > 0 {
-            return None;
+        while self.ready.count() < self.scheduled.len() {
+            if self.pool.jobs_err().ok()? > 0 {
+                return None;
+            }
+            sleep(POLL_INTERVAL);
         }
         let costs = match self.costs.lock() {
             Ok(costs) => costs,
@@ -273,4 +1314,377 @@ impl MtdDijkstra {
         }.get(&node)?.clone();
         return Some(costs);
     }
-}
\ No newline at end of file
+
+    /// Get a copy of the cost to get to all destination [`Node`]s from
+    /// `source`, blocking only until that row has been calculated instead of
+    /// waiting for every scheduled job.
+    /// 
+    /// Returns [`None`] if `source` was never scheduled with
+    /// [`MtdDijkstra::calculate`] or [`MtdDijkstra::calculate_from`], or if a
+    /// job has failed.
+    pub fn get_from(&mut self, source: Node) -> Option<Vec<Option<Cost>>> {
+        if !self.scheduled.contains(&source) {
+            return None;
+        }
+        while !self.ready.contains(source) {
+            if self.pool.jobs_err().ok()? > 0 {
+                return None;
+            }
+            sleep(POLL_INTERVAL);
+        }
+        let costs = match self.costs.lock() {
+            Ok(costs) => costs,
+            Err(_error) => return None,
+        }.get(&source)?.clone();
+        return Some(costs);
+    }
+
+    /// Get the labels used to look up results by name.
+    pub fn labels(&self) -> &NodeLabels {
+        return &self.labels;
+    }
+
+    /// Replaces the labels used to look up results by name, for graphs
+    /// given as a [`CsrGraph`], which has no labels of its own.
+    pub fn set_labels(&mut self, labels: NodeLabels) {
+        self.labels = labels;
+    }
+
+    /// Like [`MtdDijkstra::get_from`], but the source is found by its label
+    /// and each destination is given by its label, or its number if it has
+    /// none. [`None`] is returned if no [`Node`] is labelled `source` or
+    /// [`MtdDijkstra::get_from`] returns [`None`].
+    pub fn get_by_label(
+        &mut self,
+        source: &str,
+    ) -> Option<Vec<(String, Option<Cost>)>> {
+        let node = self.labels.node(source)?;
+        let costs = self.get_from(node)?;
+        return Some(
+            costs
+                .into_iter()
+                .enumerate()
+                .map(|(node, cost)| (self.labels.name(node), cost))
+                .collect()
+        );
+    }
+
+    /// Get a copy of the cost to get to all destination [`Node`]s from
+    /// `source`, blocking until that row has been calculated. Unlike
+    /// [`MtdDijkstra::get`] and [`MtdDijkstra::get_from`], the reason is
+    /// given when the row cannot be returned.
+    /// 
+    /// # Error
+    /// 
+    /// A [`DijkstraError`] describing the problem is returned if `source` is
+    /// not in the graph or was never scheduled, a job has failed, or the
+    /// results could not be read.
+    pub fn get_checked(
+        &mut self,
+        source: Node,
+    ) -> Result<Vec<Option<Cost>>, DijkstraError> {
+        if source >= self.nodes {
+            return Err(DijkstraError::UnknownNode(source));
+        }
+        if !self.scheduled.contains(&source) {
+            return Err(DijkstraError::NotScheduled(source));
+        }
+        while !self.ready.contains(source) {
+            let failed = self.pool.jobs_err().map_err(|error| {
+                return DijkstraError::Channel(error.to_string());
+            })?;
+            if failed > 0 {
+                return Err(DijkstraError::JobFailed(failed));
+            }
+            sleep(POLL_INTERVAL);
+        }
+        let costs = match self.costs.lock() {
+            Ok(costs) => costs,
+            Err(_error) => return Err(DijkstraError::Poisoned),
+        };
+        return costs
+            .get(&source)
+            .cloned()
+            .ok_or(DijkstraError::NotScheduled(source));
+    }
+
+    /// Get a copy of the cost to get to all destination [`Node`]s from
+    /// `source` like [`MtdDijkstra::get_from`], but give up once `timeout`
+    /// has passed instead of waiting forever for a job which never
+    /// finishes. A `timeout` too long to be a point in time, such as
+    /// [`Duration::MAX`], waits like [`MtdDijkstra::get_from`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `source` was never scheduled, a
+    /// job has failed, or the row is still not ready after `timeout`, in
+    /// which case its kind is [`ErrorKind::TimedOut`].
+    pub fn get_timeout(
+        &mut self,
+        source: Node,
+        timeout: Duration,
+    ) -> Result<Vec<Option<Cost>>, Error> {
+        if !self.scheduled.contains(&source) {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} has not been scheduled.", source)
+            ));
+        }
+        let deadline = Instant::now().checked_add(timeout);
+        while !self.ready.contains(source) {
+            if self.pool.jobs_err()? > 0 {
+                return Err(Error::other("A job calculating the costs failed."));
+            }
+            let now = Instant::now();
+            let left = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(now),
+                None => POLL_INTERVAL,
+            };
+            if left.is_zero() {
+                return Err(Error::new(
+                    ErrorKind::TimedOut,
+                    format!(
+                        "The costs from {} were not ready after {:?}.",
+                        source, timeout
+                    )
+                ));
+            }
+            sleep(POLL_INTERVAL.min(left));
+        }
+        let costs = unwrapmutex!(self.costs.lock());
+        return Ok(unwrapoption!(costs.get(&source)).clone());
+    }
+}
+
+/// A handle to the [`DistanceTable`] an [`MtdDijkstra`] is writing to, made
+/// with [`MtdDijkstra::results`].
+/// 
+/// Rows are never changed once a job has written them, so every row the
+/// handle has seen is kept and shared between snapshots. Each
+/// [`DijkstraResults::snapshot`] only copies the rows finished since the
+/// last one, which keeps the [`DistanceTable`] locked for as short a time
+/// as possible.
+#[derive(Debug, Clone)]
+pub struct DijkstraResults {
+    costs: Arc<Mutex<DistanceTable>>,
+    seen: BTreeMap<Node, Arc<[Option<Cost>]>>,
+}
+
+impl DijkstraResults {
+    /// Creates a new [`DijkstraResults`] handle to `costs`.
+    fn new(costs: Arc<Mutex<DistanceTable>>) -> Self {
+        return Self {costs, seen: BTreeMap::new()};
+    }
+
+    /// Get an immutable [`DijkstraSnapshot`] of the rows which have been
+    /// calculated so far. Jobs carry on while the snapshot is used and rows
+    /// finished afterwards do not show up in it.
+    pub fn snapshot(&mut self) -> Result<DijkstraSnapshot, Error> {
+        {
+            let costs = unwrapmutex!(self.costs.lock());
+            if costs.len() != self.seen.len() {
+                for (source, row) in costs.iter() {
+                    if !self.seen.contains_key(source) {
+                        self.seen.insert(*source, Arc::from(row.as_slice()));
+                    }
+                }
+            }
+        }
+        return Ok(DijkstraSnapshot {rows: self.seen.clone()});
+    }
+}
+
+/// The rows of a [`DistanceTable`] which were complete when
+/// [`DijkstraResults::snapshot`] was called.
+#[derive(Debug, Clone, Default)]
+pub struct DijkstraSnapshot {
+    rows: BTreeMap<Node, Arc<[Option<Cost>]>>,
+}
+
+impl DijkstraSnapshot {
+    /// Get the [`Cost`] to get to all destination [`Node`]s from `source`,
+    /// if its row was complete.
+    pub fn get(&self, source: Node) -> Option<&[Option<Cost>]> {
+        return self.rows.get(&source).map(|row| &**row);
+    }
+
+    /// Whether the row of `source` was complete.
+    pub fn contains(&self, source: Node) -> bool {
+        return self.rows.contains_key(&source);
+    }
+
+    /// Number of complete rows.
+    pub fn len(&self) -> usize {
+        return self.rows.len();
+    }
+
+    /// Whether no rows were complete.
+    pub fn is_empty(&self) -> bool {
+        return self.rows.is_empty();
+    }
+
+    /// Iterates over the complete rows in order of their source [`Node`].
+    pub fn iter(&self) -> impl Iterator<Item = (Node, &[Option<Cost>])> {
+        return self.rows.iter().map(|(source, row)| (*source, &**row));
+    }
+}
+
+impl Display for DijkstraSnapshot {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        return write_table(formatter, self.iter().collect(), &NodeLabels::default());
+    }
+}
+
+/// Renders the rows of `costs` as an aligned table, with a row for each
+/// calculated source [`Node`] and a column for each destination [`Node`].
+/// Unreachable [`Node`]s are shown as `∞`, and [`Node`]s with a label in
+/// `labels` are shown by it instead of their number.
+pub fn render_table(costs: &DistanceTable, labels: &NodeLabels) -> String {
+    let mut rows: Vec<(Node, &[Option<Cost>])> = costs
+        .iter()
+        .map(|(source, row)| (*source, row.as_slice()))
+        .collect();
+    rows.sort_unstable_by_key(|(source, _)| *source);
+    let mut table = String::new();
+    // Writing to a String cannot fail.
+    let _ = write_table(&mut table, rows, labels);
+    return table;
+}
+
+/// Writes `rows`, sorted by source [`Node`], as an aligned table. See
+/// [`render_table`].
+fn write_table<W: FmtWrite>(
+    output: &mut W,
+    rows: Vec<(Node, &[Option<Cost>])>,
+    labels: &NodeLabels,
+) -> FmtResult {
+    let columns = rows.iter().map(|(_, row)| row.len()).max().unwrap_or(0);
+    let mut cells: Vec<Vec<String>> = Vec::with_capacity(rows.len() + 1);
+    let mut header = vec![String::new()];
+    header.extend((0..columns).map(|node| labels.name(node)));
+    cells.push(header);
+    for (source, row) in rows {
+        let mut line = vec![labels.name(source)];
+        line.extend((0..columns).map(|node| {
+            return match row.get(node).copied().flatten() {
+                Some(cost) => cost.to_string(),
+                None => "∞".to_string(),
+            };
+        }));
+        cells.push(line);
+    }
+
+    let mut widths: Vec<usize> = vec![0; columns + 1];
+    for line in &cells {
+        for (column, cell) in line.iter().enumerate() {
+            widths[column] = widths[column].max(cell.chars().count());
+        }
+    }
+    for line in &cells {
+        write!(output, "{:<width$}", line[0], width = widths[0])?;
+        for (column, cell) in line.iter().enumerate().skip(1) {
+            write!(output, " {:>width$}", cell, width = widths[column])?;
+        }
+        writeln!(output)?;
+    }
+    return Ok(());
+}
+
+/// Which [`Node`]s can be reached from each calculated source, made with
+/// [`MtdDijkstra::reachability`]. Each row is a bitset with one bit per
+/// [`Node`], so it takes 128 times less memory than the [`Cost`]s.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ReachabilityData"))]
+pub struct Reachability {
+    nodes: Node,
+    rows: BTreeMap<Node, Vec<u64>>,
+}
+
+/// The fields of a [`Reachability`] as they are deserialized, before they
+/// are checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ReachabilityData {
+    nodes: Node,
+    rows: BTreeMap<Node, Vec<u64>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ReachabilityData> for Reachability {
+    type Error = Error;
+
+    /// Checks that every row has a bit per [`Node`], with the spare bits of
+    /// its last word cleared.
+    fn try_from(data: ReachabilityData) -> Result<Self, Error> {
+        let ReachabilityData {nodes, rows} = data;
+        let words = nodes.div_ceil(64);
+        let spare = match nodes % 64 {
+            0 => 0,
+            used => u64::MAX << used,
+        };
+        for (source, row) in &rows {
+            if row.len() != words || row.last().is_some_and(|word| word & spare != 0) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "The row of {} does not hold exactly {} bits.",
+                        source, nodes
+                    )
+                ));
+            }
+        }
+        return Ok(Self {nodes, rows});
+    }
+}
+
+impl Reachability {
+    /// Builds the [`Reachability`] of every row in `costs`, which has
+    /// `nodes` [`Node`]s in each row.
+    pub fn from_costs(nodes: Node, costs: &DistanceTable) -> Self {
+        let mut rows = BTreeMap::new();
+        for (source, row) in costs {
+            let mut bits: Vec<u64> = vec![0; nodes.div_ceil(64)];
+            for (node, cost) in row.iter().enumerate().take(nodes) {
+                if cost.is_some() {
+                    bits[node / 64] |= 1 << (node % 64);
+                }
+            }
+            rows.insert(*source, bits);
+        }
+        return Self {nodes, rows};
+    }
+
+    /// Whether `to` can be reached from `from`, or [`None`] if `from` was
+    /// never calculated.
+    pub fn is_reachable(&self, from: Node, to: Node) -> Option<bool> {
+        let row = self.rows.get(&from)?;
+        return Some(to < self.nodes && row[to / 64] & (1 << (to % 64)) != 0);
+    }
+
+    /// Iterates over the [`Node`]s which can be reached from `from` in
+    /// order, or [`None`] if `from` was never calculated.
+    pub fn reachable_from(&self, from: Node) -> Option<impl Iterator<Item = Node> + '_> {
+        let row = self.rows.get(&from)?;
+        return Some(
+            (0..self.nodes).filter(move |node| row[node / 64] & (1 << (node % 64)) != 0)
+        );
+    }
+
+    /// Number of [`Node`]s which can be reached from `from`, including
+    /// itself, or [`None`] if `from` was never calculated.
+    pub fn count_from(&self, from: Node) -> Option<usize> {
+        let row = self.rows.get(&from)?;
+        return Some(row.iter().map(|word| word.count_ones() as usize).sum());
+    }
+
+    /// Iterates over the calculated sources in order.
+    pub fn sources(&self) -> impl Iterator<Item = Node> + '_ {
+        return self.rows.keys().copied();
+    }
+
+    /// Number of [`Node`]s in each row.
+    pub fn nodes(&self) -> Node {
+        return self.nodes;
+    }
+}
@@ -0,0 +1,231 @@
+//! Module for summarising the shape of an [`AdjacencyMatrix`] before running
+//! the Dijkstra Algorithm on it.
+//! 
+//! [`AdjacencyMatrix::stats`] counts the edges, how many [`Node`]s have each
+//! in and out degree, and how the [`Cost`]s of the edges are spread out. It
+//! only reads each edge a couple of times, spread over a [`ThreadPool`], so
+//! it is a cheap check that a graph looks as expected before starting a
+//! long all-pairs run.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use super::simple::{AdjacencyMatrix, Cost, CsrGraph, JOBS_PER_THREAD, Node};
+use crate::pool::{ThreadPool, chunk::{ChunkPolicy, ranges}};
+
+/// How many edges have a [`Cost`] in each of a row of equally wide ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CostHistogram {
+    /// The lowest [`Cost`] in the first bucket.
+    pub lowest: Cost,
+    /// How many [`Cost`]s each bucket covers.
+    pub width: Cost,
+    /// Number of edges in each bucket. Bucket `i` holds the [`Cost`]s from
+    /// `lowest + i * width` up to, but not including,
+    /// `lowest + (i + 1) * width`.
+    pub counts: Vec<usize>,
+}
+
+impl CostHistogram {
+    /// The range of [`Cost`]s in `bucket`, as `(lowest, highest)` with both
+    /// ends included, or [`None`] if there is no such bucket.
+    pub fn range(&self, bucket: usize) -> Option<(Cost, Cost)> {
+        if bucket >= self.counts.len() {
+            return None;
+        }
+        let lowest = self
+            .lowest
+            .saturating_add((bucket as Cost).saturating_mul(self.width));
+        return Some((lowest, lowest.saturating_add(self.width.saturating_sub(1))));
+    }
+}
+
+/// What [`AdjacencyMatrix::stats`] found in a graph.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphStats {
+    /// Number of [`Node`]s in the graph.
+    pub nodes: usize,
+    /// Number of edges in the graph.
+    pub edges: usize,
+    /// Number of [`Node`]s with each number of edges leaving them.
+    pub out_degrees: BTreeMap<usize, usize>,
+    /// Number of [`Node`]s with each number of edges entering them.
+    pub in_degrees: BTreeMap<usize, usize>,
+    /// Average number of edges leaving a [`Node`], which is also the
+    /// average number entering one.
+    pub average_degree: f64,
+    /// The cheapest edge, if there are any edges.
+    pub min_cost: Option<Cost>,
+    /// The most expensive edge, if there are any edges.
+    pub max_cost: Option<Cost>,
+    /// How the [`Cost`]s of the edges are spread between
+    /// [`GraphStats::min_cost`] and [`GraphStats::max_cost`].
+    pub costs: CostHistogram,
+}
+
+impl GraphStats {
+    /// The largest number of edges leaving any [`Node`].
+    pub fn max_out_degree(&self) -> usize {
+        return self.out_degrees.keys().next_back().copied().unwrap_or(0);
+    }
+
+    /// The largest number of edges entering any [`Node`].
+    pub fn max_in_degree(&self) -> usize {
+        return self.in_degrees.keys().next_back().copied().unwrap_or(0);
+    }
+}
+
+impl Display for GraphStats {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        writeln!(formatter, "{} nodes, {} edges", self.nodes, self.edges)?;
+        writeln!(
+            formatter,
+            "Average degree {:.2}, largest out degree {}, largest in degree {}",
+            self.average_degree,
+            self.max_out_degree(),
+            self.max_in_degree()
+        )?;
+        if let (Some(min_cost), Some(max_cost)) = (self.min_cost, self.max_cost) {
+            writeln!(formatter, "Costs from {} to {}", min_cost, max_cost)?;
+            for bucket in 0..self.costs.counts.len() {
+                if let Some((lowest, highest)) = self.costs.range(bucket) {
+                    writeln!(
+                        formatter,
+                        "  {}..={}: {}",
+                        lowest,
+                        highest,
+                        self.costs.counts[bucket]
+                    )?;
+                }
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// What one job finds in its range of [`Node`]s.
+#[derive(Default)]
+struct Partial {
+    out_degrees: BTreeMap<usize, usize>,
+    in_degrees: HashMap<Node, usize>,
+    min_cost: Option<Cost>,
+    max_cost: Option<Cost>,
+}
+
+/// The cheaper of 2 [`Cost`]s, where [`None`] means there is no [`Cost`]
+/// yet rather than an infinite one.
+fn cheaper(a: Option<Cost>, b: Option<Cost>) -> Option<Cost> {
+    return match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        _ => a.or(b),
+    };
+}
+
+impl AdjacencyMatrix {
+    /// Counts the degrees of the [`Node`]s and the spread of the [`Cost`]s
+    /// of the edges, in parallel on `pool`. See [`GraphStats`].
+    /// 
+    /// # Parameters
+    /// 1. ```pool: &mut ThreadPool``` => The threads which read the edges.
+    /// 2. ```buckets: usize``` => Number of buckets in the [`CostHistogram`].
+    ///    Fewer are used if there are not that many different [`Cost`]s
+    ///    between the cheapest and most expensive edge.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `buckets` is `0` or a job in
+    /// `pool` fails.
+    pub fn stats(
+        &self,
+        pool: &mut ThreadPool,
+        buckets: usize,
+    ) -> Result<GraphStats, Error> {
+        if buckets == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The cost histogram needs at least 1 bucket."
+            ));
+        }
+        let total = self.total();
+        let graph = Arc::new(CsrGraph::from(self));
+        let ranges = Arc::new(ranges(total, pool.threads() * JOBS_PER_THREAD));
+
+        let shared = graph.clone();
+        let partials = pool.map(ranges.clone(), ChunkPolicy::Fixed(1), move |range| {
+            let mut partial = Partial::default();
+            for from in range.0..range.1 {
+                let adjacents = shared.get_node(from).unwrap_or(&[]);
+                *partial.out_degrees.entry(adjacents.len()).or_insert(0) += 1;
+                for adjacent in adjacents {
+                    *partial.in_degrees.entry(adjacent.node).or_insert(0) += 1;
+                    let cost = Some(adjacent.cost);
+                    partial.min_cost = cheaper(partial.min_cost, cost);
+                    partial.max_cost = partial.max_cost.max(cost);
+                }
+            }
+            return partial;
+        })?;
+
+        let mut stats = GraphStats {
+            nodes: total,
+            edges: graph.edges(),
+            ..GraphStats::default()
+        };
+        let mut in_degrees: HashMap<Node, usize> = HashMap::new();
+        for partial in partials {
+            for (degree, count) in partial.out_degrees {
+                *stats.out_degrees.entry(degree).or_insert(0) += count;
+            }
+            for (node, degree) in partial.in_degrees {
+                *in_degrees.entry(node).or_insert(0) += degree;
+            }
+            stats.min_cost = cheaper(stats.min_cost, partial.min_cost);
+            stats.max_cost = stats.max_cost.max(partial.max_cost);
+        }
+        let entered = (0..total).filter(|node| in_degrees.contains_key(node)).count();
+        if entered < total {
+            stats.in_degrees.insert(0, total - entered);
+        }
+        // Edges leading outside the graph do not give it more Nodes.
+        for (node, degree) in in_degrees {
+            if node < total {
+                *stats.in_degrees.entry(degree).or_insert(0) += 1;
+            }
+        }
+        if total > 0 {
+            stats.average_degree = stats.edges as f64 / total as f64;
+        }
+
+        let (lowest, highest) = match (stats.min_cost, stats.max_cost) {
+            (Some(lowest), Some(highest)) => (lowest, highest),
+            _ => return Ok(stats),
+        };
+        let span = highest - lowest;
+        let width = (span / buckets as Cost).saturating_add(1);
+        let used = ((span / width) as usize + 1).min(buckets);
+        let counts = pool.map(ranges, ChunkPolicy::Fixed(1), move |range| {
+            let mut counts: Vec<usize> = vec![0; used];
+            for from in range.0..range.1 {
+                for adjacent in graph.get_node(from).unwrap_or(&[]) {
+                    let bucket = ((adjacent.cost - lowest) / width) as usize;
+                    counts[bucket.min(used - 1)] += 1;
+                }
+            }
+            return counts;
+        })?;
+        let mut histogram = CostHistogram {lowest, width, counts: vec![0; used]};
+        for partial in counts {
+            for (bucket, count) in partial.into_iter().enumerate() {
+                histogram.counts[bucket] += count;
+            }
+        }
+        stats.costs = histogram;
+        return Ok(stats);
+    }
+}
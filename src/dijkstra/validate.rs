@@ -0,0 +1,153 @@
+//! Module for checking an [`AdjacencyMatrix`] for data which is probably a
+//! mistake before running the Dijkstra Algorithm on it.
+//! 
+//! Bad input usually shows up as [`None`] costs in the results with no
+//! explanation. [`AdjacencyMatrix::validate`] points at the likely causes
+//! instead: [`Node`]s with no edges at all, edges which loop back to their
+//! own [`Node`] or appear twice (which [`AdjacencyMatrix::push`] never adds,
+//! but a deserialized matrix can hold), edges leading outside the matrix,
+//! and parts of the graph which are not connected to each other.
+
+use std::{
+    cmp::Reverse,
+    collections::{HashSet, VecDeque},
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use super::simple::{AdjacencyMatrix, Node};
+
+/// What [`AdjacencyMatrix::validate`] found in a graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphReport {
+    /// Number of [`Node`]s in the graph.
+    pub nodes: usize,
+    /// Number of edges in the graph.
+    pub edges: usize,
+    /// [`Node`]s which no edge leaves or enters.
+    pub isolated: Vec<Node>,
+    /// [`Node`]s with an edge to themselves.
+    pub self_loops: Vec<Node>,
+    /// Edges which appear more than once, as `(from, to)`.
+    pub duplicate_edges: Vec<(Node, Node)>,
+    /// Edges leading to a [`Node`] outside the graph, as `(from, to)`.
+    pub dangling_edges: Vec<(Node, Node)>,
+    /// The groups of [`Node`]s which are connected when the direction of
+    /// the edges is ignored, from largest to smallest. No [`Node`] in one
+    /// group can reach a [`Node`] in another.
+    pub components: Vec<Vec<Node>>,
+}
+
+impl GraphReport {
+    /// Whether nothing suspicious was found: there are no isolated
+    /// [`Node`]s, self-loops, duplicate or dangling edges, and the graph is
+    /// in one piece.
+    pub fn is_clean(&self) -> bool {
+        return self.isolated.is_empty()
+            && self.self_loops.is_empty()
+            && self.duplicate_edges.is_empty()
+            && self.dangling_edges.is_empty()
+            && self.components.len() <= 1;
+    }
+
+    /// Number of [`Node`]s outside the largest component, which cannot be
+    /// reached from most of the graph.
+    pub fn unreachable(&self) -> usize {
+        return self.components.iter().skip(1).map(Vec::len).sum();
+    }
+}
+
+impl Display for GraphReport {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        writeln!(formatter, "{} nodes, {} edges", self.nodes, self.edges)?;
+        if self.is_clean() {
+            return writeln!(formatter, "No problems found.");
+        }
+        if !self.isolated.is_empty() {
+            writeln!(formatter, "Isolated nodes: {:?}", self.isolated)?;
+        }
+        if !self.self_loops.is_empty() {
+            writeln!(formatter, "Self-loops on: {:?}", self.self_loops)?;
+        }
+        if !self.duplicate_edges.is_empty() {
+            writeln!(formatter, "Duplicate edges: {:?}", self.duplicate_edges)?;
+        }
+        if !self.dangling_edges.is_empty() {
+            writeln!(formatter, "Dangling edges: {:?}", self.dangling_edges)?;
+        }
+        if self.components.len() > 1 {
+            writeln!(
+                formatter,
+                "{} components, {} nodes outside the largest one",
+                self.components.len(),
+                self.unreachable()
+            )?;
+        }
+        return Ok(());
+    }
+}
+
+impl AdjacencyMatrix {
+    /// Checks the graph for isolated [`Node`]s, self-loops, duplicate and
+    /// dangling edges, and parts which are not connected to each other.
+    /// See [`GraphReport`].
+    pub fn validate(&self) -> GraphReport {
+        let total = self.total();
+        let mut report = GraphReport {
+            nodes: total,
+            edges: self.edges(),
+            ..GraphReport::default()
+        };
+
+        // Edges in both directions, so the components ignore direction.
+        let mut neighbours: Vec<Vec<Node>> = vec![Vec::new(); total];
+        for from in 0..total {
+            let mut seen: HashSet<Node> = HashSet::new();
+            for adjacent in self.get_node(from).into_iter().flatten() {
+                let to = adjacent.node;
+                if to >= total {
+                    report.dangling_edges.push((from, to));
+                    continue;
+                }
+                if to == from && !report.self_loops.contains(&from) {
+                    report.self_loops.push(from);
+                }
+                if !seen.insert(to) {
+                    report.duplicate_edges.push((from, to));
+                }
+                neighbours[from].push(to);
+                neighbours[to].push(from);
+            }
+        }
+        report.duplicate_edges.sort_unstable();
+        report.duplicate_edges.dedup();
+        report.isolated = (0..total)
+            .filter(|node| neighbours[*node].iter().all(|other| other == node))
+            .collect();
+
+        let mut visited = vec![false; total];
+        let mut queue: VecDeque<Node> = VecDeque::new();
+        for start in 0..total {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            queue.push_back(start);
+            let mut component: Vec<Node> = Vec::new();
+            while let Some(node) = queue.pop_front() {
+                component.push(node);
+                for other in &neighbours[node] {
+                    if !visited[*other] {
+                        visited[*other] = true;
+                        queue.push_back(*other);
+                    }
+                }
+            }
+            component.sort_unstable();
+            report.components.push(component);
+        }
+        // Stable, so components of the same size stay in order.
+        report.components.sort_by_key(|component| Reverse(component.len()));
+        return report;
+    }
+}
@@ -0,0 +1,53 @@
+//! Module for estimating how expensive running an algorithm will be before
+//! any threads are started, so that obviously infeasible requests can be
+//! refused or re-planned up front.
+//! 
+//! Estimates are rough upper bounds from the sizes of the inputs, not
+//! measurements, and are only meant to be compared in orders of magnitude.
+
+use std::ops::Add;
+
+/// Predicted cost of running an algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Estimate {
+    /// Bytes of memory the algorithm will allocate while running, including
+    /// its result.
+    pub memory: u128,
+    /// Number of basic steps the algorithm will take, such as relaxing an
+    /// edge or moving an item. Only useful for comparing estimates with each
+    /// other.
+    pub work: u128,
+}
+
+impl Estimate {
+    /// Creates a new [`Estimate`].
+    pub fn new(memory: u128, work: u128) -> Self {
+        return Self {memory, work};
+    }
+
+    /// Whether the estimated memory usage is within `budget` bytes.
+    pub fn fits_in(&self, budget: u128) -> bool {
+        return self.memory <= budget;
+    }
+}
+
+impl Add for Estimate {
+    type Output = Self;
+
+    /// Adds up the estimates of 2 steps which run one after another.
+    fn add(self, other: Self) -> Self {
+        return Self {
+            memory: self.memory.saturating_add(other.memory),
+            work: self.work.saturating_add(other.work),
+        };
+    }
+}
+
+/// Rounded up base 2 logarithm of `value`, and at least 1, for `O(log n)`
+/// terms in estimates.
+pub fn log2_ceil(value: u128) -> u128 {
+    if value <= 2 {
+        return 1;
+    }
+    return (u128::BITS - (value - 1).leading_zeros()) as u128;
+}
@@ -0,0 +1,154 @@
+//! Module for the Floyd-Warshall Algorithm, which finds the cheapest route
+//! between every pair of [`Node`]s of an [`AdjacencyMatrix`] at once.
+//! 
+//! The algorithm lets the routes go through one more [`Node`] `k` at a time,
+//! checking for every pair whether going through `k` is cheaper. While `k`
+//! is being tried, only row `k` of the distance matrix is read by every
+//! other row and row `k` itself does not change, so the rows are updated in
+//! parallel on a [`ThreadPool`] with a copy of row `k`. It takes `n³` steps
+//! whatever the number of edges, so it pays off for dense graphs, where
+//! running the Dijkstra Algorithm from every [`Node`] costs about as much
+//! and is harder to spread evenly over the threads.
+
+use std::{
+    io::Error,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, Cost, DistanceTable, Node, Path, check_node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    unwrapmutex,
+};
+
+/// One row of the distance matrix, holding the [`Cost`] of reaching every
+/// [`Node`] from one source and the first [`Node`] after the source on the
+/// cheapest route there.
+struct Row {
+    costs: Vec<Option<Cost>>,
+    next: Vec<Option<Node>>,
+}
+
+/// The cheapest route between every pair of [`Node`]s, as found by
+/// [`all_pairs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllPairs {
+    /// `costs[from][to]` is the [`Cost`] of the cheapest route from `from`
+    /// to `to`, or [`None`] if `to` cannot be reached.
+    pub costs: Vec<Vec<Option<Cost>>>,
+    /// `next[from][to]` is the [`Node`] visited right after `from` on the
+    /// cheapest route to `to`.
+    pub next: Vec<Vec<Option<Node>>>,
+}
+
+impl AllPairs {
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.costs.len();
+    }
+
+    /// Get the [`Cost`] of the cheapest route from `from` to `to`, or
+    /// [`None`] if there is no such route.
+    pub fn get(&self, from: Node, to: Node) -> Option<Cost> {
+        return self.costs.get(from)?.get(to).copied().flatten();
+    }
+
+    /// Get the [`Cost`] of reaching every [`Node`] from `from`, or [`None`]
+    /// if `from` is not in the graph.
+    pub fn row(&self, from: Node) -> Option<&[Option<Cost>]> {
+        return self.costs.get(from).map(|row| row.as_slice());
+    }
+
+    /// Get the cheapest route from `from` to `to`, or [`None`] if there is
+    /// no such route.
+    pub fn path(&self, from: Node, to: Node) -> Option<Path> {
+        let cost = self.get(from, to)?;
+        let mut nodes = vec![from];
+        let mut node = from;
+        while node != to {
+            node = self.next[node][to]?;
+            nodes.push(node);
+        }
+        return Some(Path {nodes, cost});
+    }
+
+    /// Converts the distance matrix into a [`DistanceTable`] like the one
+    /// calculated by [`crate::dijkstra::simple::MtdDijkstra`].
+    pub fn into_table(self) -> DistanceTable {
+        return self.costs.into_iter().enumerate().collect();
+    }
+}
+
+/// Calculates the cheapest route between every pair of [`Node`]s in
+/// `matrix`, updating the rows of the distance matrix in parallel on `pool`.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which update the rows.
+/// 2. ```matrix: &AdjacencyMatrix``` => The graph.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `matrix` has an edge to a [`Node`]
+/// outside of it or a job on the [`ThreadPool`] fails.
+pub fn all_pairs(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+) -> Result<AllPairs, Error> {
+    let total = matrix.total();
+    let mut rows: Vec<Mutex<Row>> = Vec::with_capacity(total);
+    for from in 0..total {
+        let mut row = Row {costs: vec![None; total], next: vec![None; total]};
+        row.costs[from] = Some(0);
+        row.next[from] = Some(from);
+        for adjacent in matrix.get_node(from).map_or(&[][..], |edges| edges) {
+            check_node(adjacent.node, total)?;
+            if adjacent.node != from
+                && row.costs[adjacent.node].is_none_or(|cost| adjacent.cost < cost)
+            {
+                row.costs[adjacent.node] = Some(adjacent.cost);
+                row.next[adjacent.node] = Some(adjacent.node);
+            }
+        }
+        rows.push(Mutex::new(row));
+    }
+    let rows = Arc::new(rows);
+
+    for through in 0..total {
+        let via = Arc::new(unwrapmutex!(rows[through].lock()).costs.clone());
+        pool.for_each_chunk(rows.clone(), ChunkPolicy::Auto, move |chunk| {
+            for row in chunk {
+                let mut row = unwrapmutex!(row.lock());
+                let first = match row.costs[through] {
+                    Some(first) => first,
+                    None => continue,
+                };
+                let hop = row.next[through];
+                for to in 0..via.len() {
+                    let cost = match via[to].map(|rest| first.saturating_add(rest)) {
+                        Some(cost) => cost,
+                        None => continue,
+                    };
+                    if row.costs[to].is_none_or(|before| cost < before) {
+                        row.costs[to] = Some(cost);
+                        row.next[to] = hop;
+                    }
+                }
+            }
+            return Ok(());
+        })?;
+    }
+
+    let mut result = AllPairs {
+        costs: Vec::with_capacity(total),
+        next: Vec::with_capacity(total),
+    };
+    // The workers may not have dropped their handles to the rows yet, so
+    // the rows are moved out from behind their locks instead.
+    for row in rows.iter() {
+        let mut row = unwrapmutex!(row.lock());
+        result.costs.push(std::mem::take(&mut row.costs));
+        result.next.push(std::mem::take(&mut row.next));
+    }
+    return Ok(result);
+}
@@ -0,0 +1,217 @@
+//! Module for searching the game trees of 2 player games with minimax, which
+//! picks the move leading to the best score for the player to move if both
+//! players keep playing their best moves.
+//! 
+//! A game is described by implementing [`GameState`], which gives the moves
+//! from a state, the state each move leads to, and a score of the state for
+//! the player to move. Scores are negamax scores, so a state good for one
+//! player has the negated score for the other.
+//! 
+//! [`minimax`] searches with alpha-beta pruning, skipping moves which
+//! cannot change the result, and deepens the search one level at a time
+//! until the deepest level or the time budget is reached. Each level is
+//! searched with Young Brothers Wait at the root: the first move, which is
+//! the best of the previous level, is searched on the calling thread to get
+//! a score to beat, and the other moves are then searched in parallel on a
+//! [`ThreadPool`]. The score to beat is shared in an atomic, so every job
+//! prunes with the best score found by any of them so far.
+
+use std::{
+    cmp::Reverse,
+    io::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicI64, Ordering::SeqCst},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::pool::{ThreadPool, chunk::ChunkPolicy};
+
+/// The highest score. Scores given by [`GameState::score`] are kept
+/// between `-INFINITY` and `INFINITY`, so they can always be negated.
+pub const INFINITY: i64 = i64::MAX;
+/// The number of states searched between checks of the time budget.
+const CHECK_INTERVAL: u64 = 1024;
+
+/// A state of a 2 player game, with one of the players to move.
+pub trait GameState: Clone + Send + Sync + 'static {
+    /// A move from one state to another.
+    type Move: Clone + Send + Sync + 'static;
+
+    /// The moves the player to move can make, in the order they should be
+    /// searched. The game is over if there are none.
+    fn moves(&self) -> Vec<Self::Move>;
+
+    /// The state `played` leads to, with the other player to move.
+    fn play(&self, played: &Self::Move) -> Self;
+
+    /// The score of this state for the player to move, which is higher the
+    /// better it is for them. It is used once the game is over or the
+    /// search is deep enough.
+    fn score(&self) -> i64;
+}
+
+/// The move found by [`minimax`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Searched<M> {
+    /// The best move for the player to move, or [`None`] if the game is
+    /// over.
+    pub best: Option<M>,
+    /// The score [`Searched::best`] leads to for the player to move.
+    pub score: i64,
+    /// The deepest level searched in full.
+    pub depth: usize,
+    /// The number of states searched, including those of a level which was
+    /// not finished in time.
+    pub nodes: u64,
+}
+
+/// Counts the states searched and checks the time budget.
+struct Clock {
+    deadline: Option<Instant>,
+    nodes: u64,
+}
+
+impl Clock {
+    /// Counts a state. Returns [`None`] if the time budget has run out.
+    fn tick(&mut self) -> Option<()> {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) {
+            if let Some(deadline) = self.deadline {
+                if Instant::now() >= deadline {
+                    return None;
+                }
+            }
+        }
+        return Some(());
+    }
+}
+
+/// Searches `state` to `depth` levels with alpha-beta pruning, returning
+/// its score for the player to move, or [`None`] if the time budget runs
+/// out. If `shared` is given, it holds the score to beat at the root, one
+/// level up, and `beta` is lowered whenever it rises.
+fn negamax<S: GameState>(
+    state: &S,
+    depth: usize,
+    mut alpha: i64,
+    mut beta: i64,
+    clock: &mut Clock,
+    shared: Option<&AtomicI64>,
+) -> Option<i64> {
+    clock.tick()?;
+    let moves = if depth == 0 { Vec::new() } else { state.moves() };
+    if moves.is_empty() {
+        return Some(state.score().max(-INFINITY));
+    }
+    let mut best = -INFINITY;
+    for played in moves {
+        let next = state.play(&played);
+        let score = -negamax(&next, depth - 1, -beta, -alpha, clock, None)?;
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if let Some(shared) = shared {
+            beta = beta.min(-shared.load(SeqCst));
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    return Some(best);
+}
+
+/// Searches every move of `moves` from `state` to `depth` levels, the first
+/// on the calling thread and the others in parallel. Returns the score of
+/// each move and whether that score is exact rather than an upper bound,
+/// or [`None`] if the time budget runs out.
+fn search_root<S: GameState>(
+    pool: &mut ThreadPool,
+    state: &S,
+    moves: &[S::Move],
+    depth: usize,
+    deadline: Option<Instant>,
+    nodes: &mut u64,
+) -> Result<Option<Vec<(i64, bool)>>, Error> {
+    let mut clock = Clock {deadline, nodes: 0};
+    let next = state.play(&moves[0]);
+    let first = negamax(&next, depth - 1, -INFINITY, INFINITY, &mut clock, None);
+    *nodes += clock.nodes;
+    let Some(first) = first.map(|score| -score) else {
+        return Ok(None);
+    };
+
+    let shared = Arc::new(AtomicI64::new(first));
+    let children: Vec<S> = moves[1..].iter().map(|played| state.play(played)).collect();
+    let searched = pool.map(Arc::new(children), ChunkPolicy::Fixed(1), move |child| {
+        let mut clock = Clock {deadline, nodes: 0};
+        let beta = -shared.load(SeqCst);
+        let score = negamax(
+            child, depth - 1, -INFINITY, beta, &mut clock, Some(&shared)
+        );
+        // Only a score above every score to beat used in pruning is exact.
+        let scored = score.map(|score| {
+            return (-score, -score > shared.fetch_max(-score, SeqCst));
+        });
+        return (scored, clock.nodes);
+    })?;
+
+    let mut scores: Vec<(i64, bool)> = vec![(first, true)];
+    let mut finished = true;
+    for (scored, counted) in searched {
+        *nodes += counted;
+        match scored {
+            Some(scored) => scores.push(scored),
+            None => finished = false,
+        }
+    }
+    return Ok(if finished { Some(scores) } else { None });
+}
+
+/// Finds the best move from `state` with minimax. See the module
+/// documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads searching the moves after
+///    the first one.
+/// 2. ```state: &S``` => The state to move from.
+/// 3. ```max_depth: usize``` => The deepest level to search, in moves.
+/// 4. ```budget: Duration``` => How long the search may take. The first
+///    level is always searched in full, and the result of the deepest level
+///    finished in time is returned.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn minimax<S: GameState>(
+    pool: &mut ThreadPool,
+    state: &S,
+    max_depth: usize,
+    budget: Duration,
+) -> Result<Searched<S::Move>, Error> {
+    let deadline = Instant::now().checked_add(budget);
+    let mut moves = state.moves();
+    if moves.is_empty() || max_depth == 0 {
+        let score = state.score().max(-INFINITY);
+        return Ok(Searched {best: None, score, depth: 0, nodes: 1});
+    }
+
+    let mut searched = Searched {best: None, score: -INFINITY, depth: 0, nodes: 1};
+    for depth in 1..=max_depth {
+        let limit = if depth == 1 { None } else { deadline };
+        let nodes = &mut searched.nodes;
+        let scores = search_root(pool, state, &moves, depth, limit, nodes)?;
+        let Some(scores) = scores else {
+            break;
+        };
+        // The best exact score goes first, ties going to the earlier move.
+        let mut order: Vec<usize> = (0..moves.len()).collect();
+        order.sort_by_key(|index| (!scores[*index].1, Reverse(scores[*index].0)));
+        moves = order.iter().map(|index| moves[*index].clone()).collect();
+        searched.best = Some(moves[0].clone());
+        searched.score = scores[order[0]].0;
+        searched.depth = depth;
+    }
+    return Ok(searched);
+}
@@ -0,0 +1,404 @@
+//! Module for genetic algorithms, which look for a genome with the highest
+//! fitness by breeding a population of genomes over many generations.
+//! 
+//! Each generation, the [`ELITES`] fittest genomes are kept as they are, and
+//! the rest of the population is replaced by children. Each child is bred
+//! by crossing over 2 parents, each the fittest of [`TOURNAMENT_SIZE`]
+//! genomes picked at random, and is then mutated with the mutation rate.
+//! [`MtdGa`] is generic over the type of the genomes: the fitness of a
+//! genome, crossing over 2 genomes and mutating a genome are given as
+//! closures.
+//! 
+//! The population is split into islands which breed on their own, one job
+//! each on a [`ThreadPool`], so the fitness of the children of every island
+//! is worked out in parallel. Each island draws from its own random stream.
+//! Every few generations, the islands stop and each sends copies of its
+//! fittest genomes to the next island in a ring, where they replace the
+//! least fit genomes. This migration spreads good genomes around while the
+//! islands still search different parts of the space between migrations.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+};
+
+/// The number of generations bred unless another is set with
+/// [`MtdGa::set_generations`].
+pub const DEFAULT_GENERATIONS: usize = 100;
+/// The number of generations between migrations unless another is set with
+/// [`MtdGa::set_migration_interval`].
+pub const DEFAULT_MIGRATION_INTERVAL: usize = 10;
+/// The number of genomes each island sends at each migration unless another
+/// is set with [`MtdGa::set_migrants`].
+pub const DEFAULT_MIGRANTS: usize = 2;
+/// The chance of mutating each child unless another is set with
+/// [`MtdGa::set_mutation_rate`].
+pub const DEFAULT_MUTATION_RATE: f64 = 0.2;
+/// The number of genomes picked at random to choose each parent from.
+pub const TOURNAMENT_SIZE: usize = 3;
+/// The number of the fittest genomes of each island kept as they are from
+/// one generation to the next.
+pub const ELITES: usize = 1;
+
+/// Closure giving the fitness of a genome.
+type Fitness<G> = Arc<dyn Fn(&G) -> f64 + Send + Sync + 'static>;
+/// Closure breeding a child from 2 parents.
+type Crossover<G> = Arc<dyn Fn(&G, &G, &mut Rng) -> G + Send + Sync + 'static>;
+/// Closure making a random change to a genome.
+type Mutation<G> = Arc<dyn Fn(&G, &mut Rng) -> G + Send + Sync + 'static>;
+
+/// The fittest genome found by [`MtdGa::run`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Evolved<G> {
+    /// The genome with the highest fitness found on any island.
+    pub genome: G,
+    /// The fitness of [`Evolved::genome`].
+    pub fitness: f64,
+    /// The highest fitness found after each generation, starting with the
+    /// population given.
+    pub history: Vec<f64>,
+}
+
+/// The closures and settings shared by every island.
+struct Breeding<G> {
+    fitness: Fitness<G>,
+    crossover: Crossover<G>,
+    mutation: Mutation<G>,
+    mutation_rate: f64,
+}
+
+impl<G> Breeding<G> {
+    /// Get the fitness of `genome`, with a fitness which is not a number
+    /// counting as the lowest.
+    fn evaluate(&self, genome: &G) -> f64 {
+        let fitness = (self.fitness)(genome);
+        return if fitness.is_nan() { f64::NEG_INFINITY } else { fitness };
+    }
+}
+
+/// One island of genomes with their fitness, fittest first.
+#[derive(Clone)]
+struct Island<G> {
+    members: Vec<(G, f64)>,
+    rng: Rng,
+}
+
+impl<G: Clone> Island<G> {
+    /// Sorts the members fittest first, keeping the order of ties.
+    fn sort(&mut self) {
+        self.members.sort_by(|a, b| b.1.total_cmp(&a.1));
+    }
+
+    /// Picks the fittest of [`TOURNAMENT_SIZE`] members picked at random.
+    fn tournament(&mut self) -> usize {
+        return (0..TOURNAMENT_SIZE)
+            .map(|_| self.rng.below(self.members.len()))
+            .min()
+            .unwrap_or_default();
+    }
+
+    /// Breeds a copy of the island for `generations` generations. Returns
+    /// the island after them and its highest fitness after each.
+    fn breed(&self, breeding: &Breeding<G>, generations: usize) -> (Self, Vec<f64>) {
+        let mut island = self.clone();
+        let mut history: Vec<f64> = Vec::with_capacity(generations);
+        let size = island.members.len();
+        for _ in 0..generations {
+            let mut next: Vec<(G, f64)> = island.members[..ELITES.min(size)].to_vec();
+            while next.len() < size {
+                let (first, second) = (island.tournament(), island.tournament());
+                let mut child = (breeding.crossover)(
+                    &island.members[first].0,
+                    &island.members[second].0,
+                    &mut island.rng,
+                );
+                if island.rng.chance(breeding.mutation_rate) {
+                    child = (breeding.mutation)(&child, &mut island.rng);
+                }
+                let fitness = breeding.evaluate(&child);
+                next.push((child, fitness));
+            }
+            island.members = next;
+            island.sort();
+            history.push(island.members[0].1);
+        }
+        return (island, history);
+    }
+}
+
+/// This `struct` breeds genomes of type `G` on several islands using
+/// multiple threads. See the module documentation.
+pub struct MtdGa<G> {
+    pool: ThreadPool,
+    fitness: Fitness<G>,
+    crossover: Crossover<G>,
+    mutation: Mutation<G>,
+    islands: usize,
+    generations: usize,
+    migration_interval: usize,
+    migrants: usize,
+    mutation_rate: f64,
+}
+
+impl<G> MtdGa<G>
+where
+    G: Clone + Send + Sync + 'static,
+{
+    /// Creates a new [`MtdGa`] instance with one island per thread,
+    /// [`DEFAULT_GENERATIONS`], [`DEFAULT_MIGRATION_INTERVAL`],
+    /// [`DEFAULT_MIGRANTS`] and [`DEFAULT_MUTATION_RATE`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```fitness: F``` => Gives the fitness of a genome, which is to be
+    ///    as high as possible. A fitness which is not a number counts as the
+    ///    lowest.
+    /// 3. ```crossover: C``` => Breeds a child from 2 parents, drawing from
+    ///    the given [`Rng`]. The parents may be the same genome.
+    /// 4. ```mutation: M``` => Makes a random change to a genome, drawing
+    ///    from the given [`Rng`].
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new<F, C, M>(
+        threads: usize,
+        fitness: F,
+        crossover: C,
+        mutation: M,
+    ) -> Result<Self, Error>
+    where
+        F: Fn(&G) -> f64 + Send + Sync + 'static,
+        C: Fn(&G, &G, &mut Rng) -> G + Send + Sync + 'static,
+        M: Fn(&G, &mut Rng) -> G + Send + Sync + 'static,
+    {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {
+            islands: pool.threads(),
+            pool,
+            fitness: Arc::new(fitness),
+            crossover: Arc::new(crossover),
+            mutation: Arc::new(mutation),
+            generations: DEFAULT_GENERATIONS,
+            migration_interval: DEFAULT_MIGRATION_INTERVAL,
+            migrants: DEFAULT_MIGRANTS,
+            mutation_rate: DEFAULT_MUTATION_RATE,
+        });
+    }
+
+    /// Get the number of islands the population is split into.
+    pub fn islands(&self) -> usize {
+        return self.islands;
+    }
+
+    /// Sets the number of islands the population is split into.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `islands` is `0`, and the number
+    /// of islands is left as it was.
+    pub fn set_islands(&mut self, islands: usize) -> Result<(), Error> {
+        if islands == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "At least 1 island is needed."
+            ));
+        }
+        self.islands = islands;
+        return Ok(());
+    }
+
+    /// Get the number of generations bred.
+    pub fn generations(&self) -> usize {
+        return self.generations;
+    }
+
+    /// Sets the number of generations bred.
+    pub fn set_generations(&mut self, generations: usize) {
+        self.generations = generations;
+    }
+
+    /// Get the number of generations between migrations.
+    pub fn migration_interval(&self) -> usize {
+        return self.migration_interval;
+    }
+
+    /// Sets the number of generations between migrations.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `migration_interval` is `0`, and
+    /// the interval is left as it was.
+    pub fn set_migration_interval(
+        &mut self,
+        migration_interval: usize,
+    ) -> Result<(), Error> {
+        if migration_interval == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The migration interval must be at least 1 generation."
+            ));
+        }
+        self.migration_interval = migration_interval;
+        return Ok(());
+    }
+
+    /// Get the number of genomes each island sends at each migration.
+    pub fn migrants(&self) -> usize {
+        return self.migrants;
+    }
+
+    /// Sets the number of genomes each island sends at each migration. With
+    /// `0`, the islands never share genomes. No island sends more genomes
+    /// than it has.
+    pub fn set_migrants(&mut self, migrants: usize) {
+        self.migrants = migrants;
+    }
+
+    /// Get the chance of mutating each child.
+    pub fn mutation_rate(&self) -> f64 {
+        return self.mutation_rate;
+    }
+
+    /// Sets the chance of mutating each child.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `mutation_rate` is not between
+    /// `0` and `1`, and the mutation rate is left as it was.
+    pub fn set_mutation_rate(&mut self, mutation_rate: f64) -> Result<(), Error> {
+        if !(0.0..=1.0).contains(&mutation_rate) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The mutation rate {} is not in [0, 1].", mutation_rate)
+            ));
+        }
+        self.mutation_rate = mutation_rate;
+        return Ok(());
+    }
+
+    /// Breeds `population` and returns the fittest genome found. See the
+    /// module documentation.
+    /// 
+    /// # Parameters
+    /// 1. ```population: Vec<G>``` => The first generation. It is dealt out
+    ///    to the islands in turn, and each island keeps the number of
+    ///    genomes it was dealt.
+    /// 2. ```seed: u64``` => Seed for the random streams of the islands. The
+    ///    same seed always gives the same result.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `population` has fewer genomes
+    /// than there are islands, or if a job on the [`ThreadPool`] fails.
+    pub fn run(&mut self, population: Vec<G>, seed: u64) -> Result<Evolved<G>, Error> {
+        if population.len() < self.islands {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "A population of {} cannot be split into {} islands.",
+                    population.len(), self.islands
+                )
+            ));
+        }
+        let breeding = Arc::new(Breeding {
+            fitness: self.fitness.clone(),
+            crossover: self.crossover.clone(),
+            mutation: self.mutation.clone(),
+            mutation_rate: self.mutation_rate,
+        });
+        let mut dealt: Vec<Vec<G>> = vec![Vec::new(); self.islands];
+        for (index, genome) in population.into_iter().enumerate() {
+            dealt[index % self.islands].push(genome);
+        }
+        let shared = breeding.clone();
+        let mut islands: Vec<Island<G>> = self
+            .pool
+            .map(Arc::new(dealt), ChunkPolicy::Fixed(1), move |genomes| {
+                return genomes
+                    .iter()
+                    .map(|genome| (genome.clone(), shared.evaluate(genome)))
+                    .collect::<Vec<(G, f64)>>();
+            })?
+            .into_iter()
+            .enumerate()
+            .map(|(index, members)| {
+                let mut island = Island {members, rng: Rng::stream(seed, index as u64)};
+                island.sort();
+                return island;
+            })
+            .collect();
+
+        let mut history: Vec<f64> = vec![best(&islands).1];
+        let mut done: usize = 0;
+        while done < self.generations {
+            let generations = self.migration_interval.min(self.generations - done);
+            let shared = breeding.clone();
+            let policy = ChunkPolicy::Fixed(1);
+            let bred = self.pool.map(Arc::new(islands), policy, move |island| {
+                return island.breed(&shared, generations);
+            })?;
+            done += generations;
+
+            let mut fittest: Vec<f64> = vec![f64::NEG_INFINITY; generations];
+            islands = Vec::with_capacity(bred.len());
+            for (island, record) in bred {
+                for (highest, fitness) in fittest.iter_mut().zip(record) {
+                    *highest = highest.max(fitness);
+                }
+                islands.push(island);
+            }
+            history.extend(fittest);
+            if done < self.generations {
+                migrate(&mut islands, self.migrants);
+            }
+        }
+
+        let (island, _) = best(&islands);
+        let (genome, fitness) = islands[island].members[0].clone();
+        return Ok(Evolved {genome, fitness, history});
+    }
+}
+
+/// Get the island holding the fittest genome, which is the first on that
+/// island, and its fitness, ties going to the first island.
+fn best<G>(islands: &[Island<G>]) -> (usize, f64) {
+    let mut best: (usize, f64) = (0, f64::NEG_INFINITY);
+    for (index, island) in islands.iter().enumerate() {
+        if let Some((_, fitness)) = island.members.first() {
+            if *fitness > best.1 {
+                best = (index, *fitness);
+            }
+        }
+    }
+    return best;
+}
+
+/// Sends copies of the `migrants` fittest genomes of each island to the
+/// next island in a ring, replacing its least fit genomes.
+fn migrate<G: Clone>(islands: &mut [Island<G>], migrants: usize) {
+    if islands.len() < 2 {
+        return;
+    }
+    let leaving: Vec<Vec<(G, f64)>> = islands
+        .iter()
+        .map(|island| island.members[..migrants.min(island.members.len())].to_vec())
+        .collect();
+    let count = islands.len();
+    for (index, mut arriving) in leaving.into_iter().enumerate() {
+        let island = &mut islands[(index + 1) % count];
+        arriving.truncate(island.members.len());
+        let kept = island.members.len().saturating_sub(arriving.len());
+        island.members.truncate(kept);
+        island.members.extend(arriving);
+        island.sort();
+    }
+}
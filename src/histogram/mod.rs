@@ -0,0 +1,203 @@
+//! Module for counting how many items of a large list fall into each bin
+//! of a histogram.
+//! 
+//! The list is cut into a piece per thread of a [`ThreadPool`], and each
+//! job counts its piece into a histogram of its own, so no counts are
+//! shared while counting. The histograms of the jobs are added together
+//! once every job is done.
+//! 
+//! [`build`] counts numbers into [`Bins`] of the same width, using
+//! [`crate::kernels::count_bins`], and [`build_by_key`] counts items by a
+//! key taken from each item, such as a category.
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    kernels,
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+};
+
+/// Bins of the same width next to one another, the first starting at
+/// [`Bins::start`]. Each bin holds the values from its start up to but not
+/// including its end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bins {
+    start: f64,
+    width: f64,
+    count: usize,
+}
+
+impl Bins {
+    /// Creates `count` [`Bins`] of width `width`, the first starting at
+    /// `start`.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `start` is not finite, `width`
+    /// is not a positive finite number, or `count` is 0.
+    pub fn new(start: f64, width: f64, count: usize) -> Result<Self, Error> {
+        if !start.is_finite() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The start {} of the bins is not finite.", start)
+            ));
+        }
+        if !width.is_finite() || width <= 0.0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The width {} is not a positive number.", width)
+            ));
+        }
+        if count == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "There must be at least 1 bin."
+            ));
+        }
+        return Ok(Self {start, width, count});
+    }
+
+    /// Creates `count` [`Bins`] splitting the values from `low` up to but
+    /// not including `high` evenly.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `low` and `high` are not finite,
+    /// `high` is not above `low`, or `count` is 0.
+    pub fn spanning(low: f64, high: f64, count: usize) -> Result<Self, Error> {
+        if !low.is_finite() || !high.is_finite() || high <= low {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The bins cannot span from {} to {}.", low, high)
+            ));
+        }
+        return Self::new(low, (high - low) / count.max(1) as f64, count);
+    }
+
+    /// Get where the first bin starts.
+    pub fn start(&self) -> f64 {
+        return self.start;
+    }
+
+    /// Get the width of every bin.
+    pub fn width(&self) -> f64 {
+        return self.width;
+    }
+
+    /// Get the number of bins.
+    pub fn count(&self) -> usize {
+        return self.count;
+    }
+}
+
+/// The counts found by [`build`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Histogram {
+    /// Where the first bin starts.
+    pub start: f64,
+    /// The width of every bin.
+    pub width: f64,
+    /// The number of values in each bin.
+    pub counts: Vec<usize>,
+    /// The number of values outside of every bin, including NaN.
+    pub outside: usize,
+}
+
+impl Histogram {
+    /// Get where bin `bin` starts and ends.
+    pub fn bounds(&self, bin: usize) -> (f64, f64) {
+        let low = self.start + self.width * bin as f64;
+        return (low, low + self.width);
+    }
+
+    /// Get the number of values counted into any of the bins.
+    pub fn total(&self) -> usize {
+        return self.counts.iter().sum();
+    }
+}
+
+/// Counts how many of `data` fall into each of `bins` in parallel on
+/// `pool`. See the module documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which do the counting.
+/// 2. ```data: Arc<Vec<f64>>``` => The values to count, which are shared
+///    with the jobs rather than copied.
+/// 3. ```bins: Bins``` => The bins to count the values into.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn build(
+    pool: &mut ThreadPool,
+    data: Arc<Vec<f64>>,
+    bins: Bins,
+) -> Result<Histogram, Error> {
+    let len = data.len();
+    let pieces = ranges(len, pool.threads());
+    let local = pool.map(Arc::new(pieces), ChunkPolicy::Fixed(1), move |piece| {
+        let (first, last) = *piece;
+        let mut counts: Vec<usize> = vec![0; bins.count];
+        kernels::count_bins(&data[first..last], bins.start, bins.width, &mut counts);
+        return counts;
+    })?;
+
+    let mut counts: Vec<usize> = vec![0; bins.count];
+    for piece in local {
+        for (count, counted) in counts.iter_mut().zip(piece) {
+            *count += counted;
+        }
+    }
+    let outside = len - counts.iter().sum::<usize>();
+    return Ok(Histogram {start: bins.start, width: bins.width, counts, outside});
+}
+
+/// Counts how many of `data` have each key given by `key` in parallel on
+/// `pool`, such as the number of items in each category. Keys which no
+/// item has are left out.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which do the counting.
+/// 2. ```data: Arc<Vec<T>>``` => The items to count, which are shared with
+///    the jobs rather than copied.
+/// 3. ```key: F``` => The key of an item.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn build_by_key<T, K, F>(
+    pool: &mut ThreadPool,
+    data: Arc<Vec<T>>,
+    key: F,
+) -> Result<HashMap<K, usize>, Error>
+where
+    T: Send + Sync + 'static,
+    K: Eq + Hash + Send + 'static,
+    F: Fn(&T) -> K + Send + Sync + 'static,
+{
+    let pieces = ranges(data.len(), pool.threads());
+    let local = pool.map(Arc::new(pieces), ChunkPolicy::Fixed(1), move |piece| {
+        let (first, last) = *piece;
+        let mut counts: HashMap<K, usize> = HashMap::new();
+        for item in &data[first..last] {
+            *counts.entry(key(item)).or_insert(0) += 1;
+        }
+        return counts;
+    })?;
+
+    let mut local = local.into_iter();
+    let mut counts = local.next().unwrap_or_default();
+    for piece in local {
+        for (found, counted) in piece {
+            *counts.entry(found).or_insert(0) += counted;
+        }
+    }
+    return Ok(counts);
+}
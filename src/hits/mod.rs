@@ -0,0 +1,234 @@
+//! Module for HITS (Hyperlink-Induced Topic Search), which gives every
+//! [`Node`] of a graph 2 scores: how good a hub it is, by pointing at good
+//! authorities, and how good an authority it is, by being pointed at by good
+//! hubs.
+//! 
+//! Where [`crate::pagerank`] gives a single rank, HITS tells apart the
+//! [`Node`]s which collect links, such as directories, from those which are
+//! linked to. [`MtdHits`] finds the scores by iteration: every [`Node`]
+//! starts with the same scores, and each iteration sets the authority of
+//! every [`Node`] to the sum of the hub scores of the [`Node`]s with an edge
+//! into it, then the hub score of every [`Node`] to the sum of the new
+//! authorities of the [`Node`]s its edges lead to. Both are then scaled so
+//! that their squares add up to `1`. The [`Node`]s are split into ranges
+//! between the workers of a [`ThreadPool`], and each worker collects the
+//! scores of its own [`Node`]s, so no locking is needed. The iterations stop
+//! once the scores change by less than the tolerance in total, or after the
+//! most iterations allowed.
+//! 
+//! The [`Cost`]s of the edges are ignored.
+//! 
+//! [`Cost`]: crate::dijkstra::simple::Cost
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, JOBS_PER_THREAD, Node},
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+};
+
+/// The tolerance used unless another is set with [`MtdHits::set_tolerance`].
+pub const DEFAULT_TOLERANCE: f64 = 1e-6;
+/// The most iterations run unless another limit is set with
+/// [`MtdHits::set_max_iterations`].
+pub const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+/// The scores found by [`MtdHits::calculate`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HitsScores {
+    /// The hub score of each [`Node`]. The squares of the scores add up to
+    /// `1`, unless the graph has no edges and every score is `0`.
+    pub hubs: Vec<f64>,
+    /// The authority of each [`Node`], scaled like [`HitsScores::hubs`].
+    pub authorities: Vec<f64>,
+    /// Number of iterations run.
+    pub iterations: usize,
+    /// Whether the scores settled within the tolerance before the most
+    /// iterations allowed were run.
+    pub converged: bool,
+}
+
+impl HitsScores {
+    /// Get the hub score of `node`, or [`None`] if it is not in the graph.
+    pub fn hub(&self, node: Node) -> Option<f64> {
+        return self.hubs.get(node).copied();
+    }
+
+    /// Get the authority of `node`, or [`None`] if it is not in the graph.
+    pub fn authority(&self, node: Node) -> Option<f64> {
+        return self.authorities.get(node).copied();
+    }
+}
+
+/// Sums `scores` over the [`Node`]s each [`Node`] of `ranges` has an edge
+/// to in `graph`, on `pool`. Returns the sums and the sum of their squares.
+fn gather(
+    pool: &mut ThreadPool,
+    ranges: &Arc<Vec<(Node, Node)>>,
+    graph: &Arc<CsrGraph>,
+    scores: Vec<f64>,
+) -> Result<(Vec<f64>, f64), Error> {
+    let graph = graph.clone();
+    let shared = Arc::new(scores);
+    let chunks = pool.map(ranges.clone(), ChunkPolicy::Fixed(1), move |range| {
+        let mut sums: Vec<f64> = Vec::with_capacity(range.1 - range.0);
+        let mut squares: f64 = 0.0;
+        for node in range.0..range.1 {
+            let sum: f64 = graph
+                .get_node(node)
+                .unwrap_or(&[])
+                .iter()
+                .map(|adjacent| shared[adjacent.node])
+                .sum();
+            squares += sum * sum;
+            sums.push(sum);
+        }
+        return (sums, squares);
+    })?;
+
+    let total = ranges.last().map_or(0, |range| range.1);
+    let mut sums: Vec<f64> = Vec::with_capacity(total);
+    let mut squares: f64 = 0.0;
+    for (part, held) in chunks {
+        sums.extend(part);
+        squares += held;
+    }
+    return Ok((sums, squares));
+}
+
+/// Scales `scores` so that their squares, which add up to `squares`, add up
+/// to `1`, and returns how much they changed in total from `previous`.
+fn normalise(scores: &mut [f64], squares: f64, previous: &[f64]) -> f64 {
+    let norm = squares.sqrt();
+    let mut change: f64 = 0.0;
+    for (score, old) in scores.iter_mut().zip(previous) {
+        if norm > 0.0 {
+            *score /= norm;
+        }
+        change += (*score - old).abs();
+    }
+    return change;
+}
+
+/// This `struct` calculates the hub and authority scores of every [`Node`]
+/// of a graph using multiple threads. See the module documentation.
+pub struct MtdHits {
+    pool: ThreadPool,
+    graph: Arc<CsrGraph>,
+    reversed: Arc<CsrGraph>,
+    tolerance: f64,
+    max_iterations: usize,
+}
+
+impl MtdHits {
+    /// Creates a new [`MtdHits`] instance with [`DEFAULT_TOLERANCE`] and
+    /// [`DEFAULT_MAX_ITERATIONS`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix``` => The graph. The [`Cost`]s of its
+    ///    edges are ignored.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    /// 
+    /// [`Cost`]: crate::dijkstra::simple::Cost
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix) -> Result<Self, Error> {
+        return Self::from_csr(threads, CsrGraph::from(matrix));
+    }
+
+    /// Creates a new [`MtdHits`] instance from a graph which is already a
+    /// [`CsrGraph`]. See [`MtdHits::new`].
+    pub fn from_csr(threads: usize, graph: CsrGraph) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        let reversed = Arc::new(graph.reversed());
+        return Ok(Self {
+            pool,
+            graph: Arc::new(graph),
+            reversed,
+            tolerance: DEFAULT_TOLERANCE,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        });
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.graph.total();
+    }
+
+    /// Get how much the scores may change in total in the last iteration.
+    pub fn tolerance(&self) -> f64 {
+        return self.tolerance;
+    }
+
+    /// Sets how much the scores may change in total in the last iteration,
+    /// which is the sum of how much each hub score and each authority
+    /// changed.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `tolerance` is negative or not a
+    /// number, and the tolerance is left as it was.
+    pub fn set_tolerance(&mut self, tolerance: f64) -> Result<(), Error> {
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The tolerance {} is not a non-negative number.", tolerance)
+            ));
+        }
+        self.tolerance = tolerance;
+        return Ok(());
+    }
+
+    /// Get the most iterations run.
+    pub fn max_iterations(&self) -> usize {
+        return self.max_iterations;
+    }
+
+    /// Sets the most iterations run. The scores are returned as they are
+    /// after the last one even if they have not settled yet.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Calculates the hub and authority scores of every [`Node`]. See the
+    /// module documentation.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn calculate(&mut self) -> Result<HitsScores, Error> {
+        let total = self.total();
+        let start = 1.0 / (total as f64).sqrt();
+        let mut result = HitsScores {
+            hubs: vec![start; total],
+            authorities: vec![start; total],
+            iterations: 0,
+            converged: total == 0,
+        };
+        let ranges = Arc::new(ranges(total, self.pool.threads() * JOBS_PER_THREAD));
+        while !result.converged && result.iterations < self.max_iterations {
+            let hubs = result.hubs.clone();
+            let (mut authorities, squares) =
+                gather(&mut self.pool, &ranges, &self.reversed, hubs)?;
+            let mut change = normalise(&mut authorities, squares, &result.authorities);
+            let (mut hubs, squares) =
+                gather(&mut self.pool, &ranges, &self.graph, authorities.clone())?;
+            change += normalise(&mut hubs, squares, &result.hubs);
+
+            result.hubs = hubs;
+            result.authorities = authorities;
+            result.iterations += 1;
+            result.converged = change < self.tolerance;
+        }
+        return Ok(result);
+    }
+}
@@ -0,0 +1,127 @@
+//! Module for Johnson's Algorithm, which finds the cheapest route between
+//! every pair of [`Node`]s of a sparse [`SignedMatrix`] whose edges may have
+//! negative costs.
+//! 
+//! The Dijkstra Algorithm gives wrong answers when an edge costs less than
+//! nothing, so every [`Node`] `v` is first given a potential `h(v)`, the
+//! cost of the cheapest route to `v` from a new [`Node`] with a free edge
+//! to every other [`Node`], found by [`MtdBellmanFord`]. Each edge from `u`
+//! to `v` then costs `cost + h(u) - h(v)`, which is never negative, and the
+//! cheapest routes stay the same since every route from `a` to `b` changes
+//! by the same `h(a) - h(b)`. [`MtdDijkstra`] runs on the new costs from
+//! every [`Node`] in parallel, and the potentials are taken back out of the
+//! results. If the graph has a negative cycle there are no cheapest routes,
+//! and the cycle is returned instead.
+
+use std::{collections::HashMap, io::Error};
+
+use crate::{
+    bellmanford::{BellmanFordResult, MtdBellmanFord},
+    dijkstra::{
+        signed::{SignedCost, SignedMatrix},
+        simple::{AdjacencyMatrix, Cost, MtdDijkstra, Node},
+    },
+};
+
+/// Contains the [`SignedCost`] of the cheapest route from every source
+/// [`Node`] to every [`Node`], like
+/// [`crate::dijkstra::simple::DistanceTable`].
+pub type SignedTable = HashMap<Node, Vec<Option<SignedCost>>>;
+
+/// What [`all_pairs`] found.
+/// 
+/// # Variants
+/// 
+/// 1. Costs([`SignedTable`]) => The cost of the cheapest route between
+///    every pair of [`Node`]s.
+/// 2. NegativeCycle([`Vec<Node>`]) => A cycle whose edges cost less than
+///    nothing in total, in the order it is travelled. See
+///    [`BellmanFordResult::NegativeCycle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JohnsonResult {
+    Costs(SignedTable),
+    NegativeCycle(Vec<Node>),
+}
+
+/// Finds the potential of every [`Node`] of `matrix` with
+/// [`MtdBellmanFord`], or a negative cycle if there is one.
+fn potentials(
+    threads: usize,
+    matrix: &SignedMatrix,
+) -> Result<Result<Vec<SignedCost>, Vec<Node>>, Error> {
+    let total = matrix.total();
+    // The extra Node has no edges going into it, so it cannot be on a
+    // negative cycle and is never part of the cycle which is returned.
+    let mut extended = SignedMatrix::new(total + 1);
+    for (from, to, cost) in matrix.iter() {
+        extended.push(from, to, cost)?;
+    }
+    for node in 0..total {
+        extended.push(total, node, 0)?;
+    }
+    let mut processor = MtdBellmanFord::new(threads, &extended)?;
+    return Ok(match processor.shortest_from(total)? {
+        BellmanFordResult::Paths(paths) => {
+            // Every Node can be reached from the extra Node.
+            let mut costs: Vec<SignedCost> = paths
+                .costs
+                .into_iter()
+                .map(|cost| cost.unwrap_or(0))
+                .collect();
+            costs.truncate(total);
+            Ok(costs)
+        },
+        BellmanFordResult::NegativeCycle(cycle) => Err(cycle),
+    });
+}
+
+/// Calculates the cheapest route between every pair of [`Node`]s in
+/// `matrix`. See the module documentation.
+/// 
+/// # Parameters
+/// 1. ```threads: usize``` => Number of threads used by [`MtdBellmanFord`]
+///    and then by [`MtdDijkstra`].
+/// 2. ```matrix: &SignedMatrix``` => The graph, which may have edges with
+///    negative costs.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `threads` is less than `1` or a job
+/// on either [`crate::pool::ThreadPool`] fails.
+pub fn all_pairs(
+    threads: usize,
+    matrix: &SignedMatrix,
+) -> Result<JohnsonResult, Error> {
+    let potentials = match potentials(threads, matrix)? {
+        Ok(potentials) => potentials,
+        Err(cycle) => return Ok(JohnsonResult::NegativeCycle(cycle)),
+    };
+    let total = matrix.total();
+    let edges = matrix.iter().map(|(from, to, cost)| {
+        let cost = cost as i128 + potentials[from] as i128 - potentials[to] as i128;
+        return (from, to, cost as Cost);
+    });
+    let reweighted = AdjacencyMatrix::from_edges(total, edges)?;
+    let mut processor = MtdDijkstra::new(threads, total, reweighted)?;
+    processor.calculate()?;
+    let table = processor.into_result()?;
+
+    let mut costs: SignedTable = HashMap::with_capacity(total);
+    for (from, row) in table {
+        let row = row
+            .into_iter()
+            .enumerate()
+            .map(|(to, cost)| {
+                return cost.map(|cost| {
+                    let cost = cost as i128 - potentials[from] as i128
+                        + potentials[to] as i128;
+                    let limit = SignedCost::MAX as i128;
+                    return cost.clamp(-limit - 1, limit) as SignedCost;
+                });
+            })
+            .collect();
+        costs.insert(from, row);
+    }
+    return Ok(JohnsonResult::Costs(costs));
+}
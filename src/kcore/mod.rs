@@ -0,0 +1,175 @@
+//! Module for k-core decomposition. The k-core of a graph is the largest
+//! part of it in which every [`Node`] has at least `k` neighbours, and the
+//! coreness of a [`Node`] is the largest `k` whose k-core it is in.
+//! 
+//! Keeping only a k-core is a cheap way to prune the loosely attached
+//! [`Node`]s of a graph before running something expensive, such as the
+//! Dijkstra Algorithm from every [`Node`]. The edges of an
+//! [`AdjacencyMatrix`] are treated as undirected and their [`Cost`]s are
+//! ignored, so 2 [`Node`]s are neighbours if an edge joins them in either
+//! direction.
+//! 
+//! [`coreness`] peels the graph. Starting with `k` as the smallest number
+//! of neighbours, every [`Node`] with at most `k` neighbours left is
+//! removed at the same time on a [`ThreadPool`], which may leave more
+//! [`Node`]s with only `k`, which are removed next, and so on. Every
+//! [`Node`] removed this way has a coreness of `k`. Once none are left,
+//! `k` goes up to the smallest number of neighbours left.
+//! 
+//! [`Cost`]: crate::dijkstra::simple::Cost
+
+use std::{
+    io::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+use crate::{
+    bfs::SEQUENTIAL_FRONTIER,
+    dijkstra::simple::{AdjacencyMatrix, JOBS_PER_THREAD, Node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// The coreness of every [`Node`] of a graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cores {
+    /// The coreness of each [`Node`].
+    pub coreness: Vec<usize>,
+    /// The largest coreness of any [`Node`], or `0` for a graph without
+    /// [`Node`]s.
+    pub degeneracy: usize,
+}
+
+impl Cores {
+    /// Get the [`Node`]s of the k-core, in order.
+    pub fn members(&self, k: usize) -> Vec<Node> {
+        return (0..self.coreness.len())
+            .filter(|node| self.coreness[*node] >= k)
+            .collect();
+    }
+
+    /// Builds an [`AdjacencyMatrix`] holding only the edges of `matrix`
+    /// between [`Node`]s of the k-core. The [`Node`]s keep their numbers,
+    /// and those outside the k-core are left without edges.
+    pub fn prune(&self, matrix: &AdjacencyMatrix, k: usize) -> AdjacencyMatrix {
+        let total = matrix.total();
+        let mut pruned = AdjacencyMatrix::with_policy(total, matrix.policy());
+        let inside = |node: Node| {
+            return self.coreness.get(node).is_some_and(|core| *core >= k);
+        };
+        for from in (0..total).filter(|from| inside(*from)) {
+            for adjacent in matrix.get_node(from).map_or(&[][..], |edges| edges) {
+                if inside(adjacent.node) {
+                    // Both ends are in the graph, so this cannot fail.
+                    let _ = pruned.push(from, *adjacent);
+                }
+            }
+        }
+        return pruned;
+    }
+}
+
+/// Lists the neighbours of every [`Node`] of `matrix`, following edges both
+/// ways, without repeats.
+fn neighbours(matrix: &AdjacencyMatrix) -> Vec<Vec<Node>> {
+    let total = matrix.total();
+    let mut neighbours: Vec<Vec<Node>> = vec![Vec::new(); total];
+    for from in 0..total {
+        for adjacent in matrix.get_node(from).map_or(&[][..], |edges| edges) {
+            neighbours[from].push(adjacent.node);
+            neighbours[adjacent.node].push(from);
+        }
+    }
+    for nodes in &mut neighbours {
+        nodes.sort_unstable();
+        nodes.dedup();
+    }
+    return neighbours;
+}
+
+/// Takes `node` out of the graph while peeling at `k`, lowering the number
+/// of neighbours left of each neighbour which still has more than `k`.
+/// Returns the neighbours which are left with exactly `k`, which are peeled
+/// next.
+fn peel(
+    neighbours: &[Vec<Node>],
+    degrees: &[AtomicUsize],
+    node: Node,
+    k: usize,
+) -> Vec<Node> {
+    let mut next: Vec<Node> = Vec::new();
+    for neighbour in &neighbours[node] {
+        let lowered = degrees[*neighbour].fetch_update(SeqCst, SeqCst, |degree| {
+            return if degree > k { Some(degree - 1) } else { None };
+        });
+        if lowered == Ok(k + 1) {
+            next.push(*neighbour);
+        }
+    }
+    return next;
+}
+
+/// Calculates the coreness of every [`Node`] of `matrix`. See the module
+/// documentation.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn coreness(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+) -> Result<Cores, Error> {
+    let neighbours = Arc::new(neighbours(matrix));
+    let total = neighbours.len();
+    let degrees: Arc<Vec<AtomicUsize>> = Arc::new(
+        neighbours.iter().map(|nodes| AtomicUsize::new(nodes.len())).collect()
+    );
+    let size = total.div_ceil(pool.threads() * JOBS_PER_THREAD).max(1);
+    let mut remaining: Vec<Node> = (0..total).collect();
+    let mut cores = Cores {coreness: vec![0; total], degeneracy: 0};
+
+    while !remaining.is_empty() {
+        let k = remaining
+            .iter()
+            .map(|node| degrees[*node].load(SeqCst))
+            .min()
+            .unwrap_or_default();
+        cores.degeneracy = k;
+        let mut frontier: Vec<Node> = remaining
+            .iter()
+            .copied()
+            .filter(|node| degrees[*node].load(SeqCst) == k)
+            .collect();
+        let mut peeled: Vec<bool> = vec![false; total];
+        while !frontier.is_empty() {
+            for node in &frontier {
+                cores.coreness[*node] = k;
+                peeled[*node] = true;
+            }
+            frontier = if frontier.len() < SEQUENTIAL_FRONTIER {
+                frontier
+                    .iter()
+                    .flat_map(|node| peel(&neighbours, &degrees, *node, k))
+                    .collect()
+            } else {
+                let (neighbours, degrees) = (neighbours.clone(), degrees.clone());
+                let groups: Vec<Vec<Node>> =
+                    frontier.chunks(size).map(<[_]>::to_vec).collect();
+                pool.map(Arc::new(groups), ChunkPolicy::Fixed(1), move |group| {
+                    return group
+                        .iter()
+                        .flat_map(|node| peel(&neighbours, &degrees, *node, k))
+                        .collect::<Vec<Node>>();
+                })?
+                .into_iter()
+                .flatten()
+                .collect()
+            };
+        }
+        remaining.retain(|node| !peeled[*node]);
+    }
+    return Ok(cores);
+}
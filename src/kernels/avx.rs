@@ -0,0 +1,98 @@
+//! AVX implementations of the kernels in [`crate::kernels`]. Every function
+//! in this module must only be called after checking that the CPU supports
+//! AVX.
+
+use std::arch::x86_64::*;
+
+use super::scalar;
+
+/// Number of [`f64`]s in an AVX register.
+const LANES: usize = 4;
+
+/// AVX version of [`scalar::dot`].
+#[target_feature(enable = "avx")]
+pub unsafe fn dot(a: &[f64], b: &[f64]) -> f64 {
+    let len = a.len().min(b.len());
+    let chunks = len / LANES;
+    let mut sum = _mm256_setzero_pd();
+    for chunk in 0..chunks {
+        let offset = chunk * LANES;
+        let x = _mm256_loadu_pd(a.as_ptr().add(offset));
+        let y = _mm256_loadu_pd(b.as_ptr().add(offset));
+        sum = _mm256_add_pd(sum, _mm256_mul_pd(x, y));
+    }
+    let mut lanes = [0.0; LANES];
+    _mm256_storeu_pd(lanes.as_mut_ptr(), sum);
+    let tail = chunks * LANES;
+    return lanes.iter().sum::<f64>()
+        + scalar::dot(&a[tail..len], &b[tail..len]);
+}
+
+/// AVX version of [`scalar::relax`].
+#[target_feature(enable = "avx")]
+pub unsafe fn relax(base: f64, weights: &[f64], distances: &mut [f64]) -> usize {
+    let len = weights.len().min(distances.len());
+    let chunks = len / LANES;
+    let base_lanes = _mm256_set1_pd(base);
+    let mut lowered: usize = 0;
+    for chunk in 0..chunks {
+        let offset = chunk * LANES;
+        let weight = _mm256_loadu_pd(weights.as_ptr().add(offset));
+        let distance = _mm256_loadu_pd(distances.as_ptr().add(offset));
+        let candidate = _mm256_add_pd(base_lanes, weight);
+        let cheaper = _mm256_cmp_pd::<_CMP_LT_OQ>(candidate, distance);
+        lowered += _mm256_movemask_pd(cheaper).count_ones() as usize;
+        _mm256_storeu_pd(
+            distances.as_mut_ptr().add(offset),
+            _mm256_blendv_pd(distance, candidate, cheaper),
+        );
+    }
+    let tail = chunks * LANES;
+    return lowered
+        + scalar::relax(base, &weights[tail..len], &mut distances[tail..len]);
+}
+
+/// AVX version of [`scalar::count_bins`]. The bin of each value is found
+/// with vector instructions, and counts are spread over several partial
+/// histograms so consecutive values in the same bin do not wait on each
+/// other.
+#[target_feature(enable = "avx")]
+pub unsafe fn count_bins(
+    values: &[f64],
+    start: f64,
+    width: f64,
+    counts: &mut [usize],
+) -> usize {
+    let bins = counts.len();
+    let chunks = values.len() / LANES;
+    let start_lanes = _mm256_set1_pd(start);
+    let width_lanes = _mm256_set1_pd(width);
+    let mut partial = vec![0usize; bins * LANES];
+    let mut counted: usize = 0;
+    for chunk in 0..chunks {
+        let offset = chunk * LANES;
+        let value = _mm256_loadu_pd(values.as_ptr().add(offset));
+        let position = _mm256_floor_pd(_mm256_div_pd(
+            _mm256_sub_pd(value, start_lanes),
+            width_lanes,
+        ));
+        let mut positions = [0.0; LANES];
+        _mm256_storeu_pd(positions.as_mut_ptr(), position);
+        for (lane, position) in positions.iter().enumerate() {
+            if *position >= 0.0 && *position < bins as f64 {
+                partial[lane * bins + *position as usize] += 1;
+                counted += 1;
+            }
+        }
+    }
+    for lane in 0..LANES {
+        for (count, extra) in counts
+            .iter_mut()
+            .zip(&partial[lane * bins..(lane + 1) * bins])
+        {
+            *count += extra;
+        }
+    }
+    let tail = chunks * LANES;
+    return counted + scalar::count_bins(&values[tail..], start, width, counts);
+}
@@ -0,0 +1,72 @@
+//! Module for the hot inner loops shared by the algorithms in this crate.
+//! 
+//! Every kernel has a plain implementation in [`self::scalar`]. When the
+//! crate is built with the `simd` feature, the functions in this module
+//! check at runtime whether the CPU supports AVX and use a vectorised
+//! implementation if it does, falling back to [`self::scalar`] otherwise.
+//! Without the feature, they always use [`self::scalar`].
+//! 
+//! The vectorised kernels add floating point numbers in a different order
+//! from the scalar ones, so their results may differ in the last few bits.
+
+pub mod scalar;
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod avx;
+
+/// Whether the vectorised kernels can be used on this machine.
+pub fn simd_available() -> bool {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        return is_x86_feature_detected!("avx");
+    }
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+    {
+        return false;
+    }
+}
+
+/// Calculates the dot product of `a` and `b`. If the slices have different
+/// lengths, the extra items of the longer slice are ignored.
+pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if simd_available() {
+        // AVX support was checked just above.
+        return unsafe {avx::dot(a, b)};
+    }
+    return scalar::dot(a, b);
+}
+
+/// Relaxes a batch of edges leaving a node which is `base` away from the
+/// source. Each `distances[i]` is lowered to `base + weights[i]` if that is
+/// cheaper, with [`f64::INFINITY`] marking unreached nodes. If the slices
+/// have different lengths, the extra items of the longer slice are ignored.
+/// 
+/// Returns the number of distances which were lowered.
+pub fn relax(base: f64, weights: &[f64], distances: &mut [f64]) -> usize {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if simd_available() {
+        // AVX support was checked just above.
+        return unsafe {avx::relax(base, weights, distances)};
+    }
+    return scalar::relax(base, weights, distances);
+}
+
+/// Counts how many of `values` fall into each of the `counts.len()` bins of
+/// width `width` starting at `start`, adding to the existing counts. Values
+/// outside of the bins (including NaN) are skipped.
+/// 
+/// Returns the number of values which were counted.
+pub fn count_bins(
+    values: &[f64],
+    start: f64,
+    width: f64,
+    counts: &mut [usize],
+) -> usize {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if simd_available() {
+        // AVX support was checked just above.
+        return unsafe {avx::count_bins(values, start, width, counts)};
+    }
+    return scalar::count_bins(values, start, width, counts);
+}
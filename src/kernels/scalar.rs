@@ -0,0 +1,56 @@
+//! Plain implementations of the kernels in [`crate::kernels`], which work on
+//! every machine.
+
+/// Calculates the dot product of `a` and `b`. If the slices have different
+/// lengths, the extra items of the longer slice are ignored.
+pub fn dot(a: &[f64], b: &[f64]) -> f64 {
+    return a.iter().zip(b).map(|(a, b)| a * b).sum();
+}
+
+/// Lowers each `distances[i]` to `base + weights[i]` if that is cheaper and
+/// returns the number of distances which were lowered.
+pub fn relax(base: f64, weights: &[f64], distances: &mut [f64]) -> usize {
+    let mut lowered: usize = 0;
+    for (distance, weight) in distances.iter_mut().zip(weights) {
+        let candidate = base + weight;
+        if candidate < *distance {
+            *distance = candidate;
+            lowered += 1;
+        }
+    }
+    return lowered;
+}
+
+/// Finds the bin `value` falls into, if any.
+fn bin_of(
+    value: f64,
+    start: f64,
+    width: f64,
+    bins: usize,
+) -> Option<usize> {
+    let position = ((value - start) / width).floor();
+    // NaN fails both comparisons.
+    if position >= 0.0 && position < bins as f64 {
+        return Some(position as usize);
+    }
+    return None;
+}
+
+/// Counts how many of `values` fall into each of the `counts.len()` bins of
+/// width `width` starting at `start` and returns the number of values which
+/// were counted.
+pub fn count_bins(
+    values: &[f64],
+    start: f64,
+    width: f64,
+    counts: &mut [usize],
+) -> usize {
+    let mut counted: usize = 0;
+    for &value in values {
+        if let Some(bin) = bin_of(value, start, width, counts.len()) {
+            counts[bin] += 1;
+            counted += 1;
+        }
+    }
+    return counted;
+}
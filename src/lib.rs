@@ -9,7 +9,94 @@
 //! 
 //! These are the algorithms implemented so far:
 //! 1. [`crate::dijkstra`].
+//! 2. [`crate::matrix`] (dense and sparse matrices and linear systems).
+//! 3. [`crate::bellmanford`] (shortest routes with negative costs).
+//! 4. [`crate::floydwarshall`] (all-pairs shortest routes on dense graphs).
+//! 5. [`crate::johnson`] (all-pairs shortest routes with negative costs).
+//! 6. [`crate::bfs`] (fewest hops on unweighted graphs).
+//! 7. [`crate::dfs`] (reachability and depth-first numbering).
+//! 8. [`crate::components`] (connected components).
+//! 9. [`crate::scc`] (strongly connected components and condensation).
+//! 10. [`crate::toposort`] (ordering graphs without cycles).
+//! 11. [`crate::mst`] (minimum spanning trees).
+//! 12. [`crate::sort`] (sorting large lists).
+//! 13. [`crate::maxflow`] (maximum flow and minimum cut).
+//! 14. [`crate::matching`] (maximum matchings of bipartite graphs).
+//! 15. [`crate::pagerank`] (ranking Nodes by random walks).
+//! 16. [`crate::centrality`] (betweenness and closeness of Nodes).
+//! 17. [`crate::coloring`] (coloring graphs so neighbours differ).
+//! 18. [`crate::community`] (community detection).
+//! 19. [`crate::kcore`] (coreness for pruning graphs).
+//! 20. [`crate::mis`] (maximal independent sets).
+//! 21. [`crate::hits`] (hub and authority scores of Nodes).
+//! 22. [`crate::randomwalk`] (visit counts of random walks).
+//! 23. [`crate::stats`] (bounds on the diameter of large graphs).
+//! 24. [`crate::tsp`] (cheap tours through every Node).
+//! 25. [`crate::anneal`] (simulated annealing of any kind of state).
+//! 26. [`crate::genetic`] (genetic algorithms over islands of genomes).
+//! 27. [`crate::aco`] (ant colony optimisation of paths and tours).
+//! 28. [`crate::pso`] (particle swarm optimisation of functions).
+//! 29. [`crate::branchbound`] (branch and bound, with exact knapsacks).
+//! 30. [`crate::backtrack`] (backtracking, with N-queens counts).
+//! 31. [`crate::gametree`] (minimax with alpha-beta pruning for games).
+//! 32. [`crate::mcts`] (Monte Carlo tree search for games).
+//! 33. [`crate::search`] (parallel A* over implicit graphs).
+//! 34. [`crate::cluster`] (k-means clustering of points).
+//! 35. [`crate::select`] (medians and percentiles of large lists).
+//! 36. [`crate::histogram`] (counting values into bins or by key).
+//! 37. [`crate::dedup`] (removing repeated items from large lists).
+//! 38. [`crate::merge`] (merging many sorted lists into one).
+//! 
+//! Graph transforms and algorithms can be chained with
+//! [`crate::pipeline::Pipeline`], and the most used items can be brought in
+//! with `use mtdalgos::prelude::*;`.
+
+#![allow(clippy::needless_return)]
 
+pub mod aco;
+pub mod anneal;
+pub mod arena;
+pub mod backtrack;
+pub mod bellmanford;
+pub mod bfs;
+pub mod branchbound;
+pub mod centrality;
+pub mod cluster;
+pub mod coloring;
+pub mod community;
+pub mod components;
+pub mod dedup;
+pub mod dfs;
 pub mod dijkstra;
+pub mod estimate;
+pub mod floydwarshall;
+pub mod gametree;
+pub mod genetic;
+pub mod histogram;
+pub mod hits;
+pub mod johnson;
+pub mod kcore;
+pub mod kernels;
 pub mod macros;
+pub mod matching;
+pub mod matrix;
+pub mod maxflow;
+pub mod mcts;
+pub mod merge;
+pub mod mis;
+pub mod mst;
+pub mod pagerank;
+pub mod pipeline;
 pub mod pool;
+pub mod prelude;
+pub mod pso;
+pub mod randomwalk;
+pub mod rng;
+pub mod scc;
+pub mod scenarios;
+pub mod search;
+pub mod select;
+pub mod sort;
+pub mod stats;
+pub mod toposort;
+pub mod tsp;
@@ -0,0 +1,55 @@
+//! This module contains the [`graph!`] macro for writing small graphs for
+//! the [`crate::dijkstra::simple`] edition by hand.
+
+/// Builds a [`crate::dijkstra::simple::AdjacencyMatrix`] from a list of
+/// edges, evaluating to the [`Result`] of
+/// [`crate::dijkstra::simple::AdjacencyMatrix::from_edges`].
+/// 
+/// Each edge is written as `from -> to: cost` for an edge going one way or
+/// `from <-> to: cost` for an edge going both ways, and edges are separated
+/// by commas. The [`crate::dijkstra::simple::Node`]s must be single tokens,
+/// such as literals, variables or expressions in brackets. The graph has one
+/// more [`crate::dijkstra::simple::Node`] than the largest one used, unless
+/// the number of [`crate::dijkstra::simple::Node`]s is given first and
+/// followed by a semicolon, as in `graph!{6; 0 -> 1: 4, 1 <-> 2: 7}`.
+/// 
+/// Each edge is expanded separately, so very long lists of edges can reach
+/// the recursion limit of the compiler. Use
+/// [`crate::dijkstra::simple::AdjacencyMatrix::from_edges`] for those.
+#[macro_export]
+macro_rules! graph {
+    (@edges $edges: ident;) => {};
+    (@edges $edges: ident; $from: tt -> $to: tt : $cost: expr $(, $($rest: tt)*)?) => {
+        $edges.push(($from, $to, $cost));
+        $crate::graph!(@edges $edges; $($($rest)*)?);
+    };
+    (@edges $edges: ident; $a: tt <-> $b: tt : $cost: expr $(, $($rest: tt)*)?) => {
+        let cost: $crate::dijkstra::simple::Cost = $cost;
+        $edges.push(($a, $b, cost));
+        $edges.push(($b, $a, cost));
+        $crate::graph!(@edges $edges; $($($rest)*)?);
+    };
+    ($total: expr; $($edges: tt)*) => {{
+        let mut edges: Vec<(
+            $crate::dijkstra::simple::Node,
+            $crate::dijkstra::simple::Node,
+            $crate::dijkstra::simple::Cost,
+        )> = Vec::new();
+        $crate::graph!(@edges edges; $($edges)*);
+        $crate::dijkstra::simple::AdjacencyMatrix::from_edges($total, edges)
+    }};
+    ($($edges: tt)*) => {{
+        let mut edges: Vec<(
+            $crate::dijkstra::simple::Node,
+            $crate::dijkstra::simple::Node,
+            $crate::dijkstra::simple::Cost,
+        )> = Vec::new();
+        $crate::graph!(@edges edges; $($edges)*);
+        let total = edges
+            .iter()
+            .map(|(from, to, _cost)| 1 + ::std::cmp::max(*from, *to))
+            .max()
+            .unwrap_or(0);
+        $crate::dijkstra::simple::AdjacencyMatrix::from_edges(total, edges)
+    }};
+}
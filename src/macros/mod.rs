@@ -1,3 +1,4 @@
 //! Separate module for containing macro definitions.
 
 pub mod errconvert;
+pub mod graph;
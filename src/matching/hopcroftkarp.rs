@@ -0,0 +1,182 @@
+//! The Hopcroft-Karp Algorithm, which grows a matching in phases, adding
+//! as many of the shortest augmenting paths as it can in each phase.
+
+use std::{
+    io::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+use super::{Matching, neighbours};
+use crate::{
+    bfs::SEQUENTIAL_FRONTIER,
+    dijkstra::simple::{AdjacencyMatrix, Node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// Marks a [`Node`] which is not paired, or a left [`Node`] which was not
+/// reached by the search.
+const NONE: usize = usize::MAX;
+
+/// Claims the left [`Node`]s paired with the right neighbours of `node`
+/// which were not reached yet, giving them `level`. Returns the [`Node`]s
+/// claimed, and whether `node` has a right neighbour which is not paired.
+fn expand(
+    neighbours: &[Vec<Node>],
+    mates: &[usize],
+    levels: &[AtomicUsize],
+    node: Node,
+    level: usize,
+) -> (Vec<Node>, bool) {
+    let mut claimed: Vec<Node> = Vec::new();
+    let mut free = false;
+    for right in &neighbours[node] {
+        let mate = mates[*right];
+        if mate == NONE {
+            free = true;
+        } else if levels[mate].load(SeqCst) == NONE
+            && levels[mate].compare_exchange(NONE, level, SeqCst, SeqCst).is_ok()
+        {
+            claimed.push(mate);
+        }
+    }
+    return (claimed, free);
+}
+
+/// Looks for an augmenting path from the unpaired left [`Node`] `root`
+/// which goes up one level at each step and ends at an unpaired right
+/// [`Node`] from a left [`Node`] on level `last`, and flips the pairs along
+/// it if there is one. Left [`Node`]s which lead nowhere are taken off
+/// their level so that they are not searched again in this phase.
+fn augment(
+    neighbours: &[Vec<Node>],
+    mates: &mut [usize],
+    levels: &mut [usize],
+    cursors: &mut [usize],
+    root: Node,
+    last: usize,
+) -> bool {
+    let mut stack: Vec<Node> = vec![root];
+    while let Some(&node) = stack.last() {
+        let Some(&right) = neighbours[node].get(cursors[node]) else {
+            levels[node] = NONE;
+            stack.pop();
+            continue;
+        };
+        cursors[node] += 1;
+        let mate = mates[right];
+        if mate == NONE {
+            if levels[node] != last {
+                continue;
+            }
+            // Each left Node on the stack is paired with the right Node it
+            // last moved through.
+            for node in stack {
+                let right = neighbours[node][cursors[node] - 1];
+                mates[node] = right;
+                mates[right] = node;
+            }
+            return true;
+        }
+        if levels[mate] != NONE && levels[mate] == levels[node] + 1 {
+            stack.push(mate);
+        }
+    }
+    return false;
+}
+
+/// Finds a maximum matching of the bipartite graph `matrix`, where the first
+/// `left` [`Node`]s are on one side and the rest are on the other, with the
+/// Hopcroft-Karp Algorithm.
+/// 
+/// Each phase starts with a Breadth-First Search from all the unpaired left
+/// [`Node`]s at once, which sorts the left [`Node`]s into levels by how far
+/// they are from one of them. Large levels are split between the workers of
+/// `pool`, which claim the [`Node`]s of the next level without locking. The
+/// search stops at the first level which reaches an unpaired right [`Node`],
+/// and the shortest augmenting paths found through the levels are then
+/// flipped on the calling thread. There are at most about `2 sqrt(n)`
+/// phases for `n` [`Node`]s.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which run the searches.
+/// 2. ```left: Node``` => Number of [`Node`]s on the left side.
+/// 3. ```matrix: &AdjacencyMatrix``` => The graph. The [`Cost`]s of its
+///    edges are ignored.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `left` exceeds the number of
+/// [`Node`]s, an edge joins 2 [`Node`]s on the same side, or a job on the
+/// [`ThreadPool`] fails.
+/// 
+/// [`Cost`]: crate::dijkstra::simple::Cost
+pub fn hopcroft_karp(
+    pool: &mut ThreadPool,
+    left: Node,
+    matrix: &AdjacencyMatrix,
+) -> Result<Matching, Error> {
+    let neighbours = Arc::new(neighbours(left, matrix)?);
+    let mut mates: Vec<usize> = vec![NONE; matrix.total()];
+    let mut size: usize = 0;
+    loop {
+        let levels: Arc<Vec<AtomicUsize>> = Arc::new(
+            (0..left).map(|_| AtomicUsize::new(NONE)).collect()
+        );
+        let mut frontier: Vec<Node> = (0..left)
+            .filter(|node| mates[*node] == NONE)
+            .collect();
+        for node in &frontier {
+            levels[*node].store(0, SeqCst);
+        }
+        let shared: Arc<Vec<usize>> = Arc::new(mates.clone());
+        let mut level: usize = 0;
+        let mut last: Option<usize> = None;
+        while !frontier.is_empty() {
+            let next = level + 1;
+            let expanded = if frontier.len() < SEQUENTIAL_FRONTIER {
+                frontier
+                    .iter()
+                    .map(|node| expand(&neighbours, &shared, &levels, *node, next))
+                    .collect()
+            } else {
+                let (neighbours, mates, levels) =
+                    (neighbours.clone(), shared.clone(), levels.clone());
+                pool.map(Arc::new(frontier), ChunkPolicy::Auto, move |node| {
+                    return expand(&neighbours, &mates, &levels, *node, next);
+                })?
+            };
+            if expanded.iter().any(|(_, free)| *free) {
+                last = Some(level);
+                break;
+            }
+            frontier = expanded.into_iter().flat_map(|(claimed, _)| claimed).collect();
+            level = next;
+        }
+        let Some(last) = last else {
+            break;
+        };
+
+        let mut levels: Vec<usize> = levels
+            .iter()
+            .map(|level| level.load(SeqCst))
+            .collect();
+        let mut cursors: Vec<usize> = vec![0; left];
+        for root in 0..left {
+            if mates[root] != NONE {
+                continue;
+            }
+            if augment(&neighbours, &mut mates, &mut levels, &mut cursors, root, last) {
+                size += 1;
+            }
+        }
+    }
+
+    let mates = mates
+        .into_iter()
+        .map(|mate| if mate == NONE { None } else { Some(mate) })
+        .collect();
+    return Ok(Matching {left, mates, size});
+}
@@ -0,0 +1,91 @@
+//! Module for finding maximum matchings in bipartite graphs, which pair up
+//! as many [`Node`]s on one side with [`Node`]s on the other side as
+//! possible, such as jobs with the workers who can do them.
+//! 
+//! The graph is an [`AdjacencyMatrix`] where the first `left` [`Node`]s are
+//! on one side and the rest are on the other. Its edges are treated as
+//! undirected and their [`Cost`]s are ignored, so an edge pushed in either
+//! direction lets its 2 [`Node`]s be paired.
+//! 
+//! These are the algorithms available:
+//! 1. [`hopcroft_karp`], which adds many shortest augmenting paths at once.
+//! 
+//! [`Cost`]: crate::dijkstra::simple::Cost
+
+mod hopcroftkarp;
+
+pub use hopcroftkarp::hopcroft_karp;
+
+use std::io::{Error, ErrorKind};
+
+use crate::dijkstra::simple::{AdjacencyMatrix, Node};
+
+/// A matching of a bipartite graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Matching {
+    /// Number of [`Node`]s on the left side.
+    pub left: Node,
+    /// The [`Node`] each [`Node`] is paired with, with [`None`] marking
+    /// [`Node`]s which are not paired.
+    pub mates: Vec<Option<Node>>,
+    /// Number of pairs.
+    pub size: usize,
+}
+
+impl Matching {
+    /// Get the [`Node`] `node` is paired with, if any.
+    pub fn mate(&self, node: Node) -> Option<Node> {
+        return self.mates.get(node).copied().flatten();
+    }
+
+    /// Get the pairs as `(left, right)`, in order of their left [`Node`].
+    pub fn pairs(&self) -> Vec<(Node, Node)> {
+        return self.mates[..self.left]
+            .iter()
+            .enumerate()
+            .filter_map(|(node, mate)| mate.map(|mate| (node, mate)))
+            .collect();
+    }
+
+    /// Whether every [`Node`] on both sides is paired.
+    pub fn is_perfect(&self) -> bool {
+        return self.mates.iter().all(Option::is_some);
+    }
+}
+
+/// Lists the right [`Node`]s each left [`Node`] of `matrix` can be paired
+/// with, without repeats.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `left` exceeds the number of
+/// [`Node`]s or an edge joins 2 [`Node`]s on the same side.
+fn neighbours(left: Node, matrix: &AdjacencyMatrix) -> Result<Vec<Vec<Node>>, Error> {
+    let total = matrix.total();
+    if left > total {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} exceeds matrix size.", left)
+        ));
+    }
+    let mut neighbours: Vec<Vec<Node>> = vec![Vec::new(); left];
+    for from in 0..total {
+        for adjacent in matrix.get_node(from).map_or(&[][..], |edges| edges) {
+            let to = adjacent.node;
+            match (from < left, to < left) {
+                (true, false) => neighbours[from].push(to),
+                (false, true) => neighbours[to].push(from),
+                _ => return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{} and {} are on the same side.", from, to)
+                )),
+            }
+        }
+    }
+    for nodes in &mut neighbours {
+        nodes.sort_unstable();
+        nodes.dedup();
+    }
+    return Ok(neighbours);
+}
@@ -0,0 +1,142 @@
+//! The conjugate gradient method, which solves sparse symmetric positive
+//! definite linear systems by iterating.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use super::{
+    dense::MtdMatrix,
+    jacobi::Solution,
+    sparse::SparseMatrix,
+};
+use crate::{kernels, pool::chunk::ChunkPolicy, unwrapoption};
+
+/// The vectors of [`MtdMatrix::cg_solve`] shared with the jobs of an
+/// update, as `(x, r, p, q)`.
+type Vectors = (Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>);
+
+impl MtdMatrix {
+    /// Solves `a * x = b` for `x` with the conjugate gradient method,
+    /// starting from zeros. `a` must be symmetric and positive definite,
+    /// such as a graph Laplacian from [`SparseMatrix::laplacian`] with a
+    /// little added to its diagonal.
+    /// 
+    /// Each iteration takes a step along a search direction `p`, which is
+    /// kept conjugate to every step before, so in exact arithmetic `x` is
+    /// found after at most a step per row. The rows are split into ranges
+    /// holding about as many items as each other, a range per thread. Each
+    /// iteration multiplies `a` by `p` with a job per range, which also
+    /// finds its part of the dot product of `p` and that product, then
+    /// updates `x` and the residual `b - a * x` with a job per range, which
+    /// also finds its part of the squared length of the residual. The parts
+    /// are added up on the calling thread. The iterations stop once the
+    /// residual is no longer than [`MtdMatrix::tolerance`] times `b`, or
+    /// after [`MtdMatrix::max_iterations`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `a` is not square, if `b` does
+    /// not hold an item per row of `a`, if `a` turns out not to be positive
+    /// definite, or if a job on the [`ThreadPool`] fails.
+    /// 
+    /// [`ThreadPool`]: crate::pool::ThreadPool
+    pub fn cg_solve(&mut self, a: &SparseMatrix, b: &[f64]) -> Result<Solution, Error> {
+        if !a.is_square() || b.len() != a.rows() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "A {}x{} matrix and {} items on the right hand side are \
+                    not a square linear system.",
+                    a.rows(), a.cols(), b.len()
+                )
+            ));
+        }
+        let size = a.rows();
+        let matrix = Arc::new(a.clone());
+        let chunks = Arc::new(a.row_chunks(self.pool.threads()));
+
+        let shared = Arc::new(b.to_vec());
+        let policy = ChunkPolicy::Fixed(1);
+        let parts = self.pool.map(chunks.clone(), policy, move |chunk| {
+            let (first, end) = *chunk;
+            return kernels::dot(&shared[first..end], &shared[first..end]);
+        })?;
+        let mut squared: f64 = parts.into_iter().sum();
+        let limit = self.tolerance * squared.sqrt();
+
+        let mut solution = Solution {
+            x: vec![0.0; size],
+            iterations: 0,
+            converged: squared.sqrt() <= limit,
+        };
+        let mut residual = b.to_vec();
+        let mut direction = b.to_vec();
+        while !solution.converged && solution.iterations < self.max_iterations {
+            let shared = Arc::new(direction);
+            let (matrix, p) = (matrix.clone(), shared.clone());
+            let policy = ChunkPolicy::Fixed(1);
+            let parts = self.pool.map(chunks.clone(), policy, move |chunk| {
+                let (first, end) = *chunk;
+                let q = matrix.multiply_rows(*chunk, &p);
+                let curvature = kernels::dot(&p[first..end], &q);
+                return (q, curvature);
+            })?;
+            direction = unwrapoption!(Arc::try_unwrap(shared).ok());
+
+            let mut product: Vec<f64> = Vec::with_capacity(size);
+            let mut curvature: f64 = 0.0;
+            for (q, part) in parts {
+                product.extend(q);
+                curvature += part;
+            }
+            if curvature.is_nan() || curvature <= 0.0 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "The matrix is not positive definite."
+                ));
+            }
+            let step = squared / curvature;
+
+            let vectors: Arc<Vectors> = Arc::new(
+                (solution.x, residual, direction, product)
+            );
+            let shared = vectors.clone();
+            let policy = ChunkPolicy::Fixed(1);
+            let parts = self.pool.map(chunks.clone(), policy, move |chunk| {
+                let (x, r, p, q) = &*shared;
+                let (first, end) = *chunk;
+                let x: Vec<f64> = (first..end)
+                    .map(|row| x[row] + step * p[row])
+                    .collect();
+                let r: Vec<f64> = (first..end)
+                    .map(|row| r[row] - step * q[row])
+                    .collect();
+                let part = kernels::dot(&r, &r);
+                return (x, r, part);
+            })?;
+            let (_, _, previous, _) = unwrapoption!(Arc::try_unwrap(vectors).ok());
+
+            let mut x: Vec<f64> = Vec::with_capacity(size);
+            residual = Vec::with_capacity(size);
+            let mut next: f64 = 0.0;
+            for (xs, rs, part) in parts {
+                x.extend(xs);
+                residual.extend(rs);
+                next += part;
+            }
+            let ratio = next / squared;
+            direction = residual
+                .iter()
+                .zip(previous)
+                .map(|(r, p)| r + ratio * p)
+                .collect();
+            squared = next;
+            solution.x = x;
+            solution.iterations += 1;
+            solution.converged = squared.sqrt() <= limit;
+        }
+        return Ok(solution);
+    }
+}
@@ -0,0 +1,428 @@
+//! Dense matrices of [`f64`] and [`MtdMatrix`], which does arithmetic on
+//! them using multiple threads.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use super::{
+    check_shape, transpose_block, transpose_parallel,
+    jacobi::{DEFAULT_MAX_ITERATIONS, DEFAULT_TOLERANCE},
+    strassen::DEFAULT_STRASSEN_CUTOFF,
+};
+use crate::{
+    kernels,
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+};
+
+/// The default width and height of the tiles [`MtdMatrix::matmul`] splits
+/// the product into.
+pub const DEFAULT_TILE: usize = 64;
+
+/// A dense matrix of [`f64`], stored row by row.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MatrixData"))]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+/// The fields of a [`Matrix`] as they are deserialized, before they are
+/// checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MatrixData {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MatrixData> for Matrix {
+    type Error = Error;
+
+    /// Checks that `data` holds `rows * cols` items, like [`Matrix::new`].
+    fn try_from(data: MatrixData) -> Result<Self, Error> {
+        return Self::new(data.rows, data.cols, data.data);
+    }
+}
+
+impl Matrix {
+    /// Creates a [`Matrix`] with `rows` rows and `cols` columns from `data`,
+    /// which holds the items row by row.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `data` does not hold
+    /// `rows * cols` items.
+    pub fn new(rows: usize, cols: usize, data: Vec<f64>) -> Result<Self, Error> {
+        check_shape(&data, rows, cols)?;
+        return Ok(Self {rows, cols, data});
+    }
+
+    /// Creates a [`Matrix`] with `rows` rows and `cols` columns of zeros.
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        return Self {rows, cols, data: vec![0.0; rows * cols]};
+    }
+
+    /// Creates the identity [`Matrix`] with `size` rows and columns.
+    pub fn identity(size: usize) -> Self {
+        let mut identity = Self::zeros(size, size);
+        for index in 0..size {
+            identity.data[index * size + index] = 1.0;
+        }
+        return identity;
+    }
+
+    /// Get the number of rows.
+    pub fn rows(&self) -> usize {
+        return self.rows;
+    }
+
+    /// Get the number of columns.
+    pub fn cols(&self) -> usize {
+        return self.cols;
+    }
+
+    /// Whether the [`Matrix`] has as many rows as columns.
+    pub fn is_square(&self) -> bool {
+        return self.rows == self.cols;
+    }
+
+    /// Get the items row by row.
+    pub fn data(&self) -> &[f64] {
+        return &self.data;
+    }
+
+    /// Takes the items out of the [`Matrix`], row by row.
+    pub fn into_data(self) -> Vec<f64> {
+        return self.data;
+    }
+
+    /// Get row `row`.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `row` is not below [`Matrix::rows`].
+    pub fn row(&self, row: usize) -> &[f64] {
+        return &self.data[row * self.cols..(row + 1) * self.cols];
+    }
+
+    /// Get the item in row `row` and column `col`.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `row` or `col` is outside of the [`Matrix`].
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        assert!(col < self.cols, "Column {} is outside of the matrix.", col);
+        return self.data[row * self.cols + col];
+    }
+
+    /// Sets the item in row `row` and column `col` to `value`.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `row` or `col` is outside of the [`Matrix`].
+    pub fn set(&mut self, row: usize, col: usize, value: f64) {
+        assert!(col < self.cols, "Column {} is outside of the matrix.", col);
+        self.data[row * self.cols + col] = value;
+    }
+}
+
+/// The shape of a product of a matrix with `rows` rows and `inner` columns
+/// and a matrix with `inner` rows and `cols` columns.
+#[derive(Clone, Copy)]
+struct Shape {
+    rows: usize,
+    cols: usize,
+    inner: usize,
+}
+
+/// Works out the tile of a product whose top left corner is `corner`, as
+/// `(row, col)`, row by row. `right` holds the right hand side transposed,
+/// so every item of the tile is the dot product of a row of `left` and a
+/// row of `right`, and the rows are walked `tile` items at a time.
+fn tile_product(
+    left: &[f64],
+    right: &[f64],
+    shape: Shape,
+    (row, col): (usize, usize),
+    tile: usize,
+) -> Vec<f64> {
+    let Shape {rows, cols, inner} = shape;
+    let (row_end, col_end) = ((row + tile).min(rows), (col + tile).min(cols));
+    let width = col_end - col;
+    let mut block: Vec<f64> = vec![0.0; (row_end - row) * width];
+    for start in (0..inner).step_by(tile) {
+        let end = (start + tile).min(inner);
+        for (index, sum) in block.iter_mut().enumerate() {
+            let from = (row + index / width) * inner;
+            let to = (col + index % width) * inner;
+            *sum += kernels::dot(
+                &left[from + start..from + end],
+                &right[to + start..to + end],
+            );
+        }
+    }
+    return block;
+}
+
+/// Get the top left corner of every tile of a product with `rows` rows and
+/// `cols` columns, row by row.
+fn corners(rows: usize, cols: usize, tile: usize) -> Vec<(usize, usize)> {
+    let mut corners: Vec<(usize, usize)> = Vec::new();
+    for row in (0..rows).step_by(tile) {
+        for col in (0..cols).step_by(tile) {
+            corners.push((row, col));
+        }
+    }
+    return corners;
+}
+
+/// Copies `blocks`, the tiles found by [`tile_product`] for each of
+/// `corners`, into `product`, which has `cols` columns.
+fn place_tiles(
+    product: &mut [f64],
+    cols: usize,
+    tile: usize,
+    corners: &[(usize, usize)],
+    blocks: Vec<Vec<f64>>,
+) {
+    for ((row, col), block) in corners.iter().zip(blocks) {
+        let width = (col + tile).min(cols) - col;
+        for (offset, line) in block.chunks(width).enumerate() {
+            let start = (row + offset) * cols + col;
+            product[start..start + width].copy_from_slice(line);
+        }
+    }
+}
+
+/// Multiplies the square matrices `a` and `b`, which have `size` rows and
+/// columns, tile by tile on the calling thread.
+pub(super) fn blocked_product(
+    a: &[f64],
+    b: &[f64],
+    size: usize,
+    tile: usize,
+) -> Vec<f64> {
+    let mut product: Vec<f64> = vec![0.0; size * size];
+    if size == 0 {
+        return product;
+    }
+    let mut right = b.to_vec();
+    transpose_block(b, size, size, &mut right, 0, (0, size), (0, size));
+    let shape = Shape {rows: size, cols: size, inner: size};
+    let corners = corners(size, size, tile);
+    let blocks: Vec<Vec<f64>> = corners
+        .iter()
+        .map(|corner| tile_product(a, &right, shape, *corner, tile))
+        .collect();
+    place_tiles(&mut product, size, tile, &corners, blocks);
+    return product;
+}
+
+/// This `struct` multiplies, transposes and combines [`Matrix`]es using
+/// multiple threads.
+/// 
+/// [`MtdMatrix::matmul`] splits the product into square tiles, each of
+/// which is worked out by its own job. The right hand side is transposed
+/// first, so every item of a tile is the dot product of a row of each
+/// matrix, and the rows are walked a tile's width at a time so that the
+/// rows feeding a tile stay in cache.
+pub struct MtdMatrix {
+    pub(super) pool: ThreadPool,
+    pub(super) tile: usize,
+    pub(super) strassen_cutoff: usize,
+    pub(super) tolerance: f64,
+    pub(super) max_iterations: usize,
+}
+
+impl MtdMatrix {
+    /// Creates a new [`MtdMatrix`] instance with tiles of [`DEFAULT_TILE`],
+    /// a Strassen cutoff of [`DEFAULT_STRASSEN_CUTOFF`], and iterative
+    /// solvers using [`DEFAULT_TOLERANCE`] and [`DEFAULT_MAX_ITERATIONS`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {
+            pool,
+            tile: DEFAULT_TILE,
+            strassen_cutoff: DEFAULT_STRASSEN_CUTOFF,
+            tolerance: DEFAULT_TOLERANCE,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        });
+    }
+
+    /// Get the width and height of the tiles of a product.
+    pub fn tile(&self) -> usize {
+        return self.tile;
+    }
+
+    /// Sets the width and height of the tiles of a product. Smaller tiles
+    /// make more jobs, and larger tiles need more cache.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `tile` is `0`, and the tile size
+    /// is left as it was.
+    pub fn set_tile(&mut self, tile: usize) -> Result<(), Error> {
+        if tile == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The tiles must be at least 1 item wide."
+            ));
+        }
+        self.tile = tile;
+        return Ok(());
+    }
+
+    /// Multiplies `a` by `b`. See [`MtdMatrix`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `a` does not have as many
+    /// columns as `b` has rows, or if a job on the [`ThreadPool`] fails.
+    pub fn matmul(&mut self, a: &Matrix, b: &Matrix) -> Result<Matrix, Error> {
+        if a.cols != b.rows {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "A {}x{} matrix cannot be multiplied by a {}x{} matrix.",
+                    a.rows, a.cols, b.rows, b.cols
+                )
+            ));
+        }
+        let (rows, cols, inner) = (a.rows, b.cols, a.cols);
+        if rows == 0 || cols == 0 {
+            return Ok(Matrix::zeros(rows, cols));
+        }
+        let left = Arc::new(a.data.clone());
+        let right = Arc::new(transpose_parallel(&mut self.pool, &b.data, inner, cols)?);
+
+        let tile = self.tile;
+        let corners = Arc::new(corners(rows, cols, tile));
+        let policy = ChunkPolicy::Fixed(1);
+        let blocks = self.pool.map(corners.clone(), policy, move |corner| {
+            let shape = Shape {rows, cols, inner};
+            return tile_product(&left, &right, shape, *corner, tile);
+        })?;
+
+        let mut product = Matrix::zeros(rows, cols);
+        place_tiles(&mut product.data, cols, tile, &corners, blocks);
+        return Ok(product);
+    }
+
+    /// Transposes `a`, using [`transpose_parallel`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn transpose(&mut self, a: &Matrix) -> Result<Matrix, Error> {
+        let data = transpose_parallel(&mut self.pool, &a.data, a.rows, a.cols)?;
+        return Ok(Matrix {rows: a.cols, cols: a.rows, data});
+    }
+
+    /// Applies `function` to every item of `a`. The items are split into a
+    /// range per thread, row by row, and each range is mapped by its own
+    /// job, so a range may start or end part of the way through a row.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn map<F>(&mut self, a: &Matrix, function: F) -> Result<Matrix, Error>
+    where
+        F: Fn(f64) -> f64 + Send + Sync + 'static,
+    {
+        let items = Arc::new(a.data.clone());
+        let pieces = ranges(items.len(), self.pool.threads());
+        let policy = ChunkPolicy::Fixed(1);
+        let mapped = self.pool.map(Arc::new(pieces), policy, move |piece| {
+            let (first, end) = *piece;
+            return items[first..end]
+                .iter()
+                .map(|item| function(*item))
+                .collect::<Vec<f64>>();
+        })?;
+        let data: Vec<f64> = mapped.into_iter().flatten().collect();
+        return Ok(Matrix {rows: a.rows, cols: a.cols, data});
+    }
+
+    /// Combines the items of `a` and `b` in the same places with
+    /// `function`. The items are split into a range per thread, row by
+    /// row, and each range is combined by its own job, so a range may
+    /// start or end part of the way through a row.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `a` and `b` do not have the same
+    /// shape, or if a job on the [`ThreadPool`] fails.
+    pub fn zip_with<F>(
+        &mut self,
+        a: &Matrix,
+        b: &Matrix,
+        function: F,
+    ) -> Result<Matrix, Error>
+    where
+        F: Fn(f64, f64) -> f64 + Send + Sync + 'static,
+    {
+        if a.rows != b.rows || a.cols != b.cols {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "A {}x{} matrix cannot be combined with a {}x{} matrix.",
+                    a.rows, a.cols, b.rows, b.cols
+                )
+            ));
+        }
+        let pairs = Arc::new((a.data.clone(), b.data.clone()));
+        let pieces = ranges(a.data.len(), self.pool.threads());
+        let policy = ChunkPolicy::Fixed(1);
+        let zipped = self.pool.map(Arc::new(pieces), policy, move |piece| {
+            let (first, end) = *piece;
+            let (left, right) = &*pairs;
+            return left[first..end]
+                .iter()
+                .zip(&right[first..end])
+                .map(|(x, y)| function(*x, *y))
+                .collect::<Vec<f64>>();
+        })?;
+        let data: Vec<f64> = zipped.into_iter().flatten().collect();
+        return Ok(Matrix {rows: a.rows, cols: a.cols, data});
+    }
+
+    /// Adds `b` to `a`. See [`MtdMatrix::zip_with`].
+    pub fn add(&mut self, a: &Matrix, b: &Matrix) -> Result<Matrix, Error> {
+        return self.zip_with(a, b, |x, y| x + y);
+    }
+
+    /// Subtracts `b` from `a`. See [`MtdMatrix::zip_with`].
+    pub fn sub(&mut self, a: &Matrix, b: &Matrix) -> Result<Matrix, Error> {
+        return self.zip_with(a, b, |x, y| x - y);
+    }
+
+    /// Multiplies the items of `a` and `b` in the same places, which is the
+    /// Hadamard product. See [`MtdMatrix::zip_with`].
+    pub fn hadamard(&mut self, a: &Matrix, b: &Matrix) -> Result<Matrix, Error> {
+        return self.zip_with(a, b, |x, y| x * y);
+    }
+
+    /// Multiplies every item of `a` by `factor`. See [`MtdMatrix::map`].
+    pub fn scale(&mut self, a: &Matrix, factor: f64) -> Result<Matrix, Error> {
+        return self.map(a, move |x| x * factor);
+    }
+}
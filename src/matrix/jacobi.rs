@@ -0,0 +1,162 @@
+//! The Jacobi method, which solves linear systems by iterating, and the
+//! tolerance and iteration limit of the iterative solvers of
+//! [`MtdMatrix`].
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use super::{Matrix, dense::MtdMatrix};
+use crate::pool::chunk::{ChunkPolicy, ranges};
+
+/// The tolerance used unless another is set with
+/// [`MtdMatrix::set_tolerance`].
+pub const DEFAULT_TOLERANCE: f64 = 1e-10;
+/// The most iterations run unless another limit is set with
+/// [`MtdMatrix::set_max_iterations`].
+pub const DEFAULT_MAX_ITERATIONS: usize = 1000;
+
+/// The solution of a linear system found by iterating, such as by
+/// [`MtdMatrix::jacobi_solve`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solution {
+    /// The solution after the last iteration.
+    pub x: Vec<f64>,
+    /// Number of iterations run.
+    pub iterations: usize,
+    /// Whether the solution settled within the tolerance before the most
+    /// iterations allowed were run.
+    pub converged: bool,
+}
+
+/// Get the larger of 2 changes of a solution, counting a change which is
+/// not a number as the largest so that diverging solutions never settle.
+fn larger(change: f64, other: f64) -> f64 {
+    return if other.is_nan() || other > change { other } else { change };
+}
+
+impl MtdMatrix {
+    /// Get the tolerance of the iterative solvers, such as
+    /// [`MtdMatrix::jacobi_solve`].
+    pub fn tolerance(&self) -> f64 {
+        return self.tolerance;
+    }
+
+    /// Sets the tolerance of the iterative solvers. See each solver for how
+    /// it is compared with the solution.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `tolerance` is negative or not a
+    /// number, and the tolerance is left as it was.
+    pub fn set_tolerance(&mut self, tolerance: f64) -> Result<(), Error> {
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The tolerance {} is not a non-negative number.", tolerance)
+            ));
+        }
+        self.tolerance = tolerance;
+        return Ok(());
+    }
+
+    /// Get the most iterations run by the iterative solvers.
+    pub fn max_iterations(&self) -> usize {
+        return self.max_iterations;
+    }
+
+    /// Sets the most iterations run by the iterative solvers. The solution
+    /// is returned as it is after the last one even if it has not settled
+    /// yet.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Solves `a * x = b` for `x` with the Jacobi method, starting from
+    /// zeros.
+    /// 
+    /// Each iteration works out every item of `x` again from row `row` of
+    /// `a` and the `x` of the iteration before, as
+    /// `(b[row] - sum of a[row][col] * x[col] for col != row) / a[row][row]`.
+    /// No row needs another row's new item, so the rows are split into a
+    /// range per thread, each updated by its own job. The iterations stop
+    /// once no item of `x` changes by more than
+    /// [`MtdMatrix::tolerance`], or after [`MtdMatrix::max_iterations`].
+    /// 
+    /// The iterations are sure to settle when `a` is strictly diagonally
+    /// dominant, with every item on the diagonal larger in size than the
+    /// rest of its row put together. Otherwise they may not settle, and
+    /// [`Solution::converged`] is `false`.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `a` is not square or has a zero
+    /// on its diagonal, if `b` does not hold an item per row of `a`, or if
+    /// a job on the [`ThreadPool`] fails.
+    /// 
+    /// [`ThreadPool`]: crate::pool::ThreadPool
+    pub fn jacobi_solve(&mut self, a: &Matrix, b: &[f64]) -> Result<Solution, Error> {
+        if !a.is_square() || b.len() != a.rows() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "A {}x{} matrix and {} items on the right hand side are \
+                    not a square linear system.",
+                    a.rows(), a.cols(), b.len()
+                )
+            ));
+        }
+        let size = a.rows();
+        if let Some(row) = (0..size).find(|row| a.get(*row, *row) == 0.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Row {} has a zero on the diagonal.", row)
+            ));
+        }
+        let mut solution = Solution {
+            x: vec![0.0; size],
+            iterations: 0,
+            converged: size == 0,
+        };
+        let system = Arc::new((a.clone(), b.to_vec()));
+        let pieces = Arc::new(ranges(size, self.pool.threads()));
+        while !solution.converged && solution.iterations < self.max_iterations {
+            let shared = Arc::new(solution.x);
+            let system = system.clone();
+            let policy = ChunkPolicy::Fixed(1);
+            let updated = self.pool.map(pieces.clone(), policy, move |piece| {
+                let (first, end) = *piece;
+                let (a, b) = &*system;
+                let mut x: Vec<f64> = Vec::with_capacity(end - first);
+                let mut change: f64 = 0.0;
+                for row in first..end {
+                    let line = a.row(row);
+                    let sum: f64 = line
+                        .iter()
+                        .zip(shared.iter())
+                        .enumerate()
+                        .filter(|(col, _)| *col != row)
+                        .map(|(_, (item, value))| item * value)
+                        .sum();
+                    let next = (b[row] - sum) / line[row];
+                    change = larger(change, (next - shared[row]).abs());
+                    x.push(next);
+                }
+                return (x, change);
+            })?;
+
+            let mut next: Vec<f64> = Vec::with_capacity(size);
+            let mut change: f64 = 0.0;
+            for (x, part) in updated {
+                next.extend(x);
+                change = larger(change, part);
+            }
+            solution.x = next;
+            solution.iterations += 1;
+            solution.converged = change <= self.tolerance;
+        }
+        return Ok(solution);
+    }
+}
@@ -0,0 +1,286 @@
+//! LU decomposition of square [`Matrix`]es with partial pivoting, and the
+//! linear systems and determinants it makes cheap to work out.
+
+use std::{
+    io::{Error, ErrorKind},
+    mem,
+    sync::{Arc, Mutex},
+};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use super::{Matrix, dense::MtdMatrix};
+use crate::{pool::chunk::ChunkPolicy, unwrapmutex, unwrapoption};
+
+/// The fewest items a job of [`MtdMatrix::lu`] updates in one step. Steps
+/// with fewer items left to update below the pivot run on the calling
+/// thread.
+const MIN_JOB_ITEMS: usize = 4096;
+
+/// The LU decomposition of a square matrix `A` with partial pivoting, found
+/// by [`MtdMatrix::lu`]. `P * A = L * U`, where `P` moves row
+/// `pivots[index]` of `A` to row `index`, `L` is lower triangular with ones
+/// on its diagonal and `U` is upper triangular.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "LuData"))]
+pub struct Lu {
+    factors: Matrix,
+    pivots: Vec<usize>,
+    swaps: usize,
+}
+
+/// The fields of an [`Lu`] as they are deserialized, before they are
+/// checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct LuData {
+    factors: Matrix,
+    pivots: Vec<usize>,
+    swaps: usize,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<LuData> for Lu {
+    type Error = Error;
+
+    /// Checks that the factors are square and that there is a pivot per
+    /// row of them, each of which is a different row.
+    fn try_from(data: LuData) -> Result<Self, Error> {
+        let LuData {factors, pivots, swaps} = data;
+        let size = factors.rows();
+        if !factors.is_square() || pivots.len() != size {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Expected square factors and a pivot per row, but got \
+                    {}x{} factors and {} pivots.",
+                    size, factors.cols(), pivots.len()
+                )
+            ));
+        }
+        let mut seen: Vec<bool> = vec![false; size];
+        for pivot in &pivots {
+            if *pivot >= size || seen[*pivot] {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("The pivot {} is repeated or not below {}.", pivot, size)
+                ));
+            }
+            seen[*pivot] = true;
+        }
+        return Ok(Self {factors, pivots, swaps});
+    }
+}
+
+impl Lu {
+    /// Get the number of rows and columns of the matrix decomposed.
+    pub fn size(&self) -> usize {
+        return self.factors.rows();
+    }
+
+    /// Get `L` and `U` in one [`Matrix`], `L` below the diagonal and `U`
+    /// on and above it. The ones on the diagonal of `L` are left out.
+    pub fn factors(&self) -> &Matrix {
+        return &self.factors;
+    }
+
+    /// Get the row of the matrix decomposed which was moved to each row.
+    pub fn pivots(&self) -> &[usize] {
+        return &self.pivots;
+    }
+
+    /// Get the lower triangular `L`, with ones on its diagonal.
+    pub fn lower(&self) -> Matrix {
+        let size = self.size();
+        let mut lower = Matrix::identity(size);
+        for row in 0..size {
+            for col in 0..row {
+                lower.set(row, col, self.factors.get(row, col));
+            }
+        }
+        return lower;
+    }
+
+    /// Get the upper triangular `U`.
+    pub fn upper(&self) -> Matrix {
+        let size = self.size();
+        let mut upper = Matrix::zeros(size, size);
+        for row in 0..size {
+            for col in row..size {
+                upper.set(row, col, self.factors.get(row, col));
+            }
+        }
+        return upper;
+    }
+
+    /// Whether the matrix decomposed is singular, which is when `U` has a
+    /// zero on its diagonal.
+    pub fn is_singular(&self) -> bool {
+        return (0..self.size()).any(|index| self.factors.get(index, index) == 0.0);
+    }
+
+    /// Get the determinant of the matrix decomposed, which is the product
+    /// of the diagonal of `U`, negated for every row swap.
+    pub fn determinant(&self) -> f64 {
+        let product: f64 = (0..self.size())
+            .map(|index| self.factors.get(index, index))
+            .product();
+        return if self.swaps.is_multiple_of(2) { product } else { -product };
+    }
+
+    /// Solves `A * x = b` for `x`, where `A` is the matrix decomposed, by
+    /// substituting forwards through `L` and backwards through `U` on the
+    /// calling thread.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `b` does not hold an item per
+    /// row of `A` or if `A` is singular.
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, Error> {
+        let size = self.size();
+        if b.len() != size {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Expected {} items on the right hand side but got {}.",
+                    size, b.len()
+                )
+            ));
+        }
+        if self.is_singular() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The matrix is singular, so the system has no single solution."
+            ));
+        }
+        let mut x: Vec<f64> = self.pivots.iter().map(|pivot| b[*pivot]).collect();
+        for row in 0..size {
+            let line = self.factors.row(row);
+            let sum: f64 = (0..row).map(|col| line[col] * x[col]).sum();
+            x[row] -= sum;
+        }
+        for row in (0..size).rev() {
+            let line = self.factors.row(row);
+            let sum: f64 = (row + 1..size).map(|col| line[col] * x[col]).sum();
+            x[row] = (x[row] - sum) / line[row];
+        }
+        return Ok(x);
+    }
+}
+
+/// Eliminates column `step` from `row` using `pivot`, the pivot row of the
+/// step, leaving the factor of `L` in its place.
+fn eliminate(row: &mut [f64], pivot: &[f64], step: usize) {
+    let factor = row[step] / pivot[step];
+    row[step] = factor;
+    if factor == 0.0 {
+        return;
+    }
+    for (item, above) in row[step + 1..].iter_mut().zip(&pivot[step + 1..]) {
+        *item -= factor * above;
+    }
+}
+
+impl MtdMatrix {
+    /// Decomposes the square matrix `a` into [`Lu`] with partial pivoting.
+    /// 
+    /// Each step moves the row with the largest item in the column of the
+    /// step, on or below the diagonal, up to the diagonal, then eliminates
+    /// that column from every row below it. The rows below the pivot are
+    /// split into a range per thread, each updated by its own job, until
+    /// too few items are left for the jobs to pay off. Columns with nothing
+    /// but zeros to pivot on are skipped, so singular matrices decompose
+    /// too, with a zero on the diagonal of `U`.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `a` is not square, or if a job
+    /// on the [`ThreadPool`] fails.
+    /// 
+    /// [`ThreadPool`]: crate::pool::ThreadPool
+    pub fn lu(&mut self, a: &Matrix) -> Result<Lu, Error> {
+        if !a.is_square() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Only square matrices can be decomposed, not a {}x{} matrix.",
+                    a.rows(), a.cols()
+                )
+            ));
+        }
+        let size = a.rows();
+        let rows: Arc<Vec<Mutex<Vec<f64>>>> = Arc::new(
+            (0..size).map(|row| Mutex::new(a.row(row).to_vec())).collect()
+        );
+        let mut pivots: Vec<usize> = (0..size).collect();
+        let mut swaps = 0;
+
+        for step in 0..size {
+            let mut best = step;
+            let mut largest = 0.0;
+            for row in step..size {
+                let item = unwrapmutex!(rows[row].lock())[step].abs();
+                if item > largest {
+                    (best, largest) = (row, item);
+                }
+            }
+            if largest == 0.0 {
+                continue;
+            }
+            if best != step {
+                let mut upper = unwrapmutex!(rows[step].lock());
+                mem::swap(&mut *upper, &mut *unwrapmutex!(rows[best].lock()));
+                pivots.swap(step, best);
+                swaps += 1;
+            }
+            let pivot = Arc::new(unwrapmutex!(rows[step].lock()).clone());
+
+            let below = size - step - 1;
+            let jobs = self.pool.threads().min(below * (size - step) / MIN_JOB_ITEMS);
+            if jobs < 2 {
+                for row in step + 1..size {
+                    eliminate(&mut unwrapmutex!(rows[row].lock()), &pivot, step);
+                }
+                continue;
+            }
+            let count = below.div_ceil(jobs);
+            let pieces: Vec<(usize, usize)> = (step + 1..size)
+                .step_by(count)
+                .map(|first| (first, (first + count).min(size)))
+                .collect();
+            let shared = rows.clone();
+            let policy = ChunkPolicy::Fixed(1);
+            let updated = self.pool.map(Arc::new(pieces), policy, move |piece| {
+                let (first, end) = *piece;
+                for row in first..end {
+                    eliminate(&mut unwrapmutex!(shared[row].lock()), &pivot, step);
+                }
+                return Ok(());
+            })?;
+            updated.into_iter().collect::<Result<(), Error>>()?;
+        }
+
+        let mut data: Vec<f64> = Vec::with_capacity(size * size);
+        for row in unwrapoption!(Arc::try_unwrap(rows).ok()) {
+            data.append(&mut unwrapmutex!(row.into_inner()));
+        }
+        let factors = Matrix::new(size, size, data)?;
+        return Ok(Lu {factors, pivots, swaps});
+    }
+
+    /// Solves `a * x = b` for `x`, decomposing `a` with [`MtdMatrix::lu`]
+    /// and solving with [`Lu::solve`]. Decompose `a` once with
+    /// [`MtdMatrix::lu`] instead to solve for many `b`s.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `a` is not square or is
+    /// singular, if `b` does not hold an item per row of `a`, or if a job on
+    /// the [`ThreadPool`] fails.
+    /// 
+    /// [`ThreadPool`]: crate::pool::ThreadPool
+    pub fn solve(&mut self, a: &Matrix, b: &[f64]) -> Result<Vec<f64>, Error> {
+        return self.lu(a)?.solve(b);
+    }
+}
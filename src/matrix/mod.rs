@@ -0,0 +1,275 @@
+//! Module for dense matrices stored as flat slices.
+//! 
+//! A matrix with `rows` rows and `cols` columns is stored in a slice of
+//! `rows * cols` items, either row by row ([`Layout::RowMajor`]) or column by
+//! column ([`Layout::ColumnMajor`]). Transposing uses cache-oblivious
+//! recursion, halving the longer side of the block until it fits in cache,
+//! and [`transpose_parallel`] splits the work across a [`ThreadPool`].
+//! 
+//! [`Matrix`] holds a dense matrix of [`f64`] row by row, and [`MtdMatrix`]
+//! multiplies, transposes and combines [`Matrix`]es on a [`ThreadPool`].
+//! Large square [`Matrix`]es can also be multiplied with Strassen's
+//! algorithm through [`MtdMatrix::strassen`], and [`MtdMatrix::lu`]
+//! decomposes square [`Matrix`]es into [`Lu`] to solve linear systems and
+//! find determinants. [`MtdMatrix::jacobi_solve`] solves diagonally
+//! dominant linear systems by iterating instead.
+//! 
+//! [`SparseMatrix`] holds a sparse matrix of [`f64`] in the compressed
+//! sparse row layout, such as the Laplacian of a graph, and
+//! [`MtdMatrix::cg_solve`] solves symmetric positive definite systems of
+//! them with the conjugate gradient method.
+
+use std::{
+    io::{Error, ErrorKind},
+    mem::size_of,
+    sync::{Arc, Mutex},
+};
+
+use crate::{estimate::Estimate, pool::ThreadPool, unwrapmutex};
+
+mod cg;
+mod dense;
+mod jacobi;
+mod lu;
+mod sparse;
+mod strassen;
+
+pub use dense::{DEFAULT_TILE, Matrix, MtdMatrix};
+pub use jacobi::{DEFAULT_MAX_ITERATIONS, DEFAULT_TOLERANCE, Solution};
+pub use lu::Lu;
+pub use sparse::SparseMatrix;
+pub use strassen::DEFAULT_STRASSEN_CUTOFF;
+
+/// Blocks with at most this many items are transposed with a plain loop.
+const LEAF_ITEMS: usize = 32 * 32;
+
+/// Minimum number of columns of the source matrix given to one job.
+const MIN_JOB_COLS: usize = 32;
+
+/// How the items of a matrix are ordered in its slice.
+/// 
+/// # Variants
+/// 
+/// 1. RowMajor => Each row is stored contiguously, one after another.
+/// 2. ColumnMajor => Each column is stored contiguously, one after another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    RowMajor,
+    ColumnMajor,
+}
+
+/// Checks that `data` holds exactly `rows * cols` items.
+fn check_shape<T>(data: &[T], rows: usize, cols: usize) -> Result<(), Error> {
+    let expected = match rows.checked_mul(cols) {
+        Some(expected) => expected,
+        None => return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("A {}x{} matrix is too large.", rows, cols)
+        )),
+    };
+    if data.len() != expected {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Expected {} items for a {}x{} matrix but got {}.",
+                expected, rows, cols, data.len()
+            )
+        ));
+    }
+    return Ok(());
+}
+
+/// Transposes the block of rows `row_start..row_end` and columns
+/// `col_start..col_end` of `source` (a row-major matrix with `cols` columns)
+/// into `target`, a row-major matrix with `rows` columns whose first row is
+/// column `col_offset` of `source`.
+#[allow(clippy::too_many_arguments)]
+fn transpose_block<T: Copy>(
+    source: &[T],
+    rows: usize,
+    cols: usize,
+    target: &mut [T],
+    col_offset: usize,
+    (row_start, row_end): (usize, usize),
+    (col_start, col_end): (usize, usize),
+) {
+    let height = row_end - row_start;
+    let width = col_end - col_start;
+    if height * width <= LEAF_ITEMS {
+        for row in row_start..row_end {
+            for col in col_start..col_end {
+                target[(col - col_offset) * rows + row] =
+                    source[row * cols + col];
+            }
+        }
+    } else if height >= width {
+        let middle = row_start + height / 2;
+        transpose_block(
+            source, rows, cols, target, col_offset,
+            (row_start, middle), (col_start, col_end),
+        );
+        transpose_block(
+            source, rows, cols, target, col_offset,
+            (middle, row_end), (col_start, col_end),
+        );
+    } else {
+        let middle = col_start + width / 2;
+        transpose_block(
+            source, rows, cols, target, col_offset,
+            (row_start, row_end), (col_start, middle),
+        );
+        transpose_block(
+            source, rows, cols, target, col_offset,
+            (row_start, row_end), (middle, col_end),
+        );
+    }
+}
+
+/// Transposes a row-major matrix with `rows` rows and `cols` columns on the
+/// current thread, returning a row-major matrix with `cols` rows and `rows`
+/// columns.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `data` does not hold `rows * cols`
+/// items.
+pub fn transpose<T: Copy>(
+    data: &[T],
+    rows: usize,
+    cols: usize,
+) -> Result<Vec<T>, Error> {
+    check_shape(data, rows, cols)?;
+    let mut target = data.to_vec();
+    transpose_block(data, rows, cols, &mut target, 0, (0, rows), (0, cols));
+    return Ok(target);
+}
+
+/// Transposes a row-major matrix with `rows` rows and `cols` columns using
+/// the worker threads in `pool`, returning a row-major matrix with `cols`
+/// rows and `rows` columns.
+/// 
+/// The columns of `data` are split into bands and each band is transposed by
+/// its own job, so every job writes to a contiguous range of rows in the
+/// result.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The pool to run the jobs in.
+/// 2. ```data: &[T]``` => The matrix to transpose, in row-major order.
+/// 3. ```rows: usize``` => Number of rows in `data`.
+/// 4. ```cols: usize``` => Number of columns in `data`.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `data` does not hold `rows * cols`
+/// items or if a job fails.
+pub fn transpose_parallel<T>(
+    pool: &mut ThreadPool,
+    data: &[T],
+    rows: usize,
+    cols: usize,
+) -> Result<Vec<T>, Error>
+where
+    T: Copy + Send + Sync + 'static,
+{
+    check_shape(data, rows, cols)?;
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    let source = Arc::new(data.to_vec());
+    let target = Arc::new(Mutex::new(data.to_vec()));
+    let jobs = (pool.threads() * 4).min(cols.div_ceil(MIN_JOB_COLS)).max(1);
+    let band = cols.div_ceil(jobs);
+
+    for col_start in (0..cols).step_by(band) {
+        let col_end = (col_start + band).min(cols);
+        let source = source.clone();
+        let target = target.clone();
+        pool.execute(move || {
+            let mut local = source[..(col_end - col_start) * rows].to_vec();
+            transpose_block(
+                &source, rows, cols, &mut local, col_start,
+                (0, rows), (col_start, col_end),
+            );
+            unwrapmutex!(target.lock())[col_start * rows..col_end * rows]
+                .copy_from_slice(&local);
+            return Ok(());
+        })?;
+    }
+    pool.wait()?;
+
+    return Ok(unwrapmutex!(target.lock()).clone());
+}
+
+/// Estimates the cost of [`transpose_parallel`] on a matrix of `T` with
+/// `rows` rows and `cols` columns. Besides the result, the input is copied
+/// once to share it with the jobs and each band is transposed into a
+/// temporary buffer, so up to 3 copies of the matrix exist at once.
+pub fn estimate_transpose<T>(rows: usize, cols: usize) -> Estimate {
+    let items = rows as u128 * cols as u128;
+    return Estimate::new(3 * items * size_of::<T>() as u128, items);
+}
+
+/// Converts a matrix with `rows` rows and `cols` columns from the `from`
+/// [`Layout`] to the `to` [`Layout`] using the worker threads in `pool`. If
+/// both [`Layout`]s are the same, the data is copied as it is.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `data` does not hold `rows * cols`
+/// items or if a job fails.
+pub fn convert_layout<T>(
+    pool: &mut ThreadPool,
+    data: &[T],
+    rows: usize,
+    cols: usize,
+    from: Layout,
+    to: Layout,
+) -> Result<Vec<T>, Error>
+where
+    T: Copy + Send + Sync + 'static,
+{
+    check_shape(data, rows, cols)?;
+    return match (from, to) {
+        (Layout::RowMajor, Layout::RowMajor)
+        | (Layout::ColumnMajor, Layout::ColumnMajor) => Ok(data.to_vec()),
+        // A row-major matrix read column by column is its transpose.
+        (Layout::RowMajor, Layout::ColumnMajor) => {
+            transpose_parallel(pool, data, rows, cols)
+        },
+        (Layout::ColumnMajor, Layout::RowMajor) => {
+            transpose_parallel(pool, data, cols, rows)
+        },
+    };
+}
+
+/// Converts a row-major matrix with `rows` rows and `cols` columns into
+/// column-major order using `pool`.
+pub fn to_column_major<T>(
+    pool: &mut ThreadPool,
+    data: &[T],
+    rows: usize,
+    cols: usize,
+) -> Result<Vec<T>, Error>
+where
+    T: Copy + Send + Sync + 'static,
+{
+    return convert_layout(
+        pool, data, rows, cols, Layout::RowMajor, Layout::ColumnMajor
+    );
+}
+
+/// Converts a column-major matrix with `rows` rows and `cols` columns into
+/// row-major order using `pool`.
+pub fn to_row_major<T>(
+    pool: &mut ThreadPool,
+    data: &[T],
+    rows: usize,
+    cols: usize,
+) -> Result<Vec<T>, Error>
+where
+    T: Copy + Send + Sync + 'static,
+{
+    return convert_layout(
+        pool, data, rows, cols, Layout::ColumnMajor, Layout::RowMajor
+    );
+}
@@ -0,0 +1,328 @@
+//! Sparse matrices of [`f64`] in the compressed sparse row layout, and
+//! multiplying them by vectors using multiple threads.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+
+use super::{Matrix, dense::MtdMatrix};
+use crate::{
+    dijkstra::simple::CsrGraph,
+    pool::chunk::ChunkPolicy,
+};
+
+/// A sparse matrix of [`f64`] in the compressed sparse row layout.
+/// 
+/// Only the items which were set are stored, row by row, with
+/// `offsets[row]` to `offsets[row + 1]` marking the items of `row` in
+/// `columns` and `values`. The columns of each row are in order.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SparseMatrixData"))]
+pub struct SparseMatrix {
+    cols: usize,
+    offsets: Vec<usize>,
+    columns: Vec<usize>,
+    values: Vec<f64>,
+}
+
+/// The fields of a [`SparseMatrix`] as they are deserialized, before they
+/// are checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SparseMatrixData {
+    cols: usize,
+    offsets: Vec<usize>,
+    columns: Vec<usize>,
+    values: Vec<f64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SparseMatrixData> for SparseMatrix {
+    type Error = Error;
+
+    /// Checks that there is a value per column, that `offsets` starts at
+    /// `0`, never goes down and ends at the number of values, and that the
+    /// columns of every row are in order and inside of the matrix.
+    fn try_from(data: SparseMatrixData) -> Result<Self, Error> {
+        let SparseMatrixData {cols, offsets, columns, values} = data;
+        let stored = values.len();
+        if columns.len() != stored
+            || offsets.first() != Some(&0)
+            || offsets.last() != Some(&stored)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Expected offsets from 0 to {} and as many columns as \
+                    values, but got {} columns.",
+                    stored, columns.len()
+                )
+            ));
+        }
+        if let Some(row) = offsets.windows(2).position(|pair| pair[0] > pair[1]) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The offsets go down after row {}.", row)
+            ));
+        }
+        for (row, pair) in offsets.windows(2).enumerate() {
+            let line = &columns[pair[0]..pair[1]];
+            let sorted = line.windows(2).all(|cols| cols[0] < cols[1]);
+            if !sorted || line.last().is_some_and(|col| *col >= cols) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "The columns of row {} are not in order or not \
+                        below {}.",
+                        row, cols
+                    )
+                ));
+            }
+        }
+        return Ok(Self {cols, offsets, columns, values});
+    }
+}
+
+impl SparseMatrix {
+    /// Creates a [`SparseMatrix`] with `rows` rows and `cols` columns from
+    /// `entries`, each of which is `(row, col, value)`. The values of
+    /// entries in the same place are added together.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if an entry is outside of the
+    /// matrix.
+    pub fn from_triplets<I>(
+        rows: usize,
+        cols: usize,
+        entries: I,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = (usize, usize, f64)>,
+    {
+        let entries: Vec<(usize, usize, f64)> = entries.into_iter().collect();
+        if let Some((row, col, _)) = entries
+            .iter()
+            .find(|(row, col, _)| *row >= rows || *col >= cols)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "The entry at ({}, {}) is outside of a {}x{} matrix.",
+                    row, col, rows, cols
+                )
+            ));
+        }
+        return Ok(Self::assemble(rows, cols, entries));
+    }
+
+    /// Creates the Laplacian of `graph`, `D - W`, which has a row and a
+    /// column per [`Node`].
+    /// 
+    /// The [`Cost`] of each edge is its weight, and the graph is treated as
+    /// undirected by giving `W` the mean of the weights of the edges each
+    /// way between 2 [`Node`]s, so a graph whose edges were all pushed both
+    /// ways keeps its weights. `D` holds the sum of each row of `W` on its
+    /// diagonal. Edges from a [`Node`] to itself are left out.
+    /// 
+    /// The Laplacian is symmetric, but only positive semi-definite: every
+    /// connected component adds a way of shifting a solution without
+    /// changing `D - W` times it. [`MtdMatrix::cg_solve`] still settles on
+    /// it as long as the right hand side adds up to `0` over every
+    /// component.
+    /// 
+    /// [`Cost`]: crate::dijkstra::simple::Cost
+    /// [`Node`]: crate::dijkstra::simple::Node
+    pub fn laplacian(graph: &CsrGraph) -> Self {
+        let total = graph.total();
+        let mut entries: Vec<(usize, usize, f64)> = Vec::with_capacity(
+            4 * graph.edges() + total
+        );
+        for from in 0..total {
+            for edge in graph.get_node(from).unwrap_or(&[]) {
+                if edge.node == from {
+                    continue;
+                }
+                let half = edge.cost as f64 / 2.0;
+                entries.push((from, edge.node, -half));
+                entries.push((edge.node, from, -half));
+                entries.push((from, from, half));
+                entries.push((edge.node, edge.node, half));
+            }
+        }
+        entries.extend((0..total).map(|node| (node, node, 0.0)));
+        return Self::assemble(total, total, entries);
+    }
+
+    /// Builds a [`SparseMatrix`] from `entries`, which must all be inside of
+    /// it. See [`SparseMatrix::from_triplets`].
+    fn assemble(
+        rows: usize,
+        cols: usize,
+        mut entries: Vec<(usize, usize, f64)>,
+    ) -> Self {
+        entries.sort_unstable_by_key(|(row, col, _)| (*row, *col));
+        let mut offsets: Vec<usize> = vec![0; rows + 1];
+        let mut columns: Vec<usize> = Vec::with_capacity(entries.len());
+        let mut values: Vec<f64> = Vec::with_capacity(entries.len());
+        let mut last: Option<(usize, usize)> = None;
+        for (row, col, value) in entries {
+            if last == Some((row, col)) {
+                if let Some(total) = values.last_mut() {
+                    *total += value;
+                }
+                continue;
+            }
+            last = Some((row, col));
+            offsets[row + 1] += 1;
+            columns.push(col);
+            values.push(value);
+        }
+        for row in 1..offsets.len() {
+            offsets[row] += offsets[row - 1];
+        }
+        return Self {cols, offsets, columns, values};
+    }
+
+    /// Get the number of rows.
+    pub fn rows(&self) -> usize {
+        return self.offsets.len() - 1;
+    }
+
+    /// Get the number of columns.
+    pub fn cols(&self) -> usize {
+        return self.cols;
+    }
+
+    /// Whether the [`SparseMatrix`] has as many rows as columns.
+    pub fn is_square(&self) -> bool {
+        return self.rows() == self.cols;
+    }
+
+    /// Get the number of items stored.
+    pub fn stored(&self) -> usize {
+        return self.values.len();
+    }
+
+    /// Get the columns and values of the items stored in row `row`, the
+    /// columns in order.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `row` is not below [`SparseMatrix::rows`].
+    pub fn row(&self, row: usize) -> (&[usize], &[f64]) {
+        let (start, end) = (self.offsets[row], self.offsets[row + 1]);
+        return (&self.columns[start..end], &self.values[start..end]);
+    }
+
+    /// Get the item in row `row` and column `col`, which is `0` if it is not
+    /// stored.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `row` or `col` is outside of the [`SparseMatrix`].
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        assert!(col < self.cols, "Column {} is outside of the matrix.", col);
+        let (columns, values) = self.row(row);
+        return columns.binary_search(&col).map_or(0.0, |index| values[index]);
+    }
+
+    /// Get the same matrix as a dense [`Matrix`].
+    pub fn to_dense(&self) -> Matrix {
+        let mut dense = Matrix::zeros(self.rows(), self.cols);
+        for row in 0..self.rows() {
+            let (columns, values) = self.row(row);
+            for (col, value) in columns.iter().zip(values) {
+                dense.set(row, *col, *value);
+            }
+        }
+        return dense;
+    }
+
+    /// Get the item of the product of rows `first..end` and `x`, for each
+    /// of those rows.
+    pub(super) fn multiply_rows(
+        &self,
+        (first, end): (usize, usize),
+        x: &[f64],
+    ) -> Vec<f64> {
+        return (first..end)
+            .map(|row| {
+                let (columns, values) = self.row(row);
+                return columns
+                    .iter()
+                    .zip(values)
+                    .map(|(col, value)| value * x[*col])
+                    .sum::<f64>();
+            })
+            .collect();
+    }
+
+    /// Splits the rows into about `count` ranges holding about as many
+    /// items each, as `(first, end)`. Every row counts as an item too, so
+    /// empty rows are spread out as well.
+    pub(super) fn row_chunks(&self, count: usize) -> Vec<(usize, usize)> {
+        let rows = self.rows();
+        let size = (self.stored() + rows).div_ceil(count.max(1)).max(1);
+        let mut chunks: Vec<(usize, usize)> = Vec::new();
+        let mut first = 0;
+        let weight = |row: usize| self.offsets[row] + row;
+        for row in 0..rows {
+            if weight(row + 1) - weight(first) >= size {
+                chunks.push((first, row + 1));
+                first = row + 1;
+            }
+        }
+        if first < rows {
+            chunks.push((first, rows));
+        }
+        return chunks;
+    }
+}
+
+impl Default for SparseMatrix {
+    /// An empty [`SparseMatrix`] without any rows or columns.
+    fn default() -> Self {
+        return Self {
+            cols: 0,
+            offsets: vec![0],
+            columns: Vec::new(),
+            values: Vec::new(),
+        };
+    }
+}
+
+impl MtdMatrix {
+    /// Multiplies `a` by the vector `x`, a range of rows holding about as
+    /// many items as each other per job.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `x` does not hold an item per
+    /// column of `a`, or if a job on the [`ThreadPool`] fails.
+    /// 
+    /// [`ThreadPool`]: crate::pool::ThreadPool
+    pub fn spmv(&mut self, a: &SparseMatrix, x: &[f64]) -> Result<Vec<f64>, Error> {
+        if x.len() != a.cols() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "A {}x{} matrix cannot be multiplied by {} items.",
+                    a.rows(), a.cols(), x.len()
+                )
+            ));
+        }
+        let shared = Arc::new((a.clone(), x.to_vec()));
+        let chunks = Arc::new(a.row_chunks(self.pool.threads()));
+        let policy = ChunkPolicy::Fixed(1);
+        let parts = self.pool.map(chunks, policy, move |chunk| {
+            let (a, x) = &*shared;
+            return a.multiply_rows(*chunk, x);
+        })?;
+        return Ok(parts.into_iter().flatten().collect());
+    }
+}
@@ -0,0 +1,205 @@
+//! Strassen multiplication of square [`Matrix`]es, which multiplies the
+//! halves of the matrices 7 times instead of 8 and so needs fewer than
+//! `size³` multiplications on large matrices.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use super::{
+    Matrix,
+    dense::{MtdMatrix, blocked_product},
+};
+use crate::pool::chunk::ChunkPolicy;
+
+/// The default size of the matrices [`MtdMatrix::strassen`] multiplies
+/// tile by tile instead of halving them again.
+pub const DEFAULT_STRASSEN_CUTOFF: usize = 128;
+
+/// Get the item in row `row` and column `col` of the square matrix `a`
+/// with `size` rows and columns, or `0` if it is outside of `a`.
+fn padded(a: &[f64], size: usize, row: usize, col: usize) -> f64 {
+    return if row < size && col < size { a[row * size + col] } else { 0.0 };
+}
+
+/// Splits the square matrix `a` with `size` rows and columns into its 4
+/// quarters, top left, top right, bottom left and bottom right. An odd
+/// `size` is padded with zeros to make the quarters the same size.
+fn quarters(a: &[f64], size: usize) -> [Vec<f64>; 4] {
+    let half = size.div_ceil(2);
+    let quarter = |top: usize, left: usize| {
+        let mut quarter: Vec<f64> = Vec::with_capacity(half * half);
+        for row in top..top + half {
+            for col in left..left + half {
+                quarter.push(padded(a, size, row, col));
+            }
+        }
+        return quarter;
+    };
+    return [quarter(0, 0), quarter(0, half), quarter(half, 0), quarter(half, half)];
+}
+
+/// Adds the items of `terms` in the same places, each multiplied by its
+/// sign.
+fn combine(terms: &[(f64, &[f64])]) -> Vec<f64> {
+    let mut sum: Vec<f64> = vec![0.0; terms[0].1.len()];
+    for (sign, term) in terms {
+        for (total, item) in sum.iter_mut().zip(term.iter()) {
+            *total += sign * item;
+        }
+    }
+    return sum;
+}
+
+/// Get the 7 pairs of matrices whose products make up the product of the
+/// square matrices `a` and `b` with `size` rows and columns.
+fn operands(a: &[f64], b: &[f64], size: usize) -> Vec<(Vec<f64>, Vec<f64>)> {
+    let [a11, a12, a21, a22] = quarters(a, size);
+    let [b11, b12, b21, b22] = quarters(b, size);
+    return vec![
+        (combine(&[(1.0, &a11), (1.0, &a22)]), combine(&[(1.0, &b11), (1.0, &b22)])),
+        (combine(&[(1.0, &a21), (1.0, &a22)]), b11.clone()),
+        (a11.clone(), combine(&[(1.0, &b12), (-1.0, &b22)])),
+        (a22.clone(), combine(&[(1.0, &b21), (-1.0, &b11)])),
+        (combine(&[(1.0, &a11), (1.0, &a12)]), b22.clone()),
+        (combine(&[(1.0, &a21), (-1.0, &a11)]), combine(&[(1.0, &b11), (1.0, &b12)])),
+        (combine(&[(1.0, &a12), (-1.0, &a22)]), combine(&[(1.0, &b21), (1.0, &b22)])),
+    ];
+}
+
+/// Puts the product of 2 square matrices with `size` rows and columns
+/// together from the 7 `products` of their [`operands`].
+fn assemble(products: &[Vec<f64>], size: usize) -> Vec<f64> {
+    let half = size.div_ceil(2);
+    let [m1, m2, m3, m4, m5, m6, m7] = [
+        &products[0][..], &products[1][..], &products[2][..], &products[3][..],
+        &products[4][..], &products[5][..], &products[6][..],
+    ];
+    let quarters = [
+        combine(&[(1.0, m1), (1.0, m4), (-1.0, m5), (1.0, m7)]),
+        combine(&[(1.0, m3), (1.0, m5)]),
+        combine(&[(1.0, m2), (1.0, m4)]),
+        combine(&[(1.0, m1), (-1.0, m2), (1.0, m3), (1.0, m6)]),
+    ];
+    let mut product: Vec<f64> = Vec::with_capacity(size * size);
+    for row in 0..size {
+        let (top, inside) = (row / half, row % half);
+        for col in 0..size {
+            let quarter = &quarters[top * 2 + col / half];
+            product.push(quarter[inside * half + col % half]);
+        }
+    }
+    return product;
+}
+
+/// Multiplies the square matrices `a` and `b` with `size` rows and columns
+/// with Strassen's algorithm on the calling thread, multiplying matrices
+/// no larger than `cutoff` tile by tile.
+fn strassen_sequential(
+    a: &[f64],
+    b: &[f64],
+    size: usize,
+    cutoff: usize,
+    tile: usize,
+) -> Vec<f64> {
+    if size <= cutoff {
+        return blocked_product(a, b, size, tile);
+    }
+    let half = size.div_ceil(2);
+    let products: Vec<Vec<f64>> = operands(a, b, size)
+        .iter()
+        .map(|(left, right)| strassen_sequential(left, right, half, cutoff, tile))
+        .collect();
+    return assemble(&products, size);
+}
+
+impl MtdMatrix {
+    /// Get the size of the matrices [`MtdMatrix::strassen`] multiplies tile
+    /// by tile instead of halving them again.
+    pub fn strassen_cutoff(&self) -> usize {
+        return self.strassen_cutoff;
+    }
+
+    /// Sets the size of the matrices [`MtdMatrix::strassen`] multiplies
+    /// tile by tile instead of halving them again. Halving small matrices
+    /// costs more in additions than it saves in multiplications.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `cutoff` is `0`, and the cutoff
+    /// is left as it was.
+    pub fn set_strassen_cutoff(&mut self, cutoff: usize) -> Result<(), Error> {
+        if cutoff == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The Strassen cutoff must be at least 1."
+            ));
+        }
+        self.strassen_cutoff = cutoff;
+        return Ok(());
+    }
+
+    /// Multiplies the square matrices `a` and `b` with Strassen's algorithm.
+    /// 
+    /// Each product is split into the 7 products of the halves of its
+    /// matrices, halves of an odd size being padded with zeros, until there
+    /// are at least as many products as threads or the halves are no larger
+    /// than [`MtdMatrix::strassen_cutoff`]. Each of those products is then
+    /// worked out by its own job, which keeps halving its matrices down to
+    /// the cutoff and multiplies them tile by tile, like
+    /// [`MtdMatrix::matmul`] does. Matrices no larger than the cutoff are
+    /// multiplied with [`MtdMatrix::matmul`] straight away.
+    /// 
+    /// Strassen's algorithm adds and subtracts more than plain
+    /// multiplication does, so the result may differ from
+    /// [`MtdMatrix::matmul`] in the last few bits.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `a` and `b` are not square
+    /// matrices of the same size, or if a job on the [`ThreadPool`] fails.
+    /// 
+    /// [`ThreadPool`]: crate::pool::ThreadPool
+    pub fn strassen(&mut self, a: &Matrix, b: &Matrix) -> Result<Matrix, Error> {
+        if !a.is_square() || !b.is_square() || a.rows() != b.rows() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Strassen multiplication needs square matrices of the \
+                    same size, not {}x{} and {}x{}.",
+                    a.rows(), a.cols(), b.rows(), b.cols()
+                )
+            ));
+        }
+        let size = a.rows();
+        if size <= self.strassen_cutoff {
+            return self.matmul(a, b);
+        }
+        let pairs = vec![(a.data().to_vec(), b.data().to_vec())];
+        let data = self.products(pairs, size)?.remove(0);
+        return Matrix::new(size, size, data);
+    }
+
+    /// Multiplies every pair of `pairs`, which are square matrices with
+    /// `size` rows and columns. See [`MtdMatrix::strassen`].
+    fn products(
+        &mut self,
+        pairs: Vec<(Vec<f64>, Vec<f64>)>,
+        size: usize,
+    ) -> Result<Vec<Vec<f64>>, Error> {
+        let (cutoff, tile) = (self.strassen_cutoff, self.tile);
+        if pairs.len() >= self.pool.threads() || size <= cutoff {
+            let policy = ChunkPolicy::Fixed(1);
+            return self.pool.map(Arc::new(pairs), policy, move |(left, right)| {
+                return strassen_sequential(left, right, size, cutoff, tile);
+            });
+        }
+        let halves: Vec<(Vec<f64>, Vec<f64>)> = pairs
+            .iter()
+            .flat_map(|(left, right)| operands(left, right, size))
+            .collect();
+        let products = self.products(halves, size.div_ceil(2))?;
+        return Ok(products.chunks(7).map(|seven| assemble(seven, size)).collect());
+    }
+}
@@ -0,0 +1,351 @@
+//! Module for finding the maximum flow from a source [`Node`] to a sink
+//! [`Node`], where the [`Cost`] of each edge of an [`AdjacencyMatrix`] is
+//! how much can flow along it.
+//! 
+//! [`max_flow`] uses the push-relabel method. Every [`Node`] has a height,
+//! and flow only runs downhill by one step at a time. The source starts by
+//! sending as much as its edges can carry, and every other [`Node`] holding
+//! more flow than it passed on pushes the extra down to its neighbours, or
+//! rises when it cannot. Once nothing is held up anywhere, the flow is the
+//! largest possible.
+//! 
+//! The work happens in rounds. First, every [`Node`] holding extra flow
+//! works out its pushes at the same time on a [`ThreadPool`], reading the
+//! network as it was at the start of the round. This is safe because flow
+//! only goes downhill, so 2 [`Node`]s never push along the same pair of
+//! edges in one round. The pushes are then applied together, and the
+//! [`Node`]s which still hold extra flow work out their new heights, again
+//! at the same time. Every so often, all the heights are worked out again
+//! from scratch by their distance to the sink, which saves many rounds of
+//! [`Node`]s rising one step at a time.
+
+use std::{
+    collections::VecDeque,
+    io::{Error, ErrorKind},
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    bfs::SEQUENTIAL_FRONTIER,
+    dijkstra::simple::{AdjacencyMatrix, Cost, CsrGraph, Node, check_node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    unwrapmutex,
+};
+
+/// The largest flow found by [`max_flow`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaxFlow {
+    /// How much flows from the source to the sink, which is at most
+    /// [`Cost::MAX`] even if the edges leaving the source could carry more.
+    pub value: Cost,
+    /// How much flows along each edge of the graph, as
+    /// `(from, to, flow)`, in the order of the edges in the graph.
+    pub flows: Vec<(Node, Node, Cost)>,
+    /// Whether each [`Node`] is on the source side of a minimum cut, which
+    /// is a cheapest set of edges whose removal separates the sink from the
+    /// source. The edges from the source side to the other side are all
+    /// used to the full.
+    pub source_side: Vec<bool>,
+}
+
+impl MaxFlow {
+    /// Get the edges of the minimum cut as `(from, to)`. See
+    /// [`MaxFlow::source_side`].
+    pub fn cut(&self) -> Vec<(Node, Node)> {
+        return self
+            .flows
+            .iter()
+            .filter(|(from, to, _)| {
+                return self.source_side[*from] && !self.source_side[*to];
+            })
+            .map(|(from, to, _)| (*from, *to))
+            .collect();
+    }
+}
+
+/// The residual network. Edge `2 * i` is the `i`th edge of the graph and
+/// edge `2 * i + 1` is its reverse, which carries flow back.
+struct Network {
+    /// `arcs[offsets[node]..offsets[node + 1]]` are the edges leaving
+    /// `node`.
+    offsets: Vec<usize>,
+    arcs: Vec<usize>,
+    /// The [`Node`] each edge leads to.
+    heads: Vec<Node>,
+    /// How much more can flow along each edge.
+    residual: Vec<Cost>,
+    /// How much more flow has come into each [`Node`] than has left it.
+    excess: Vec<Cost>,
+    heights: Vec<usize>,
+}
+
+impl Network {
+    /// Get the edges leaving `node`.
+    fn arcs(&self, node: Node) -> &[usize] {
+        return &self.arcs[self.offsets[node]..self.offsets[node + 1]];
+    }
+
+    /// Works out how `node` pushes its extra flow downhill, as
+    /// `(edge, amount)`, and whether it still holds extra flow afterwards.
+    fn discharge(&self, node: Node) -> (Vec<(usize, Cost)>, bool) {
+        let mut excess = self.excess[node];
+        let mut pushes: Vec<(usize, Cost)> = Vec::new();
+        for edge in self.arcs(node) {
+            if excess == 0 {
+                break;
+            }
+            let residual = self.residual[*edge];
+            let downhill = self.heights[node] == self.heights[self.heads[*edge]] + 1;
+            if residual > 0 && downhill {
+                let amount = excess.min(residual);
+                pushes.push((*edge, amount));
+                excess -= amount;
+            }
+        }
+        return (pushes, excess > 0);
+    }
+
+    /// Works out the height `node` has to rise to so that it can push again,
+    /// which is just above its lowest neighbour it can still push to.
+    fn relabel(&self, node: Node) -> usize {
+        let limit = 2 * self.heights.len();
+        return self
+            .arcs(node)
+            .iter()
+            .filter(|edge| self.residual[**edge] > 0)
+            .map(|edge| self.heights[self.heads[*edge]] + 1)
+            .min()
+            .unwrap_or(limit)
+            .min(limit);
+    }
+
+    /// Sets every height to the distance to `sink` along edges which can
+    /// still carry flow, or for [`Node`]s which cannot reach the sink, to
+    /// the number of [`Node`]s plus the distance to `source`.
+    fn global_relabel(&mut self, source: Node, sink: Node) {
+        let total = self.heights.len();
+        let unseen = 2 * total;
+        self.heights.iter_mut().for_each(|height| *height = unseen);
+        for (start, base) in [(sink, 0), (source, total)] {
+            if self.heights[start] != unseen {
+                continue;
+            }
+            self.heights[start] = base;
+            let mut queue: VecDeque<Node> = VecDeque::from([start]);
+            while let Some(node) = queue.pop_front() {
+                for index in self.offsets[node]..self.offsets[node + 1] {
+                    let edge = self.arcs[index];
+                    // The edge going the other way, towards `node`.
+                    let back = edge ^ 1;
+                    let other = self.heads[edge];
+                    if self.residual[back] > 0 && self.heights[other] == unseen {
+                        self.heights[other] = self.heights[node] + 1;
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+        self.heights[source] = total;
+    }
+
+    /// Moves `amount` of flow along `edge`.
+    fn push(&mut self, edge: usize, amount: Cost) {
+        let (from, to) = (self.heads[edge ^ 1], self.heads[edge]);
+        self.residual[edge] -= amount;
+        self.residual[edge ^ 1] += amount;
+        self.excess[from] -= amount;
+        self.excess[to] += amount;
+    }
+}
+
+/// Runs `function` on every [`Node`] of `nodes` against the network, on the
+/// calling thread if there are only a few of them and on `pool` otherwise.
+fn run<R, F>(
+    pool: &mut ThreadPool,
+    network: &Arc<RwLock<Network>>,
+    nodes: &[Node],
+    function: F,
+) -> Result<Vec<R>, Error>
+where
+    R: Send + 'static,
+    F: Fn(&Network, Node) -> R + Send + Sync + 'static,
+{
+    if nodes.len() < SEQUENTIAL_FRONTIER {
+        let network = unwrapmutex!(network.read());
+        return Ok(nodes.iter().map(|node| function(&network, *node)).collect());
+    }
+    let shared = network.clone();
+    let results = pool.map(Arc::new(nodes.to_vec()), ChunkPolicy::Auto, move |node| {
+        let network = unwrapmutex!(shared.read());
+        return Ok(function(&network, *node));
+    })?;
+    return results.into_iter().collect();
+}
+
+/// Finds the largest flow from `source` to `sink` in `matrix`, where the
+/// [`Cost`] of each edge is how much can flow along it. See the module
+/// documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which push the flow.
+/// 2. ```matrix: &AdjacencyMatrix``` => The graph, with the capacity of
+///    each edge as its [`Cost`].
+/// 3. ```source: Node``` => Where the flow starts.
+/// 4. ```sink: Node``` => Where the flow ends.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `source` or `sink` is not in the
+/// graph, if they are the same [`Node`], or if a job on the [`ThreadPool`]
+/// fails.
+pub fn max_flow(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+    source: Node,
+    sink: Node,
+) -> Result<MaxFlow, Error> {
+    let graph = CsrGraph::from(matrix);
+    let total = graph.total();
+    check_node(source, total)?;
+    check_node(sink, total)?;
+    if source == sink {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "The source and the sink must be different Nodes."
+        ));
+    }
+
+    let mut edges: Vec<(Node, Node, Cost)> = Vec::with_capacity(graph.edges());
+    for from in 0..total {
+        for adjacent in graph.get_node(from).unwrap_or(&[]) {
+            edges.push((from, adjacent.node, adjacent.cost));
+        }
+    }
+    let mut network = Network {
+        offsets: vec![0; total + 1],
+        arcs: vec![0; 2 * edges.len()],
+        heads: Vec::with_capacity(2 * edges.len()),
+        residual: Vec::with_capacity(2 * edges.len()),
+        excess: vec![0; total],
+        heights: vec![0; total],
+    };
+    for (from, to, capacity) in &edges {
+        network.heads.extend([*to, *from]);
+        network.residual.extend([*capacity, 0]);
+        network.offsets[from + 1] += 1;
+        network.offsets[to + 1] += 1;
+    }
+    for node in 0..total {
+        network.offsets[node + 1] += network.offsets[node];
+    }
+    let mut next = network.offsets.clone();
+    for edge in 0..network.heads.len() {
+        let from = network.heads[edge ^ 1];
+        network.arcs[next[from]] = edge;
+        next[from] += 1;
+    }
+
+    // No more than a Cost can leave the source, so no excess can overflow.
+    let mut active: Vec<Node> = Vec::new();
+    let mut sent: Cost = 0;
+    for index in network.offsets[source]..network.offsets[source + 1] {
+        let edge = network.arcs[index];
+        let amount = network.residual[edge].min(Cost::MAX - sent);
+        if amount > 0 {
+            sent += amount;
+            network.excess[source] += amount;
+            network.push(edge, amount);
+        }
+    }
+    network.global_relabel(source, sink);
+    for node in 0..total {
+        if node != source && node != sink && network.excess[node] > 0 {
+            active.push(node);
+        }
+    }
+
+    let network = Arc::new(RwLock::new(network));
+    let mut queued: Vec<bool> = vec![false; total];
+    let mut relabels: usize = 0;
+    while !active.is_empty() {
+        let discharged = run(pool, &network, &active, |network, node| {
+            return network.discharge(node);
+        })?;
+        let mut stuck: Vec<Node> = Vec::new();
+        let mut next: Vec<Node> = Vec::new();
+        {
+            let mut network = unwrapmutex!(network.write());
+            for (node, (pushes, left)) in active.iter().zip(discharged) {
+                for (edge, amount) in pushes {
+                    network.push(edge, amount);
+                    let to = network.heads[edge];
+                    if to != source && to != sink && !queued[to] {
+                        queued[to] = true;
+                        next.push(to);
+                    }
+                }
+                if left {
+                    stuck.push(*node);
+                }
+            }
+        }
+
+        let heights = run(pool, &network, &stuck, |network, node| {
+            return network.relabel(node);
+        })?;
+        {
+            let mut network = unwrapmutex!(network.write());
+            for (node, height) in stuck.iter().zip(heights) {
+                if height > network.heights[*node] {
+                    network.heights[*node] = height;
+                    relabels += 1;
+                }
+            }
+            if relabels >= total {
+                network.global_relabel(source, sink);
+                relabels = 0;
+            }
+            // A stuck Node may also have been pushed to, so it must not be
+            // made active twice.
+            for node in &next {
+                queued[*node] = false;
+            }
+            for node in &stuck {
+                queued[*node] = true;
+            }
+            for node in next {
+                if network.excess[node] > 0 && !queued[node] {
+                    queued[node] = true;
+                    stuck.push(node);
+                }
+            }
+            for node in &stuck {
+                queued[*node] = false;
+            }
+            active = stuck;
+        }
+    }
+
+    let network = unwrapmutex!(network.read());
+    let mut source_side: Vec<bool> = vec![false; total];
+    source_side[source] = true;
+    let mut queue: VecDeque<Node> = VecDeque::from([source]);
+    while let Some(node) = queue.pop_front() {
+        for edge in network.arcs(node) {
+            let other = network.heads[*edge];
+            if network.residual[*edge] > 0 && !source_side[other] {
+                source_side[other] = true;
+                queue.push_back(other);
+            }
+        }
+    }
+    let flows = edges
+        .iter()
+        .enumerate()
+        .map(|(index, (from, to, capacity))| {
+            return (*from, *to, capacity - network.residual[2 * index]);
+        })
+        .collect();
+    return Ok(MaxFlow {value: network.excess[sink], flows, source_side});
+}
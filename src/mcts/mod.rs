@@ -0,0 +1,494 @@
+//! Module for Monte Carlo tree search, which picks a move in a 2 player game
+//! by playing many random games from the current state and growing a tree
+//! of the moves which did best in them.
+//! 
+//! Each iteration walks down the tree, at each state picking the move with
+//! the best Upper Confidence Bound, which favours moves with high rewards
+//! and moves tried only a few times. The first move not in the tree yet is
+//! added to it, and a playout of random moves is made from the state it
+//! leads to until the game is over. The reward of the playout, `1` for a
+//! win, `0.5` for a draw and `0` for a loss, is then added to every move on
+//! the way back up. Games are described with the [`GameState`] trait of
+//! [`crate::gametree`], whose [`GameState::score`] decides who won once
+//! there are no moves left.
+//! 
+//! [`MtdMcts`] searches in one of 2 [`Mode`]s on a [`ThreadPool`]:
+//! - [`Mode::Root`], where every thread grows its own tree with its own
+//!   random stream, and the visits of the moves from the root are summed
+//!   over every tree at the end.
+//! - [`Mode::Leaf`], where one tree is grown, and each batch of iterations
+//!   walks down it once per thread before playing out from every state
+//!   reached in parallel. Each walk adds a virtual loss to the moves it
+//!   passes through until its playout is added, steering the other walks
+//!   of the batch towards other moves.
+//! 
+//! The search stops once the [`Budget`] of playouts or time is spent, and
+//! the move from the root visited most often is picked.
+
+use std::{
+    cmp::Ordering,
+    io::{Error, ErrorKind},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    gametree::GameState,
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+};
+
+/// The weight of trying moves which were tried only a few times unless
+/// another is set with [`MtdMcts::set_exploration`].
+pub const DEFAULT_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+/// The most moves made by a playout before its state is scored even though
+/// the game is not over.
+pub const MAX_PLAYOUT_MOVES: usize = 1_000;
+
+/// The index among the moves from the root, visits and total reward of
+/// each move from the root of a [`Tree`].
+type Statistics = Vec<(usize, u64, f64)>;
+
+/// How the threads of [`MtdMcts`] share the search. See the module
+/// documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    /// Every thread grows its own tree.
+    #[default]
+    Root,
+    /// The threads play out from different states of one tree.
+    Leaf,
+}
+
+/// When [`MtdMcts`] stops searching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Budget {
+    /// After this many playouts, shared out between the threads.
+    Iterations(u64),
+    /// Once this long has passed since the search started.
+    Time(Duration),
+}
+
+impl Budget {
+    /// The [`Budget`] used unless another is set with
+    /// [`MtdMcts::set_budget`], which is `10_000` playouts.
+    pub const DEFAULT: Self = Self::Iterations(10_000);
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        return Self::DEFAULT;
+    }
+}
+
+/// How often a move from the root was visited by [`MtdMcts::search`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Visited<M> {
+    /// The move.
+    pub played: M,
+    /// The number of playouts made after this move.
+    pub visits: u64,
+    /// The mean reward of those playouts for the player making the move.
+    pub reward: f64,
+}
+
+/// The move picked by [`MtdMcts::search`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Decision<M> {
+    /// The move from the root visited most often, or [`None`] if the game
+    /// is over.
+    pub best: Option<M>,
+    /// Every move from the root which was visited, in the order given by
+    /// [`GameState::moves`].
+    pub moves: Vec<Visited<M>>,
+    /// The number of playouts made.
+    pub playouts: u64,
+}
+
+/// A state in a [`Tree`].
+struct Node<S: GameState> {
+    state: S,
+    parent: Option<usize>,
+    /// The index of the move leading here among the moves of the parent.
+    index: usize,
+    moves: Vec<S::Move>,
+    /// The indices of the moves not in the tree yet, the last added first.
+    untried: Vec<usize>,
+    children: Vec<usize>,
+    visits: u64,
+    /// The walks passing through which have not been played out yet.
+    pending: u64,
+    /// The total reward for the player making the move leading here.
+    reward: f64,
+}
+
+impl<S: GameState> Node<S> {
+    /// Creates a [`Node`] which was not visited yet.
+    fn new(state: S, parent: Option<usize>, index: usize) -> Self {
+        let moves = state.moves();
+        let untried: Vec<usize> = (0..moves.len()).rev().collect();
+        return Self {
+            state,
+            parent,
+            index,
+            moves,
+            untried,
+            children: Vec::new(),
+            visits: 0,
+            pending: 0,
+            reward: 0.0,
+        };
+    }
+}
+
+/// A search tree, with the root at index `0`.
+struct Tree<S: GameState> {
+    nodes: Vec<Node<S>>,
+    exploration: f64,
+}
+
+impl<S: GameState> Tree<S> {
+    /// Creates a [`Tree`] holding only `root`.
+    fn new(root: S, exploration: f64) -> Self {
+        return Self {nodes: vec![Node::new(root, None, 0)], exploration};
+    }
+
+    /// Get the Upper Confidence Bound of `child`, counting the walks which
+    /// were not played out yet as losses.
+    fn bound(&self, parent: usize, child: usize) -> f64 {
+        let (parent, child) = (&self.nodes[parent], &self.nodes[child]);
+        let tried = (child.visits + child.pending) as f64;
+        if tried == 0.0 {
+            return f64::INFINITY;
+        }
+        let total = (parent.visits + parent.pending).max(1) as f64;
+        return child.reward / tried + self.exploration * (total.ln() / tried).sqrt();
+    }
+
+    /// Walks down the tree and adds the first move not in it yet, or stops
+    /// at a state where the game is over. Every [`Node`] on the way is
+    /// marked as pending until [`Tree::backpropagate`] is called. Returns
+    /// the [`Node`] the walk stopped at.
+    fn select(&mut self) -> usize {
+        let mut current = 0;
+        loop {
+            self.nodes[current].pending += 1;
+            if let Some(index) = self.nodes[current].untried.pop() {
+                let node = &self.nodes[current];
+                let state = node.state.play(&node.moves[index]);
+                let child = self.nodes.len();
+                let mut added = Node::new(state, Some(current), index);
+                added.pending = 1;
+                self.nodes.push(added);
+                self.nodes[current].children.push(child);
+                return child;
+            }
+            let mut best: Option<(usize, f64)> = None;
+            for child in &self.nodes[current].children {
+                let bound = self.bound(current, *child);
+                if best.is_none_or(|(_, highest)| bound > highest) {
+                    best = Some((*child, bound));
+                }
+            }
+            let Some((child, _)) = best else {
+                return current;
+            };
+            current = child;
+        }
+    }
+
+    /// Adds `reward`, the reward of a playout from `leaf` for the player to
+    /// move there, to every [`Node`] from `leaf` up to the root.
+    fn backpropagate(&mut self, leaf: usize, reward: f64) {
+        let mut current = Some(leaf);
+        let mut reward = 1.0 - reward;
+        while let Some(index) = current {
+            let node = &mut self.nodes[index];
+            node.pending -= 1;
+            node.visits += 1;
+            node.reward += reward;
+            reward = 1.0 - reward;
+            current = node.parent;
+        }
+    }
+
+    /// Get the index, visits and total reward of every move from the root
+    /// in the tree.
+    fn statistics(&self) -> Statistics {
+        return self.nodes[0]
+            .children
+            .iter()
+            .map(|child| {
+                let node = &self.nodes[*child];
+                return (node.index, node.visits, node.reward);
+            })
+            .collect();
+    }
+}
+
+/// Plays random moves from `state` until the game is over or
+/// [`MAX_PLAYOUT_MOVES`] are made. Returns the reward for the player to
+/// move at `state`.
+fn playout<S: GameState>(state: &S, rng: &mut Rng) -> f64 {
+    let mut state = state.clone();
+    let mut flipped = false;
+    for _ in 0..MAX_PLAYOUT_MOVES {
+        let moves = state.moves();
+        if moves.is_empty() {
+            break;
+        }
+        state = state.play(&moves[rng.below(moves.len())]);
+        flipped = !flipped;
+    }
+    let reward = match state.score().cmp(&0) {
+        Ordering::Greater => 1.0,
+        Ordering::Equal => 0.5,
+        Ordering::Less => 0.0,
+    };
+    return if flipped { 1.0 - reward } else { reward };
+}
+
+/// This `struct` picks moves with Monte Carlo tree search using multiple
+/// threads. See the module documentation.
+pub struct MtdMcts {
+    pool: ThreadPool,
+    mode: Mode,
+    budget: Budget,
+    exploration: f64,
+}
+
+impl MtdMcts {
+    /// Creates a new [`MtdMcts`] instance searching in [`Mode::Root`] with
+    /// [`Budget::DEFAULT`] and [`DEFAULT_EXPLORATION`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {
+            pool,
+            mode: Mode::default(),
+            budget: Budget::DEFAULT,
+            exploration: DEFAULT_EXPLORATION,
+        });
+    }
+
+    /// Get how the threads share the search.
+    pub fn mode(&self) -> Mode {
+        return self.mode;
+    }
+
+    /// Sets how the threads share the search.
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    /// Get when the search stops.
+    pub fn budget(&self) -> Budget {
+        return self.budget;
+    }
+
+    /// Sets when the search stops.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `budget` is
+    /// [`Budget::Iterations`] with `0`, or a [`Budget::Time`] too long to
+    /// add to [`Instant::now`] (such as [`Duration::MAX`]), and the budget
+    /// is left as it was.
+    pub fn set_budget(&mut self, budget: Budget) -> Result<(), Error> {
+        if budget == Budget::Iterations(0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "At least 1 playout is needed."
+            ));
+        }
+        if let Budget::Time(time) = budget {
+            if Instant::now().checked_add(time).is_none() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{:?} is too long to wait for.", time)
+                ));
+            }
+        }
+        self.budget = budget;
+        return Ok(());
+    }
+
+    /// Get the weight of trying moves which were tried only a few times.
+    pub fn exploration(&self) -> f64 {
+        return self.exploration;
+    }
+
+    /// Sets the weight of trying moves which were tried only a few times.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `exploration` is negative or not
+    /// finite, and the weight is left as it was.
+    pub fn set_exploration(&mut self, exploration: f64) -> Result<(), Error> {
+        if !(exploration.is_finite() && exploration >= 0.0) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is not a non-negative number.", exploration)
+            ));
+        }
+        self.exploration = exploration;
+        return Ok(());
+    }
+
+    /// Picks a move from `state`. See the module documentation.
+    /// 
+    /// # Parameters
+    /// 1. ```state: &S``` => The state to move from.
+    /// 2. ```seed: u64``` => Seed for the random streams of the playouts.
+    ///    With a [`Budget::Iterations`], the same seed and number of threads
+    ///    always give the same result.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn search<S: GameState>(
+        &mut self,
+        state: &S,
+        seed: u64,
+    ) -> Result<Decision<S::Move>, Error> {
+        let moves = state.moves();
+        if moves.is_empty() {
+            return Ok(Decision {best: None, moves: Vec::new(), playouts: 0});
+        }
+        let (statistics, playouts) = match self.mode {
+            Mode::Root => self.search_root(state, seed)?,
+            Mode::Leaf => self.search_leaf(state, seed)?,
+        };
+
+        let mut totals: Vec<(u64, f64)> = vec![(0, 0.0); moves.len()];
+        for (index, visits, reward) in statistics {
+            totals[index].0 += visits;
+            totals[index].1 += reward;
+        }
+        let mut best: Option<(usize, u64)> = None;
+        let mut visited: Vec<Visited<S::Move>> = Vec::new();
+        for (index, (visits, reward)) in totals.into_iter().enumerate() {
+            if visits == 0 {
+                continue;
+            }
+            if best.is_none_or(|(_, most)| visits > most) {
+                best = Some((index, visits));
+            }
+            visited.push(Visited {
+                played: moves[index].clone(),
+                visits,
+                reward: reward / visits as f64,
+            });
+        }
+        let best = best.map(|(index, _)| moves[index].clone());
+        return Ok(Decision {best, moves: visited, playouts});
+    }
+
+    /// Get the time the search must stop by, if the budget is a time.
+    fn deadline(&self) -> Result<Option<Instant>, Error> {
+        return match self.budget {
+            Budget::Iterations(_) => Ok(None),
+            Budget::Time(budget) => match Instant::now().checked_add(budget) {
+                Some(deadline) => Ok(Some(deadline)),
+                None => Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{:?} is too long to wait for.", budget)
+                )),
+            },
+        };
+    }
+
+    /// Searches with [`Mode::Root`]. Returns the statistics of the moves from
+    /// the root of every tree and the number of playouts made.
+    fn search_root<S: GameState>(
+        &mut self,
+        state: &S,
+        seed: u64,
+    ) -> Result<(Statistics, u64), Error> {
+        let deadline = self.deadline()?;
+        let threads = self.pool.threads() as u64;
+        let (budget, exploration) = (self.budget, self.exploration);
+        let root = state.clone();
+        let workers = Arc::new((0..threads).collect::<Vec<u64>>());
+        let grown = self.pool.map(workers, ChunkPolicy::Fixed(1), move |worker| {
+            let share = match budget {
+                Budget::Iterations(total) => {
+                    Some(total / threads + u64::from(*worker < total % threads))
+                },
+                Budget::Time(_) => None,
+            };
+            let mut rng = Rng::stream(seed, *worker);
+            let mut tree = Tree::new(root.clone(), exploration);
+            let mut playouts: u64 = 0;
+            while share.is_none_or(|share| playouts < share)
+                && deadline.is_none_or(|deadline| Instant::now() < deadline)
+            {
+                let leaf = tree.select();
+                let reward = playout(&tree.nodes[leaf].state, &mut rng);
+                tree.backpropagate(leaf, reward);
+                playouts += 1;
+            }
+            return (tree.statistics(), playouts);
+        })?;
+
+        let mut statistics: Statistics = Vec::new();
+        let mut playouts: u64 = 0;
+        for (grown, made) in grown {
+            statistics.extend(grown);
+            playouts += made;
+        }
+        return Ok((statistics, playouts));
+    }
+
+    /// Searches with [`Mode::Leaf`]. Returns the statistics of the moves from
+    /// the root and the number of playouts made.
+    fn search_leaf<S: GameState>(
+        &mut self,
+        state: &S,
+        seed: u64,
+    ) -> Result<(Statistics, u64), Error> {
+        let deadline = self.deadline()?;
+        let threads = self.pool.threads() as u64;
+        let mut tree = Tree::new(state.clone(), self.exploration);
+        let mut playouts: u64 = 0;
+        loop {
+            let batch = match self.budget {
+                Budget::Iterations(total) => threads.min(total - playouts),
+                Budget::Time(_) => threads,
+            };
+            let late = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            if batch == 0 || late {
+                break;
+            }
+            let leaves: Vec<usize> = (0..batch).map(|_| tree.select()).collect();
+            let starts: Vec<(S, u64)> = leaves
+                .iter()
+                .enumerate()
+                .map(|(offset, leaf)| {
+                    return (tree.nodes[*leaf].state.clone(), playouts + offset as u64);
+                })
+                .collect();
+            let rewards = self.pool.map(
+                Arc::new(starts),
+                ChunkPolicy::Fixed(1),
+                move |(start, stream)| playout(start, &mut Rng::stream(seed, *stream)),
+            )?;
+            for (leaf, reward) in leaves.into_iter().zip(rewards) {
+                tree.backpropagate(leaf, reward);
+            }
+            playouts += batch;
+        }
+        return Ok((tree.statistics(), playouts));
+    }
+}
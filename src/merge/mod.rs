@@ -0,0 +1,118 @@
+//! Module for merging lists which are already sorted into one sorted list,
+//! such as the runs of an external sort or the sorted results of the
+//! workers of a [`ThreadPool`].
+//! 
+//! [`k_way`] merges the lists in rounds. Each round merges neighbouring
+//! lists in pairs, each pair as a job, so a round halves the number of
+//! lists and the whole merge takes about log2 of the number of lists
+//! rounds. Lists holding fewer items than [`SEQUENTIAL_CUTOFF`] in total
+//! are merged on the calling thread instead.
+//! 
+//! [`SEQUENTIAL_CUTOFF`]: crate::sort::SEQUENTIAL_CUTOFF
+
+use std::{
+    cmp::Ordering,
+    io::Error,
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    sort::SEQUENTIAL_CUTOFF,
+    unwrapmutex,
+};
+
+/// Merges 2 lists sorted by `compare` into one, keeping the items of
+/// `left` before equal items of `right`.
+fn merge<T, F>(left: Vec<T>, right: Vec<T>, compare: &F) -> Vec<T>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let mut merged: Vec<T> = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+    while let (Some(first), Some(second)) = (left.peek(), right.peek()) {
+        let next = match compare(second, first) {
+            Ordering::Less => right.next(),
+            _ => left.next(),
+        };
+        merged.extend(next);
+    }
+    merged.extend(left);
+    merged.extend(right);
+    return merged;
+}
+
+/// Merges `lists`, each of which must already be sorted, into one sorted
+/// list in parallel on `pool`. Equal items keep their order, with the items
+/// of earlier lists first. See [`k_way_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn k_way<T>(pool: &mut ThreadPool, lists: Vec<Vec<T>>) -> Result<Vec<T>, Error>
+where
+    T: Ord + Send + 'static,
+{
+    return k_way_by(pool, lists, T::cmp);
+}
+
+/// Merges `lists`, each of which must already be sorted by `compare`, into
+/// one list sorted by `compare` in parallel on `pool`. Items which
+/// `compare` finds equal keep their order, with the items of earlier lists
+/// first. See the module documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which do the merging.
+/// 2. ```lists: Vec<Vec<T>>``` => The sorted lists to merge.
+/// 3. ```compare: F``` => Decides the order of 2 items, like
+///    [`slice::sort_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn k_way_by<T, F>(
+    pool: &mut ThreadPool,
+    mut lists: Vec<Vec<T>>,
+    compare: F,
+) -> Result<Vec<T>, Error>
+where
+    T: Send + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    lists.retain(|list| !list.is_empty());
+    let total: usize = lists.iter().map(Vec::len).sum();
+    let compare = Arc::new(compare);
+
+    while lists.len() > 1 {
+        let mut pairs: Vec<(Vec<T>, Vec<T>)> = Vec::new();
+        let mut leftover: Option<Vec<T>> = None;
+        let mut drained = lists.into_iter();
+        while let Some(left) = drained.next() {
+            match drained.next() {
+                Some(right) => pairs.push((left, right)),
+                None => leftover = Some(left),
+            }
+        }
+        if total < SEQUENTIAL_CUTOFF {
+            lists = pairs
+                .into_iter()
+                .map(|(left, right)| merge(left, right, &*compare))
+                .collect();
+        } else {
+            let pairs: Vec<Mutex<(Vec<T>, Vec<T>)>> = pairs
+                .into_iter()
+                .map(Mutex::new)
+                .collect();
+            let shared = compare.clone();
+            let merged = pool.map(Arc::new(pairs), ChunkPolicy::Fixed(1), move |pair| {
+                let (left, right) = mem::take(&mut *unwrapmutex!(pair.lock()));
+                return Ok(merge(left, right, &*shared));
+            })?;
+            lists = merged.into_iter().collect::<Result<_, Error>>()?;
+        }
+        lists.extend(leftover);
+    }
+    return Ok(lists.pop().unwrap_or_default());
+}
@@ -0,0 +1,229 @@
+//! Module for maximal independent sets. An independent set of a graph is a
+//! group of [`Node`]s of which no 2 are joined by an edge, and it is
+//! maximal if every other [`Node`] is joined to one of them, so none can be
+//! added. This is not the largest independent set, which is much harder to
+//! find.
+//! 
+//! Picking a maximal independent set over and over is one way to color a
+//! graph, or to pick [`Node`]s which can all be worked on at the same time
+//! without touching each other. The edges of an [`AdjacencyMatrix`] are
+//! treated as undirected and their [`Cost`]s are ignored.
+//! 
+//! [`luby`] picks the set in rounds. Each round, every [`Node`] left draws
+//! a random priority, and every [`Node`] whose priority beats those of all
+//! its neighbours left joins the set. No 2 of them are neighbours, so they
+//! all join at the same time, and they are taken out of the graph along with
+//! their neighbours. Around half of the edges left are taken out each
+//! round, so few rounds are needed.
+//! 
+//! [`Cost`]: crate::dijkstra::simple::Cost
+
+use std::{
+    io::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+use crate::{
+    bfs::SEQUENTIAL_FRONTIER,
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, JOBS_PER_THREAD, Node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+};
+
+/// Marks a [`Node`] which is still in the graph.
+const UNDECIDED: usize = 0;
+/// Marks a [`Node`] in the independent set.
+const CHOSEN: usize = 1;
+/// Marks a [`Node`] taken out of the graph because a neighbour was chosen.
+const EXCLUDED: usize = 2;
+
+/// A maximal independent set of a graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndependentSet {
+    /// The [`Node`]s in the set, in order.
+    pub members: Vec<Node>,
+    /// Number of rounds it took to pick the set.
+    pub rounds: usize,
+}
+
+impl IndependentSet {
+    /// Whether `node` is in the set.
+    pub fn contains(&self, node: Node) -> bool {
+        return self.members.binary_search(&node).is_ok();
+    }
+
+    /// Whether no edge of `matrix` joins 2 [`Node`]s of the set.
+    pub fn is_independent(&self, matrix: &AdjacencyMatrix) -> bool {
+        return self.members.iter().all(|from| {
+            return matrix
+                .get_node(*from)
+                .map_or(&[][..], |edges| edges)
+                .iter()
+                .all(|adjacent| !self.contains(adjacent.node));
+        });
+    }
+
+    /// Whether every [`Node`] of `matrix` outside the set is joined by an
+    /// edge to one inside it, in either direction.
+    pub fn is_maximal(&self, matrix: &AdjacencyMatrix) -> bool {
+        let mut covered: Vec<bool> = vec![false; matrix.total()];
+        for from in 0..matrix.total() {
+            for adjacent in matrix.get_node(from).map_or(&[][..], |edges| edges) {
+                if self.contains(from) {
+                    covered[adjacent.node] = true;
+                }
+                if self.contains(adjacent.node) {
+                    covered[from] = true;
+                }
+            }
+        }
+        return (0..matrix.total()).all(|node| covered[node] || self.contains(node));
+    }
+}
+
+/// The shared state of a set being picked.
+struct Shared {
+    graph: CsrGraph,
+    reversed: CsrGraph,
+    priorities: Vec<AtomicU64>,
+    states: Vec<AtomicUsize>,
+}
+
+impl Shared {
+    /// Get the neighbours of `node`, following edges both ways.
+    fn neighbours(&self, node: Node) -> impl Iterator<Item = Node> + '_ {
+        return self
+            .graph
+            .get_node(node)
+            .unwrap_or(&[])
+            .iter()
+            .chain(self.reversed.get_node(node).unwrap_or(&[]))
+            .map(|adjacent| adjacent.node);
+    }
+
+    /// Get the priority of `node`, with ties going to the higher [`Node`].
+    fn priority(&self, node: Node) -> (u64, Node) {
+        return (self.priorities[node].load(SeqCst), node);
+    }
+
+    /// Whether `node` beats all its neighbours still in the graph.
+    fn wins(&self, node: Node) -> bool {
+        let priority = self.priority(node);
+        return self.neighbours(node).all(|neighbour| {
+            return self.states[neighbour].load(SeqCst) != UNDECIDED
+                || self.priority(neighbour) < priority;
+        });
+    }
+}
+
+/// Runs `function` on every group of `groups`, on `pool` if there are
+/// enough [`Node`]s to be worth it. Each group comes with the number of its
+/// random stream.
+fn run<R, F>(
+    pool: &mut ThreadPool,
+    shared: &Arc<Shared>,
+    groups: &Arc<Vec<(u64, Vec<Node>)>>,
+    function: F,
+) -> Result<Vec<R>, Error>
+where
+    R: Send + 'static,
+    F: Fn(&Shared, u64, &[Node]) -> R + Send + Sync + 'static,
+{
+    let nodes: usize = groups.iter().map(|(_, group)| group.len()).sum();
+    if nodes < SEQUENTIAL_FRONTIER {
+        return Ok(groups
+            .iter()
+            .map(|(stream, group)| function(shared, *stream, group))
+            .collect());
+    }
+    let state = shared.clone();
+    return pool.map(groups.clone(), ChunkPolicy::Fixed(1), move |(stream, group)| {
+        return function(&state, *stream, group);
+    });
+}
+
+/// Picks a maximal independent set of `matrix` with Luby's Algorithm. See
+/// the module documentation.
+/// 
+/// The [`Node`]s left each round are split into groups, one job each on
+/// `pool`, and each group draws its priorities from its own random stream
+/// of `seed`, so no 2 jobs share a generator.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which pick the
+///    [`Node`]s.
+/// 2. ```matrix: &AdjacencyMatrix``` => The graph.
+/// 3. ```seed: u64``` => Seed for the random priorities. The same seed and
+///    number of threads always give the same set.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn luby(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+    seed: u64,
+) -> Result<IndependentSet, Error> {
+    let graph = CsrGraph::from(matrix);
+    let total = graph.total();
+    let shared = Arc::new(Shared {
+        reversed: graph.reversed(),
+        graph,
+        priorities: (0..total).map(|_| AtomicU64::new(0)).collect(),
+        states: (0..total).map(|_| AtomicUsize::new(UNDECIDED)).collect(),
+    });
+    let jobs = pool.threads() * JOBS_PER_THREAD;
+
+    let mut remaining: Vec<Node> = (0..total).collect();
+    let mut rounds: usize = 0;
+    while !remaining.is_empty() {
+        let size = remaining.len().div_ceil(jobs);
+        let groups: Arc<Vec<(u64, Vec<Node>)>> = Arc::new(
+            remaining
+                .chunks(size)
+                .enumerate()
+                .map(|(index, group)| ((rounds * jobs + index) as u64, group.to_vec()))
+                .collect(),
+        );
+        run(pool, &shared, &groups, move |shared, stream, group| {
+            let mut rng = Rng::stream(seed, stream);
+            for node in group {
+                shared.priorities[*node].store(rng.next_u64(), SeqCst);
+            }
+        })?;
+        let winners: Vec<Vec<Node>> = run(pool, &shared, &groups, |shared, _, group| {
+            return group.iter().copied().filter(|node| shared.wins(*node)).collect();
+        })?;
+        // Winners are only marked once every Node has been checked, so that
+        // all of them see the same Nodes left.
+        for node in winners.iter().flatten() {
+            shared.states[*node].store(CHOSEN, SeqCst);
+        }
+        let chosen: Arc<Vec<(u64, Vec<Node>)>> = Arc::new(
+            winners.into_iter().map(|group| (0, group)).collect()
+        );
+        run(pool, &shared, &chosen, |shared, _, group| {
+            for node in group {
+                for neighbour in shared.neighbours(*node) {
+                    let _ = shared.states[neighbour].compare_exchange(
+                        UNDECIDED,
+                        EXCLUDED,
+                        SeqCst,
+                        SeqCst,
+                    );
+                }
+            }
+        })?;
+        remaining.retain(|node| shared.states[*node].load(SeqCst) == UNDECIDED);
+        rounds += 1;
+    }
+
+    let members: Vec<Node> = (0..total)
+        .filter(|node| shared.states[*node].load(SeqCst) == CHOSEN)
+        .collect();
+    return Ok(IndependentSet {members, rounds});
+}
@@ -0,0 +1,93 @@
+//! Borůvka's Algorithm, which joins every tree to its nearest neighbour
+//! at once in rounds, starting with every [`Node`] as a tree of its own.
+
+use std::{collections::HashMap, io::Error, sync::Arc};
+
+use super::{DisjointSet, SpanningTree};
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, Cost, JOBS_PER_THREAD, Node},
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+};
+
+/// The cheapest edge leaving each tree, as `(cost, index)` where `index` is
+/// the position of the edge in the list of edges.
+type Cheapest = HashMap<Node, (Cost, usize)>;
+
+/// Keeps `edge` for `tree` in `cheapest` if it is cheaper than the edge
+/// kept so far. Ties go to the lower index, so every round picks edges in
+/// the same order and equal [`Cost`]s cannot close a cycle.
+fn offer(cheapest: &mut Cheapest, tree: Node, edge: (Cost, usize)) {
+    let kept = cheapest.entry(tree).or_insert(edge);
+    if edge < *kept {
+        *kept = edge;
+    }
+}
+
+/// Finds a minimum spanning forest of `matrix` with Borůvka's Algorithm.
+/// 
+/// Each round, the edges are split into ranges between the workers of
+/// `pool`, which all look for the cheapest edge leaving each tree at the
+/// same time. Those edges are then added together, which at least halves
+/// the number of trees, so there are at most `log n` rounds. Nearly all the
+/// work is spread over the threads, which makes this the one to use when
+/// there are many of them.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails,
+/// or if the total [`Cost`] of the tree does not fit in a [`Cost`].
+pub fn boruvka(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+) -> Result<SpanningTree, Error> {
+    let total = matrix.total();
+    let mut edges: Vec<(Cost, Node, Node)> = Vec::with_capacity(matrix.edges());
+    for from in 0..total {
+        for adjacent in matrix.get_node(from).map_or(&[][..], |edges| edges) {
+            edges.push((adjacent.cost, from, adjacent.node));
+        }
+    }
+    let edges = Arc::new(edges);
+    let ranges = Arc::new(ranges(edges.len(), pool.threads() * JOBS_PER_THREAD));
+
+    let mut sets = DisjointSet::new(total);
+    let mut tree = SpanningTree {trees: total, ..SpanningTree::default()};
+    loop {
+        let trees: Arc<Vec<Node>> = Arc::new(
+            (0..total).map(|node| sets.find(node)).collect()
+        );
+        let shared = edges.clone();
+        let partials = pool.map(ranges.clone(), ChunkPolicy::Fixed(1), move |range| {
+            let mut cheapest = Cheapest::new();
+            for index in range.0..range.1 {
+                let (cost, from, to) = shared[index];
+                let (first, second) = (trees[from], trees[to]);
+                if first != second {
+                    offer(&mut cheapest, first, (cost, index));
+                    offer(&mut cheapest, second, (cost, index));
+                }
+            }
+            return cheapest;
+        })?;
+        let mut cheapest = Cheapest::new();
+        for partial in partials {
+            for (tree, edge) in partial {
+                offer(&mut cheapest, tree, edge);
+            }
+        }
+        if cheapest.is_empty() {
+            return Ok(tree);
+        }
+
+        let mut chosen: Vec<(Cost, usize)> = cheapest.into_values().collect();
+        chosen.sort_unstable();
+        for (cost, index) in chosen {
+            let (_, from, to) = edges[index];
+            // Both trees at the ends of an edge may pick it.
+            if sets.union(from, to) {
+                tree.add_edge(from, to, cost)?;
+                tree.trees -= 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Kruskal's Algorithm, which goes through the edges from cheapest to most
+//! expensive and keeps each one that joins 2 separate trees.
+
+use std::io::Error;
+
+use super::{DisjointSet, SpanningTree};
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, Cost, Node},
+    pool::ThreadPool,
+    sort::merge_sort,
+};
+
+/// Finds a minimum spanning forest of `matrix` with Kruskal's Algorithm.
+/// 
+/// The edges are sorted by [`Cost`] in parallel on `pool` with
+/// [`merge_sort`], which is where most of the time goes, and are then
+/// joined up on the calling thread. This takes about `m log m` steps for
+/// `m` edges, which suits sparse graphs.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails,
+/// or if the total [`Cost`] of the tree does not fit in a [`Cost`].
+pub fn kruskal(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+) -> Result<SpanningTree, Error> {
+    let total = matrix.total();
+    let mut edges: Vec<(Cost, Node, Node)> = Vec::with_capacity(matrix.edges());
+    for from in 0..total {
+        for adjacent in matrix.get_node(from).map_or(&[][..], |edges| edges) {
+            edges.push((adjacent.cost, from, adjacent.node));
+        }
+    }
+    let edges = merge_sort(pool, edges)?;
+
+    let mut sets = DisjointSet::new(total);
+    let mut tree = SpanningTree {trees: total, ..SpanningTree::default()};
+    for (cost, from, to) in edges {
+        if tree.trees <= 1 {
+            break;
+        }
+        if sets.union(from, to) {
+            tree.add_edge(from, to, cost)?;
+            tree.trees -= 1;
+        }
+    }
+    return Ok(tree);
+}
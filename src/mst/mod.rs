@@ -0,0 +1,118 @@
+//! Module for finding minimum spanning trees, the cheapest sets of edges
+//! which join together every [`Node`] that can be joined.
+//! 
+//! The edges of an [`AdjacencyMatrix`] are treated as undirected, so an
+//! edge pushed in either direction joins its 2 [`Node`]s, and the cheaper
+//! of the 2 is used if both directions were pushed. If the graph falls apart
+//! into several pieces, a tree is found for each of them, and together they
+//! form a spanning forest.
+//! 
+//! These are the algorithms available:
+//! 1. [`prim`], which grows one tree at a time and suits dense graphs.
+//! 2. [`kruskal`], which sorts the edges first and suits sparse graphs.
+//! 3. [`boruvka`], which joins all the trees to their nearest neighbours at
+//!    once and spreads its work best over many threads.
+
+mod boruvka;
+mod kruskal;
+mod prim;
+
+pub use boruvka::boruvka;
+pub use kruskal::kruskal;
+pub use prim::prim;
+
+use std::io::{Error, ErrorKind};
+
+use crate::dijkstra::simple::{AdjacencyMatrix, Cost, Node};
+
+/// A minimum spanning tree, or a forest of them if the graph is not
+/// connected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpanningTree {
+    /// The edges of the tree as `(from, to, cost)`.
+    pub edges: Vec<(Node, Node, Cost)>,
+    /// The total [`Cost`] of the edges.
+    pub cost: Cost,
+    /// Number of trees in the forest, which is `1` if the graph is
+    /// connected and `0` if it has no [`Node`]s.
+    pub trees: usize,
+}
+
+impl SpanningTree {
+    /// Whether every [`Node`] is in the same tree.
+    pub fn is_connected(&self) -> bool {
+        return self.trees <= 1;
+    }
+
+    /// Builds an [`AdjacencyMatrix`] with `total` [`Node`]s holding the
+    /// edges of the tree in both directions.
+    /// 
+    /// # Error
+    /// 
+    /// If an edge refers to a [`Node`] which exceeds `total`, an error is
+    /// returned.
+    pub fn to_matrix(&self, total: Node) -> Result<AdjacencyMatrix, Error> {
+        let mut matrix = AdjacencyMatrix::new(total);
+        for (from, to, cost) in &self.edges {
+            matrix.push_undirected(*from, *to, *cost)?;
+        }
+        return Ok(matrix);
+    }
+
+    /// Adds the edge from `from` to `to` to the tree.
+    /// 
+    /// # Error
+    /// 
+    /// If the total [`Cost`] would no longer fit in a [`Cost`], an error is
+    /// returned and the tree is left as it was.
+    fn add_edge(&mut self, from: Node, to: Node, cost: Cost) -> Result<(), Error> {
+        self.cost = match self.cost.checked_add(cost) {
+            Some(total) => total,
+            None => return Err(Error::new(
+                ErrorKind::InvalidData,
+                "The total cost of the tree does not fit in a Cost."
+            )),
+        };
+        self.edges.push((from, to, cost));
+        return Ok(());
+    }
+}
+
+/// A union-find structure for keeping track of which tree each [`Node`] is
+/// in while the trees are joined up.
+struct DisjointSet {
+    parents: Vec<Node>,
+    sizes: Vec<usize>,
+}
+
+impl DisjointSet {
+    /// Creates a new [`DisjointSet`] with every [`Node`] in a set of its own.
+    fn new(total: Node) -> Self {
+        return Self {parents: (0..total).collect(), sizes: vec![1; total]};
+    }
+
+    /// Get the [`Node`] standing for the set of `node`, shortening the way
+    /// there for later calls.
+    fn find(&mut self, mut node: Node) -> Node {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        return node;
+    }
+
+    /// Joins the sets of `a` and `b`, returning whether they were separate.
+    fn union(&mut self, a: Node, b: Node) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        return true;
+    }
+}
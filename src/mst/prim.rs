@@ -0,0 +1,152 @@
+//! Prim's Algorithm, which grows a tree from one [`Node`] by adding the
+//! cheapest edge leaving it over and over.
+
+use std::{
+    io::Error,
+    sync::{Arc, Mutex},
+};
+
+use super::SpanningTree;
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, Cost, CsrGraph, Node},
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+    unwrapmutex,
+};
+
+/// The [`Node`]s from `first` onwards which one job looks after.
+struct Block {
+    first: Node,
+    /// The cheapest edge joining each [`Node`] to the tree, as
+    /// `(cost, from)`.
+    cheapest: Vec<Option<(Cost, Node)>>,
+    /// Whether each [`Node`] is in the tree.
+    added: Vec<bool>,
+}
+
+impl Block {
+    /// Lowers the cheapest edges of the [`Node`]s in the block with the
+    /// edges of `node`, which was just added to the tree, and returns the
+    /// cheapest edge from the tree to a [`Node`] in the block as
+    /// `(cost, to, from)`.
+    fn update(
+        &mut self,
+        graph: &CsrGraph,
+        reversed: &CsrGraph,
+        node: Node,
+    ) -> Option<(Cost, Node, Node)> {
+        let end = self.first + self.added.len();
+        if (self.first..end).contains(&node) {
+            self.added[node - self.first] = true;
+        }
+        let adjacents = graph
+            .get_node(node)
+            .unwrap_or(&[])
+            .iter()
+            .chain(reversed.get_node(node).unwrap_or(&[]));
+        for adjacent in adjacents {
+            if !(self.first..end).contains(&adjacent.node) {
+                continue;
+            }
+            let index = adjacent.node - self.first;
+            if self.added[index] {
+                continue;
+            }
+            let cheapest = &mut self.cheapest[index];
+            if cheapest.is_none_or(|(cost, _)| adjacent.cost < cost) {
+                *cheapest = Some((adjacent.cost, node));
+            }
+        }
+
+        let mut best: Option<(Cost, Node, Node)> = None;
+        for index in 0..self.added.len() {
+            if self.added[index] {
+                continue;
+            }
+            if let Some((cost, from)) = self.cheapest[index] {
+                let candidate = (cost, self.first + index, from);
+                if best.is_none_or(|best| candidate < best) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        return best;
+    }
+}
+
+/// Finds a minimum spanning tree of `matrix` with Prim's Algorithm,
+/// starting again from the lowest [`Node`] left out whenever a tree cannot
+/// grow any further.
+/// 
+/// Each time a [`Node`] is added, the [`Node`]s are split into one block
+/// per thread of `pool`, and every block updates the cheapest edges from
+/// the tree to its [`Node`]s and picks the cheapest among them at the same
+/// time. This takes about `n²` steps in total however few edges there
+/// are, which suits dense graphs.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails,
+/// or if the total [`Cost`] of the tree does not fit in a [`Cost`].
+pub fn prim(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+) -> Result<SpanningTree, Error> {
+    let graph = Arc::new(CsrGraph::from(matrix));
+    let reversed = Arc::new(graph.reversed());
+    let total = graph.total();
+    let blocks: Arc<Vec<Mutex<Block>>> = Arc::new(
+        ranges(total, pool.threads())
+            .into_iter()
+            .map(|(first, end)| {
+                let length = end - first;
+                return Mutex::new(Block {
+                    first,
+                    cheapest: vec![None; length],
+                    added: vec![false; length],
+                });
+            })
+            .collect()
+    );
+
+    let mut tree = SpanningTree::default();
+    let mut added: Vec<bool> = vec![false; total];
+    // Every Node before this one is in a tree.
+    let mut lowest: Node = 0;
+    let mut node = match total {
+        0 => return Ok(tree),
+        _ => 0,
+    };
+    tree.trees = 1;
+    loop {
+        added[node] = true;
+        let (graph, reversed) = (graph.clone(), reversed.clone());
+        let found = pool.map(blocks.clone(), ChunkPolicy::Fixed(1), move |block| {
+            let mut block = unwrapmutex!(block.lock());
+            return Ok(block.update(&graph, &reversed, node));
+        })?;
+        let mut best: Option<(Cost, Node, Node)> = None;
+        for candidate in found {
+            if let Some(candidate) = candidate? {
+                if best.is_none_or(|best| candidate < best) {
+                    best = Some(candidate);
+                }
+            }
+        }
+        node = match best {
+            Some((cost, to, from)) => {
+                tree.add_edge(from, to, cost)?;
+                to
+            },
+            None => {
+                while lowest < total && added[lowest] {
+                    lowest += 1;
+                }
+                if lowest == total {
+                    return Ok(tree);
+                }
+                tree.trees += 1;
+                lowest
+            },
+        };
+    }
+}
@@ -0,0 +1,252 @@
+//! Module for PageRank, which scores every [`Node`] of a graph by how likely
+//! someone following edges at random is to be there.
+//! 
+//! At each step, the walker follows a random edge out of its [`Node`] with a
+//! chance of the damping factor, and jumps to a [`Node`] picked at random
+//! otherwise, or always when there are no edges out. [`MtdPageRank`] finds
+//! the ranks by power iteration: every [`Node`] starts with the same rank,
+//! and each iteration hands the rank of every [`Node`] out evenly along its
+//! edges. The [`Node`]s are split into ranges between the workers of a
+//! [`ThreadPool`], and each worker collects the rank flowing into its own
+//! [`Node`]s, so no locking is needed. The iterations stop once the ranks
+//! change by less than the tolerance in total, or after the most iterations
+//! allowed.
+//! 
+//! The [`Cost`]s of the edges are ignored.
+//! 
+//! [`Cost`]: crate::dijkstra::simple::Cost
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, JOBS_PER_THREAD, Node},
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+};
+
+/// The damping factor used unless another is set with
+/// [`MtdPageRank::set_damping`].
+pub const DEFAULT_DAMPING: f64 = 0.85;
+/// The tolerance used unless another is set with
+/// [`MtdPageRank::set_tolerance`].
+pub const DEFAULT_TOLERANCE: f64 = 1e-6;
+/// The most iterations run unless another limit is set with
+/// [`MtdPageRank::set_max_iterations`].
+pub const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+/// The ranks found by [`MtdPageRank::calculate`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageRanks {
+    /// The rank of each [`Node`]. The ranks add up to `1`.
+    pub ranks: Vec<f64>,
+    /// Number of iterations run.
+    pub iterations: usize,
+    /// Whether the ranks settled within the tolerance before the most
+    /// iterations allowed were run.
+    pub converged: bool,
+}
+
+impl PageRanks {
+    /// Get the rank of `node`, or [`None`] if it is not in the graph.
+    pub fn rank(&self, node: Node) -> Option<f64> {
+        return self.ranks.get(node).copied();
+    }
+
+    /// Get the `count` [`Node`]s with the highest ranks as `(node, rank)`,
+    /// highest first. Ties go to the lower [`Node`].
+    pub fn top(&self, count: usize) -> Vec<(Node, f64)> {
+        let mut ranked: Vec<(Node, f64)> = self
+            .ranks
+            .iter()
+            .copied()
+            .enumerate()
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.truncate(count);
+        return ranked;
+    }
+}
+
+/// Number of edges leaving `node` in `graph`.
+fn out_degree(graph: &CsrGraph, node: Node) -> usize {
+    return graph.get_node(node).map_or(0, <[_]>::len);
+}
+
+/// This `struct` calculates the PageRank of every [`Node`] of a graph using
+/// multiple threads. See the module documentation.
+pub struct MtdPageRank {
+    pool: ThreadPool,
+    graph: Arc<CsrGraph>,
+    reversed: Arc<CsrGraph>,
+    damping: f64,
+    tolerance: f64,
+    max_iterations: usize,
+}
+
+impl MtdPageRank {
+    /// Creates a new [`MtdPageRank`] instance with [`DEFAULT_DAMPING`],
+    /// [`DEFAULT_TOLERANCE`] and [`DEFAULT_MAX_ITERATIONS`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix``` => The graph. The [`Cost`]s of its
+    ///    edges are ignored.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    /// 
+    /// [`Cost`]: crate::dijkstra::simple::Cost
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix) -> Result<Self, Error> {
+        return Self::from_csr(threads, CsrGraph::from(matrix));
+    }
+
+    /// Creates a new [`MtdPageRank`] instance from a graph which is already
+    /// a [`CsrGraph`]. See [`MtdPageRank::new`].
+    pub fn from_csr(threads: usize, graph: CsrGraph) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        let reversed = Arc::new(graph.reversed());
+        return Ok(Self {
+            pool,
+            graph: Arc::new(graph),
+            reversed,
+            damping: DEFAULT_DAMPING,
+            tolerance: DEFAULT_TOLERANCE,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        });
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.graph.total();
+    }
+
+    /// Get the chance of following an edge instead of jumping to a random
+    /// [`Node`].
+    pub fn damping(&self) -> f64 {
+        return self.damping;
+    }
+
+    /// Sets the chance of following an edge instead of jumping to a random
+    /// [`Node`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `damping` is not at least `0` and
+    /// less than `1`, and the damping factor is left as it was.
+    pub fn set_damping(&mut self, damping: f64) -> Result<(), Error> {
+        if !(0.0..1.0).contains(&damping) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The damping factor {} is not in [0, 1).", damping)
+            ));
+        }
+        self.damping = damping;
+        return Ok(());
+    }
+
+    /// Get how much the ranks may change in total in the last iteration.
+    pub fn tolerance(&self) -> f64 {
+        return self.tolerance;
+    }
+
+    /// Sets how much the ranks may change in total in the last iteration,
+    /// which is the sum of how much each rank changed.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `tolerance` is negative or not a
+    /// number, and the tolerance is left as it was.
+    pub fn set_tolerance(&mut self, tolerance: f64) -> Result<(), Error> {
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The tolerance {} is not a non-negative number.", tolerance)
+            ));
+        }
+        self.tolerance = tolerance;
+        return Ok(());
+    }
+
+    /// Get the most iterations run.
+    pub fn max_iterations(&self) -> usize {
+        return self.max_iterations;
+    }
+
+    /// Sets the most iterations run. The ranks are returned as they are
+    /// after the last one even if they have not settled yet.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Calculates the PageRank of every [`Node`]. See the module
+    /// documentation.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn calculate(&mut self) -> Result<PageRanks, Error> {
+        let total = self.total();
+        let mut result = PageRanks {
+            ranks: vec![1.0 / total as f64; total],
+            iterations: 0,
+            converged: total == 0,
+        };
+        let ranges = Arc::new(ranges(total, self.pool.threads() * JOBS_PER_THREAD));
+        // Rank held by Nodes without edges out, which is spread over every
+        // Node.
+        let mut dangling: f64 = (0..total)
+            .filter(|node| out_degree(&self.graph, *node) == 0)
+            .map(|node| result.ranks[node])
+            .sum();
+        while !result.converged && result.iterations < self.max_iterations {
+            let shared = Arc::new(result.ranks);
+            let graph = self.graph.clone();
+            let reversed = self.reversed.clone();
+            let damping = self.damping;
+            let base = (1.0 - damping + damping * dangling) / total as f64;
+            let policy = ChunkPolicy::Fixed(1);
+            let chunks = self.pool.map(ranges.clone(), policy, move |range| {
+                let mut ranks: Vec<f64> = Vec::with_capacity(range.1 - range.0);
+                let (mut change, mut dangling) = (0.0, 0.0);
+                for node in range.0..range.1 {
+                    let incoming: f64 = reversed
+                        .get_node(node)
+                        .unwrap_or(&[])
+                        .iter()
+                        .map(|adjacent| {
+                            let out = out_degree(&graph, adjacent.node);
+                            return shared[adjacent.node] / out as f64;
+                        })
+                        .sum();
+                    let rank = base + damping * incoming;
+                    change += (rank - shared[node]).abs();
+                    if out_degree(&graph, node) == 0 {
+                        dangling += rank;
+                    }
+                    ranks.push(rank);
+                }
+                return (ranks, change, dangling);
+            })?;
+
+            let mut next: Vec<f64> = Vec::with_capacity(total);
+            let mut change: f64 = 0.0;
+            dangling = 0.0;
+            for (ranks, part, held) in chunks {
+                next.extend(ranks);
+                change += part;
+                dangling += held;
+            }
+            result.ranks = next;
+            result.iterations += 1;
+            result.converged = change < self.tolerance;
+        }
+        return Ok(result);
+    }
+}
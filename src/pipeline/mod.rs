@@ -0,0 +1,339 @@
+//! Module for chaining graph transforms and algorithms into a [`Pipeline`].
+//! 
+//! A [`Pipeline`] is built up front from stages and only does any work when
+//! [`Pipeline::run`] is called with a [`ThreadPool`] and an input. Each stage
+//! takes the artifact made by the stage before it, so the types of the
+//! stages are checked when the [`Pipeline`] is built. The built-in stages
+//! work on a [`Subgraph`], which remembers which [`Node`] of the input graph
+//! each of its [`Node`]s came from, and end with a [`DistanceTable`] in the
+//! numbering of the input graph. For example,
+//! `Pipeline::new().symmetrize().compact().largest_component()
+//! .dijkstra_random(10, 7)` makes every edge two-way, drops unused
+//! [`Node`]s, keeps the largest component and finds the shortest routes
+//! from 10 random [`Node`]s of it, once run on a [`Subgraph`] made from an
+//! [`AdjacencyMatrix`].
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io::Error,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    arena::reset_local,
+    dijkstra::simple::{
+        AdjacencyMatrix,
+        Cost,
+        CsrGraph,
+        DistanceTable,
+        Node,
+        NodeWithCost,
+        shortest_from,
+    },
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+    unwrapmutex,
+    unwrapoption,
+};
+
+/// A stage of a [`Pipeline`], turning an artifact of type `I` into one of
+/// type `O` with the help of the [`ThreadPool`].
+type Stage<I, O> = Box<dyn FnOnce(I, &mut ThreadPool) -> Result<O, Error>>;
+
+/// A chain of stages turning an input of type `I` into an artifact of type
+/// `O`, run on a shared [`ThreadPool`] with [`Pipeline::run`].
+pub struct Pipeline<I, O> {
+    stages: Stage<I, O>,
+    names: Vec<&'static str>,
+}
+
+impl<I: 'static> Pipeline<I, I> {
+    /// Creates a new [`Pipeline`] without any stages, which gives back its
+    /// input as it is.
+    pub fn new() -> Self {
+        return Self {
+            stages: Box::new(|input, _pool| Ok(input)),
+            names: Vec::new(),
+        };
+    }
+}
+
+impl<I: 'static> Default for Pipeline<I, I> {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<I: 'static, O: 'static> Pipeline<I, O> {
+    /// Adds a stage called `name` to the end of the [`Pipeline`]. `stage`
+    /// receives the artifact of the previous stage and the [`ThreadPool`]
+    /// given to [`Pipeline::run`], which it may send jobs to.
+    pub fn then<U, F>(self, name: &'static str, stage: F) -> Pipeline<I, U>
+    where
+        U: 'static,
+        F: FnOnce(O, &mut ThreadPool) -> Result<U, Error> + 'static,
+    {
+        let previous = self.stages;
+        let mut names = self.names;
+        names.push(name);
+        return Pipeline {
+            stages: Box::new(move |input, pool| {
+                let artifact = previous(input, pool)?;
+                return stage(artifact, pool);
+            }),
+            names,
+        };
+    }
+
+    /// Adds a stage called `name` which runs `stage` as a single job on the
+    /// [`ThreadPool`], for stages which cannot be split up.
+    pub fn then_job<U, F>(self, name: &'static str, stage: F) -> Pipeline<I, U>
+    where
+        O: Send,
+        U: Send + 'static,
+        F: FnOnce(O) -> Result<U, Error> + Send + 'static,
+    {
+        return self.then(name, move |artifact, pool| {
+            let slot: Arc<Mutex<Option<U>>> = Arc::new(Mutex::new(None));
+            let output = slot.clone();
+            pool.execute(move || {
+                let result = stage(artifact)?;
+                *unwrapmutex!(output.lock()) = Some(result);
+                return Ok(());
+            })?;
+            pool.wait()?;
+            let result = unwrapmutex!(slot.lock()).take();
+            return Ok(unwrapoption!(result));
+        });
+    }
+
+    /// Names of the stages in the order they run.
+    pub fn stages(&self) -> &[&'static str] {
+        return &self.names;
+    }
+
+    /// Runs every stage in order on `pool`, starting from `input`.
+    /// 
+    /// # Error
+    /// 
+    /// The first [`std::io::Error`] returned by a stage is returned and the
+    /// stages after it are not run.
+    pub fn run(self, pool: &mut ThreadPool, input: I) -> Result<O, Error> {
+        return (self.stages)(input, pool);
+    }
+}
+
+/// A graph made by the stages of a [`Pipeline`], along with the [`Node`] of
+/// the input graph each of its [`Node`]s came from.
+#[derive(Debug)]
+pub struct Subgraph {
+    /// The graph itself.
+    pub graph: AdjacencyMatrix,
+    /// `original[node]` is the [`Node`] of the input graph which `node` came
+    /// from.
+    pub original: Vec<Node>,
+    /// Number of [`Node`]s in the input graph.
+    pub total: Node,
+}
+
+impl Subgraph {
+    /// Keeps only the [`Node`]s where `keep` is `true` and the edges between
+    /// them, numbering the kept [`Node`]s in their current order.
+    fn retain(self, keep: &[bool]) -> Result<Self, Error> {
+        let mut renumbered: Vec<Option<Node>> = vec![None; keep.len()];
+        let mut original: Vec<Node> = Vec::new();
+        for (node, kept) in keep.iter().enumerate() {
+            if *kept {
+                renumbered[node] = Some(original.len());
+                original.push(self.original[node]);
+            }
+        }
+        let mut edges: Vec<(Node, Node, Cost)> = Vec::new();
+        for (from, new_from) in renumbered.iter().enumerate() {
+            let new_from = match new_from {
+                Some(new_from) => *new_from,
+                None => continue,
+            };
+            for adjacent in self.graph.get_node(from).into_iter().flatten() {
+                if let Some(new_to) = renumbered[adjacent.node] {
+                    edges.push((new_from, new_to, adjacent.cost));
+                }
+            }
+        }
+        let graph = AdjacencyMatrix::from_edges_with_policy(
+            original.len(),
+            self.graph.policy(),
+            edges,
+        )?;
+        return Ok(Self {graph, original, total: self.total});
+    }
+}
+
+impl From<AdjacencyMatrix> for Subgraph {
+    /// Wraps a whole graph, with every [`Node`] mapping to itself.
+    fn from(graph: AdjacencyMatrix) -> Self {
+        let total = graph.total();
+        return Self {graph, original: (0..total).collect(), total};
+    }
+}
+
+/// Adds an edge going the other way with the same [`Cost`] for every edge
+/// in the graph.
+fn symmetrize(mut subgraph: Subgraph) -> Result<Subgraph, Error> {
+    let mut reversed: Vec<(Node, Node, Cost)> = Vec::new();
+    for from in 0..subgraph.graph.total() {
+        for adjacent in subgraph.graph.get_node(from).into_iter().flatten() {
+            reversed.push((adjacent.node, from, adjacent.cost));
+        }
+    }
+    for (from, to, cost) in reversed {
+        let exists = subgraph
+            .graph
+            .get_node(from)
+            .into_iter()
+            .flatten()
+            .any(|adjacent| adjacent.node == to);
+        if !exists {
+            subgraph.graph.push(from, NodeWithCost::new(to, cost))?;
+        }
+    }
+    return Ok(subgraph);
+}
+
+/// Removes the [`Node`]s without any edges going into or out of them.
+fn compact(subgraph: Subgraph) -> Result<Subgraph, Error> {
+    let total = subgraph.graph.total();
+    let mut keep = vec![false; total];
+    for from in 0..total {
+        for adjacent in subgraph.graph.get_node(from).into_iter().flatten() {
+            keep[from] = true;
+            keep[adjacent.node] = true;
+        }
+    }
+    return subgraph.retain(&keep);
+}
+
+/// Keeps only the [`Node`]s in the largest weakly connected component,
+/// preferring the component found first on a tie.
+fn largest_component(subgraph: Subgraph) -> Result<Subgraph, Error> {
+    let total = subgraph.graph.total();
+    let mut neighbours: Vec<Vec<Node>> = vec![Vec::new(); total];
+    for from in 0..total {
+        for adjacent in subgraph.graph.get_node(from).into_iter().flatten() {
+            neighbours[from].push(adjacent.node);
+            neighbours[adjacent.node].push(from);
+        }
+    }
+    let mut component: Vec<Option<usize>> = vec![None; total];
+    let mut sizes: Vec<usize> = Vec::new();
+    for start in 0..total {
+        if component[start].is_some() {
+            continue;
+        }
+        let id = sizes.len();
+        sizes.push(0);
+        component[start] = Some(id);
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            sizes[id] += 1;
+            for next in &neighbours[node] {
+                if component[*next].is_none() {
+                    component[*next] = Some(id);
+                    queue.push_back(*next);
+                }
+            }
+        }
+    }
+    let mut largest: Option<usize> = None;
+    for (id, size) in sizes.iter().enumerate() {
+        if largest.is_none_or(|largest| *size > sizes[largest]) {
+            largest = Some(id);
+        }
+    }
+    let keep: Vec<bool> = component.iter().map(|id| *id == largest).collect();
+    return subgraph.retain(&keep);
+}
+
+/// Runs the Dijkstra Algorithm from each of `sources`, which are numbered
+/// like the [`Subgraph`], with one job per chunk of sources on `pool`. The
+/// result is numbered like the input graph.
+fn dijkstra(
+    subgraph: Subgraph,
+    sources: Vec<Node>,
+    pool: &mut ThreadPool,
+) -> Result<DistanceTable, Error> {
+    let nodes = subgraph.graph.total();
+    let graph = Arc::new(CsrGraph::from(&subgraph.graph));
+    let sources = Arc::new(sources);
+    let rows = pool.map(sources.clone(), ChunkPolicy::Auto, move |source| {
+        let row = shortest_from(*source, nodes, &graph, None);
+        // Each chunk holds several sources, so the arena is reset here
+        // rather than only after the whole job.
+        reset_local();
+        return row;
+    })?;
+    let mut table: DistanceTable = HashMap::with_capacity(rows.len());
+    for (source, row) in sources.iter().zip(rows) {
+        let mut costs: Vec<Option<Cost>> = vec![None; subgraph.total];
+        for (node, cost) in row?.into_iter().enumerate() {
+            costs[subgraph.original[node]] = cost;
+        }
+        table.insert(subgraph.original[*source], costs);
+    }
+    return Ok(table);
+}
+
+impl<I: 'static> Pipeline<I, Subgraph> {
+    /// Adds a stage which gives every edge a twin going the other way with
+    /// the same [`Cost`], unless there is already an edge going that way.
+    pub fn symmetrize(self) -> Self {
+        return self.then_job("symmetrize", symmetrize);
+    }
+
+    /// Adds a stage which removes [`Node`]s without any edges, such as the
+    /// ones removed with [`AdjacencyMatrix::remove_node`], and numbers the
+    /// rest from `0` again.
+    pub fn compact(self) -> Self {
+        return self.then_job("compact", compact);
+    }
+
+    /// Adds a stage which keeps only the largest weakly connected component
+    /// and numbers its [`Node`]s from `0` again.
+    pub fn largest_component(self) -> Self {
+        return self.then_job("largest component", largest_component);
+    }
+
+    /// Adds a stage which runs the Dijkstra Algorithm from each of
+    /// `sources`, given as [`Node`]s of the input graph. Sources which were
+    /// removed by an earlier stage are skipped.
+    pub fn dijkstra(self, sources: Vec<Node>) -> Pipeline<I, DistanceTable> {
+        return self.then("dijkstra", move |subgraph, pool| {
+            let mut renumbered: HashMap<Node, Node> = HashMap::new();
+            for (node, original) in subgraph.original.iter().enumerate() {
+                renumbered.insert(*original, node);
+            }
+            let sources: Vec<Node> = sources
+                .iter()
+                .filter_map(|source| renumbered.get(source).copied())
+                .collect();
+            return dijkstra(subgraph, sources, pool);
+        });
+    }
+
+    /// Adds a stage which runs the Dijkstra Algorithm from `count` different
+    /// [`Node`]s picked at random with `seed`, or from every [`Node`] if
+    /// there are fewer than `count`.
+    pub fn dijkstra_random(
+        self,
+        count: usize,
+        seed: u64,
+    ) -> Pipeline<I, DistanceTable> {
+        return self.then("dijkstra", move |subgraph, pool| {
+            let mut sources: Vec<Node> = (0..subgraph.graph.total()).collect();
+            Rng::new(seed).shuffle(&mut sources);
+            sources.truncate(count);
+            sources.sort_unstable();
+            return dijkstra(subgraph, sources, pool);
+        });
+    }
+}
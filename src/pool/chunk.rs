@@ -0,0 +1,277 @@
+//! This module contains helpers for splitting a list of items into chunks
+//! and running a function over every chunk in a [`ThreadPool`].
+//! 
+//! Instead of splitting the items up front, each worker thread keeps
+//! taking the next chunk until none are left, and the size of each chunk is
+//! decided by a [`ChunkTuner`] according to the [`ChunkPolicy`] passed in.
+
+use std::{
+    io::Error,
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{Arc, Mutex, mpsc::channel},
+    time::{Duration, Instant},
+};
+
+use super::ThreadPool;
+use crate::{unwrapmutex, unwrapoption, unwrapreceiver};
+
+/// Chunks taking less time than this are grown by [`ChunkPolicy::Auto`].
+pub const MIN_CHUNK_DURATION: Duration = Duration::from_millis(1);
+
+/// Chunks taking more time than this are shrunk by [`ChunkPolicy::Auto`].
+pub const MAX_CHUNK_DURATION: Duration = Duration::from_millis(10);
+
+/// How long [`ChunkPolicy::Auto`] tries to make each chunk take.
+pub const TARGET_CHUNK_DURATION: Duration = Duration::from_millis(5);
+
+/// Weight given to the newest measurement when averaging the time taken per
+/// item.
+const SMOOTHING: f64 = 0.3;
+
+/// Decides how many items are given to a job at a time.
+/// 
+/// # Variants
+/// 
+/// 1. Fixed(usize) => Every chunk has this many items (at least 1).
+/// 2. Auto => Start with single items and measure how long each chunk
+///    takes, growing or shrinking the chunks until each one takes about
+///    [`TARGET_CHUNK_DURATION`]. This is the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChunkPolicy {
+    Fixed(usize),
+    #[default]
+    Auto,
+}
+
+/// An online tuner which picks the size of the next chunk from how long
+/// previous chunks took.
+#[derive(Debug, Clone)]
+pub struct ChunkTuner {
+    size: usize,
+    max: usize,
+    adaptive: bool,
+    /// Running average of the seconds taken per item.
+    per_item: Option<f64>,
+}
+
+impl ChunkTuner {
+    /// Creates a new [`ChunkTuner`] for `policy` which never hands out
+    /// chunks larger than `max` items.
+    pub fn new(policy: ChunkPolicy, max: usize) -> Self {
+        let max = max.max(1);
+        return match policy {
+            ChunkPolicy::Fixed(size) => Self {
+                size: size.clamp(1, max),
+                max,
+                adaptive: false,
+                per_item: None,
+            },
+            ChunkPolicy::Auto => Self {
+                size: 1,
+                max,
+                adaptive: true,
+                per_item: None,
+            },
+        };
+    }
+
+    /// Number of items to put in the next chunk.
+    pub fn size(&self) -> usize {
+        return self.size;
+    }
+
+    /// Records that a chunk of `items` items took `elapsed` to run and
+    /// adjusts the size of the next chunk if the policy is
+    /// [`ChunkPolicy::Auto`].
+    /// 
+    /// The size at most doubles or halves after each chunk so that one
+    /// unusually fast or slow chunk cannot throw the tuner off.
+    pub fn record(&mut self, items: usize, elapsed: Duration) {
+        if !self.adaptive || items == 0 {
+            return;
+        }
+        let sample = elapsed.as_secs_f64() / items as f64;
+        let per_item = match self.per_item {
+            Some(average) => average + SMOOTHING * (sample - average),
+            None => sample,
+        };
+        self.per_item = Some(per_item);
+
+        let chunk_time = per_item * self.size as f64;
+        if chunk_time >= MIN_CHUNK_DURATION.as_secs_f64()
+            && chunk_time <= MAX_CHUNK_DURATION.as_secs_f64()
+        {
+            return;
+        }
+        let wanted = if per_item > 0.0 {
+            TARGET_CHUNK_DURATION.as_secs_f64() / per_item
+        } else {
+            f64::MAX
+        };
+        let wanted = wanted.clamp(
+            (self.size / 2).max(1) as f64,
+            self.size.saturating_mul(2) as f64,
+        );
+        self.size = (wanted as usize).clamp(1, self.max);
+    }
+}
+
+/// Splits `total` items into about `pieces` ranges of the same size, as
+/// `(first, end)`. No range is empty, so there are fewer ranges than
+/// `pieces` when there are too few items to go around.
+pub(crate) fn ranges(total: usize, pieces: usize) -> Vec<(usize, usize)> {
+    let size = total.div_ceil(pieces.max(1)).max(1);
+    return (0..total)
+        .step_by(size)
+        .map(|first| (first, (first + size).min(total)))
+        .collect();
+}
+
+/// Shared state handing out chunks to the jobs.
+struct Cursor {
+    next: usize,
+    tuner: ChunkTuner,
+}
+
+impl ThreadPool {
+    /// Runs `function` over every `(start, end)` range of `len` items, with
+    /// the size of each range chosen by `policy`, and blocks until all of
+    /// them are done.
+    /// 
+    /// Each job reports back on a channel of its own call, so only the jobs
+    /// sent here are waited for, and other jobs on the [`ThreadPool`] are
+    /// left for [`ThreadPool::wait`]. A job which panics reports an error
+    /// instead of never reporting back.
+    fn run_chunks<F>(
+        &mut self,
+        len: usize,
+        policy: ChunkPolicy,
+        function: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(usize, usize) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        if len == 0 {
+            return Ok(());
+        }
+        let threads = self.threads();
+        // Leave enough chunks for every worker to get one.
+        let tuner = ChunkTuner::new(policy, len.div_ceil(threads));
+        let cursor = Arc::new(Mutex::new(Cursor {next: 0, tuner}));
+        let function = Arc::new(function);
+        let (sender, receiver) = channel::<Result<(), Error>>();
+        let jobs = threads.min(len);
+        for _ in 0..jobs {
+            let cursor = cursor.clone();
+            let function = function.clone();
+            let sender = sender.clone();
+            let chunks = move || -> Result<(), Error> {
+                loop {
+                    let (start, end) = {
+                        let mut cursor = unwrapmutex!(cursor.lock());
+                        if cursor.next >= len {
+                            return Ok(());
+                        }
+                        let start = cursor.next;
+                        cursor.next = (start + cursor.tuner.size()).min(len);
+                        (start, cursor.next)
+                    };
+                    let timer = Instant::now();
+                    if let Err(error) = function(start, end) {
+                        // Stop the other jobs from taking more chunks.
+                        unwrapmutex!(cursor.lock()).next = len;
+                        return Err(error);
+                    }
+                    unwrapmutex!(cursor.lock())
+                        .tuner
+                        .record(end - start, timer.elapsed());
+                }
+            };
+            self.execute(move || {
+                let result = match catch_unwind(AssertUnwindSafe(chunks)) {
+                    Ok(result) => result,
+                    Err(_panic) => Err(Error::other("A chunk panicked.")),
+                };
+                // The call may have given up already if sending a job failed.
+                let _ = sender.send(result);
+                return Ok(());
+            })?;
+        }
+
+        let mut first_error: Option<Error> = None;
+        for _ in 0..jobs {
+            let result = unwrapreceiver!(receiver.recv());
+            if let (Err(error), None) = (result, &first_error) {
+                first_error = Some(error);
+            }
+        }
+        return match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        };
+    }
+
+    /// Runs `function` over chunks of `items` in parallel, with the size of
+    /// each chunk chosen by `policy`, and blocks until every chunk is done.
+    /// 
+    /// # Error
+    /// 
+    /// If `function` returns an error for any chunk, no new chunks are
+    /// started and the first error is returned. An error is also returned if
+    /// `function` panics.
+    pub fn for_each_chunk<T, F>(
+        &mut self,
+        items: Arc<Vec<T>>,
+        policy: ChunkPolicy,
+        function: F,
+    ) -> Result<(), Error>
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&[T]) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        let len = items.len();
+        return self.run_chunks(len, policy, move |start, end| {
+            return function(&items[start..end]);
+        });
+    }
+
+    /// Applies `function` to every item in `items` in parallel, with the
+    /// items split into chunks according to `policy`, and returns the
+    /// results in the same order as `items`.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `function` panics for any item.
+    pub fn map<T, R, F>(
+        &mut self,
+        items: Arc<Vec<T>>,
+        policy: ChunkPolicy,
+        function: F,
+    ) -> Result<Vec<R>, Error>
+    where
+        T: Send + Sync + 'static,
+        R: Send + 'static,
+        F: Fn(&T) -> R + Send + Sync + 'static,
+    {
+        let len = items.len();
+        let mut results: Vec<Option<R>> = Vec::with_capacity(len);
+        results.resize_with(len, || None);
+        let results = Arc::new(Mutex::new(results));
+        let shared = results.clone();
+        self.run_chunks(len, policy, move |start, end| {
+            let mapped: Vec<R> = items[start..end].iter().map(&function).collect();
+            let mut results = unwrapmutex!(shared.lock());
+            for (slot, result) in results[start..end].iter_mut().zip(mapped) {
+                *slot = Some(result);
+            }
+            return Ok(());
+        })?;
+
+        let mut results = unwrapmutex!(results.lock());
+        let mut collected: Vec<R> = Vec::with_capacity(len);
+        for result in results.iter_mut() {
+            collected.push(unwrapoption!(result.take()));
+        }
+        return Ok(collected);
+    }
+}
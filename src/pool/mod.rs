@@ -4,11 +4,26 @@
 //#![allow(unused_imports)]
 use std::{
     io::{Error, ErrorKind},
-    sync::{Arc, Mutex, mpsc::{channel, Receiver, Sender}},
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+    },
     thread::{JoinHandle, spawn},
+    time::Duration,
 };
 
-use crate::{unwrapmutex, unwrapreceiver, unwrapsender};
+use crate::{arena::reset_local, unwrapmutex, unwrapreceiver};
+
+pub mod chunk;
+pub mod pressure;
+pub mod priority;
+
+use self::{
+    pressure::PressureState,
+    priority::{DEFAULT_AGING_INTERVAL, JobQueue},
+};
 
 /// The typedef for a function sent to [`Worker`] threads to be run.
 type Job = Box<dyn FnOnce() -> ConsolidatedMessage + Send + 'static>;
@@ -19,10 +34,13 @@ type Job = Box<dyn FnOnce() -> ConsolidatedMessage + Send + 'static>;
 /// # Variants
 /// 
 /// 1. Job([`Job`]) => A function to be sent to the [`Worker`] for running.
-/// 2. Terminate => Tells the [`Worker`] to stop looping and join the main
-/// thread.
+/// 2. Queued => Tells the [`Worker`] to run the job with the highest
+///    effective [`priority::Priority`] in the [`ThreadPool`]'s queue.
+/// 3. Terminate => Tells the [`Worker`] to stop looping and join the main
+///    thread.
 pub enum WorkerMessage {
     Job(Job),
+    Queued,
     Terminate,
 }
 
@@ -43,6 +61,9 @@ pub struct ThreadPool {
     receiver: Arc<Mutex<Receiver<ConsolidatedMessage>>>,
     received_ok: usize,
     received_err: usize,
+    pending: AtomicUsize,
+    queue: Arc<Mutex<JobQueue>>,
+    pressure: Arc<PressureState>,
 }
 
 impl ThreadPool {
@@ -56,6 +77,25 @@ impl ThreadPool {
     /// # Error
     /// If `threads` is less than 1, a [`std::io::Error`] is returned.
     pub fn new(threads: usize) -> Result<Self, Error> {
+        return Self::with_aging(threads, Some(DEFAULT_AGING_INTERVAL));
+    }
+
+    /// Creates a new [`ThreadPool`] instance where a queued job's effective
+    /// [`priority::Priority`] rises by 1 for every `aging` it waits. If
+    /// `aging` is [`None`], jobs are run strictly by
+    /// [`priority::Priority`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads, must be at least 1.
+    /// 2. ```aging: Option<Duration>``` => How long a job waits before its
+    ///    effective [`priority::Priority`] goes up by 1.
+    /// 
+    /// # Error
+    /// If `threads` is less than 1, a [`std::io::Error`] is returned.
+    pub fn with_aging(
+        threads: usize,
+        aging: Option<Duration>,
+    ) -> Result<Self, Error> {
         if threads < 1 {
             return Err(Error::new(
                 ErrorKind::InvalidInput,
@@ -69,17 +109,22 @@ impl ThreadPool {
         let receiver = Arc::new(Mutex::new(receiver));
         let worker_receiver = Arc::new(Mutex::new(worker_receiver));
         let worker_transmitter = Arc::new(Mutex::new(worker_transmitter));
+        let queue = Arc::new(Mutex::new(JobQueue::new(aging)));
+        let pressure = Arc::new(PressureState::default());
         let mut workers: Vec<Worker> = Vec::with_capacity(threads);
         for id in 0..threads {
             workers.push(Worker::new(
                 id,
                 worker_receiver.clone(),
-                worker_transmitter.clone()
+                worker_transmitter.clone(),
+                queue.clone(),
+                pressure.clone(),
             ));
         }
 
         let received_ok: usize = 0;
         let received_err: usize = 0;
+        let pending = AtomicUsize::new(0);
 
         return Ok(Self {
             workers,
@@ -87,22 +132,83 @@ impl ThreadPool {
             receiver,
             received_ok,
             received_err,
+            pending,
+            queue,
+            pressure,
         });
     }
 
+    /// Number of [`Worker`] threads in the [`ThreadPool`].
+    pub fn threads(&self) -> usize {
+        return self.workers.len();
+    }
+
+    /// Logs a [`ConsolidatedMessage`] from a [`Worker`] to `self.received_ok`
+    /// or `self.received_err`.
+    fn log(&mut self, message: &ConsolidatedMessage) {
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+        if message.is_ok() {
+            self.received_ok += 1;
+        } else {
+            self.received_err += 1;
+        }
+    }
+
     /// Clear the receiver and logs each [`Result`] to `self.received_ok` and
     /// `self.received_err`.
     fn read_receiver(&mut self) -> Result<(), Error> {
-        while let Ok(message) = unwrapmutex!(self.receiver.lock()).try_recv() {
-            if message.is_ok() {
-                self.received_ok += 1;
-            } else {
-                self.received_err += 1;
-            }
+        loop {
+            let message = match unwrapmutex!(self.receiver.lock()).try_recv() {
+                Ok(message) => message,
+                Err(_error) => break,
+            };
+            self.log(&message);
         }
         return Ok(());
     }
 
+    /// Blocks until every job sent with [`ThreadPool::execute`] so far has
+    /// finished running. If a starvation watchdog is set, the queue is
+    /// checked for starving jobs while waiting. See
+    /// [`ThreadPool::set_starvation_watchdog`].
+    /// 
+    /// # Error
+    /// 
+    /// If any job which finished while waiting returned an error, the first
+    /// such error is returned once all jobs are done.
+    pub fn wait(&mut self) -> Result<(), Error> {
+        let mut first_error: Option<Error> = None;
+        while self.pending.load(Ordering::SeqCst) > 0 {
+            let (report, threshold) = {
+                let mut queue = unwrapmutex!(self.queue.lock());
+                (queue.report(), queue.threshold())
+            };
+            if let Some((watchdog, starving)) = report {
+                watchdog(&starving);
+            }
+            let receiver = unwrapmutex!(self.receiver.lock());
+            let message = match threshold {
+                Some(threshold) => {
+                    receiver.recv_timeout(threshold.max(Duration::from_millis(1)))
+                },
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            drop(receiver);
+            let message = match message {
+                Err(RecvTimeoutError::Timeout) => continue,
+                message => unwrapreceiver!(message),
+            };
+            self.log(&message);
+            if let (Err(error), None) = (message, &first_error) {
+                first_error = Some(error);
+            }
+        }
+        return match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        };
+    }
+
     /// Check how many jobs succeeded.
     pub fn jobs_ok(&mut self) -> Result<usize, Error> {
         self.read_receiver()?;
@@ -121,17 +227,15 @@ impl ThreadPool {
         self.received_err = 0;
     }
 
-    /// Execute a function which runs once.
+    /// Execute a function which runs once. The job has a
+    /// [`priority::Priority`] of `0`, so it runs after any queued jobs sent
+    /// earlier with the same effective [`priority::Priority`].
     pub fn execute<F>(&self, function: F) -> Result<(), Error>
     where
         F: FnOnce() -> ConsolidatedMessage + Send + 'static
     {
-        let job = Box::new(function);
-        return Ok(
-            unwrapsender!(unwrapmutex!(self.transmitter.lock())
-                .send(WorkerMessage::Job(job))
-            )
-        );
+        self.execute_with_priority(0, function)?;
+        return Ok(());
     }
 
     #[
@@ -139,7 +243,11 @@ impl ThreadPool {
         to another object."
     ]
     pub fn collect_node(&self) -> ConsolidatedMessage {
-        return unwrapreceiver!(unwrapmutex!(self.receiver.lock()).recv());
+        let message = unwrapreceiver!(
+            unwrapmutex!(self.receiver.lock()).recv()
+        );
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+        return message;
     }
 }
 
@@ -167,6 +275,7 @@ impl Drop for ThreadPool {
 /// A [`Worker`] contains an `id` which identifies itself and has a `thread`
 /// within itself.
 struct Worker {
+    #[allow(dead_code)]
     pub id: usize,
     thread: Option<JoinHandle<()>>
 }
@@ -178,15 +287,22 @@ impl Worker {
     /// 
     /// 1. ```id: usize``` => Identifier for each [`Worker`]
     /// 2. ```receiver: Arc<Mutex<Receiver<WorkerMessage>>>``` => A receiver
-    /// which receives instructions from the [`ThreadPool`] the [`Worker`]
-    /// resides in.
+    ///    which receives instructions from the [`ThreadPool`] the [`Worker`]
+    ///    resides in.
     /// 3. ```transmitter: Arc<Mutex<Sender<ConsolidatedMessage>>>``` =>
-    /// A transmitter to the [`ThreadPool`].
+    ///    A transmitter to the [`ThreadPool`].
+    /// 4. ```queue: Arc<Mutex<JobQueue>>``` => The queue of jobs waiting to
+    ///    be run, shared by every [`Worker`] in the [`ThreadPool`].
+    /// 5. ```pressure: Arc<PressureState>``` => The memory pressure flag and
+    ///    the hooks to run when it is raised.
     pub fn new(
         id: usize,
         receiver: Arc<Mutex<Receiver<WorkerMessage>>>,
         transmitter: Arc<Mutex<Sender<ConsolidatedMessage>>>,
+        queue: Arc<Mutex<JobQueue>>,
+        pressure: Arc<PressureState>,
     ) -> Self {
+        let mut seen_pressure: usize = 0;
         let thread = spawn(move || loop {
             let message = receiver
                 .lock()
@@ -194,18 +310,28 @@ impl Worker {
                 .recv()
                 .unwrap();
 
-            match message {
-                WorkerMessage::Job(job) => {
-                    transmitter
-                        .lock()
-                        .unwrap()
-                        .send(job())
-                        .unwrap();
+            let job = match message {
+                WorkerMessage::Job(job) => job,
+                WorkerMessage::Queued => {
+                    // Every Queued message has exactly one job in the queue.
+                    let (job, report) = queue.lock().unwrap().pop().unwrap();
+                    if let Some((watchdog, starving)) = report {
+                        watchdog(&starving);
+                    }
+                    job
                 },
                 WorkerMessage::Terminate => {
-                    return ();
+                    return;
                 }
-            }
+            };
+            let result = job();
+            reset_local();
+            pressure.relieve(&mut seen_pressure);
+            transmitter
+                .lock()
+                .unwrap()
+                .send(result)
+                .unwrap();
         });
 
         return Self {id, thread: Some(thread)};
@@ -0,0 +1,224 @@
+//! This module lets the program embedding a [`ThreadPool`] tell it that
+//! memory is running low.
+//! 
+//! When [`MemoryPressure::signal`] is called, each worker thread runs the
+//! hooks registered with [`ThreadPool::on_memory_pressure`] once before
+//! taking its next job and releases the memory held by its
+//! [`crate::arena::Arena`]. Algorithms can use the hooks to shrink caches or
+//! spill partial results. A [`MemoryBudget`] signals the pressure by itself
+//! when the memory reserved from it passes a soft limit.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::{
+        Arc,
+        Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+use super::ThreadPool;
+use crate::{arena::shrink_local, estimate::Estimate, unwrapmutex};
+
+/// Fraction of a [`MemoryBudget`] which can be reserved before memory
+/// pressure is signalled.
+pub const SOFT_LIMIT_RATIO: f64 = 0.8;
+
+/// Callback run by worker threads when memory pressure is signalled.
+type Hook = Arc<dyn Fn() + Send + Sync + 'static>;
+
+/// A flag which can be raised by the embedding program to ask a
+/// [`ThreadPool`] to free memory. Cloning it gives another handle to the
+/// same flag.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPressure {
+    active: Arc<AtomicBool>,
+    /// Counts how many times the flag has been raised, so each worker runs
+    /// the hooks once per signal.
+    generation: Arc<AtomicUsize>,
+}
+
+impl MemoryPressure {
+    /// Creates a new [`MemoryPressure`] flag which is not raised.
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Raises the flag. Each worker will run the hooks once, even if the
+    /// flag was already raised.
+    pub fn signal(&self) {
+        self.generation.fetch_add(1, SeqCst);
+        self.active.store(true, SeqCst);
+    }
+
+    /// Lowers the flag.
+    pub fn clear(&self) {
+        self.active.store(false, SeqCst);
+    }
+
+    /// Whether the flag is raised.
+    pub fn is_signalled(&self) -> bool {
+        return self.active.load(SeqCst);
+    }
+}
+
+/// The memory pressure flag and hooks shared between a [`ThreadPool`] and
+/// its worker threads.
+#[derive(Default)]
+pub(super) struct PressureState {
+    pub(super) pressure: MemoryPressure,
+    hooks: Mutex<Vec<Hook>>,
+}
+
+impl PressureState {
+    /// Runs the hooks and frees the current thread's
+    /// [`crate::arena::Arena`] if the flag has been raised since `seen`,
+    /// which is then updated.
+    pub(super) fn relieve(&self, seen: &mut usize) {
+        if !self.pressure.is_signalled() {
+            return;
+        }
+        let generation = self.pressure.generation.load(SeqCst);
+        if generation == *seen {
+            return;
+        }
+        *seen = generation;
+        let hooks: Vec<Hook> = match self.hooks.lock() {
+            Ok(hooks) => hooks.clone(),
+            Err(_error) => return,
+        };
+        for hook in hooks {
+            hook();
+        }
+        shrink_local();
+    }
+}
+
+/// A limit on the memory algorithms may reserve. Reserving more than
+/// [`SOFT_LIMIT_RATIO`] of the limit signals memory pressure on the
+/// [`ThreadPool`] the budget came from, and reserving past the limit fails.
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    limit: u128,
+    soft_limit: u128,
+    used: Arc<Mutex<u128>>,
+    /// The generation of `pressure` raised by this budget, or `0` if the
+    /// flag was not raised by it, so it never lowers a flag raised by
+    /// someone else.
+    raised: Arc<AtomicUsize>,
+    pressure: MemoryPressure,
+}
+
+impl MemoryBudget {
+    /// Creates a new [`MemoryBudget`] of `limit` bytes which raises
+    /// `pressure` past its soft limit.
+    pub fn new(limit: u128, pressure: MemoryPressure) -> Self {
+        let soft_limit = (limit as f64 * SOFT_LIMIT_RATIO) as u128;
+        return Self {
+            limit,
+            soft_limit,
+            used: Arc::new(Mutex::new(0)),
+            raised: Arc::new(AtomicUsize::new(0)),
+            pressure,
+        };
+    }
+
+    /// Reserves `bytes` bytes until the returned [`Reservation`] is dropped.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] of kind [`ErrorKind::OutOfMemory`] is returned if
+    /// the reservation would go over the limit.
+    pub fn reserve(&self, bytes: u128) -> Result<Reservation, Error> {
+        let mut used = unwrapmutex!(self.used.lock());
+        let total = used.saturating_add(bytes);
+        if total > self.limit {
+            return Err(Error::new(
+                ErrorKind::OutOfMemory,
+                format!(
+                    "Reserving {} bytes would exceed the budget of {} bytes \
+                    ({} already reserved).",
+                    bytes, self.limit, *used
+                )
+            ));
+        }
+        *used = total;
+        if total > self.soft_limit && !self.pressure.is_signalled() {
+            self.pressure.signal();
+            self.raised.store(self.pressure.generation.load(SeqCst), SeqCst);
+        }
+        return Ok(Reservation {bytes, budget: self.clone()});
+    }
+
+    /// Reserves the memory predicted by `estimate`. See
+    /// [`MemoryBudget::reserve`].
+    pub fn reserve_estimate(&self, estimate: &Estimate) -> Result<Reservation, Error> {
+        return self.reserve(estimate.memory);
+    }
+
+    /// Number of bytes currently reserved.
+    pub fn used(&self) -> Result<u128, Error> {
+        return Ok(*unwrapmutex!(self.used.lock()));
+    }
+
+    /// The limit of the budget in bytes.
+    pub fn limit(&self) -> u128 {
+        return self.limit;
+    }
+}
+
+/// Memory reserved from a [`MemoryBudget`], which is given back when the
+/// [`Reservation`] is dropped.
+#[derive(Debug)]
+pub struct Reservation {
+    bytes: u128,
+    budget: MemoryBudget,
+}
+
+impl Reservation {
+    /// Number of bytes reserved.
+    pub fn bytes(&self) -> u128 {
+        return self.bytes;
+    }
+}
+
+impl Drop for Reservation {
+    /// Gives the bytes back to the budget, lowering the memory pressure flag
+    /// once usage drops back under the soft limit, if the budget raised it
+    /// and it has not been raised again since.
+    fn drop(&mut self) {
+        if let Ok(mut used) = self.budget.used.lock() {
+            *used = used.saturating_sub(self.bytes);
+            if *used <= self.budget.soft_limit {
+                let raised = self.budget.raised.swap(0, SeqCst);
+                let generation = self.budget.pressure.generation.load(SeqCst);
+                if raised != 0 && raised == generation {
+                    self.budget.pressure.clear();
+                }
+            }
+        }
+    }
+}
+
+impl ThreadPool {
+    /// Registers `hook` to be run by every worker thread, between jobs, once
+    /// each time memory pressure is signalled.
+    pub fn on_memory_pressure<F>(&self, hook: F) -> Result<(), Error>
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        unwrapmutex!(self.pressure.hooks.lock()).push(Arc::new(hook));
+        return Ok(());
+    }
+
+    /// Get a handle to the memory pressure flag of the [`ThreadPool`].
+    pub fn memory_pressure(&self) -> MemoryPressure {
+        return self.pressure.pressure.clone();
+    }
+
+    /// Creates a [`MemoryBudget`] of `limit` bytes which signals memory
+    /// pressure on this [`ThreadPool`] past its soft limit.
+    pub fn memory_budget(&self, limit: u128) -> MemoryBudget {
+        return MemoryBudget::new(limit, self.memory_pressure());
+    }
+}
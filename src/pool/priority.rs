@@ -0,0 +1,233 @@
+//! This module contains the queue deciding which job a [`ThreadPool`] runs
+//! next.
+//! 
+//! Every job has a [`Priority`] and jobs with a higher [`Priority`] are run
+//! first. To stop a steady stream of important jobs from starving the rest,
+//! a job's effective [`Priority`] rises by 1 for every aging interval it
+//! spends waiting in the queue. Because every job ages at the same rate, the
+//! order of 2 queued jobs never changes while they wait, so the queue can be
+//! kept in a [`BinaryHeap`].
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashSet},
+    io::Error,
+    sync::{Arc, atomic::Ordering::SeqCst},
+    time::{Duration, Instant},
+};
+
+use super::{Job, ThreadPool, WorkerMessage};
+use crate::{unwrapmutex, unwrapsender};
+
+/// Priority of a job. Jobs with a higher [`Priority`] are run first and jobs
+/// sent with [`ThreadPool::execute`] have a [`Priority`] of `0`.
+pub type Priority = i64;
+
+/// How long a job waits before its effective [`Priority`] rises by 1 in a
+/// [`ThreadPool`] created with [`ThreadPool::new`].
+pub const DEFAULT_AGING_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A job which has been waiting in the queue for longer than the threshold
+/// given to [`ThreadPool::starving`] or
+/// [`ThreadPool::set_starvation_watchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StarvingJob {
+    /// Identifier returned by [`ThreadPool::execute_with_priority`].
+    pub id: usize,
+    /// The [`Priority`] the job was sent with.
+    pub priority: Priority,
+    /// How long the job has been waiting.
+    pub waited: Duration,
+}
+
+/// Callback run with the jobs found by a starvation watchdog.
+type Watchdog = Arc<dyn Fn(&[StarvingJob]) + Send + Sync + 'static>;
+
+/// Newly starving jobs and the watchdog they should be reported to.
+pub(super) type StarvationReport = (Watchdog, Vec<StarvingJob>);
+
+/// A job waiting in the [`JobQueue`].
+struct QueuedJob {
+    /// Effective priority at the time the queue was created. Comparing keys
+    /// gives the same result as comparing effective priorities at any later
+    /// time.
+    key: i128,
+    id: usize,
+    priority: Priority,
+    queued_at: Instant,
+    job: Job,
+}
+
+impl PartialEq for QueuedJob {
+    fn eq(&self, other: &Self) -> bool {
+        return self.cmp(other) == Ordering::Equal;
+    }
+}
+
+impl Eq for QueuedJob {}
+
+impl PartialOrd for QueuedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for QueuedJob {
+    /// Higher keys come first, and jobs with the same key are run in the
+    /// order they were sent.
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.key.cmp(&other.key).then(other.id.cmp(&self.id));
+    }
+}
+
+/// The queue of jobs shared between a [`ThreadPool`] and its worker threads.
+pub(super) struct JobQueue {
+    heap: BinaryHeap<QueuedJob>,
+    epoch: Instant,
+    aging: Option<Duration>,
+    next_id: usize,
+    watchdog: Option<(Duration, Watchdog)>,
+    reported: HashSet<usize>,
+}
+
+impl JobQueue {
+    /// Creates a new [`JobQueue`] where waiting jobs gain 1 [`Priority`]
+    /// every `aging`, or never if `aging` is [`None`].
+    pub(super) fn new(aging: Option<Duration>) -> Self {
+        return Self {
+            heap: BinaryHeap::new(),
+            epoch: Instant::now(),
+            aging,
+            next_id: 0,
+            watchdog: None,
+            reported: HashSet::new(),
+        };
+    }
+
+    /// Adds a job to the queue and returns its identifier.
+    fn push(&mut self, priority: Priority, job: Job) -> usize {
+        let queued_at = Instant::now();
+        let key = match self.aging {
+            Some(aging) => {
+                let aging = aging.as_nanos().max(1) as i128;
+                let since_epoch = (queued_at - self.epoch).as_nanos() as i128;
+                (priority as i128).saturating_mul(aging) - since_epoch
+            },
+            None => priority as i128,
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        self.heap.push(QueuedJob {key, id, priority, queued_at, job});
+        return id;
+    }
+
+    /// Takes the job with the highest effective [`Priority`] out of the
+    /// queue, along with any jobs left behind which the starvation watchdog
+    /// has not reported yet. The watchdog is called by the worker after the
+    /// queue is unlocked.
+    pub(super) fn pop(&mut self) -> Option<(Job, Option<StarvationReport>)> {
+        let queued = self.heap.pop()?;
+        self.reported.remove(&queued.id);
+        return Some((queued.job, self.report()));
+    }
+
+    /// Finds the jobs which the starvation watchdog has not reported yet,
+    /// if there is a watchdog and any such jobs.
+    pub(super) fn report(&mut self) -> Option<StarvationReport> {
+        let (threshold, watchdog) = self.watchdog.clone()?;
+        let starving: Vec<StarvingJob> = self
+            .starving(threshold)
+            .into_iter()
+            .filter(|job| self.reported.insert(job.id))
+            .collect();
+        if starving.is_empty() {
+            return None;
+        }
+        return Some((watchdog, starving));
+    }
+
+    /// Get the threshold of the starvation watchdog, if there is one.
+    pub(super) fn threshold(&self) -> Option<Duration> {
+        return self.watchdog.as_ref().map(|(threshold, _)| *threshold);
+    }
+
+    /// Lists the queued jobs which have waited longer than `threshold`.
+    fn starving(&self, threshold: Duration) -> Vec<StarvingJob> {
+        let now = Instant::now();
+        return self
+            .heap
+            .iter()
+            .filter_map(|queued| {
+                let waited = now - queued.queued_at;
+                if waited <= threshold {
+                    return None;
+                }
+                return Some(StarvingJob {
+                    id: queued.id,
+                    priority: queued.priority,
+                    waited,
+                });
+            })
+            .collect();
+    }
+}
+
+impl ThreadPool {
+    /// Execute a function which runs once, ahead of any queued jobs with a
+    /// lower effective [`Priority`]. Returns an identifier for the job which
+    /// is used when reporting [`StarvingJob`]s.
+    pub fn execute_with_priority<F>(
+        &self,
+        priority: Priority,
+        function: F,
+    ) -> Result<usize, Error>
+    where
+        F: FnOnce() -> Result<(), Error> + Send + 'static
+    {
+        let job: Job = Box::new(function);
+        let id = unwrapmutex!(self.queue.lock()).push(priority, job);
+        // Counted before sending so a fast worker cannot report back first.
+        self.pending.fetch_add(1, SeqCst);
+        let sent = unwrapmutex!(self.transmitter.lock())
+            .send(WorkerMessage::Queued);
+        if sent.is_err() {
+            self.pending.fetch_sub(1, SeqCst);
+        }
+        unwrapsender!(sent);
+        return Ok(id);
+    }
+
+    /// Lists the jobs which have been waiting to run for longer than
+    /// `threshold`.
+    pub fn starving(&self, threshold: Duration) -> Result<Vec<StarvingJob>, Error> {
+        return Ok(unwrapmutex!(self.queue.lock()).starving(threshold));
+    }
+
+    /// Sets up a watchdog which calls `callback` with the jobs that have been
+    /// waiting for longer than `threshold`. The check happens whenever a
+    /// worker thread takes a job from the queue, and every `threshold`
+    /// while [`ThreadPool::wait`] blocks, so jobs are still reported when
+    /// every worker is busy. Each job is only reported once. `callback`
+    /// runs on the worker thread before the job it took, or on the thread
+    /// calling [`ThreadPool::wait`].
+    pub fn set_starvation_watchdog<F>(
+        &self,
+        threshold: Duration,
+        callback: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(&[StarvingJob]) + Send + Sync + 'static,
+    {
+        let mut queue = unwrapmutex!(self.queue.lock());
+        queue.watchdog = Some((threshold, Arc::new(callback)));
+        queue.reported.clear();
+        return Ok(());
+    }
+
+    /// Removes the watchdog set with
+    /// [`ThreadPool::set_starvation_watchdog`].
+    pub fn clear_starvation_watchdog(&self) -> Result<(), Error> {
+        unwrapmutex!(self.queue.lock()).watchdog = None;
+        return Ok(());
+    }
+}
@@ -0,0 +1,17 @@
+//! Module re-exporting the items most programs using this crate need, so
+//! they can be brought in with `use mtdalgos::prelude::*;`.
+
+pub use crate::{
+    dijkstra::simple::{
+        AdjacencyMatrix,
+        Cost,
+        CsrGraph,
+        DistanceTable,
+        MtdDijkstra,
+        Node,
+        NodeWithCost,
+    },
+    graph,
+    pipeline::{Pipeline, Subgraph},
+    pool::ThreadPool,
+};
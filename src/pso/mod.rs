@@ -0,0 +1,334 @@
+//! Module for particle swarm optimisation, which looks for the lowest value
+//! of a function of several real numbers by flying a swarm of particles
+//! over the space of its inputs.
+//! 
+//! Each particle remembers the lowest point it has seen, and the swarm
+//! remembers the lowest point any particle has seen. Every iteration, the
+//! velocity of each particle is made up of 3 pulls:
+//! - Its velocity so far, scaled by the inertia.
+//! - Towards its own lowest point, scaled by the cognitive coefficient and
+//!   a random share.
+//! - Towards the lowest point of the swarm, scaled by the social
+//!   coefficient and a random share.
+//! 
+//! The particles then move by their velocity, staying within the bounds of
+//! each input. [`MtdPso`] moves and evaluates the particles of each
+//! iteration in parallel on a [`ThreadPool`], with every particle drawing
+//! from its own random stream, so the same seed always gives the same
+//! result whatever the number of threads. The lowest point of the swarm is
+//! only updated between iterations.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+};
+
+/// The number of particles unless another is set with
+/// [`MtdPso::set_particles`].
+pub const DEFAULT_PARTICLES: usize = 40;
+/// The number of iterations unless another is set with
+/// [`MtdPso::set_iterations`].
+pub const DEFAULT_ITERATIONS: usize = 200;
+/// The inertia unless another is set with [`MtdPso::set_coefficients`].
+pub const DEFAULT_INERTIA: f64 = 0.729;
+/// The cognitive coefficient unless another is set with
+/// [`MtdPso::set_coefficients`].
+pub const DEFAULT_COGNITIVE: f64 = 1.494;
+/// The social coefficient unless another is set with
+/// [`MtdPso::set_coefficients`].
+pub const DEFAULT_SOCIAL: f64 = 1.494;
+
+/// Closure giving the value of a point.
+type Objective = Arc<dyn Fn(&[f64]) -> f64 + Send + Sync + 'static>;
+
+/// The lowest point found by [`MtdPso::run`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Optimum {
+    /// The point with the lowest value found by any particle.
+    pub position: Vec<f64>,
+    /// The value at [`Optimum::position`].
+    pub value: f64,
+    /// The lowest value found after each iteration, starting with the
+    /// particles where they were placed.
+    pub history: Vec<f64>,
+}
+
+/// The inertia, cognitive and social coefficients.
+#[derive(Clone, Copy)]
+struct Coefficients {
+    inertia: f64,
+    cognitive: f64,
+    social: f64,
+}
+
+/// One particle of the swarm.
+#[derive(Clone)]
+struct Particle {
+    position: Vec<f64>,
+    velocity: Vec<f64>,
+    best: (Vec<f64>, f64),
+    rng: Rng,
+}
+
+impl Particle {
+    /// Places a particle at random within `bounds`, with a random velocity
+    /// of at most the width of each bound, and evaluates it.
+    fn new(objective: &Objective, bounds: &[(f64, f64)], mut rng: Rng) -> Self {
+        let position: Vec<f64> = bounds
+            .iter()
+            .map(|(low, high)| rng.range_f64(*low, *high))
+            .collect();
+        let velocity: Vec<f64> = bounds
+            .iter()
+            .map(|(low, high)| rng.range_f64(low - high, high - low))
+            .collect();
+        let value = evaluate(objective, &position);
+        return Self {best: (position.clone(), value), position, velocity, rng};
+    }
+
+    /// Moves a copy of the particle pulled towards `swarm`, the lowest point
+    /// of the swarm, and evaluates it.
+    fn step(
+        &self,
+        objective: &Objective,
+        bounds: &[(f64, f64)],
+        swarm: &[f64],
+        coefficients: Coefficients,
+    ) -> Self {
+        let mut particle = self.clone();
+        for (dimension, (low, high)) in bounds.iter().enumerate() {
+            let (own, shared) = (particle.rng.next_f64(), particle.rng.next_f64());
+            let position = particle.position[dimension];
+            let width = high - low;
+            let velocity = coefficients.inertia * particle.velocity[dimension]
+                + coefficients.cognitive * own * (particle.best.0[dimension] - position)
+                + coefficients.social * shared * (swarm[dimension] - position);
+            let velocity = velocity.clamp(-width, width);
+            let moved = position + velocity;
+            // A particle hitting a bound stops there in that dimension.
+            particle.velocity[dimension] = if (*low..=*high).contains(&moved) {
+                velocity
+            } else {
+                0.0
+            };
+            particle.position[dimension] = moved.clamp(*low, *high);
+        }
+        let value = evaluate(objective, &particle.position);
+        if value < particle.best.1 {
+            particle.best = (particle.position.clone(), value);
+        }
+        return particle;
+    }
+}
+
+/// Get the value of `position`, with a value which is not a number counting
+/// as the highest.
+fn evaluate(objective: &Objective, position: &[f64]) -> f64 {
+    let value = objective(position);
+    return if value.is_nan() { f64::INFINITY } else { value };
+}
+
+/// Get the lowest point any of `particles` has seen, ties going to the
+/// first particle.
+fn lowest(particles: &[Particle]) -> (Vec<f64>, f64) {
+    let mut lowest = &particles[0].best;
+    for particle in &particles[1..] {
+        if particle.best.1 < lowest.1 {
+            lowest = &particle.best;
+        }
+    }
+    return lowest.clone();
+}
+
+/// This `struct` minimises a function with particle swarm optimisation
+/// using multiple threads. See the module documentation.
+pub struct MtdPso {
+    pool: ThreadPool,
+    objective: Objective,
+    bounds: Arc<Vec<(f64, f64)>>,
+    particles: usize,
+    iterations: usize,
+    coefficients: Coefficients,
+}
+
+impl MtdPso {
+    /// Creates a new [`MtdPso`] instance with [`DEFAULT_PARTICLES`],
+    /// [`DEFAULT_ITERATIONS`], [`DEFAULT_INERTIA`], [`DEFAULT_COGNITIVE`]
+    /// and [`DEFAULT_SOCIAL`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```bounds: Vec<(f64, f64)>``` => The lowest and highest value of
+    ///    each input of `objective`, which are searched between.
+    /// 3. ```objective: F``` => The function to minimise, given a point
+    ///    with one value per bound. A value which is not a number counts as
+    ///    the highest.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`, if `bounds` is empty, or if a bound is not finite or its
+    /// lowest value is above its highest.
+    pub fn new<F>(
+        threads: usize,
+        bounds: Vec<(f64, f64)>,
+        objective: F,
+    ) -> Result<Self, Error>
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        if bounds.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "At least 1 bound is needed."
+            ));
+        }
+        for (dimension, (low, high)) in bounds.iter().enumerate() {
+            if !(low.is_finite() && high.is_finite() && low <= high) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "The bound {} of [{}, {}] is not valid.",
+                        dimension, low, high
+                    )
+                ));
+            }
+        }
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {
+            pool,
+            objective: Arc::new(objective),
+            bounds: Arc::new(bounds),
+            particles: DEFAULT_PARTICLES,
+            iterations: DEFAULT_ITERATIONS,
+            coefficients: Coefficients {
+                inertia: DEFAULT_INERTIA,
+                cognitive: DEFAULT_COGNITIVE,
+                social: DEFAULT_SOCIAL,
+            },
+        });
+    }
+
+    /// Get the lowest and highest value of each input.
+    pub fn bounds(&self) -> &[(f64, f64)] {
+        return &self.bounds;
+    }
+
+    /// Get the number of particles.
+    pub fn particles(&self) -> usize {
+        return self.particles;
+    }
+
+    /// Sets the number of particles.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `particles` is `0`, and the
+    /// number of particles is left as it was.
+    pub fn set_particles(&mut self, particles: usize) -> Result<(), Error> {
+        if particles == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "At least 1 particle is needed."
+            ));
+        }
+        self.particles = particles;
+        return Ok(());
+    }
+
+    /// Get the number of iterations.
+    pub fn iterations(&self) -> usize {
+        return self.iterations;
+    }
+
+    /// Sets the number of iterations.
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+
+    /// Get the inertia, cognitive and social coefficients.
+    pub fn coefficients(&self) -> (f64, f64, f64) {
+        let Coefficients {inertia, cognitive, social} = self.coefficients;
+        return (inertia, cognitive, social);
+    }
+
+    /// Sets the inertia, cognitive and social coefficients. See the module
+    /// documentation.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if any coefficient is negative or
+    /// not finite, and the coefficients are left as they were.
+    pub fn set_coefficients(
+        &mut self,
+        inertia: f64,
+        cognitive: f64,
+        social: f64,
+    ) -> Result<(), Error> {
+        for coefficient in [inertia, cognitive, social] {
+            if !(coefficient.is_finite() && coefficient >= 0.0) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "The coefficient {} is not a non-negative number.",
+                        coefficient
+                    )
+                ));
+            }
+        }
+        self.coefficients = Coefficients {inertia, cognitive, social};
+        return Ok(());
+    }
+
+    /// Flies the swarm and returns the lowest point found. See the module
+    /// documentation.
+    /// 
+    /// # Parameters
+    /// 1. ```seed: u64``` => Seed for the random streams of the particles.
+    ///    The same seed always gives the same result.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn run(&mut self, seed: u64) -> Result<Optimum, Error> {
+        let streams: Vec<u64> = (0..self.particles as u64).collect();
+        let (objective, bounds) = (self.objective.clone(), self.bounds.clone());
+        let mut particles = self.pool.map(
+            Arc::new(streams),
+            ChunkPolicy::Auto,
+            move |stream| {
+                let rng = Rng::stream(seed, *stream);
+                return Particle::new(&objective, &bounds, rng);
+            },
+        )?;
+        let mut swarm = lowest(&particles);
+        let mut history: Vec<f64> = vec![swarm.1];
+
+        for _ in 0..self.iterations {
+            let (objective, bounds) = (self.objective.clone(), self.bounds.clone());
+            let coefficients = self.coefficients;
+            let position = Arc::new(swarm.0.clone());
+            particles = self.pool.map(
+                Arc::new(particles),
+                ChunkPolicy::Auto,
+                move |particle| {
+                    return particle.step(&objective, &bounds, &position, coefficients);
+                },
+            )?;
+            let next = lowest(&particles);
+            if next.1 < swarm.1 {
+                swarm = next;
+            }
+            history.push(swarm.1);
+        }
+        return Ok(Optimum {position: swarm.0, value: swarm.1, history});
+    }
+}
@@ -0,0 +1,249 @@
+//! Module for random walks, which wander a graph by following a random edge
+//! out of the current [`Node`] at each step, and count how often each
+//! [`Node`] is visited.
+//! 
+//! At each step, a walk jumps back to where it started with the restart
+//! probability, or when there are no edges out, and follows a random edge
+//! otherwise. Started from every [`Node`], the share of the visits each
+//! [`Node`] gets is close to its rank in [`crate::pagerank`] with a damping
+//! factor of `1 - restart`, and started from a few [`Node`]s, it is close to
+//! their personalised PageRank, which scores [`Node`]s by how close they
+//! are to those few. The [`Node`]s visited are also a sample of the graph
+//! around where the walks started.
+//! 
+//! [`MtdRandomWalk`] splits the walks between the workers of a
+//! [`ThreadPool`]. Each walk draws its steps from its own random stream, so
+//! the same seed always gives the same visits, whatever the number of
+//! threads, and each worker counts the visits of its own walks, so no
+//! locking is needed until the counts are added up. The [`Cost`]s of the
+//! edges are ignored.
+//! 
+//! [`Cost`]: crate::dijkstra::simple::Cost
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, JOBS_PER_THREAD, Node, check_node},
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+    rng::Rng,
+};
+
+/// The number of steps of each walk unless another is set with
+/// [`MtdRandomWalk::set_length`].
+pub const DEFAULT_LENGTH: usize = 20;
+/// The restart probability used unless another is set with
+/// [`MtdRandomWalk::set_restart`].
+pub const DEFAULT_RESTART: f64 = 0.15;
+
+/// The visits counted by [`MtdRandomWalk::calculate`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Visits {
+    /// Number of times each [`Node`] was visited, counting the [`Node`]
+    /// each walk started from.
+    pub counts: Vec<usize>,
+    /// Number of walks run.
+    pub walks: usize,
+    /// Number of steps of each walk.
+    pub length: usize,
+}
+
+impl Visits {
+    /// Number of visits counted in total, which is `length + 1` per walk.
+    pub fn total(&self) -> usize {
+        return self.walks * (self.length + 1);
+    }
+
+    /// Get the share of the visits each [`Node`] got. The shares add up to
+    /// `1`, unless no walks were run and every share is `0`.
+    pub fn frequencies(&self) -> Vec<f64> {
+        let total = self.total().max(1) as f64;
+        return self.counts.iter().map(|count| *count as f64 / total).collect();
+    }
+
+    /// Get the [`Node`]s visited at least once, in order.
+    pub fn visited(&self) -> Vec<Node> {
+        return (0..self.counts.len()).filter(|node| self.counts[*node] > 0).collect();
+    }
+
+    /// Get the `count` [`Node`]s visited the most as `(node, visits)`, most
+    /// first. Ties go to the lower [`Node`].
+    pub fn top(&self, count: usize) -> Vec<(Node, usize)> {
+        let mut ranked: Vec<(Node, usize)> = self
+            .counts
+            .iter()
+            .copied()
+            .enumerate()
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        ranked.truncate(count);
+        return ranked;
+    }
+}
+
+/// Runs a walk of `length` steps from `start` on `graph`, drawing from `rng`
+/// and adding its visits to `counts`.
+fn walk(
+    graph: &CsrGraph,
+    start: Node,
+    length: usize,
+    restart: f64,
+    rng: &mut Rng,
+    counts: &mut [usize],
+) {
+    let mut node = start;
+    counts[node] += 1;
+    for _ in 0..length {
+        let edges = graph.get_node(node).unwrap_or(&[]);
+        node = if edges.is_empty() || rng.chance(restart) {
+            start
+        } else {
+            edges[rng.below(edges.len())].node
+        };
+        counts[node] += 1;
+    }
+}
+
+/// This `struct` runs random walks on a graph using multiple threads. See
+/// the module documentation.
+pub struct MtdRandomWalk {
+    pool: ThreadPool,
+    graph: Arc<CsrGraph>,
+    length: usize,
+    restart: f64,
+}
+
+impl MtdRandomWalk {
+    /// Creates a new [`MtdRandomWalk`] instance with [`DEFAULT_LENGTH`] and
+    /// [`DEFAULT_RESTART`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix``` => The graph. The [`Cost`]s of its
+    ///    edges are ignored.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    /// 
+    /// [`Cost`]: crate::dijkstra::simple::Cost
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix) -> Result<Self, Error> {
+        return Self::from_csr(threads, CsrGraph::from(matrix));
+    }
+
+    /// Creates a new [`MtdRandomWalk`] instance from a graph which is
+    /// already a [`CsrGraph`]. See [`MtdRandomWalk::new`].
+    pub fn from_csr(threads: usize, graph: CsrGraph) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {
+            pool,
+            graph: Arc::new(graph),
+            length: DEFAULT_LENGTH,
+            restart: DEFAULT_RESTART,
+        });
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.graph.total();
+    }
+
+    /// Get the number of steps of each walk.
+    pub fn length(&self) -> usize {
+        return self.length;
+    }
+
+    /// Sets the number of steps of each walk.
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length;
+    }
+
+    /// Get the chance of jumping back to the start at each step.
+    pub fn restart(&self) -> f64 {
+        return self.restart;
+    }
+
+    /// Sets the chance of jumping back to the start at each step.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `restart` is not between `0` and
+    /// `1`, and the restart probability is left as it was.
+    pub fn set_restart(&mut self, restart: f64) -> Result<(), Error> {
+        if !(0.0..=1.0).contains(&restart) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("The restart probability {} is not in [0, 1].", restart)
+            ));
+        }
+        self.restart = restart;
+        return Ok(());
+    }
+
+    /// Runs `walks` walks from every [`Node`] of the graph and counts the
+    /// visits. See the module documentation.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn calculate(&mut self, walks: usize, seed: u64) -> Result<Visits, Error> {
+        let sources: Vec<Node> = (0..self.total()).collect();
+        return self.calculate_sources(&sources, walks, seed);
+    }
+
+    /// Runs `walks` walks from each of `sources` and counts the visits.
+    /// Each walk jumps back to its own source when it restarts.
+    /// 
+    /// # Parameters
+    /// 1. ```sources: &[Node]``` => Where the walks start. A [`Node`] given
+    ///    twice starts twice as many walks.
+    /// 2. ```walks: usize``` => Number of walks started from each source.
+    /// 3. ```seed: u64``` => Seed for the steps. The same seed always gives
+    ///    the same visits.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if any of `sources` is not in the
+    /// graph, or if a job on the [`ThreadPool`] fails.
+    pub fn calculate_sources(
+        &mut self,
+        sources: &[Node],
+        walks: usize,
+        seed: u64,
+    ) -> Result<Visits, Error> {
+        let total = self.total();
+        for source in sources {
+            check_node(*source, total)?;
+        }
+        let count = sources.len() * walks;
+        let ranges = ranges(count, self.pool.threads() * JOBS_PER_THREAD);
+
+        let graph = self.graph.clone();
+        let sources = Arc::new(sources.to_vec());
+        let (length, restart) = (self.length, self.restart);
+        let policy = ChunkPolicy::Fixed(1);
+        let partials = self.pool.map(Arc::new(ranges), policy, move |range| {
+            let mut counts: Vec<usize> = vec![0; total];
+            for index in range.0..range.1 {
+                let mut rng = Rng::stream(seed, index as u64);
+                let start = sources[index / walks];
+                walk(&graph, start, length, restart, &mut rng, &mut counts);
+            }
+            return counts;
+        })?;
+
+        let mut visits = Visits {counts: vec![0; total], walks: count, length};
+        for counts in partials {
+            for (sum, part) in visits.counts.iter_mut().zip(counts) {
+                *sum += part;
+            }
+        }
+        return Ok(visits);
+    }
+}
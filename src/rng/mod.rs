@@ -0,0 +1,92 @@
+//! Module for a small seeded random number generator, so that algorithms
+//! which need randomness give reproducible results without pulling in any
+//! dependencies.
+//! 
+//! [`Rng`] implements xoshiro256\*\*, seeded through SplitMix64. It is fast
+//! and has good statistical quality, but it is not suitable for
+//! cryptography.
+
+/// Advances a SplitMix64 `state` and returns its next output.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut value = *state;
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    return value ^ (value >> 31);
+}
+
+/// A seeded xoshiro256\*\* random number generator. The same seed always
+/// produces the same sequence of numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    /// Creates a new [`Rng`] from `seed`.
+    pub fn new(seed: u64) -> Self {
+        let mut mixer = seed;
+        let state = [
+            splitmix64(&mut mixer),
+            splitmix64(&mut mixer),
+            splitmix64(&mut mixer),
+            splitmix64(&mut mixer),
+        ];
+        return Self {state};
+    }
+
+    /// Creates an independent [`Rng`] for stream number `stream` of `seed`,
+    /// such as one per worker, so parallel jobs do not share a generator.
+    pub fn stream(seed: u64, stream: u64) -> Self {
+        let mut mixer = seed ^ stream.wrapping_mul(0xd605_bbb5_8c8a_bbb5);
+        return Self::new(splitmix64(&mut mixer));
+    }
+
+    /// Returns the next random [`u64`].
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1]
+            .wrapping_mul(5)
+            .rotate_left(7)
+            .wrapping_mul(9);
+        let shifted = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= shifted;
+        self.state[3] = self.state[3].rotate_left(45);
+        return result;
+    }
+
+    /// Returns a random [`f64`] in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        return (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+    }
+
+    /// Returns a random [`f64`] in `[low, high)`.
+    pub fn range_f64(&mut self, low: f64, high: f64) -> f64 {
+        return low + (high - low) * self.next_f64();
+    }
+
+    /// Returns a random [`usize`] in `[0, bound)`, or `0` if `bound` is `0`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        // Multiply-shift keeps the bias negligible for any bound that fits
+        // in a usize.
+        return ((self.next_u64() as u128 * bound as u128) >> 64) as usize;
+    }
+
+    /// Returns `true` with probability `probability`.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        return self.next_f64() < probability;
+    }
+
+    /// Shuffles `items` in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index + 1));
+        }
+    }
+}
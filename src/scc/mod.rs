@@ -0,0 +1,342 @@
+//! Module for finding the strongly connected components of a directed
+//! graph, which are the groups of [`Node`]s that can all reach each other.
+//! 
+//! [`MtdScc`] uses forward-backward decomposition. The [`Node`]s which can
+//! be reached from a pivot and those which can reach it overlap in exactly
+//! the component of the pivot. What is left over falls into three groups
+//! with no component spanning two of them: reachable from the pivot only,
+//! reaching it only, and neither. The groups are decomposed the same way as
+//! separate jobs on a [`ThreadPool`], so the work spreads out as it splits.
+//! Before that, [`Node`]s with no edges coming in or going out are trimmed
+//! off one by one, since each is a component on its own and chains of them
+//! would otherwise need a job each.
+//! 
+//! The components are joined into the condensation, the graph with one
+//! [`Node`] per component, which never has a cycle.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashSet},
+    io::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+use crate::{
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, Node, NodeWithCost},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// Marks a [`Node`] whose component is not known yet.
+const UNASSIGNED: usize = usize::MAX;
+
+/// The strongly connected components found by [`MtdScc::calculate`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StronglyConnected {
+    /// The component of each [`Node`]. Components are numbered in a
+    /// topological order of the condensation, so every edge between 2
+    /// components goes from the lower number to the higher one.
+    pub labels: Vec<usize>,
+    /// Number of [`Node`]s in each component.
+    pub sizes: Vec<usize>,
+    /// The graph with one [`Node`] per component and an edge between 2
+    /// components wherever an edge joins their [`Node`]s, with the
+    /// [`Cost`] of the cheapest such edge.
+    /// 
+    /// [`Cost`]: crate::dijkstra::simple::Cost
+    pub condensation: AdjacencyMatrix,
+}
+
+impl StronglyConnected {
+    /// Number of components.
+    pub fn count(&self) -> usize {
+        return self.sizes.len();
+    }
+
+    /// Get the component of `node`, or [`None`] if it is not in the graph.
+    pub fn component(&self, node: Node) -> Option<usize> {
+        return self.labels.get(node).copied();
+    }
+
+    /// Whether `a` and `b` can reach each other.
+    pub fn connected(&self, a: Node, b: Node) -> bool {
+        return match (self.component(a), self.component(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        };
+    }
+
+    /// Get the [`Node`]s in `component`, in order.
+    pub fn members(&self, component: usize) -> Vec<Node> {
+        return (0..self.labels.len())
+            .filter(|node| self.labels[*node] == component)
+            .collect();
+    }
+
+    /// Whether every component is a single [`Node`], so that the graph
+    /// itself has no cycles apart from any self-loops.
+    pub fn is_acyclic(&self) -> bool {
+        return self.sizes.iter().all(|size| *size == 1);
+    }
+}
+
+/// The state shared by the jobs decomposing the graph.
+struct Shared {
+    graph: Arc<CsrGraph>,
+    reversed: Arc<CsrGraph>,
+    /// The component each [`Node`] was put in, or [`UNASSIGNED`].
+    labels: Vec<AtomicUsize>,
+    /// The group each unassigned [`Node`] is in. No 2 jobs work on the same
+    /// group, so a search only follows edges within its own group.
+    groups: Vec<AtomicUsize>,
+    next_label: AtomicUsize,
+    next_group: AtomicUsize,
+}
+
+impl Shared {
+    /// Finds the [`Node`]s of `group` which can be reached from `pivot`
+    /// along the edges of `graph`.
+    fn reach(&self, graph: &CsrGraph, group: usize, pivot: Node) -> HashSet<Node> {
+        let mut reached: HashSet<Node> = HashSet::from([pivot]);
+        let mut stack: Vec<Node> = vec![pivot];
+        while let Some(node) = stack.pop() {
+            for adjacent in graph.get_node(node).unwrap_or(&[]) {
+                let same = self
+                    .groups
+                    .get(adjacent.node)
+                    .is_some_and(|other| other.load(SeqCst) == group);
+                if same && reached.insert(adjacent.node) {
+                    stack.push(adjacent.node);
+                }
+            }
+        }
+        return reached;
+    }
+
+    /// Puts the component of the first [`Node`] of `members`, which all
+    /// belong to `group`, in its own label and returns the groups the rest
+    /// of `members` are split into.
+    fn split(&self, group: usize, members: &[Node]) -> Vec<(usize, Vec<Node>)> {
+        let pivot = match members.first() {
+            Some(pivot) => *pivot,
+            None => return Vec::new(),
+        };
+        let forward = self.reach(&self.graph, group, pivot);
+        let backward = self.reach(&self.reversed, group, pivot);
+        let label = self.next_label.fetch_add(1, SeqCst);
+        // Forward only, backward only and neither.
+        let mut parts: [Vec<Node>; 3] = [Vec::new(), Vec::new(), Vec::new()];
+        for node in members {
+            match (forward.contains(node), backward.contains(node)) {
+                (true, true) => self.labels[*node].store(label, SeqCst),
+                (true, false) => parts[0].push(*node),
+                (false, true) => parts[1].push(*node),
+                (false, false) => parts[2].push(*node),
+            }
+        }
+        let mut groups: Vec<(usize, Vec<Node>)> = Vec::new();
+        for part in parts {
+            if part.is_empty() {
+                continue;
+            }
+            let group = self.next_group.fetch_add(1, SeqCst);
+            for node in &part {
+                self.groups[*node].store(group, SeqCst);
+            }
+            groups.push((group, part));
+        }
+        return groups;
+    }
+
+    /// Gives every [`Node`] which has no edges coming in or no edges going
+    /// out from unassigned [`Node`]s a component of its own, over and over
+    /// until there are none, and returns the [`Node`]s left unassigned.
+    fn trim(&self) -> Vec<Node> {
+        let total = self.graph.total();
+        for graph in [&self.reversed, &self.graph] {
+            // Edges coming into each Node from unassigned Nodes, or going
+            // out of it when `graph` is the original graph.
+            let mut degrees: Vec<usize> = vec![0; total];
+            for (node, degree) in degrees.iter_mut().enumerate() {
+                if self.labels[node].load(SeqCst) != UNASSIGNED {
+                    continue;
+                }
+                *degree = graph
+                    .get_node(node)
+                    .unwrap_or(&[])
+                    .iter()
+                    .filter(|edge| {
+                        return edge.node < total
+                            && self.labels[edge.node].load(SeqCst) == UNASSIGNED;
+                    })
+                    .count();
+            }
+            let mut stack: Vec<Node> = (0..total)
+                .filter(|node| {
+                    return degrees[*node] == 0
+                        && self.labels[*node].load(SeqCst) == UNASSIGNED;
+                })
+                .collect();
+            let other = if Arc::ptr_eq(graph, &self.graph) {
+                &self.reversed
+            } else {
+                &self.graph
+            };
+            while let Some(node) = stack.pop() {
+                let label = self.next_label.fetch_add(1, SeqCst);
+                self.labels[node].store(label, SeqCst);
+                for adjacent in other.get_node(node).unwrap_or(&[]) {
+                    let next = adjacent.node;
+                    if next >= total || self.labels[next].load(SeqCst) != UNASSIGNED {
+                        continue;
+                    }
+                    degrees[next] -= 1;
+                    if degrees[next] == 0 {
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        return (0..total)
+            .filter(|node| self.labels[*node].load(SeqCst) == UNASSIGNED)
+            .collect();
+    }
+}
+
+/// This `struct` finds the strongly connected components of a graph using
+/// multiple threads. See the module documentation.
+pub struct MtdScc {
+    pool: ThreadPool,
+    graph: Arc<CsrGraph>,
+    reversed: Arc<CsrGraph>,
+}
+
+impl MtdScc {
+    /// Creates a new [`MtdScc`] instance.
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix``` => The directed graph.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix) -> Result<Self, Error> {
+        return Self::from_csr(threads, CsrGraph::from(matrix));
+    }
+
+    /// Creates a new [`MtdScc`] instance from a graph which is already a
+    /// [`CsrGraph`]. See [`MtdScc::new`].
+    pub fn from_csr(threads: usize, graph: CsrGraph) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        let reversed = Arc::new(graph.reversed());
+        return Ok(Self {pool, graph: Arc::new(graph), reversed});
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.graph.total();
+    }
+
+    /// Finds the strongly connected components of the graph and its
+    /// condensation.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn calculate(&mut self) -> Result<StronglyConnected, Error> {
+        let total = self.total();
+        let shared = Arc::new(Shared {
+            graph: self.graph.clone(),
+            reversed: self.reversed.clone(),
+            labels: (0..total).map(|_| AtomicUsize::new(UNASSIGNED)).collect(),
+            groups: (0..total).map(|_| AtomicUsize::new(0)).collect(),
+            next_label: AtomicUsize::new(0),
+            next_group: AtomicUsize::new(1),
+        });
+        let remaining = shared.trim();
+        let mut groups: Vec<(usize, Vec<Node>)> = Vec::new();
+        if !remaining.is_empty() {
+            groups.push((0, remaining));
+        }
+        while !groups.is_empty() {
+            let state = shared.clone();
+            groups = self
+                .pool
+                .map(Arc::new(groups), ChunkPolicy::Auto, move |(group, members)| {
+                    return state.split(*group, members);
+                })?
+                .into_iter()
+                .flatten()
+                .collect();
+        }
+        let labels: Vec<usize> = shared
+            .labels
+            .iter()
+            .map(|label| label.load(SeqCst))
+            .collect();
+        return Ok(self.condense(labels, shared.next_label.load(SeqCst)));
+    }
+
+    /// Renumbers the `count` components in `labels` in a topological order
+    /// of the condensation, breaking ties by the smallest [`Node`] in each
+    /// component, and builds the condensation.
+    fn condense(&self, labels: Vec<usize>, count: usize) -> StronglyConnected {
+        let mut smallest: Vec<Node> = vec![Node::MAX; count];
+        for (node, label) in labels.iter().enumerate() {
+            smallest[*label] = smallest[*label].min(node);
+        }
+        let mut edges: Vec<(usize, NodeWithCost)> = Vec::new();
+        let mut incoming: Vec<usize> = vec![0; count];
+        let mut outgoing: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for (from, label) in labels.iter().enumerate() {
+            for adjacent in self.graph.get_node(from).unwrap_or(&[]) {
+                let other = match labels.get(adjacent.node) {
+                    Some(other) if other != label => *other,
+                    _ => continue,
+                };
+                edges.push((*label, NodeWithCost::new(other, adjacent.cost)));
+                incoming[other] += 1;
+                outgoing[*label].push(other);
+            }
+        }
+
+        let mut numbers: Vec<usize> = vec![0; count];
+        let mut ready: BinaryHeap<Reverse<(Node, usize)>> = (0..count)
+            .filter(|label| incoming[*label] == 0)
+            .map(|label| Reverse((smallest[label], label)))
+            .collect();
+        let mut next: usize = 0;
+        while let Some(Reverse((_, label))) = ready.pop() {
+            numbers[label] = next;
+            next += 1;
+            for other in &outgoing[label] {
+                incoming[*other] -= 1;
+                if incoming[*other] == 0 {
+                    ready.push(Reverse((smallest[*other], *other)));
+                }
+            }
+        }
+
+        let mut result = StronglyConnected {
+            labels: labels.iter().map(|label| numbers[*label]).collect(),
+            sizes: vec![0; count],
+            condensation: AdjacencyMatrix::new(count),
+        };
+        for label in &result.labels {
+            result.sizes[*label] += 1;
+        }
+        for (from, mut edge) in edges {
+            edge.node = numbers[edge.node];
+            // Both ends are within the condensation, so this cannot fail.
+            let _ = result.condensation.push(numbers[from], edge);
+        }
+        return result;
+    }
+}
@@ -0,0 +1,250 @@
+//! Module for end-to-end scenarios which run the pieces of this crate
+//! together the way a downstream program would: a graph is generated from a
+//! seed, exported and read back, and solved on a [`crate::pool::ThreadPool`].
+//! 
+//! Each scenario returns a summary which can be checked by the caller. The
+//! same seed always gives the same summary, and each summary carries a
+//! `verified` flag which is only set if the results passed an independent
+//! check, so the scenarios double as smoke tests for an integration of the
+//! crate.
+
+use std::io::Error;
+
+use crate::{
+    dijkstra::{
+        io::{parse_dimacs, write_dimacs},
+        simple::{AdjacencyMatrix, Cost, MtdDijkstra, Node},
+    },
+    rng::Rng,
+    unwrapoption,
+};
+
+/// Number of intersections in [`route_on_random_city`].
+const CITY_INTERSECTIONS: usize = 200;
+
+/// Number of nearest intersections each intersection gets a road to in
+/// [`route_on_random_city`].
+const CITY_ROADS: usize = 3;
+
+/// Number of blobs in [`cluster_synthetic_blobs`].
+const BLOBS: usize = 5;
+
+/// Number of points in each blob in [`cluster_synthetic_blobs`].
+const BLOB_POINTS: usize = 30;
+
+/// Number of threads used by the scenarios.
+const THREADS: usize = 4;
+
+/// Summary of [`route_on_random_city`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteSummary {
+    /// Number of intersections in the city.
+    pub nodes: usize,
+    /// Number of one-way roads in the city.
+    pub edges: usize,
+    /// Size of the city in bytes when exported in the DIMACS format.
+    pub exported_bytes: usize,
+    /// Number of intersections reachable from intersection `0`.
+    pub reachable: usize,
+    /// The reachable intersection furthest from intersection `0`.
+    pub farthest: Node,
+    /// The [`Cost`] of reaching `farthest`.
+    pub farthest_cost: Cost,
+    /// Sum of the [`Cost`]s of reaching every reachable intersection.
+    pub total_cost: Cost,
+    /// Whether the [`Cost`]s were checked to be the shortest possible.
+    pub verified: bool,
+}
+
+/// Summary of [`cluster_synthetic_blobs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterSummary {
+    /// Number of points generated.
+    pub points: usize,
+    /// Number of blobs the points were generated in.
+    pub blobs: usize,
+    /// Number of clusters of mutually reachable points found.
+    pub clusters: usize,
+    /// Number of points in each cluster, from largest to smallest.
+    pub cluster_sizes: Vec<usize>,
+    /// Whether the [`Cost`]s from every point were checked to be the
+    /// shortest possible, which makes every cluster found exactly one of the
+    /// generated blobs.
+    pub verified: bool,
+}
+
+/// Straight line distance between 2 points, scaled by 100 and rounded so it
+/// can be used as a [`Cost`].
+fn distance(a: (f64, f64), b: (f64, f64)) -> Cost {
+    return ((a.0 - b.0).hypot(a.1 - b.1) * 100.0).round() as Cost;
+}
+
+/// Exports `graph` in the DIMACS format and reads it back, returning the
+/// copy and the size of the export in bytes.
+fn round_trip(graph: &AdjacencyMatrix) -> Result<(AdjacencyMatrix, usize), Error> {
+    let mut exported: Vec<u8> = Vec::new();
+    write_dimacs(graph, &mut exported)?;
+    let copy = parse_dimacs(exported.as_slice())?;
+    return Ok((copy, exported.len()));
+}
+
+/// Checks that `costs` are the shortest [`Cost`]s from `source` in `graph`:
+/// the source costs nothing, no edge leads to a cheaper route, and every
+/// other reachable [`Node`] is reached through some edge with exactly its
+/// [`Cost`].
+fn is_shortest(
+    graph: &AdjacencyMatrix,
+    source: Node,
+    costs: &[Option<Cost>],
+) -> bool {
+    if costs.get(source) != Some(&Some(0)) {
+        return false;
+    }
+    let mut tight = vec![false; graph.total()];
+    tight[source] = true;
+    for from in 0..graph.total() {
+        let from_cost = match costs[from] {
+            Some(cost) => cost,
+            None => continue,
+        };
+        for adjacent in graph.get_node(from).into_iter().flatten() {
+            match costs[adjacent.node] {
+                Some(cost) if cost < from_cost + adjacent.cost => {},
+                Some(cost) if cost == from_cost + adjacent.cost => {
+                    tight[adjacent.node] = true;
+                },
+                _ => return false,
+            }
+        }
+    }
+    return costs
+        .iter()
+        .zip(tight)
+        .all(|(cost, tight)| cost.is_none() || tight);
+}
+
+/// Builds a random city of [`CITY_INTERSECTIONS`] intersections, each with a
+/// two-way road to its [`CITY_ROADS`] nearest intersections and to the
+/// nearest intersection built before it, exports it, and calculates the
+/// shortest routes from intersection `0` on the copy read back from the
+/// export.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if any step of the scenario fails.
+pub fn route_on_random_city(seed: u64) -> Result<RouteSummary, Error> {
+    let mut rng = Rng::new(seed);
+    let points: Vec<(f64, f64)> = (0..CITY_INTERSECTIONS)
+        .map(|_| (rng.range_f64(0.0, 100.0), rng.range_f64(0.0, 100.0)))
+        .collect();
+    let mut city = AdjacencyMatrix::new(CITY_INTERSECTIONS);
+    for (from, point) in points.iter().enumerate() {
+        let mut nearest: Vec<(Cost, Node)> = points
+            .iter()
+            .enumerate()
+            .filter(|(to, _point)| *to != from)
+            .map(|(to, other)| (distance(*point, *other), to))
+            .collect();
+        nearest.sort_unstable();
+        // A road to the nearest intersection built before this one keeps
+        // the whole city connected.
+        if let Some((cost, to)) = nearest.iter().find(|(_cost, to)| *to < from) {
+            city.push_undirected(from, *to, *cost)?;
+        }
+        for (cost, to) in nearest.into_iter().take(CITY_ROADS) {
+            city.push_undirected(from, to, cost)?;
+        }
+    }
+
+    let (copy, exported_bytes) = round_trip(&city)?;
+    let mut processor = MtdDijkstra::new(THREADS, copy.total(), copy)?;
+    processor.calculate_from(0)?;
+    let costs = unwrapoption!(processor.get_from(0));
+
+    let mut summary = RouteSummary {
+        nodes: city.total(),
+        edges: city.edges(),
+        exported_bytes,
+        reachable: 0,
+        farthest: 0,
+        farthest_cost: 0,
+        total_cost: 0,
+        verified: is_shortest(&city, 0, &costs),
+    };
+    for (node, cost) in costs.iter().enumerate() {
+        if let Some(cost) = cost {
+            summary.reachable += 1;
+            summary.total_cost += cost;
+            if *cost > summary.farthest_cost {
+                summary.farthest = node;
+                summary.farthest_cost = *cost;
+            }
+        }
+    }
+    return Ok(summary);
+}
+
+/// Scatters [`BLOBS`] blobs of [`BLOB_POINTS`] points far apart from each
+/// other, links every pair of points which are close together, exports the
+/// graph, and calculates the shortest routes between all points on the copy
+/// read back from the export. Points which can reach each other are then
+/// grouped into clusters, which should be the blobs.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if any step of the scenario fails.
+pub fn cluster_synthetic_blobs(seed: u64) -> Result<ClusterSummary, Error> {
+    let mut rng = Rng::new(seed);
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    for blob in 0..BLOBS {
+        // Blob centres are at least 10 apart and every point is within 1 of
+        // its centre, so only points in the same blob are ever linked.
+        let centre = (blob as f64 * 10.0, rng.range_f64(-3.0, 3.0));
+        for _ in 0..BLOB_POINTS {
+            let angle = rng.range_f64(0.0, std::f64::consts::TAU);
+            let radius = rng.next_f64();
+            points.push((
+                centre.0 + radius * angle.cos(),
+                centre.1 + radius * angle.sin(),
+            ));
+        }
+    }
+    rng.shuffle(&mut points);
+
+    let total = points.len();
+    let mut graph = AdjacencyMatrix::new(total);
+    for a in 0..total {
+        for b in a + 1..total {
+            if distance(points[a], points[b]) <= 250 {
+                graph.push_undirected(a, b, distance(points[a], points[b]))?;
+            }
+        }
+    }
+
+    let (copy, _exported_bytes) = round_trip(&graph)?;
+    let mut processor = MtdDijkstra::new(THREADS, total, copy)?;
+    processor.calculate()?;
+
+    // Each point is labelled with the lowest point it can reach.
+    let mut labels: Vec<Node> = Vec::with_capacity(total);
+    let mut verified = true;
+    for node in 0..total {
+        let costs = unwrapoption!(processor.get(node));
+        verified &= is_shortest(&graph, node, &costs);
+        labels.push(unwrapoption!(costs.iter().position(Option::is_some)));
+    }
+    let mut cluster_sizes: Vec<usize> = vec![0; total];
+    for label in &labels {
+        cluster_sizes[*label] += 1;
+    }
+    cluster_sizes.retain(|size| *size > 0);
+    cluster_sizes.sort_unstable_by(|a, b| b.cmp(a));
+
+    return Ok(ClusterSummary {
+        points: total,
+        blobs: BLOBS,
+        clusters: cluster_sizes.len(),
+        cluster_sizes,
+        verified,
+    });
+}
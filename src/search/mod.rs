@@ -0,0 +1,327 @@
+//! Module for searching implicit graphs, whose states and edges are not
+//! stored up front but generated from closures while searching. This suits
+//! state spaces far too large to hold as an [`AdjacencyMatrix`], such as the
+//! positions of a puzzle.
+//! 
+//! [`parallel_astar`] runs Hash Distributed A* (HDA*). Every state is owned
+//! by one worker of a [`ThreadPool`], picked by hashing the state, and each
+//! worker keeps its own heap of open states and the best [`Cost`] to reach
+//! each state it owns. A worker expanding a state sends every successor to
+//! its owner over a channel, so no heap or table is shared between workers.
+//! 
+//! Workers do not expand their states in the same order as one A* search
+//! would, so the first goal found may not be the cheapest. The cheapest
+//! goal found so far is shared instead, and the search ends once no worker
+//! holds an open state estimated to be cheaper and no successors are left
+//! in the channels.
+//! 
+//! [`AdjacencyMatrix`]: crate::dijkstra::simple::AdjacencyMatrix
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::Error,
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    },
+    time::Duration,
+};
+
+use crate::{
+    dijkstra::simple::Cost,
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    unwrapmutex, unwrapoption, unwrapsender,
+};
+
+/// How long an idle worker waits for a successor before checking whether
+/// the search has ended.
+const IDLE_POLL: Duration = Duration::from_millis(1);
+
+/// Closure telling whether a state is a goal.
+type Goal<S> = Arc<dyn Fn(&S) -> bool + Send + Sync + 'static>;
+/// Closure giving the successors of a state and the [`Cost`] of reaching
+/// each of them.
+type Successors<S> = Arc<dyn Fn(&S) -> Vec<(S, Cost)> + Send + Sync + 'static>;
+/// Closure guessing the [`Cost`] left to reach a goal from a state.
+type Heuristic<S> = Arc<dyn Fn(&S) -> Cost + Send + Sync + 'static>;
+
+/// The receiving end of the channel of a worker, taken by the worker when
+/// it starts.
+type Inbox<S> = Mutex<Option<Receiver<Message<S>>>>;
+
+/// The cheapest route to a goal found by [`parallel_astar`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Found<S> {
+    /// The states along the route, from the source to the goal.
+    pub states: Vec<S>,
+    /// The [`Cost`] of the route.
+    pub cost: Cost,
+    /// The number of states expanded by every worker together.
+    pub expanded: usize,
+}
+
+/// A state sent to the worker owning it.
+struct Message<S> {
+    state: S,
+    cost: Cost,
+    parent: Option<S>,
+}
+
+/// The best route known to a state owned by a worker.
+struct Entry<S> {
+    state: S,
+    cost: Cost,
+    guess: Cost,
+    parent: Option<S>,
+}
+
+/// Everything the workers share during [`parallel_astar`].
+struct Shared<S> {
+    goal: Goal<S>,
+    successors: Successors<S>,
+    heuristic: Heuristic<S>,
+    senders: Vec<Sender<Message<S>>>,
+    /// The number of workers without an open state cheaper than the
+    /// incumbent.
+    idle: Mutex<usize>,
+    /// The number of messages sent but not yet handled.
+    outstanding: AtomicUsize,
+    done: AtomicBool,
+    incumbent: RwLock<Option<(Cost, S)>>,
+}
+
+impl<S: Clone + Eq + Hash + Send + Sync + 'static> Shared<S> {
+    /// Get the [`Cost`] of the cheapest goal found so far.
+    fn bound(&self) -> Result<Option<Cost>, Error> {
+        return Ok(unwrapmutex!(self.incumbent.read()).as_ref().map(|(cost, _)| *cost));
+    }
+
+    /// Keeps `goal` as the incumbent if it is cheaper than the cheapest goal
+    /// found so far.
+    fn offer(&self, cost: Cost, goal: &S) -> Result<(), Error> {
+        let mut incumbent = unwrapmutex!(self.incumbent.write());
+        if incumbent.as_ref().is_none_or(|(best, _)| cost < *best) {
+            *incumbent = Some((cost, goal.clone()));
+        }
+        return Ok(());
+    }
+}
+
+/// Get the index of the worker owning `state`.
+fn owner<S: Hash>(state: &S, workers: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    return (hasher.finish() % workers as u64) as usize;
+}
+
+/// The states owned by one worker.
+struct Worker<S> {
+    index: usize,
+    ids: HashMap<S, usize>,
+    entries: Vec<Entry<S>>,
+    open: BinaryHeap<Reverse<(Cost, Cost, usize)>>,
+    expanded: usize,
+}
+
+impl<S: Clone + Eq + Hash + Send + Sync + 'static> Worker<S> {
+    /// Opens the state of `message` if it is cheaper than the best route
+    /// known to it.
+    fn receive(&mut self, shared: &Shared<S>, message: Message<S>) {
+        let Message {state, cost, parent} = message;
+        let id = match self.ids.get(&state) {
+            Some(id) => {
+                let entry = &mut self.entries[*id];
+                if entry.cost <= cost {
+                    return;
+                }
+                entry.cost = cost;
+                entry.parent = parent;
+                *id
+            },
+            None => {
+                let guess = (shared.heuristic)(&state);
+                if guess == Cost::MAX {
+                    return;
+                }
+                let id = self.entries.len();
+                self.ids.insert(state.clone(), id);
+                self.entries.push(Entry {state, cost, guess, parent});
+                id
+            },
+        };
+        let estimate = cost.saturating_add(self.entries[id].guess);
+        self.open.push(Reverse((estimate, cost, id)));
+    }
+
+    /// Expands open states until the search ends.
+    fn run(
+        &mut self,
+        shared: &Shared<S>,
+        receiver: &Receiver<Message<S>>,
+    ) -> Result<(), Error> {
+        loop {
+            while let Ok(message) = receiver.try_recv() {
+                self.receive(shared, message);
+                shared.outstanding.fetch_sub(1, SeqCst);
+            }
+            let bound = shared.bound()?;
+            let next = self.open.pop().filter(|Reverse((estimate, _, _))| {
+                return bound.is_none_or(|bound| *estimate < bound);
+            });
+            let Some(Reverse((_, cost, id))) = next else {
+                // Every other open state is estimated to cost at least as
+                // much as the incumbent.
+                self.open.clear();
+                if !self.wait(shared, receiver)? {
+                    return Ok(());
+                }
+                continue;
+            };
+            if cost != self.entries[id].cost {
+                continue;
+            }
+            let state = self.entries[id].state.clone();
+            if (shared.goal)(&state) {
+                shared.offer(cost, &state)?;
+                continue;
+            }
+            self.expanded += 1;
+            for (successor, step) in (shared.successors)(&state) {
+                let to = owner(&successor, shared.senders.len());
+                let message = Message {
+                    state: successor,
+                    cost: cost.saturating_add(step),
+                    parent: Some(state.clone()),
+                };
+                if to == self.index {
+                    self.receive(shared, message);
+                } else {
+                    shared.outstanding.fetch_add(1, SeqCst);
+                    unwrapsender!(shared.senders[to].send(message));
+                }
+            }
+        }
+    }
+
+    /// Waits for a successor once the worker has no open state cheaper than
+    /// the incumbent. Returns `false` once the search has ended, which is
+    /// when every worker is waiting and no message is left to handle.
+    fn wait(
+        &mut self,
+        shared: &Shared<S>,
+        receiver: &Receiver<Message<S>>,
+    ) -> Result<bool, Error> {
+        {
+            let mut idle = unwrapmutex!(shared.idle.lock());
+            *idle += 1;
+            if *idle == shared.senders.len() && shared.outstanding.load(SeqCst) == 0 {
+                shared.done.store(true, SeqCst);
+            }
+        }
+        loop {
+            if shared.done.load(SeqCst) {
+                return Ok(false);
+            }
+            match receiver.recv_timeout(IDLE_POLL) {
+                Ok(message) => {
+                    *unwrapmutex!(shared.idle.lock()) -= 1;
+                    self.receive(shared, message);
+                    shared.outstanding.fetch_sub(1, SeqCst);
+                    return Ok(true);
+                },
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return Ok(false),
+            }
+        }
+    }
+}
+
+/// Finds the cheapest route from `source` to a goal with Hash Distributed
+/// A*, or [`None`] if no goal can be reached. See the module documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads searching the states.
+///    Every thread runs a worker until the search ends, so no other jobs
+///    should be waiting on `pool`.
+/// 2. ```source: S``` => Where the route starts.
+/// 3. ```goal: G``` => Whether a state is a goal.
+/// 4. ```successors: N``` => The states reachable from a state in one step,
+///    with the [`Cost`] of each step.
+/// 5. ```heuristic: H``` => A guess of the [`Cost`] to reach a goal from a
+///    state. It must never guess more than the real [`Cost`], or the route
+///    found may not be the cheapest, and [`Cost::MAX`] marks a state which
+///    is known not to reach a goal, so it is never expanded.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn parallel_astar<S, G, N, H>(
+    pool: &mut ThreadPool,
+    source: S,
+    goal: G,
+    successors: N,
+    heuristic: H,
+) -> Result<Option<Found<S>>, Error>
+where
+    S: Clone + Eq + Hash + Send + Sync + 'static,
+    G: Fn(&S) -> bool + Send + Sync + 'static,
+    N: Fn(&S) -> Vec<(S, Cost)> + Send + Sync + 'static,
+    H: Fn(&S) -> Cost + Send + Sync + 'static,
+{
+    let workers = pool.threads();
+    let mut senders: Vec<Sender<Message<S>>> = Vec::with_capacity(workers);
+    let mut inboxes: Vec<(usize, Inbox<S>)> = Vec::with_capacity(workers);
+    for index in 0..workers {
+        let (sender, receiver) = mpsc::channel();
+        senders.push(sender);
+        inboxes.push((index, Mutex::new(Some(receiver))));
+    }
+    let start = Message {state: source.clone(), cost: 0, parent: None};
+    unwrapsender!(senders[owner(&source, workers)].send(start));
+    let shared = Arc::new(Shared {
+        goal: Arc::new(goal),
+        successors: Arc::new(successors),
+        heuristic: Arc::new(heuristic),
+        senders,
+        idle: Mutex::new(0),
+        outstanding: AtomicUsize::new(1),
+        done: AtomicBool::new(false),
+        incumbent: RwLock::new(None),
+    });
+
+    let working = shared.clone();
+    let finished = pool.map(Arc::new(inboxes), ChunkPolicy::Fixed(1), move |item| {
+        let (index, inbox) = item;
+        let receiver = unwrapoption!(unwrapmutex!(inbox.lock()).take());
+        let mut worker = Worker {
+            index: *index,
+            ids: HashMap::new(),
+            entries: Vec::new(),
+            open: BinaryHeap::new(),
+            expanded: 0,
+        };
+        worker.run(&working, &receiver)?;
+        return Ok(worker);
+    })?
+        .into_iter()
+        .collect::<Result<Vec<Worker<S>>, Error>>()?;
+
+    let Some((cost, goal)) = unwrapmutex!(shared.incumbent.read()).clone() else {
+        return Ok(None);
+    };
+    let expanded = finished.iter().map(|worker| worker.expanded).sum();
+    let mut states: Vec<S> = Vec::new();
+    let mut current = Some(goal);
+    while let Some(state) = current.take() {
+        let worker = &finished[owner(&state, workers)];
+        let entry = &worker.entries[*unwrapoption!(worker.ids.get(&state))];
+        current = entry.parent.clone();
+        states.push(state);
+    }
+    states.reverse();
+    return Ok(Some(Found {states, cost, expanded}));
+}
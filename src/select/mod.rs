@@ -0,0 +1,188 @@
+//! Module for selecting the item of a large list which would be at a given
+//! index if the list were sorted, such as the median or a percentile of a
+//! list of costs, without sorting the whole list.
+//! 
+//! [`nth_element`] runs quickselect with the partitioning spread over a
+//! [`ThreadPool`]. Each round picks a pivot from a sample of the items left,
+//! and every piece of the items left is split by a job into the items
+//! smaller than, equal to and greater than the pivot. Only the group
+//! holding the wanted index is kept for the next round, so the items left
+//! shrink quickly, and once fewer than [`SEQUENTIAL_CUTOFF`] are left they
+//! are finished on the calling thread.
+//! 
+//! [`SEQUENTIAL_CUTOFF`]: crate::sort::SEQUENTIAL_CUTOFF
+
+use std::{
+    cmp::Ordering,
+    io::{Error, ErrorKind},
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+    sort::SEQUENTIAL_CUTOFF,
+    unwrapmutex, unwrapoption,
+};
+
+/// The number of items sampled to pick each pivot.
+const SAMPLES: usize = 64;
+
+/// The items of a piece smaller than, equal to and greater than a pivot.
+type Split<T> = (Vec<T>, Vec<T>, Vec<T>);
+
+/// Reorders `items` in parallel on `pool` so that the item at `index` is
+/// the one which would be there if `items` were sorted. See
+/// [`nth_element_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `index` is not below the length of
+/// `items` or if a job on the [`ThreadPool`] fails.
+pub fn nth_element<T>(
+    pool: &mut ThreadPool,
+    items: Vec<T>,
+    index: usize,
+) -> Result<Vec<T>, Error>
+where
+    T: Ord + Send + Sync + 'static,
+{
+    return nth_element_by(pool, items, index, T::cmp);
+}
+
+/// Reorders `items` in parallel on `pool` according to `compare` so that
+/// the item at `index` is the one which would be there if `items` were
+/// sorted, like [`slice::select_nth_unstable_by`]. No item before `index`
+/// is greater than it and no item after `index` is smaller than it, but
+/// the items on either side are in no particular order.
+/// 
+/// The pivot of each round is picked from a random sample of the items
+/// left, seeded with the length of the list, at the same rank in the
+/// sample as the wanted index has among the items left.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which split the items.
+/// 2. ```items: Vec<T>``` => The list to reorder.
+/// 3. ```index: usize``` => The index of the item to select.
+/// 4. ```compare: F``` => Decides the order of 2 items, like
+///    [`slice::sort_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `index` is not below the length of
+/// `items` or if a job on the [`ThreadPool`] fails.
+pub fn nth_element_by<T, F>(
+    pool: &mut ThreadPool,
+    mut items: Vec<T>,
+    index: usize,
+    compare: F,
+) -> Result<Vec<T>, Error>
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    let len = items.len();
+    if index >= len {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("The index {} is not below the length {} of the list.", index, len),
+        ));
+    }
+    let pieces = pool.threads().min(len / SEQUENTIAL_CUTOFF);
+    if pieces < 2 {
+        items.select_nth_unstable_by(index, compare);
+        return Ok(items);
+    }
+    let compare = Arc::new(compare);
+
+    let size = len.div_ceil(pieces);
+    let mut left: Vec<Vec<T>> = Vec::with_capacity(pieces);
+    while !items.is_empty() {
+        let start = items.len().saturating_sub(size);
+        left.push(items.split_off(start));
+    }
+    let mut below: Vec<Vec<T>> = Vec::new();
+    let mut above: Vec<Vec<T>> = Vec::new();
+    let mut rng = Rng::new(len as u64);
+    let mut rank = index;
+    let mut remaining = len;
+
+    let middle = loop {
+        if remaining < SEQUENTIAL_CUTOFF {
+            let mut middle: Vec<T> = left.into_iter().flatten().collect();
+            middle.select_nth_unstable_by(rank, |a, b| compare(a, b));
+            break middle;
+        }
+
+        // Sample items by their place among the items left, and take the
+        // pivot out of its piece so the jobs can share it.
+        let mut sample: Vec<(usize, usize)> = Vec::with_capacity(SAMPLES);
+        for _ in 0..SAMPLES {
+            let mut place = rng.below(remaining);
+            let piece = unwrapoption!(left.iter().position(|piece| {
+                let inside = place < piece.len();
+                if !inside {
+                    place -= piece.len();
+                }
+                return inside;
+            }));
+            sample.push((piece, place));
+        }
+        sample.sort_by(|(a, i), (b, j)| compare(&left[*a][*i], &left[*b][*j]));
+        let (piece, place) = sample[rank * SAMPLES / remaining];
+        let pivot = Arc::new(left[piece].swap_remove(place));
+
+        let shared = compare.clone();
+        let judged = pivot.clone();
+        let pieces: Vec<Mutex<Vec<T>>> = left.into_iter().map(Mutex::new).collect();
+        let split = pool.map(Arc::new(pieces), ChunkPolicy::Fixed(1), move |piece| {
+            let piece = mem::take(&mut *unwrapmutex!(piece.lock()));
+            let mut split: Split<T> = (Vec::new(), Vec::new(), Vec::new());
+            for item in piece {
+                match shared(&item, &judged) {
+                    Ordering::Less => split.0.push(item),
+                    Ordering::Equal => split.1.push(item),
+                    Ordering::Greater => split.2.push(item),
+                }
+            }
+            return Ok(split);
+        })?
+            .into_iter()
+            .collect::<Result<Vec<Split<T>>, Error>>()?;
+        let pivot = unwrapoption!(Arc::try_unwrap(pivot).ok());
+
+        let mut smaller: Vec<Vec<T>> = Vec::new();
+        let mut equal: Vec<Vec<T>> = vec![vec![pivot]];
+        let mut greater: Vec<Vec<T>> = Vec::new();
+        for (less, same, more) in split {
+            smaller.push(less);
+            equal.push(same);
+            greater.push(more);
+        }
+        let smaller_len: usize = smaller.iter().map(Vec::len).sum();
+        let equal_len: usize = equal.iter().map(Vec::len).sum();
+        if rank < smaller_len {
+            above.append(&mut equal);
+            above.append(&mut greater);
+            left = smaller;
+            remaining = smaller_len;
+        } else if rank < smaller_len + equal_len {
+            below.append(&mut smaller);
+            above.append(&mut greater);
+            break equal.into_iter().flatten().collect();
+        } else {
+            below.append(&mut smaller);
+            below.append(&mut equal);
+            left = greater;
+            rank -= smaller_len + equal_len;
+            remaining -= smaller_len + equal_len;
+        }
+    };
+
+    let mut selected: Vec<T> = Vec::with_capacity(len);
+    selected.extend(below.into_iter().flatten());
+    selected.extend(middle);
+    selected.extend(above.into_iter().flatten());
+    return Ok(selected);
+}
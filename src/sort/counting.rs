@@ -0,0 +1,234 @@
+//! Counting sort, which sorts items by a small whole number key by counting
+//! the items with each key and then moving every item straight to its place.
+//! 
+//! Each piece of the list is counted by its own job, and a prefix sum of
+//! the counts, taken over the keys in parallel, gives where the items of
+//! each piece with each key go. The pieces then move their items into
+//! place in parallel, without comparing any 2 items.
+
+use std::{
+    io::{Error, ErrorKind},
+    mem,
+    sync::Arc,
+};
+
+use super::SEQUENTIAL_CUTOFF;
+use crate::{
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+    unwrapoption,
+};
+
+/// Where the jobs of [`counting_sort`] move the items from and to.
+struct Scatter<T> {
+    from: *const T,
+    to: *mut T,
+}
+
+// Every item is read and written by exactly one job, so moving the items
+// to that job's thread is all that is needed.
+unsafe impl<T: Send> Send for Scatter<T> {}
+unsafe impl<T: Send> Sync for Scatter<T> {}
+
+/// A piece of the list moved into place by one job.
+struct Piece {
+    start: usize,
+    end: usize,
+    /// Where the first item of the piece with each key goes.
+    offsets: Vec<usize>,
+    /// Where the items of the piece with each key stop.
+    ends: Vec<usize>,
+}
+
+/// Get the error for an item whose key is not below `keys`.
+fn out_of_range(found: usize, keys: usize) -> Error {
+    return Error::new(
+        ErrorKind::InvalidInput,
+        format!("The key {} is not below the {} keys.", found, keys),
+    );
+}
+
+/// Get where the items of each piece with each key go, from `counts`, the
+/// number of items of each piece with each key. The items are ordered by
+/// key, and then by piece, so the sort is stable.
+/// 
+/// The keys are cut into a block per thread, and the counts of each block
+/// are first summed in parallel. The sums before each block then give
+/// where each block starts, so the blocks are filled in in parallel too.
+fn prefix_sums(
+    pool: &mut ThreadPool,
+    counts: Vec<Vec<usize>>,
+    keys: usize,
+) -> Result<Vec<Vec<usize>>, Error> {
+    let pieces = counts.len();
+    let blocks = ranges(keys, pool.threads());
+    let counts = Arc::new(counts);
+
+    let summed = counts.clone();
+    let policy = ChunkPolicy::Fixed(1);
+    let totals = pool.map(Arc::new(blocks.clone()), policy, move |block| {
+        let (start, end) = *block;
+        return summed
+            .iter()
+            .map(|piece| piece[start..end].iter().sum::<usize>())
+            .sum::<usize>();
+    })?;
+    let mut starts: Vec<(usize, usize, usize)> = Vec::with_capacity(blocks.len());
+    let mut running = 0;
+    for ((start, end), total) in blocks.into_iter().zip(totals) {
+        starts.push((start, end, running));
+        running += total;
+    }
+
+    let filled = pool.map(Arc::new(starts), ChunkPolicy::Fixed(1), move |block| {
+        let (start, end, mut running) = *block;
+        let mut offsets: Vec<Vec<usize>> = vec![Vec::new(); pieces];
+        for key in start..end {
+            for (piece, counted) in counts.iter().enumerate() {
+                offsets[piece].push(running);
+                running += counted[key];
+            }
+        }
+        return offsets;
+    })?;
+    let mut offsets: Vec<Vec<usize>> = vec![Vec::with_capacity(keys); pieces];
+    for block in filled {
+        for (piece, block) in block.into_iter().enumerate() {
+            offsets[piece].extend(block);
+        }
+    }
+    return Ok(offsets);
+}
+
+/// Sorts `items` in parallel on `pool` by the whole number `key` gives each
+/// item, which must be below `keys`. The sort is stable, so items with the
+/// same key keep their order, and takes time in proportion to the length
+/// of the list plus `keys`, so it suits lists sorted by a small key, such
+/// as edges sorted by their cost rounded into buckets.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which do the sorting.
+/// 2. ```items: Vec<T>``` => The list to sort.
+/// 3. ```keys: usize``` => The number of keys, so every key is below it.
+///    An array of this many counts is kept for every thread.
+/// 4. ```key: F``` => The key of an item. It is called twice for every
+///    item and must give the same key both times. A key which changed is
+///    only noticed if it leaves too many items with some key for a piece,
+///    and otherwise the items come out in no particular order.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a key is not below `keys`, if a key
+/// which changed the second time was noticed, or if a job on the
+/// [`ThreadPool`] fails. If the [`ThreadPool`] fails while the items are
+/// being moved into place, they are leaked rather than dropped, since jobs
+/// may still be moving them.
+pub fn counting_sort<T, F>(
+    pool: &mut ThreadPool,
+    mut items: Vec<T>,
+    keys: usize,
+    key: F,
+) -> Result<Vec<T>, Error>
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T) -> usize + Send + Sync + 'static,
+{
+    let len = items.len();
+    if len < SEQUENTIAL_CUTOFF {
+        if let Some(found) = items.iter().map(&key).find(|found| *found >= keys) {
+            return Err(out_of_range(found, keys));
+        }
+        items.sort_by_key(key);
+        return Ok(items);
+    }
+    let key = Arc::new(key);
+
+    let ranges = ranges(len, pool.threads());
+    let shared = Arc::new(items);
+    let list = shared.clone();
+    let counter = key.clone();
+    let policy = ChunkPolicy::Fixed(1);
+    let counts = pool.map(Arc::new(ranges.clone()), policy, move |range| {
+        let (start, end) = *range;
+        let mut counts: Vec<usize> = vec![0; keys];
+        for item in &list[start..end] {
+            let found = counter(item);
+            if found >= keys {
+                return Err(out_of_range(found, keys));
+            }
+            counts[found] += 1;
+        }
+        return Ok(counts);
+    })?
+        .into_iter()
+        .collect::<Result<Vec<Vec<usize>>, Error>>()?;
+    let mut items = unwrapoption!(Arc::try_unwrap(shared).ok());
+
+    // The items of a piece with a key stop where the next piece with that
+    // key starts, or where the first piece with the next key starts.
+    let offsets = prefix_sums(pool, counts, keys)?;
+    let mut jobs: Vec<Piece> = Vec::with_capacity(ranges.len());
+    for (piece, (start, end)) in ranges.into_iter().enumerate() {
+        let ends: Vec<usize> = (0..keys)
+            .map(|found| match offsets.get(piece + 1) {
+                Some(next) => next[found],
+                None => offsets[0].get(found + 1).copied().unwrap_or(len),
+            })
+            .collect();
+        jobs.push(Piece {start, end, offsets: offsets[piece].clone(), ends});
+    }
+
+    let mut sorted: Vec<T> = Vec::with_capacity(len);
+    let scatter = Arc::new(Scatter {from: items.as_ptr(), to: sorted.as_mut_ptr()});
+    // The items are moved out of `items` from here on, so it must not drop
+    // them, and `sorted` only takes them once every item is in place.
+    unsafe {
+        items.set_len(0);
+    }
+    let policy = ChunkPolicy::Fixed(1);
+    let scattered = pool.map(Arc::new(jobs), policy, move |piece| {
+        let mut next = piece.offsets.clone();
+        for index in piece.start..piece.end {
+            // Each index is read by this job alone, and each place in
+            // `sorted` is below the end for its key, so it is written once.
+            let item = unsafe {scatter.from.add(index)};
+            let found = key(unsafe {&*item});
+            if found >= keys || next[found] >= piece.ends[found] {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "An item was given a different key the second time.",
+                ));
+            }
+            unsafe {
+                scatter.to.add(next[found]).write(item.read());
+            }
+            next[found] += 1;
+        }
+        return Ok(());
+    });
+    let checked = match scattered {
+        Ok(results) => results.into_iter().collect::<Result<(), Error>>(),
+        Err(error) => {
+            // Jobs may still be reading `items` and writing `sorted` if the
+            // pool failed, so both are leaked rather than freed under them.
+            pool.wait().ok();
+            mem::forget(items);
+            mem::forget(sorted);
+            return Err(error);
+        },
+    };
+    if let Err(error) = checked {
+        // Every job is done and the jobs only copied the items, so `items`
+        // still holds all of them and takes them back, while the copies in
+        // `sorted` are never dropped.
+        unsafe {
+            items.set_len(len);
+        }
+        return Err(error);
+    }
+    // Every piece filled exactly as many places as it has items, all of
+    // them different, so every place in `sorted` now holds an item.
+    unsafe {
+        sorted.set_len(len);
+    }
+    return Ok(sorted);
+}
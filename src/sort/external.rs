@@ -0,0 +1,378 @@
+//! Module for sorting lists too large to fit in memory, by sorting pieces
+//! of the list which do fit and keeping them in files until they are
+//! merged.
+//! 
+//! [`MtdExternalSort::sort`] reads items into a piece per thread of its
+//! [`ThreadPool`] until the memory budget is used up. Each piece is then
+//! sorted and written to a run file in the temporary directory by its own
+//! job, and more pieces are read until the items run out. Once every run
+//! is written, the runs are merged in rounds, each job merging up to
+//! [`FAN_IN`] runs into a new one, until few enough runs are left to merge
+//! straight into the output on the calling thread.
+//! 
+//! Items are written to the run files with the [`Record`] trait, which is
+//! implemented for whole numbers, [`String`] and tuples of up to 3
+//! [`Record`]s. Run files are deleted once they have been merged, and also
+//! if the sort fails.
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    env,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Error, ErrorKind, Read, Write},
+    mem,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering::SeqCst},
+    },
+};
+
+use crate::{
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    unwrapmutex,
+};
+
+/// The default memory budget of [`MtdExternalSort`], in bytes.
+pub const DEFAULT_MEMORY_BUDGET: usize = 256 * 1024 * 1024;
+/// The most runs merged into one at a time.
+pub const FAN_IN: usize = 16;
+
+/// Counts the run files made by this process, so that every run file gets
+/// a name of its own.
+static RUNS_MADE: AtomicU64 = AtomicU64::new(0);
+
+/// An item which can be written to and read back from a run file.
+pub trait Record: Sized {
+    /// Writes the item to `writer`.
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+
+    /// Reads the next item from `reader`, or [`None`] if `reader` has no
+    /// more items.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `reader` ends partway through an
+    /// item or fails.
+    fn read_from<R: Read>(reader: &mut R) -> Result<Option<Self>, Error>;
+}
+
+/// Reads `N` bytes from `reader`, or [`None`] if `reader` is at its end.
+fn read_bytes<R, const N: usize>(reader: &mut R) -> Result<Option<[u8; N]>, Error>
+where
+    R: Read,
+{
+    let mut bytes = [0; N];
+    let mut filled = 0;
+    while filled < N {
+        match reader.read(&mut bytes[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(truncated()),
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    return Ok(Some(bytes));
+}
+
+/// Get the error for a run file which ends partway through an item.
+fn truncated() -> Error {
+    return Error::new(
+        ErrorKind::UnexpectedEof,
+        "The run file ends partway through an item."
+    );
+}
+
+/// Implements [`Record`] for whole numbers, written as little endian bytes.
+macro_rules! whole_record {
+    ($($number: ty),*) => {
+        $(
+            impl Record for $number {
+                fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+                    return writer.write_all(&self.to_le_bytes());
+                }
+
+                fn read_from<R: Read>(reader: &mut R) -> Result<Option<Self>, Error> {
+                    let bytes = read_bytes::<R, {mem::size_of::<$number>()}>(reader)?;
+                    return Ok(bytes.map(<$number>::from_le_bytes));
+                }
+            }
+        )*
+    };
+}
+
+whole_record!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Record for String {
+    /// Writes the length of the string in bytes and then its bytes.
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        (self.len() as u64).write_to(writer)?;
+        return writer.write_all(self.as_bytes());
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> Result<Option<Self>, Error> {
+        let Some(len) = u64::read_from(reader)? else {
+            return Ok(None);
+        };
+        let mut bytes: Vec<u8> = vec![0; len as usize];
+        reader.read_exact(&mut bytes).map_err(|_error| truncated())?;
+        return match String::from_utf8(bytes) {
+            Ok(text) => Ok(Some(text)),
+            Err(_error) => Err(Error::new(
+                ErrorKind::InvalidData,
+                "A string in the run file is not valid UTF-8."
+            )),
+        };
+    }
+}
+
+impl<A: Record, B: Record> Record for (A, B) {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.0.write_to(writer)?;
+        return self.1.write_to(writer);
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> Result<Option<Self>, Error> {
+        let Some(first) = A::read_from(reader)? else {
+            return Ok(None);
+        };
+        let second = B::read_from(reader)?.ok_or_else(truncated)?;
+        return Ok(Some((first, second)));
+    }
+}
+
+impl<A: Record, B: Record, C: Record> Record for (A, B, C) {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.0.write_to(writer)?;
+        self.1.write_to(writer)?;
+        return self.2.write_to(writer);
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> Result<Option<Self>, Error> {
+        let Some(first) = A::read_from(reader)? else {
+            return Ok(None);
+        };
+        let second = B::read_from(reader)?.ok_or_else(truncated)?;
+        let third = C::read_from(reader)?.ok_or_else(truncated)?;
+        return Ok(Some((first, second, third)));
+    }
+}
+
+/// A sorted run file, which is deleted when dropped.
+struct Run {
+    path: PathBuf,
+}
+
+impl Run {
+    /// Creates an empty run file in `directory`, returning it with a writer
+    /// to fill it.
+    fn create(directory: &Path) -> Result<(Self, BufWriter<File>), Error> {
+        let name = format!(
+            "mtdalgos-{}-{}.run",
+            process::id(),
+            RUNS_MADE.fetch_add(1, SeqCst)
+        );
+        let run = Self {path: directory.join(name)};
+        let file = File::create(&run.path)?;
+        return Ok((run, BufWriter::new(file)));
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        // The file may never have been created if the sort failed early.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Merges the items of `runs` into `output` in order, returning the number
+/// of items written. Equal items are taken from earlier runs first.
+fn merge_runs<T, W>(runs: &[Run], output: &mut W) -> Result<u64, Error>
+where
+    T: Record + Ord,
+    W: Write,
+{
+    let mut readers: Vec<BufReader<File>> = Vec::with_capacity(runs.len());
+    for run in runs {
+        readers.push(BufReader::new(File::open(&run.path)?));
+    }
+    let mut heap: BinaryHeap<Reverse<(T, usize)>> = BinaryHeap::new();
+    for (index, reader) in readers.iter_mut().enumerate() {
+        if let Some(item) = T::read_from(reader)? {
+            heap.push(Reverse((item, index)));
+        }
+    }
+    let mut written: u64 = 0;
+    while let Some(Reverse((item, index))) = heap.pop() {
+        item.write_to(output)?;
+        written += 1;
+        if let Some(next) = T::read_from(&mut readers[index])? {
+            heap.push(Reverse((next, index)));
+        }
+    }
+    return Ok(written);
+}
+
+/// This `struct` sorts lists too large to fit in memory using multiple
+/// threads and files on disk. See the module documentation.
+pub struct MtdExternalSort {
+    pool: ThreadPool,
+    temp_dir: PathBuf,
+    memory_budget: usize,
+}
+
+impl MtdExternalSort {
+    /// Creates a new [`MtdExternalSort`] instance which keeps its run files
+    /// in [`std::env::temp_dir`] with a budget of [`DEFAULT_MEMORY_BUDGET`].
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        return Ok(Self {
+            pool,
+            temp_dir: env::temp_dir(),
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+        });
+    }
+
+    /// Get the directory the run files are kept in.
+    pub fn temp_dir(&self) -> &Path {
+        return &self.temp_dir;
+    }
+
+    /// Sets the directory the run files are kept in, which must already
+    /// exist and should have room for a copy of the list.
+    pub fn set_temp_dir<P: Into<PathBuf>>(&mut self, temp_dir: P) {
+        self.temp_dir = temp_dir.into();
+    }
+
+    /// Get the most bytes of items held in memory at once.
+    pub fn memory_budget(&self) -> usize {
+        return self.memory_budget;
+    }
+
+    /// Sets the most bytes of items held in memory at once. Items are
+    /// measured with [`std::mem::size_of`], so memory the items point to,
+    /// such as the text of a [`String`], is not counted.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `memory_budget` is `0`, and the
+    /// budget is left as it was.
+    pub fn set_memory_budget(&mut self, memory_budget: usize) -> Result<(), Error> {
+        if memory_budget == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The memory budget must be at least 1 byte."
+            ));
+        }
+        self.memory_budget = memory_budget;
+        return Ok(());
+    }
+
+    /// Sorts `items` into `output`, returning the number of items written.
+    /// The sort is not stable. See the module documentation.
+    /// 
+    /// # Parameters
+    /// 1. ```items: I``` => The items to sort, which are read once and only
+    ///    as many at a time as fit in the memory budget.
+    /// 2. ```output: W``` => Where the sorted items are written, one after
+    ///    another with [`Record::write_to`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a run file cannot be written or
+    /// read, if writing to `output` fails, or if a job on the
+    /// [`ThreadPool`] fails.
+    pub fn sort<T, I, W>(&mut self, items: I, output: W) -> Result<u64, Error>
+    where
+        T: Record + Ord + Send + 'static,
+        I: IntoIterator<Item = T>,
+        W: Write,
+    {
+        let threads = self.pool.threads();
+        let budget = self.memory_budget / mem::size_of::<T>().max(1);
+        let per_piece = (budget / threads).max(1);
+        let mut items = items.into_iter();
+        let mut runs: Vec<Run> = Vec::new();
+        loop {
+            let mut pieces: Vec<Mutex<Vec<T>>> = Vec::with_capacity(threads);
+            for _ in 0..threads {
+                let piece: Vec<T> = items.by_ref().take(per_piece).collect();
+                if piece.is_empty() {
+                    break;
+                }
+                pieces.push(Mutex::new(piece));
+            }
+            if pieces.is_empty() {
+                break;
+            }
+            runs.extend(self.write_runs(pieces)?);
+        }
+
+        while runs.len() > FAN_IN {
+            runs = self.merge_round::<T>(runs)?;
+        }
+        let mut output = BufWriter::new(output);
+        let written = merge_runs::<T, _>(&runs, &mut output)?;
+        output.flush()?;
+        return Ok(written);
+    }
+
+    /// Sorts each of `pieces` and writes it to a run file, each piece as a
+    /// job.
+    fn write_runs<T>(&mut self, pieces: Vec<Mutex<Vec<T>>>) -> Result<Vec<Run>, Error>
+    where
+        T: Record + Ord + Send + 'static,
+    {
+        let directory = self.temp_dir.clone();
+        let policy = ChunkPolicy::Fixed(1);
+        let written = self.pool.map(Arc::new(pieces), policy, move |piece| {
+            let mut piece = mem::take(&mut *unwrapmutex!(piece.lock()));
+            piece.sort_unstable();
+            let (run, mut writer) = Run::create(&directory)?;
+            for item in &piece {
+                item.write_to(&mut writer)?;
+            }
+            writer.flush()?;
+            return Ok(run);
+        })?;
+        return written.into_iter().collect::<Result<_, Error>>();
+    }
+
+    /// Merges every [`FAN_IN`] neighbouring runs of `runs` into one, each
+    /// group as a job.
+    fn merge_round<T>(&mut self, runs: Vec<Run>) -> Result<Vec<Run>, Error>
+    where
+        T: Record + Ord + Send + 'static,
+    {
+        let mut groups: Vec<Mutex<Vec<Run>>> = Vec::new();
+        let mut runs = runs.into_iter().peekable();
+        while runs.peek().is_some() {
+            groups.push(Mutex::new(runs.by_ref().take(FAN_IN).collect()));
+        }
+        let directory = self.temp_dir.clone();
+        let policy = ChunkPolicy::Fixed(1);
+        let merged = self.pool.map(Arc::new(groups), policy, move |group| {
+            let mut group = mem::take(&mut *unwrapmutex!(group.lock()));
+            if group.len() == 1 {
+                return Ok(group.remove(0));
+            }
+            let (run, mut writer) = Run::create(&directory)?;
+            merge_runs::<T, _>(&group, &mut writer)?;
+            writer.flush()?;
+            return Ok(run);
+        })?;
+        return merged.into_iter().collect::<Result<_, Error>>();
+    }
+}
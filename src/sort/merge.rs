@@ -0,0 +1,79 @@
+//! Merge sort, which sorts pieces of the list separately and then merges
+//! the sorted pieces in pairs until one is left.
+
+use std::{
+    cmp::Ordering,
+    io::Error,
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use super::SEQUENTIAL_CUTOFF;
+use crate::{
+    merge::k_way_by,
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    unwrapmutex,
+};
+
+/// Sorts `items` in parallel on `pool`. The sort is stable, so equal items
+/// keep their order. See [`merge_sort_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn merge_sort<T>(pool: &mut ThreadPool, items: Vec<T>) -> Result<Vec<T>, Error>
+where
+    T: Ord + Send + 'static,
+{
+    return merge_sort_by(pool, items, T::cmp);
+}
+
+/// Sorts `items` in parallel on `pool` according to `compare`. The sort is
+/// stable, so items which `compare` finds equal keep their order.
+/// 
+/// The list is cut into 2 pieces per thread, which are sorted as separate
+/// jobs, and then neighbouring pieces are merged in pairs, each pair as a
+/// job, until the whole list is merged with [`k_way_by`].
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which do the sorting.
+/// 2. ```items: Vec<T>``` => The list to sort.
+/// 3. ```compare: F``` => Decides the order of 2 items, like
+///    [`slice::sort_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn merge_sort_by<T, F>(
+    pool: &mut ThreadPool,
+    mut items: Vec<T>,
+    compare: F,
+) -> Result<Vec<T>, Error>
+where
+    T: Send + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    let pieces = (pool.threads() * 2).min(items.len() / SEQUENTIAL_CUTOFF);
+    if pieces < 2 {
+        items.sort_by(compare);
+        return Ok(items);
+    }
+    let compare = Arc::new(compare);
+
+    // Cut from the back so that the pieces stay in order once reversed.
+    let size = items.len().div_ceil(pieces);
+    let mut runs: Vec<Mutex<Vec<T>>> = Vec::with_capacity(pieces);
+    while !items.is_empty() {
+        let start = items.len().saturating_sub(size);
+        runs.push(Mutex::new(items.split_off(start)));
+    }
+    runs.reverse();
+    let shared = compare.clone();
+    let sorted = pool.map(Arc::new(runs), ChunkPolicy::Fixed(1), move |run| {
+        let mut run = mem::take(&mut *unwrapmutex!(run.lock()));
+        run.sort_by(|a, b| shared(a, b));
+        return Ok(run);
+    })?;
+    let runs: Vec<Vec<T>> = sorted.into_iter().collect::<Result<_, Error>>()?;
+    return k_way_by(pool, runs, move |a, b| compare(a, b));
+}
@@ -0,0 +1,32 @@
+//! Module for sorting large lists on a [`crate::pool::ThreadPool`].
+//! 
+//! Every function here takes the list by value and hands back the sorted
+//! list, since the pieces are moved between the workers while sorting.
+//! Lists shorter than [`SEQUENTIAL_CUTOFF`] are sorted on the calling thread
+//! with the standard library, which is faster than sending them off.
+//! 
+//! These are the algorithms available:
+//! 1. [`merge_sort`], which is stable and suits most lists.
+//! 2. [`quicksort`], which is not stable but sorts in place, so it suits
+//!    lists too large to copy.
+//! 3. [`sample_sort`], which is stable and scales best with many threads,
+//!    as long as the list does not hold many equal items.
+//! 4. [`counting_sort`], which is stable and sorts by a small whole number
+//!    key without comparing items.
+//! 5. [`external`], which sorts lists too large to fit in memory through
+//!    run files on disk, and so streams the items in and out instead.
+
+mod counting;
+pub mod external;
+mod merge;
+mod quick;
+mod sample;
+
+pub use counting::counting_sort;
+pub use merge::{merge_sort, merge_sort_by};
+pub use quick::{quicksort, quicksort_by};
+pub use sample::{sample_sort, sample_sort_by};
+pub(crate) use sample::scatter;
+
+/// Lists with fewer items than this are sorted on the calling thread.
+pub const SEQUENTIAL_CUTOFF: usize = 4096;
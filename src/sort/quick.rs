@@ -0,0 +1,255 @@
+//! Quicksort, which partitions the list around a pivot so that smaller
+//! items come before it and larger items after it, and then sorts both
+//! sides separately.
+//! 
+//! The sides are sorted in place, so apart from the pieces handed to the
+//! jobs no memory is allocated, which suits lists too large to copy.
+
+use std::{
+    cmp::{Ordering, Reverse},
+    io::Error,
+    mem,
+    slice::from_raw_parts_mut,
+    sync::Arc,
+};
+
+use super::SEQUENTIAL_CUTOFF;
+use crate::pool::{ThreadPool, chunk::ChunkPolicy};
+
+/// The number of pieces per thread the list is partitioned into before the
+/// pieces are sorted, so that threads given small pieces can take more.
+const PIECES_PER_THREAD: usize = 4;
+
+/// A piece of the list being sorted. The pieces never overlap, and each is
+/// handed to one job at a time.
+struct Piece<T> {
+    start: *mut T,
+    len: usize,
+    /// The number of partitions which led to the piece.
+    depth: usize,
+}
+
+// A piece is only touched by the job it is handed to, so moving the items
+// to that job's thread is all that is needed.
+unsafe impl<T: Send> Send for Piece<T> {}
+unsafe impl<T: Send> Sync for Piece<T> {}
+
+impl<T> Piece<T> {
+    fn new(items: &mut [T], depth: usize) -> Self {
+        return Self {start: items.as_mut_ptr(), len: items.len(), depth};
+    }
+
+    /// Get the items of the piece.
+    /// 
+    /// # Safety
+    /// 
+    /// The list the piece was cut from must still be alive and must not be
+    /// touched by anything but this slice until it is dropped.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn items(&self) -> &mut [T] {
+        return unsafe {from_raw_parts_mut(self.start, self.len)};
+    }
+}
+
+/// Get the index of whichever of `items[a]`, `items[b]` and `items[c]` is
+/// between the other 2.
+fn median_of_three<T, F>(
+    items: &[T],
+    a: usize,
+    b: usize,
+    c: usize,
+    compare: &F,
+) -> usize
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let less = |x: usize, y: usize| compare(&items[x], &items[y]) == Ordering::Less;
+    return match (less(a, b), less(b, c), less(a, c)) {
+        (true, true, _) | (false, false, _) => b,
+        (true, false, true) | (false, true, false) => c,
+        _ => a,
+    };
+}
+
+/// Partitions `items`, which has at least 2 items, around the median of its
+/// first, middle and last items. Returns the index the pivot ends up at,
+/// with no greater item before it and no smaller item after it.
+/// 
+/// Items equal to the pivot stop the scans from both ends and get swapped,
+/// so a list of equal items is split down the middle.
+fn partition<T, F>(items: &mut [T], compare: &F) -> usize
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let last = items.len() - 1;
+    let pivot = median_of_three(items, 0, last / 2, last, compare);
+    items.swap(0, pivot);
+    let (head, rest) = items.split_at_mut(1);
+    let pivot = &head[0];
+    let (mut left, mut right) = (0, rest.len());
+    loop {
+        while left < right && compare(&rest[left], pivot) == Ordering::Less {
+            left += 1;
+        }
+        while left < right && compare(&rest[right - 1], pivot) == Ordering::Greater {
+            right -= 1;
+        }
+        if left >= right {
+            break;
+        }
+        right -= 1;
+        rest.swap(left, right);
+        left += 1;
+    }
+    items.swap(0, left);
+    return left;
+}
+
+/// Moves `items[root]` down the max heap in `items[..end]` until neither of
+/// its children is greater.
+fn sift_down<T, F>(items: &mut [T], mut root: usize, end: usize, compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= end {
+            return;
+        }
+        let right = child + 1;
+        if right < end && compare(&items[child], &items[right]) == Ordering::Less {
+            child = right;
+        }
+        if compare(&items[root], &items[child]) != Ordering::Less {
+            return;
+        }
+        items.swap(root, child);
+        root = child;
+    }
+}
+
+/// Sorts `items` with heapsort, which never takes more than O(n log n)
+/// comparisons whatever pivots quicksort would have picked.
+fn heapsort<T, F>(items: &mut [T], compare: &F)
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let len = items.len();
+    for root in (0..len / 2).rev() {
+        sift_down(items, root, len, compare);
+    }
+    for end in (1..len).rev() {
+        items.swap(0, end);
+        sift_down(items, 0, end, compare);
+    }
+}
+
+/// Sorts `items` in parallel on `pool`. The sort is not stable, so equal
+/// items may be reordered. See [`quicksort_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn quicksort<T>(pool: &mut ThreadPool, items: Vec<T>) -> Result<Vec<T>, Error>
+where
+    T: Ord + Send + 'static,
+{
+    return quicksort_by(pool, items, T::cmp);
+}
+
+/// Sorts `items` in parallel on `pool` according to `compare`, in place.
+/// The sort is not stable, so items which `compare` finds equal may be
+/// reordered. Unlike [`super::merge_sort_by`], no second list is allocated
+/// to merge into.
+/// 
+/// The list is partitioned in rounds, each piece of a round being
+/// partitioned as a job, until there are a few pieces for every
+/// thread or every piece is shorter than [`SEQUENTIAL_CUTOFF`]. Each piece
+/// is then sorted as a job. Like introsort, a piece which has been
+/// partitioned more than twice the log of the list length times, because
+/// the pivots were poor, is sorted with heapsort instead, so the sort never
+/// slows down to O(n²).
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which do the sorting.
+/// 2. ```items: Vec<T>``` => The list to sort.
+/// 3. ```compare: F``` => Decides the order of 2 items, like
+///    [`slice::sort_unstable_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn quicksort_by<T, F>(
+    pool: &mut ThreadPool,
+    mut items: Vec<T>,
+    compare: F,
+) -> Result<Vec<T>, Error>
+where
+    T: Send + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    if items.len() < SEQUENTIAL_CUTOFF {
+        items.sort_unstable_by(compare);
+        return Ok(items);
+    }
+    if let Err(error) = sort_pieces(pool, &mut items, Arc::new(compare)) {
+        // Jobs may still hold pieces of the list if the pool failed, so the
+        // list is leaked rather than freed under them.
+        mem::forget(items);
+        return Err(error);
+    }
+    return Ok(items);
+}
+
+/// Partitions `items` into pieces and sorts each piece as a job, blocking
+/// until every job is done. See [`quicksort_by`].
+fn sort_pieces<T, F>(
+    pool: &mut ThreadPool,
+    items: &mut [T],
+    compare: Arc<F>,
+) -> Result<(), Error>
+where
+    T: Send + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    let limit = 2 * items.len().ilog2() as usize;
+    let wanted = pool.threads() * PIECES_PER_THREAD;
+    let mut pieces: Vec<Piece<T>> = vec![Piece::new(items, 0)];
+    let mut leaves: Vec<Piece<T>> = Vec::new();
+
+    while !pieces.is_empty() && pieces.len() + leaves.len() < wanted {
+        let shared = compare.clone();
+        let halves = pool.map(Arc::new(pieces), ChunkPolicy::Fixed(1), move |piece| {
+            // The pieces never overlap, and `items` is borrowed until every
+            // job has returned.
+            let items = unsafe {piece.items()};
+            let split = partition(items, &*shared);
+            let (left, right) = items.split_at_mut(split);
+            let depth = piece.depth + 1;
+            return [Piece::new(left, depth), Piece::new(&mut right[1..], depth)];
+        })?;
+        pieces = Vec::new();
+        for half in halves.into_iter().flatten() {
+            if half.len < SEQUENTIAL_CUTOFF || half.depth > limit {
+                leaves.push(half);
+            } else {
+                pieces.push(half);
+            }
+        }
+    }
+    leaves.append(&mut pieces);
+    // Start with the longest pieces, so that no thread is left with a long
+    // piece once the others are done.
+    leaves.sort_unstable_by_key(|piece| Reverse(piece.len));
+
+    pool.map(Arc::new(leaves), ChunkPolicy::Fixed(1), move |piece| {
+        // As above, the pieces never overlap and `items` is still borrowed.
+        let items = unsafe {piece.items()};
+        if piece.depth > limit {
+            heapsort(items, &*compare);
+        } else {
+            items.sort_unstable_by(|a, b| compare(a, b));
+        }
+    })?;
+    return Ok(());
+}
@@ -0,0 +1,155 @@
+//! Sample sort, which picks splitters from a random sample of the list,
+//! puts every item into the bucket between the splitters around it, and
+//! sorts the buckets separately.
+//! 
+//! Every item ends up in its final bucket after one pass, so unlike
+//! [`super::merge_sort`] there are no rounds of merging left once the
+//! buckets are sorted, which is why it scales best with many threads.
+
+use std::{
+    cmp::Ordering,
+    io::Error,
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use super::SEQUENTIAL_CUTOFF;
+use crate::{
+    pool::{ThreadPool, chunk::{ChunkPolicy, ranges}},
+    rng::Rng,
+    unwrapmutex, unwrapoption,
+};
+
+/// The number of items sampled for each bucket. Sampling more items makes
+/// the buckets closer in size but takes longer to sort the sample.
+const SAMPLES_PER_BUCKET: usize = 16;
+
+/// Sorts `items` in parallel on `pool`. The sort is stable, so equal items
+/// keep their order. See [`sample_sort_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn sample_sort<T>(pool: &mut ThreadPool, items: Vec<T>) -> Result<Vec<T>, Error>
+where
+    T: Ord + Send + Sync + 'static,
+{
+    return sample_sort_by(pool, items, T::cmp);
+}
+
+/// Sorts `items` in parallel on `pool` according to `compare`. The sort is
+/// stable, so items which `compare` finds equal keep their order.
+/// 
+/// A random sample of the list, seeded with its length so the same list is
+/// always cut the same way, is sorted to pick 1 splitter fewer than there
+/// are buckets, with 2 buckets per thread. Pieces of the list are then
+/// given to jobs which find the bucket of each of their items, and once
+/// the items are moved into their buckets each bucket is sorted as a job.
+/// Equal items always share a bucket, so a list with few distinct items
+/// may be left with buckets of very different sizes.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which do the sorting.
+/// 2. ```items: Vec<T>``` => The list to sort.
+/// 3. ```compare: F``` => Decides the order of 2 items, like
+///    [`slice::sort_by`].
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if a job on the [`ThreadPool`] fails.
+pub fn sample_sort_by<T, F>(
+    pool: &mut ThreadPool,
+    mut items: Vec<T>,
+    compare: F,
+) -> Result<Vec<T>, Error>
+where
+    T: Send + Sync + 'static,
+    F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+{
+    let len = items.len();
+    let buckets = (pool.threads() * 2).min(len / SEQUENTIAL_CUTOFF);
+    if buckets < 2 {
+        items.sort_by(compare);
+        return Ok(items);
+    }
+    let compare = Arc::new(compare);
+
+    // The splitters are kept as indices into the list, which is only read
+    // until every item has found its bucket.
+    let mut rng = Rng::new(len as u64);
+    let mut sample: Vec<usize> = (0..buckets * SAMPLES_PER_BUCKET)
+        .map(|_| rng.below(len))
+        .collect();
+    sample.sort_by(|a, b| compare(&items[*a], &items[*b]));
+    let splitters: Vec<usize> = (1..buckets)
+        .map(|bucket| sample[bucket * SAMPLES_PER_BUCKET])
+        .collect();
+
+    let judge = compare.clone();
+    let bucket_of = move |list: &[T], item: &T| {
+        // Items equal to a splitter go after it, so equal items always
+        // share a bucket.
+        return splitters.partition_point(|splitter| {
+            return judge(&list[*splitter], item) != Ordering::Greater;
+        });
+    };
+    let sorted = scatter(pool, items, buckets, bucket_of, move |mut bucket| {
+        bucket.sort_by(|a, b| compare(a, b));
+        return bucket;
+    })?;
+    let mut merged: Vec<T> = Vec::with_capacity(len);
+    for mut bucket in sorted {
+        merged.append(&mut bucket);
+    }
+    return Ok(merged);
+}
+
+/// Moves every item of `items` into the bucket `bucket_of` picks for it out
+/// of `buckets`, then runs `job` over every bucket, returning what each job
+/// returns in the order of the buckets.
+/// 
+/// `bucket_of` is given the whole list with each item, so it can compare
+/// items with others in the list. The buckets of pieces of the list are
+/// found by jobs on `pool`, after which the items are moved into their
+/// buckets on the calling thread in the order of the list, so the items of
+/// each bucket keep their order. Each bucket is then handed to its own job.
+pub(crate) fn scatter<T, B, J, R>(
+    pool: &mut ThreadPool,
+    items: Vec<T>,
+    buckets: usize,
+    bucket_of: B,
+    job: J,
+) -> Result<Vec<R>, Error>
+where
+    T: Send + Sync + 'static,
+    B: Fn(&[T], &T) -> usize + Send + Sync + 'static,
+    J: Fn(Vec<T>) -> R + Send + Sync + 'static,
+    R: Send + 'static,
+{
+    let pieces = ranges(items.len(), buckets);
+    let shared = Arc::new(items);
+    let list = shared.clone();
+    let found = pool.map(Arc::new(pieces), ChunkPolicy::Fixed(1), move |piece| {
+        let (start, end) = *piece;
+        return list[start..end]
+            .iter()
+            .map(|item| bucket_of(&list, item) as u32)
+            .collect::<Vec<u32>>();
+    })?;
+    let items = unwrapoption!(Arc::try_unwrap(shared).ok());
+
+    let mut counts: Vec<usize> = vec![0; buckets];
+    for bucket in found.iter().flatten() {
+        counts[*bucket as usize] += 1;
+    }
+    let mut filled: Vec<Vec<T>> = counts.into_iter().map(Vec::with_capacity).collect();
+    for (item, bucket) in items.into_iter().zip(found.into_iter().flatten()) {
+        filled[bucket as usize].push(item);
+    }
+
+    let filled: Vec<Mutex<Vec<T>>> = filled.into_iter().map(Mutex::new).collect();
+    let results = pool.map(Arc::new(filled), ChunkPolicy::Fixed(1), move |bucket| {
+        return Ok(job(mem::take(&mut *unwrapmutex!(bucket.lock()))));
+    })?;
+    return results.into_iter().collect();
+}
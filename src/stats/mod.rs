@@ -0,0 +1,183 @@
+//! Module for statistics describing the shape of a whole graph, estimated
+//! from a sample of [`Node`]s where working them out exactly would need the
+//! [`Cost`]s between every pair of [`Node`]s.
+//! 
+//! The diameter of a graph is the largest [`Cost`] of a shortest route
+//! between 2 [`Node`]s, leaving out pairs with no route between them.
+//! [`approx_diameter`] bounds it with searches from a few sampled sources:
+//! - Every [`Cost`] found is at most the diameter, so the largest one found
+//!   is a lower bound. The [`Node`] farthest from each source is searched
+//!   from as well, since the far ends of a graph tend to be far from each
+//!   other, which usually tightens this bound a lot.
+//! - If a source reaches every [`Node`] and is reached by every [`Node`],
+//!   any route can go through it, so the [`Cost`] of the farthest [`Node`]
+//!   reaching it plus that of the farthest [`Node`] it reaches is an upper
+//!   bound. Otherwise, no upper bound is found.
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    arena::reset_local,
+    dijkstra::simple::{AdjacencyMatrix, Cost, CsrGraph, Node, shortest_from},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+    rng::Rng,
+};
+
+/// Bounds on the diameter of a graph found by [`approx_diameter`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiameterBounds {
+    /// The diameter is at least this.
+    pub lower: Cost,
+    /// The diameter is at most this, or [`None`] if no bound was found
+    /// because the graph is not strongly connected.
+    pub upper: Option<Cost>,
+    /// A pair of [`Node`]s `(from, to)` whose shortest route costs
+    /// [`DiameterBounds::lower`], or [`None`] if no route costs more than
+    /// `0`.
+    pub endpoints: Option<(Node, Node)>,
+    /// Number of [`Node`]s searched from.
+    pub searches: usize,
+}
+
+impl DiameterBounds {
+    /// Whether both bounds are the same, which makes them the diameter.
+    pub fn is_exact(&self) -> bool {
+        return self.upper == Some(self.lower);
+    }
+}
+
+/// The farthest [`Node`]s found by searching from a source.
+struct Sweep {
+    source: Node,
+    /// The [`Node`] reached from the source at the highest [`Cost`], and
+    /// that [`Cost`].
+    farthest: (Node, Cost),
+    /// The highest [`Cost`] of reaching the source from another [`Node`], if
+    /// this was searched and every [`Node`] reaches and is reached by the
+    /// source.
+    through: Option<Cost>,
+}
+
+/// Get the [`Node`] of `costs` with the highest [`Cost`] from `source`, ties
+/// going to the lower [`Node`] unless none is above `0`, and whether every
+/// [`Node`] has a [`Cost`].
+fn farthest(source: Node, costs: &[Option<Cost>]) -> ((Node, Cost), bool) {
+    let mut far: (Node, Cost) = (source, 0);
+    let mut all = true;
+    for (node, cost) in costs.iter().enumerate() {
+        match cost {
+            Some(cost) if *cost > far.1 => far = (node, *cost),
+            Some(_) => {},
+            None => all = false,
+        }
+    }
+    return (far, all);
+}
+
+/// Searches from `source` along the edges of `graph`, and against them with
+/// `reversed` if given.
+fn sweep(
+    source: Node,
+    graph: &CsrGraph,
+    reversed: Option<&CsrGraph>,
+) -> Result<Sweep, Error> {
+    let total = graph.total();
+    let (far, reaches) = farthest(source, &shortest_from(source, total, graph, None)?);
+    reset_local();
+    let mut through = None;
+    if let Some(reversed) = reversed {
+        let costs = shortest_from(source, total, reversed, None)?;
+        let ((_, back), reached) = farthest(source, &costs);
+        reset_local();
+        if reaches && reached {
+            through = Some(back.saturating_add(far.1));
+        }
+    }
+    return Ok(Sweep {source, farthest: far, through});
+}
+
+/// Runs [`sweep`] from each of `sources` on `pool`.
+fn sweeps(
+    pool: &mut ThreadPool,
+    sources: Vec<Node>,
+    graph: &Arc<CsrGraph>,
+    reversed: Option<&Arc<CsrGraph>>,
+) -> Result<Vec<Sweep>, Error> {
+    let graph = graph.clone();
+    let reversed = reversed.cloned();
+    return pool
+        .map(Arc::new(sources), ChunkPolicy::Auto, move |source| {
+            return sweep(*source, &graph, reversed.as_deref());
+        })?
+        .into_iter()
+        .collect();
+}
+
+/// Bounds the diameter of `matrix` by searching from a sample of sources.
+/// See the module documentation.
+/// 
+/// Each source is searched from as a job of its own on `pool`, both along
+/// the edges and against them, and the [`Node`]s found farthest from them
+/// are then searched from as well, along the edges only. Only the
+/// farthest [`Node`]s of each search are kept, so the whole table of
+/// [`Cost`]s is never held at once.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which run the searches.
+/// 2. ```matrix: &AdjacencyMatrix``` => The graph.
+/// 3. ```samples: usize``` => Number of sources. Sampling every [`Node`]
+///    gives the exact diameter as the lower bound.
+/// 4. ```seed: u64``` => Seed for picking the sources. The same seed always
+///    gives the same bounds.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `samples` is `0`, or if a job on the
+/// [`ThreadPool`] fails.
+pub fn approx_diameter(
+    pool: &mut ThreadPool,
+    matrix: &AdjacencyMatrix,
+    samples: usize,
+    seed: u64,
+) -> Result<DiameterBounds, Error> {
+    if samples == 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "At least 1 source must be sampled."
+        ));
+    }
+    let graph = Arc::new(CsrGraph::from(matrix));
+    let reversed = Arc::new(graph.reversed());
+    let total = graph.total();
+    let mut sources: Vec<Node> = (0..total).collect();
+    Rng::new(seed).shuffle(&mut sources);
+    sources.truncate(samples);
+    sources.sort_unstable();
+
+    let mut bounds = DiameterBounds {searches: sources.len(), ..Default::default()};
+    if total == 0 {
+        bounds.upper = Some(0);
+        return Ok(bounds);
+    }
+    let first = sweeps(pool, sources.clone(), &graph, Some(&reversed))?;
+    bounds.upper = first.iter().filter_map(|sweep| sweep.through).min();
+    let mut ends: Vec<Node> = first.iter().map(|sweep| sweep.farthest.0).collect();
+    ends.sort_unstable();
+    ends.dedup();
+    ends.retain(|end| sources.binary_search(end).is_err());
+    bounds.searches += ends.len();
+    let second = sweeps(pool, ends, &graph, None)?;
+
+    for sweep in first.iter().chain(&second) {
+        let (to, cost) = sweep.farthest;
+        if cost > bounds.lower {
+            bounds.lower = cost;
+            bounds.endpoints = Some((sweep.source, to));
+        }
+    }
+    return Ok(bounds);
+}
@@ -0,0 +1,202 @@
+//! Module for sorting the [`Node`]s of a directed graph so that every edge
+//! goes from an earlier [`Node`] to a later one, such as ordering tasks so
+//! that each one comes after everything it depends on.
+//! 
+//! [`MtdToposort`] uses Kahn's Algorithm. The [`Node`]s without edges
+//! coming in form the first level. Taking a level away leaves new [`Node`]s
+//! without edges coming in, which form the next level, and so on. The
+//! [`Node`]s of one level do not depend on each other, so large levels are
+//! split between the workers of a [`ThreadPool`], which count down the edges
+//! coming into the [`Node`]s after them without locking. If some [`Node`]s
+//! are never freed this way, they depend on each other in a cycle, and one
+//! such cycle is returned instead of an order.
+
+use std::{
+    io::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering::SeqCst},
+    },
+};
+
+use crate::{
+    bfs::SEQUENTIAL_FRONTIER,
+    dijkstra::simple::{AdjacencyMatrix, CsrGraph, Node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// An order of the [`Node`]s of a graph found by [`MtdToposort::sort`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopologicalOrder {
+    /// Every [`Node`], with each one after all the [`Node`]s with edges
+    /// leading to it.
+    pub order: Vec<Node>,
+    /// The [`Node`]s split into levels, in order. A [`Node`] is in the
+    /// level after the last level of the [`Node`]s with edges leading to
+    /// it, so the [`Node`]s within a level can be handled at the same time.
+    /// Each level is sorted.
+    pub levels: Vec<Vec<Node>>,
+}
+
+impl TopologicalOrder {
+    /// Get the position of every [`Node`] in [`TopologicalOrder::order`].
+    pub fn positions(&self) -> Vec<usize> {
+        let mut positions: Vec<usize> = vec![0; self.order.len()];
+        for (position, node) in self.order.iter().enumerate() {
+            positions[*node] = position;
+        }
+        return positions;
+    }
+}
+
+/// What [`MtdToposort::sort`] found.
+/// 
+/// # Variants
+/// 
+/// 1. Sorted([`TopologicalOrder`]) => The graph has no cycles, and this is
+///    an order of its [`Node`]s.
+/// 2. Cycle([`Vec<Node>`]) => [`Node`]s which depend on each other in a
+///    cycle, in the order of the edges between them, so no order exists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ToposortResult {
+    Sorted(TopologicalOrder),
+    Cycle(Vec<Node>),
+}
+
+/// Counts down the edges coming into the [`Node`]s after `node` in
+/// `waiting`, and returns the [`Node`]s which have none left.
+fn release(graph: &CsrGraph, waiting: &[AtomicUsize], node: Node) -> Vec<Node> {
+    let mut freed: Vec<Node> = Vec::new();
+    for adjacent in graph.get_node(node).unwrap_or(&[]) {
+        if let Some(count) = waiting.get(adjacent.node) {
+            if count.fetch_sub(1, SeqCst) == 1 {
+                freed.push(adjacent.node);
+            }
+        }
+    }
+    return freed;
+}
+
+/// This `struct` sorts the [`Node`]s of a graph using multiple threads. See
+/// the module documentation.
+pub struct MtdToposort {
+    pool: ThreadPool,
+    graph: Arc<CsrGraph>,
+    reversed: CsrGraph,
+}
+
+impl MtdToposort {
+    /// Creates a new [`MtdToposort`] instance.
+    /// 
+    /// # Parameters
+    /// 1. ```threads: usize``` => Number of threads to use. At least one
+    ///    thread is needed to run the algorithm.
+    /// 2. ```matrix: &AdjacencyMatrix``` => The directed graph, with an edge
+    ///    from each [`Node`] to every [`Node`] which has to come after it.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return a [`std::io::Error`] if `threads` is less
+    /// than `1`.
+    pub fn new(threads: usize, matrix: &AdjacencyMatrix) -> Result<Self, Error> {
+        return Self::from_csr(threads, CsrGraph::from(matrix));
+    }
+
+    /// Creates a new [`MtdToposort`] instance from a graph which is already
+    /// a [`CsrGraph`]. See [`MtdToposort::new`].
+    pub fn from_csr(threads: usize, graph: CsrGraph) -> Result<Self, Error> {
+        let pool = ThreadPool::new(threads)?;
+        let reversed = graph.reversed();
+        return Ok(Self {pool, graph: Arc::new(graph), reversed});
+    }
+
+    /// Number of [`Node`]s in the graph.
+    pub fn total(&self) -> Node {
+        return self.graph.total();
+    }
+
+    /// Sorts the [`Node`]s of the graph, or finds a cycle if they cannot be
+    /// sorted.
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if a job on the [`ThreadPool`]
+    /// fails.
+    pub fn sort(&mut self) -> Result<ToposortResult, Error> {
+        let total = self.total();
+        let waiting: Arc<Vec<AtomicUsize>> = Arc::new(
+            (0..total)
+                .map(|node| {
+                    let incoming = self.reversed.get_node(node).unwrap_or(&[]).len();
+                    return AtomicUsize::new(incoming);
+                })
+                .collect()
+        );
+        let mut result = TopologicalOrder::default();
+        let mut level: Vec<Node> = (0..total)
+            .filter(|node| waiting[*node].load(SeqCst) == 0)
+            .collect();
+        while !level.is_empty() {
+            let mut next: Vec<Node> = if level.len() < SEQUENTIAL_FRONTIER {
+                level
+                    .iter()
+                    .flat_map(|node| release(&self.graph, &waiting, *node))
+                    .collect()
+            } else {
+                let graph = self.graph.clone();
+                let shared = waiting.clone();
+                self.pool
+                    .map(Arc::new(level.clone()), ChunkPolicy::Auto, move |node| {
+                        return release(&graph, &shared, *node);
+                    })?
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            };
+            next.sort_unstable();
+            result.order.extend_from_slice(&level);
+            result.levels.push(level);
+            level = next;
+        }
+
+        if result.order.len() == total {
+            return Ok(ToposortResult::Sorted(result));
+        }
+        return Ok(ToposortResult::Cycle(self.find_cycle(&waiting)));
+    }
+
+    /// Finds a cycle among the [`Node`]s still `waiting` for edges coming
+    /// into them. Each of them has an edge coming in from another such
+    /// [`Node`], so following those edges backwards has to loop.
+    fn find_cycle(&self, waiting: &[AtomicUsize]) -> Vec<Node> {
+        let total = self.total();
+        let stuck = |node: Node| waiting[node].load(SeqCst) > 0;
+        let start = match (0..total).find(|node| stuck(*node)) {
+            Some(start) => start,
+            None => return Vec::new(),
+        };
+        // Where each Node appears on the walk, if it has been visited.
+        let mut seen: Vec<Option<usize>> = vec![None; total];
+        let mut walk: Vec<Node> = Vec::new();
+        let mut node = start;
+        while seen[node].is_none() {
+            seen[node] = Some(walk.len());
+            walk.push(node);
+            let before = self
+                .reversed
+                .get_node(node)
+                .unwrap_or(&[])
+                .iter()
+                .find(|adjacent| adjacent.node < total && stuck(adjacent.node));
+            node = match before {
+                Some(adjacent) => adjacent.node,
+                None => return Vec::new(),
+            };
+        }
+        let mut cycle = walk.split_off(seen[node].unwrap_or(0));
+        cycle.reverse();
+        return cycle;
+    }
+}
@@ -0,0 +1,256 @@
+//! Module for the Travelling Salesman Problem, which asks for the cheapest
+//! tour visiting every [`Node`] once and coming back to the start.
+//! 
+//! Finding the cheapest tour takes time exponential in the number of
+//! [`Node`]s, so [`solve`] uses heuristics instead. Each worker of a
+//! [`ThreadPool`] builds a tour from a different start by always going to
+//! the nearest [`Node`] not visited yet, then improves it with 2 kinds of
+//! moves until neither helps or the time budget runs out:
+//! - 2-opt, which swaps 2 edges of the tour for 2 cheaper ones by reversing
+//!   the part of the tour between them.
+//! - Or-opt, which moves up to [`OR_OPT_LENGTH`] [`Node`]s in a row to a
+//!   cheaper place in the tour.
+//! 
+//! The cheapest of the tours is returned. The [`Cost`]s are given as a
+//! distance matrix, such as [`crate::floydwarshall::AllPairs::costs`], and
+//! must be the same both ways, since reversing part of a tour must not
+//! change its [`Cost`].
+
+use std::{
+    io::{Error, ErrorKind},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    dijkstra::simple::{Cost, Node},
+    pool::{ThreadPool, chunk::ChunkPolicy},
+};
+
+/// The most [`Node`]s in a row moved at once by an Or-opt move.
+pub const OR_OPT_LENGTH: usize = 3;
+
+/// A tour found by [`solve`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tour {
+    /// The [`Node`]s in the order they are visited, starting with `0`. The
+    /// tour goes back to `0` after the last one.
+    pub nodes: Vec<Node>,
+    /// The [`Cost`] of the tour, including going back to the start, which
+    /// saturates at [`Cost::MAX`].
+    pub cost: Cost,
+    /// Whether no move could improve the tour any further, rather than the
+    /// time budget running out first.
+    pub settled: bool,
+}
+
+/// A distance matrix with every [`Cost`] known, kept in one block.
+struct Distances {
+    total: Node,
+    costs: Vec<Cost>,
+}
+
+impl Distances {
+    /// Copies `costs` into a [`Distances`].
+    /// 
+    /// # Error
+    /// 
+    /// A [`std::io::Error`] is returned if `costs` is not square, or a
+    /// [`Cost`] is missing or not the same both ways.
+    fn new(costs: &[Vec<Option<Cost>>]) -> Result<Self, Error> {
+        let total = costs.len();
+        // Every row is checked before any Cost is read, since checking the
+        // symmetry reads the other rows too.
+        for (from, row) in costs.iter().enumerate() {
+            if row.len() != total {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Row {} has {} Costs instead of {}.",
+                        from, row.len(), total
+                    )
+                ));
+            }
+        }
+        let mut block: Vec<Cost> = Vec::with_capacity(total * total);
+        for (from, row) in costs.iter().enumerate() {
+            for (to, cost) in row.iter().enumerate() {
+                let Some(cost) = *cost else {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("There is no route from {} to {}.", from, to)
+                    ));
+                };
+                if costs[to][from] != Some(cost) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("The Costs between {} and {} differ.", from, to)
+                    ));
+                }
+                block.push(cost);
+            }
+        }
+        return Ok(Self {total, costs: block});
+    }
+
+    /// Get the [`Cost`] between `from` and `to`.
+    fn get(&self, from: Node, to: Node) -> Cost {
+        return self.costs[from * self.total + to];
+    }
+
+    /// Get the [`Cost`] of `tour`, including going back to the start.
+    fn tour(&self, tour: &[Node]) -> Cost {
+        return (0..tour.len())
+            .map(|index| self.get(tour[index], tour[(index + 1) % tour.len()]))
+            .fold(0, Cost::saturating_add);
+    }
+
+    /// Builds a tour from `start` by always going to the nearest [`Node`]
+    /// not visited yet, ties going to the lower [`Node`].
+    fn nearest_neighbour(&self, start: Node) -> Vec<Node> {
+        let mut visited: Vec<bool> = vec![false; self.total];
+        let mut tour: Vec<Node> = Vec::with_capacity(self.total);
+        let mut node = start;
+        visited[node] = true;
+        tour.push(node);
+        while tour.len() < self.total {
+            let next = (0..self.total)
+                .filter(|other| !visited[*other])
+                .min_by_key(|other| self.get(node, *other));
+            let Some(next) = next else {
+                break;
+            };
+            visited[next] = true;
+            tour.push(next);
+            node = next;
+        }
+        return tour;
+    }
+
+    /// Makes every improving 2-opt move found in one pass over `tour`.
+    /// Returns whether a move was made.
+    fn two_opt(&self, tour: &mut [Node]) -> bool {
+        let total = tour.len();
+        let mut improved = false;
+        for first in 0..total.saturating_sub(2) {
+            // Swapping the edge closing the tour with the first edge changes
+            // nothing.
+            let last = if first == 0 { total - 1 } else { total };
+            for second in first + 2..last {
+                let (a, b) = (tour[first], tour[first + 1]);
+                let (c, d) = (tour[second], tour[(second + 1) % total]);
+                let before = self.get(a, b).saturating_add(self.get(c, d));
+                let after = self.get(a, c).saturating_add(self.get(b, d));
+                if after < before {
+                    tour[first + 1..=second].reverse();
+                    improved = true;
+                }
+            }
+        }
+        return improved;
+    }
+
+    /// Makes the first improving Or-opt move found in `tour`. Returns
+    /// whether a move was made.
+    fn or_opt(&self, tour: &mut Vec<Node>) -> bool {
+        let total = tour.len();
+        for length in 1..=OR_OPT_LENGTH.min(total.saturating_sub(3)) {
+            for start in 0..=total - length {
+                let end = start + length - 1;
+                let before = tour[(start + total - 1) % total];
+                let after = tour[(end + 1) % total];
+                let (head, tail) = (tour[start], tour[end]);
+                let saved = self
+                    .get(before, head)
+                    .saturating_add(self.get(tail, after));
+                let closed = self.get(before, after);
+                let previous = (start + total - 1) % total;
+                for gap in 0..total {
+                    // The gap between tour[gap] and the Node after it must be
+                    // outside the segment and not next to it.
+                    if (start..=end).contains(&gap) || gap == previous {
+                        continue;
+                    }
+                    let (x, y) = (tour[gap], tour[(gap + 1) % total]);
+                    let added = self.get(x, head).saturating_add(self.get(tail, y));
+                    let kept = saved.saturating_add(self.get(x, y));
+                    if closed.saturating_add(added) < kept {
+                        let segment: Vec<Node> = tour.drain(start..=end).collect();
+                        let position = tour.iter().position(|node| *node == x);
+                        let at = position.map_or(tour.len(), |index| index + 1);
+                        tour.splice(at..at, segment);
+                        return true;
+                    }
+                }
+            }
+        }
+        return false;
+    }
+}
+
+/// Improves `tour` with 2-opt and Or-opt moves until neither helps or
+/// `deadline` passes, if there is one. Returns whether it stopped because
+/// neither helps.
+fn improve(
+    distances: &Distances,
+    tour: &mut Vec<Node>,
+    deadline: Option<Instant>,
+) -> bool {
+    loop {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return false;
+        }
+        if !distances.two_opt(tour) && !distances.or_opt(tour) {
+            return true;
+        }
+    }
+}
+
+/// Finds a cheap tour through every [`Node`] of `costs`. See the module
+/// documentation.
+/// 
+/// # Parameters
+/// 1. ```pool: &mut ThreadPool``` => The threads which build and improve
+///    the tours, one tour per thread.
+/// 2. ```costs: &[Vec<Option<Cost>>]``` => `costs[from][to]` is the
+///    [`Cost`] of going from `from` to `to`.
+/// 3. ```budget: Duration``` => How long the tours may be improved for.
+///    Each tour stops improving once this has passed, and the best tour so
+///    far is returned. A budget too long to be a point in time, such as
+///    [`Duration::MAX`], lets every tour improve until no move helps.
+/// 
+/// # Error
+/// 
+/// A [`std::io::Error`] is returned if `costs` is not square, a [`Cost`] is
+/// missing or not the same both ways, or a job on the [`ThreadPool`]
+/// fails.
+pub fn solve(
+    pool: &mut ThreadPool,
+    costs: &[Vec<Option<Cost>>],
+    budget: Duration,
+) -> Result<Tour, Error> {
+    let deadline = Instant::now().checked_add(budget);
+    let distances = Arc::new(Distances::new(costs)?);
+    let total = distances.total;
+    if total == 0 {
+        return Ok(Tour {nodes: Vec::new(), cost: 0, settled: true});
+    }
+    let count = pool.threads().min(total);
+    let starts: Vec<Node> = (0..count).map(|index| index * total / count).collect();
+
+    let shared = distances.clone();
+    let tours = pool.map(Arc::new(starts), ChunkPolicy::Fixed(1), move |start| {
+        let mut tour = shared.nearest_neighbour(*start);
+        let settled = improve(&shared, &mut tour, deadline);
+        return (shared.tour(&tour), settled, tour);
+    })?;
+
+    let (cost, settled, mut nodes) = tours
+        .into_iter()
+        .min_by_key(|(cost, _, _)| *cost)
+        .unwrap_or_default();
+    let first = nodes.iter().position(|node| *node == 0).unwrap_or(0);
+    nodes.rotate_left(first);
+    return Ok(Tour {nodes, cost, settled});
+}